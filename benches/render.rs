@@ -0,0 +1,48 @@
+#![allow(clippy::needless_return)]
+
+use std::sync::Arc;
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruuvi_terminal_client::backend::Snapshot;
+use ruuvi_terminal_client::clock::SystemClock;
+use ruuvi_terminal_client::config::Config;
+use ruuvi_terminal_client::frame::Frame;
+use ruuvi_terminal_client::poller::Poller;
+use ruuvi_terminal_client::render::{self, View};
+use ruuvi_terminal_client::simulation::Simulation;
+use ruuvi_terminal_client::theme::{Theme, ThemeName};
+
+// A terminal of a common size.
+const SIZE: (i32, i32) = (40, 120);
+
+/**
+ * A poller with the demo tags fetched.
+*/
+fn poller() -> Poller {
+	let config = Config {
+		simulation: Some(Simulation::Demo),
+		..Config::default()
+	};
+	let mut poller = Poller::new(config, Arc::new(SystemClock)).unwrap();
+	poller.poll();
+	return poller;
+}
+
+/**
+ * A frame of each layout drawn into memory, and the check that decides whether the frame is drawn at all.
+*/
+fn render(criterion: &mut Criterion) {
+	let poller = poller();
+	let view = View::new(&poller.config);
+	let theme = Theme::named(ThemeName::Default, 256);
+	let data = &poller.data;
+	let mut snapshot = Snapshot::new(SIZE);
+	
+	criterion.bench_function("cards", |bench| bench.iter(|| render::render(&mut snapshot, &theme, data, &poller, &view)));
+	criterion.bench_function("table", |bench| bench.iter(|| render::render_table(&mut snapshot, &theme, data, &poller, &view)));
+	criterion.bench_function("minimal", |bench| bench.iter(|| render::render_minimal(&mut snapshot, &theme, data, &poller, &view)));
+	criterion.bench_function("kiosk", |bench| bench.iter(|| render::render_kiosk(&mut snapshot, &theme, data.first(), &poller, &view)));
+	criterion.bench_function("frame", |bench| bench.iter(|| Frame::new(data, &poller, &view, SIZE, None, None)));
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+use std::process::Command;
+
+/**
+ * Version details for --version: the git commit and the build date. SOURCE_DATE_EPOCH is used as the build date
+ * for reproducible builds.
+*/
+fn main() {
+	let hash = Command::new("git")
+		.args(["rev-parse", "--short", "HEAD"])
+		.output()
+		.ok()
+		.filter(|output| output.status.success())
+		.and_then(|output| String::from_utf8(output.stdout).ok())
+		.map(|hash| hash.trim().to_string())
+		.unwrap_or_else(|| "unknown".into());
+	
+	let date = std::env::var("SOURCE_DATE_EPOCH").ok()
+		.and_then(|epoch| epoch.parse::<i64>().ok())
+		.and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
+		.unwrap_or_else(chrono::Utc::now);
+	
+	println!("cargo:rustc-env=GIT_HASH={}", hash);
+	println!("cargo:rustc-env=BUILD_DATE={}", date.format("%Y-%m-%d"));
+	println!("cargo:rerun-if-changed=.git/HEAD");
+	println!("cargo:rerun-if-changed=.git/refs");
+	println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::{thread, time};
+use chrono::{DateTime, Utc};
+use crate::alerts::AlertMetric;
+use crate::calm::CalmFilter;
+use crate::config::Config;
+use crate::i18n::Text;
+use crate::model::{Metric, Tag};
+use crate::poller::Poller;
+use crate::shutdown;
+use crate::units::TemperatureUnit;
+
+/**
+ * Screen reader mode: each tag as a sentence on its own line, e.g. "Sauna: 62.1 degrees, rising; humidity 14
+ * percent, steady; updated 2 minutes ago", without curses or box drawing. A tag's line is printed again only when its
+ * values change, not when only the age does, so that the screen reader announces just the news.
+*/
+pub fn run(poller: &mut Poller) {
+	let mut calm = CalmFilter::new(poller.config.calm_epsilon).with_tags(&poller.config.tags);
+	// What was said last of each tag ID, without the age, and of the errors.
+	let mut said: HashMap<String, String> = HashMap::new();
+	let mut said_errors = String::new();
+	
+	poller.poll();
+	while !shutdown::requested() {
+		let lang = poller.config.lang();
+		let now = poller.clock.now();
+		let mut lines = Vec::new();
+		for tag in calm.apply(&poller.data) {
+			let description = describe(&tag, &poller.config);
+			if said.get(&tag.id) == Some(&description) {
+				continue;
+			}
+			lines.push(match updated(&tag, &poller.config, now) {
+				Some(updated) => format!("{}; {}", description, updated),
+				None => description.clone(),
+			});
+			said.insert(tag.id.clone(), description);
+		}
+		let errors = poller.errors().into_iter().map(|error| lang.text(error)).collect::<Vec<_>>().join(". ");
+		if errors != said_errors {
+			lines.push(errors.clone());
+			said_errors = errors;
+		}
+		
+		// Nobody to tell if stdout is gone, e.g. the pager quit.
+		let mut stdout = io::stdout().lock();
+		let _ = lines.iter().filter(|line| !line.is_empty()).try_for_each(|line| writeln!(stdout, "{}", line)).and_then(|_| stdout.flush());
+		drop(stdout);
+		
+		thread::sleep(time::Duration::from_secs(1));
+		poller.poll();
+	}
+}
+
+/**
+ * The tag's name, values and trends as words, e.g. "Sauna: 62.1 degrees, rising; humidity 14 percent, steady".
+*/
+fn describe(tag: &Tag, config: &Config) -> String {
+	let lang = config.lang();
+	let precision = config.units.precision;
+	let trend = |metric: &Metric| config.vocabulary.word(metric.trend.unwrap_or(0), lang).to_string();
+	let unit = match config.units.temperature {
+		TemperatureUnit::Celsius => Text::Degrees,
+		TemperatureUnit::Kelvin => Text::Kelvins,
+	};
+	
+	let mut parts = Vec::new();
+	if let Some(temperature) = tag.temperature.as_ref().filter(|_| config.shows(tag, AlertMetric::Temperature)) {
+		parts.push(format!("{} {}, {}", lang.number(config.units.convert(temperature.current), precision.temperature), lang.text(unit), trend(temperature)));
+	}
+	if let Some(humidity) = tag.humidity.as_ref().filter(|_| config.shows(tag, AlertMetric::Humidity)) {
+		parts.push(format!("{} {} {}, {}", lang.text(Text::Humidity).to_lowercase(), lang.number(humidity.current, precision.humidity), lang.text(Text::Percent), trend(humidity)));
+	}
+	if let Some(pressure) = tag.pressure.as_ref().filter(|_| config.shows(tag, AlertMetric::Pressure)) {
+		parts.push(format!("{} {} {}, {}", lang.text(Text::Pressure).to_lowercase(), lang.number(pressure.current, precision.pressure), lang.text(Text::Hectopascals), trend(pressure)));
+	}
+	for (field, value) in &tag.extras {
+		parts.push(config.extras.get(field).cloned().unwrap_or_default().format(field, *value, lang));
+	}
+	if tag.battery_critical || tag.battery_low {
+		parts.push(lang.text(if tag.battery_critical { Text::BatteryCritical } else { Text::BatteryLow }).to_lowercase());
+	}
+	if tag.unreachable {
+		parts.push(lang.text(Text::Unreachable).to_lowercase());
+	}
+	if parts.is_empty() {
+		parts.push(lang.text(Text::Missing).to_string());
+	}
+	return format!("{}: {}", tag.label(), parts.join("; "));
+}
+
+/**
+ * When the tag was updated, e.g. "updated 2 minutes ago". None for an unreachable tag, which says so instead.
+*/
+fn updated(tag: &Tag, config: &Config, now: DateTime<Utc>) -> Option<String> {
+	let lang = config.lang();
+	let datetime = tag.measured_at().filter(|_| !tag.unreachable)?;
+	return Some(format!("{} {}", lang.text(Text::Updated).to_lowercase(), lang.time_ago((now - datetime).num_seconds())));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn tags_as_sentences() {
+		let mut tag = Tag {
+			datetime: "2024-05-01T11:58:00Z".into(),
+			temperature: Some(Metric { trend: Some(1), ..Metric::from_current(62.12) }),
+			humidity: Some(Metric::from_current(14.2)),
+			battery_voltage: Some(2.9),
+			..Tag::test("1", "Sauna")
+		};
+		let config = Config::default();
+		let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		assert_eq!(describe(&tag, &config), "Sauna: 62.1 degrees, rising; humidity 14 percent, steady");
+		assert_eq!(updated(&tag, &config, now).as_deref(), Some("updated 2 minutes ago"));
+		
+		tag.unreachable = true;
+		tag.battery_low = true;
+		assert_eq!(describe(&tag, &config), "Sauna: 62.1 degrees, rising; humidity 14 percent, steady; battery low; unreachable");
+		assert_eq!(updated(&tag, &config, now), None);
+	}
+}
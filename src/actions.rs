@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::blocking::{Client, RequestBuilder};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use crate::alerts::{Alert, AlertEvent, Severity};
+use crate::model::{ApiResponse, Tag};
+use crate::notifiers::{Notification, Ntfy, Telegram};
+use crate::schema::SCHEMA_VERSION;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionEvent {
+	Unreachable,
+	// A configured threshold alert started.
+	Threshold,
+	// Battery low or critical.
+	BatteryLow,
+	// A built-in advisory, or another informational alert, started.
+	Advisory,
+	// Any alert cleared.
+	Cleared,
+	NetworkError,
+	NetworkRestored,
+}
+
+impl ActionEvent {
+	pub fn name(&self) -> &'static str {
+		match self {
+			ActionEvent::Unreachable => "unreachable",
+			ActionEvent::Threshold => "threshold",
+			ActionEvent::BatteryLow => "battery_low",
+			ActionEvent::Advisory => "advisory",
+			ActionEvent::Cleared => "cleared",
+			ActionEvent::NetworkError => "network_error",
+			ActionEvent::NetworkRestored => "network_restored",
+		}
+	}
+	
+	fn of_alert(event: &AlertEvent) -> ActionEvent {
+		match event {
+			AlertEvent::Cleared(_) | AlertEvent::Unknown(_) => ActionEvent::Cleared,
+			AlertEvent::Started(alert) if alert.severity == Severity::Info => ActionEvent::Advisory,
+			AlertEvent::Started(alert) => match alert.rule.as_str() {
+				"unreachable" => ActionEvent::Unreachable,
+				"battery_low" | "battery_critical" => ActionEvent::BatteryLow,
+				_ => ActionEvent::Threshold,
+			},
+		}
+	}
+}
+
+/**
+ * A shell command, a webhook or a push notification run on an event. The command, URL, body and text can use
+ * {{variables}}, see the README. Alert events can be limited to the listed tags (IDs or names) and threshold events
+ * to the listed rules.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct Action {
+	pub on: Vec<ActionEvent>,
+	pub command: Option<String>,
+	pub url: Option<String>,
+	// Webhook request body. Without one the variables are sent as a JSON object.
+	pub body: Option<String>,
+	pub ntfy: Option<Ntfy>,
+	pub telegram: Option<Telegram>,
+	// Text of the notifications instead of the event's own, e.g. "Fridge back within range".
+	pub text: Option<String>,
+	// Run at most once in this many minutes per tag, rule and event, so that a value going back and forth over a
+	// threshold doesn't flood the phone.
+	pub rate_limit_minutes: Option<i64>,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	#[serde(default)]
+	pub rules: Vec<String>,
+}
+
+impl Action {
+	fn applies_to(&self, event: ActionEvent, alert: Option<&Alert>) -> bool {
+		if !self.on.contains(&event) {
+			return false;
+		}
+		let Some(alert) = alert else {
+			return true;
+		};
+		return (self.tags.is_empty() || self.tags.iter().any(|key| *key == alert.tag_id || *key == alert.tag_name))
+			&& (self.rules.is_empty() || self.rules.contains(&alert.rule));
+	}
+	
+	/**
+	 * Does the action only send push notifications, so that there's nothing to run in the quiet hours.
+	*/
+	fn notifies_only(&self) -> bool {
+		return self.command.is_none() && self.url.is_none();
+	}
+}
+
+/**
+ * What the o key opens for the tag shown in the kiosk view: a URL in the browser, e.g. the room's dashboard, and/or
+ * a shell command. Both can use the {{variables}} of the tag.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct TagLink {
+	pub url: Option<String>,
+	pub command: Option<String>,
+}
+
+// Opens a URL in the default browser.
+const OPENER: &str = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+
+/**
+ * Runs the actions in the background so that a slow webhook doesn't hold up the UI.
+*/
+#[derive(Debug)]
+pub struct Actions {
+	client: Client,
+	// Set by the background threads when a command or webhook fails.
+	failed: Arc<AtomicBool>,
+	// When each action last ran per tag, rule and event, for the rate limits.
+	ran: HashMap<(usize, String), DateTime<Utc>>,
+}
+
+impl Actions {
+	pub fn new(client: Client) -> Actions {
+		return Actions { client, failed: Arc::default(), ran: HashMap::new() };
+	}
+	
+	/**
+	 * Run the actions bound to the alert events and the network state change, if any. The push notifications are left
+	 * out for the severities quiet tells to keep quiet, None for the network events.
+	*/
+	pub fn run(&mut self, actions: &[Action], alert_events: &[AlertEvent], network: Option<(ActionEvent, Option<&str>)>, data: &ApiResponse, quiet: impl Fn(Option<Severity>) -> bool, now: DateTime<Utc>) {
+		if actions.is_empty() {
+			return;
+		}
+		
+		for alert_event in alert_events {
+			let (AlertEvent::Started(alert) | AlertEvent::Cleared(alert) | AlertEvent::Unknown(alert)) = alert_event;
+			let event = ActionEvent::of_alert(alert_event);
+			let tag = data.iter().find(|tag| tag.id == alert.tag_id);
+			let variables = variables(event.name(), Some(alert), tag, None, now);
+			let key = format!("{} {} {}", alert.tag_id, alert.rule, event.name());
+			let quiet = quiet(Some(alert.severity));
+			for (index, action) in actions.iter().enumerate().filter(|(_, action)| action.applies_to(event, Some(alert))) {
+				if !(quiet && action.notifies_only()) && self.allowed(index, action, &key, now) {
+					self.spawn(action, &variables, !quiet);
+				}
+			}
+		}
+		
+		if let Some((event, error)) = network {
+			let variables = variables(event.name(), None, None, error, now);
+			let quiet = quiet(None);
+			for (index, action) in actions.iter().enumerate().filter(|(_, action)| action.applies_to(event, None)) {
+				if !(quiet && action.notifies_only()) && self.allowed(index, action, event.name(), now) {
+					self.spawn(action, &variables, !quiet);
+				}
+			}
+		}
+	}
+	
+	/**
+	 * Whether the action can run for the key within its rate limit, noting the run if so.
+	*/
+	fn allowed(&mut self, index: usize, action: &Action, key: &str, now: DateTime<Utc>) -> bool {
+		let Some(minutes) = action.rate_limit_minutes else {
+			return true;
+		};
+		let key = (index, key.to_string());
+		if self.ran.get(&key).is_some_and(|ran| now - *ran < Duration::minutes(minutes)) {
+			return false;
+		}
+		self.ran.insert(key, now);
+		return true;
+	}
+	
+	/**
+	 * Open the tag's link in the background. The output is discarded so that it doesn't end up on the TUI.
+	*/
+	pub fn open(&self, link: &TagLink, tag: &Tag, now: DateTime<Utc>) {
+		let variables = variables("open", None, Some(tag), None, now);
+		let command = link.command.as_ref().map(|command| expand(command, &variables, shell_quote));
+		let url = link.url.as_ref().map(|url| expand(url, &variables, url_encode));
+		let failed = self.failed.clone();
+		
+		thread::spawn(move || {
+			let run = |command: &mut Command| command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+				.status()
+				.is_ok_and(|status| status.success());
+			let mut ok = true;
+			if let Some(url) = url {
+				ok &= run(Command::new(OPENER).arg(url));
+			}
+			if let Some(command) = command {
+				ok &= run(Command::new("sh").arg("-c").arg(command));
+			}
+			if !ok {
+				failed.store(true, Ordering::SeqCst);
+			}
+		});
+	}
+	
+	/**
+	 * True if an action failed since the last call.
+	*/
+	pub fn take_failed(&self) -> bool {
+		return self.failed.swap(false, Ordering::SeqCst);
+	}
+	
+	fn spawn(&self, action: &Action, variables: &HashMap<&'static str, String>, notify: bool) {
+		let command = action.command.as_ref().map(|command| expand(command, variables, shell_quote));
+		let url = action.url.as_ref().map(|url| expand(url, variables, url_encode));
+		let body = match &action.body {
+			Some(body) => expand(body, variables, json_escape),
+			None => serde_json::to_string(&WebhookBody::new(variables)).unwrap_or_default(),
+		};
+		let notification = notification(action, variables);
+		let mut requests: Vec<RequestBuilder> = Vec::new();
+		if notify {
+			requests.extend(action.ntfy.iter().map(|ntfy| ntfy.request(&self.client, &notification)));
+			requests.extend(action.telegram.iter().map(|telegram| telegram.request(&self.client, &notification)));
+		}
+		if let Some(url) = url {
+			requests.push(self.client.post(url).header("Content-Type", "application/json").body(body));
+		}
+		let failed = self.failed.clone();
+		
+		thread::spawn(move || {
+			let mut ok = true;
+			if let Some(command) = command {
+				ok &= Command::new("sh").arg("-c").arg(command).status().is_ok_and(|status| status.success());
+			}
+			for request in requests {
+				ok &= request.send().is_ok_and(|response| response.status().is_success());
+			}
+			if !ok {
+				failed.store(true, Ordering::SeqCst);
+			}
+		});
+	}
+}
+
+/**
+ * Body of the webhooks without their own: the template variables with the schema version, see SCHEMA_VERSION for
+ * what may change. The values are strings like in the templates, missing ones empty.
+*/
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WebhookBody {
+	/// Version of this schema.
+	pub schema_version: u32,
+	/// unreachable, threshold, battery_low, advisory, cleared, network_error or network_restored.
+	pub event: String,
+	/// RFC 3339.
+	pub time: String,
+	pub tag_id: String,
+	pub tag_name: String,
+	/// °C with two decimals.
+	pub temperature: String,
+	/// % with two decimals.
+	pub humidity: String,
+	/// hPa with one decimal.
+	pub pressure: String,
+	/// Volts with three decimals.
+	pub battery_voltage: String,
+	pub movement_counter: String,
+	pub measurement_sequence: String,
+	/// ID of the alert, the same when it starts and clears.
+	pub alert_id: String,
+	pub rule: String,
+	/// info, warning or critical.
+	pub severity: String,
+	pub message: String,
+	/// The value that started the alert.
+	pub value: String,
+	/// The network error.
+	pub error: String,
+}
+
+impl WebhookBody {
+	fn new(variables: &HashMap<&'static str, String>) -> WebhookBody {
+		let variable = |name: &str| variables.get(name).cloned().unwrap_or_default();
+		return WebhookBody {
+			schema_version: SCHEMA_VERSION,
+			event: variable("event"),
+			time: variable("time"),
+			tag_id: variable("tag_id"),
+			tag_name: variable("tag_name"),
+			temperature: variable("temperature"),
+			humidity: variable("humidity"),
+			pressure: variable("pressure"),
+			battery_voltage: variable("battery_voltage"),
+			movement_counter: variable("movement_counter"),
+			measurement_sequence: variable("measurement_sequence"),
+			alert_id: variable("alert_id"),
+			rule: variable("rule"),
+			severity: variable("severity"),
+			message: variable("message"),
+			value: variable("value"),
+			error: variable("error"),
+		};
+	}
+}
+
+/**
+ * The template variables of an event. Missing values are empty.
+*/
+fn variables(event: &str, alert: Option<&Alert>, tag: Option<&Tag>, error: Option<&str>, now: DateTime<Utc>) -> HashMap<&'static str, String> {
+	let number = |value: Option<f64>, decimals: usize| value.map(|value| format!("{:.*}", decimals, value)).unwrap_or_default();
+	
+	return HashMap::from([
+		("event", event.to_string()),
+		("time", now.to_rfc3339()),
+		("tag_id", alert.map(|alert| alert.tag_id.clone()).or(tag.map(|tag| tag.id.clone())).unwrap_or_default()),
+		("tag_name", alert.map(|alert| alert.tag_name.clone()).or(tag.map(|tag| tag.label().to_string())).unwrap_or_default()),
+		("temperature", number(tag.and_then(Tag::temperature), 2)),
+		("humidity", number(tag.and_then(Tag::humidity), 2)),
+		("pressure", number(tag.and_then(Tag::pressure), 1)),
+		("battery_voltage", number(tag.and_then(|tag| tag.battery_voltage), 3)),
+		("movement_counter", tag.and_then(|tag| tag.movement_counter).map(|count| count.to_string()).unwrap_or_default()),
+		("measurement_sequence", tag.and_then(|tag| tag.measurement_sequence).map(|sequence| sequence.to_string()).unwrap_or_default()),
+		("alert_id", alert.map(|alert| alert.id()).unwrap_or_default()),
+		("rule", alert.map(|alert| alert.rule.clone()).unwrap_or_default()),
+		("severity", alert.map(|alert| alert.severity.name().to_string()).unwrap_or_default()),
+		("message", alert.map(|alert| alert.message.clone()).unwrap_or_default()),
+		("value", number(alert.and_then(|alert| alert.value), 2)),
+		("error", error.unwrap_or_default().to_string()),
+	]);
+}
+
+/**
+ * The push notification of an event: the tag as the title and the action's text, or the event's own, e.g. "Fridge
+ * back within range".
+*/
+fn notification(action: &Action, variables: &HashMap<&'static str, String>) -> Notification {
+	let variable = |name: &str| variables.get(name).map_or("", String::as_str);
+	let (event, tag) = (variable("event"), variable("tag_name"));
+	let text = match (&action.text, event, variable("rule")) {
+		(Some(text), _, _) => expand(text, variables, |value| value.to_string()),
+		(None, "cleared", "unreachable") => format!("{} reachable again", tag),
+		(None, "cleared", "battery_low" | "battery_critical") => format!("{} battery back to normal", tag),
+		(None, "cleared", _) => format!("{} back within range", tag),
+		(None, "network_error", _) => format!("Network error: {}", variable("error")),
+		(None, "network_restored", _) => "Network restored".into(),
+		(None, _, _) => format!("{}: {}", tag, variable("message")),
+	};
+	return Notification {
+		title: if tag.is_empty() { "Ruuvi".into() } else { tag.into() },
+		text,
+		severity: variable("severity").into(),
+		cleared: event == "cleared",
+	};
+}
+
+/**
+ * Replace the {{variables}} in the template, escaping the values for where they end up. Unknown variables are
+ * left as they are.
+*/
+fn expand(template: &str, variables: &HashMap<&'static str, String>, escape: fn(&str) -> String) -> String {
+	let mut result = String::new();
+	let mut rest = template;
+	
+	while let Some(start) = rest.find("{{") {
+		let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+			break;
+		};
+		result += &rest[..start];
+		match variables.get(rest[start + 2..end].trim()) {
+			Some(value) => result += &escape(value),
+			None => result += &rest[start..end + 2],
+		}
+		rest = &rest[end + 2..];
+	}
+	
+	return result + rest;
+}
+
+/**
+ * Values in commands are single quoted so that tag names and messages can't run anything.
+*/
+fn shell_quote(value: &str) -> String {
+	return format!("'{}'", value.replace('\'', "'\\''"));
+}
+
+/**
+ * Values in a JSON body go inside strings written in the template, e.g. "{{tag_name}}".
+*/
+fn json_escape(value: &str) -> String {
+	let quoted = serde_json::to_string(value).unwrap_or_default();
+	return quoted[1..quoted.len() - 1].to_string();
+}
+
+fn url_encode(value: &str) -> String {
+	return value.bytes()
+		.map(|byte| match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+			_ => format!("%{:02X}", byte),
+		})
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::Metric;
+	
+	fn variables() -> HashMap<&'static str, String> {
+		return HashMap::from([("tag_name", "Bob's sauna".to_string()), ("temperature", "81.50".to_string())]);
+	}
+	
+	#[test]
+	fn commands_quote_the_values() {
+		assert_eq!(
+			expand("notify-send {{tag_name}} {{ temperature }}", &variables(), shell_quote),
+			"notify-send 'Bob'\\''s sauna' '81.50'"
+		);
+	}
+	
+	#[test]
+	fn bodies_escape_the_values() {
+		assert_eq!(
+			expand(r#"{"text": "{{tag_name}}: \"{{temperature}}\""}"#, &HashMap::from([("tag_name", "a\"b".to_string()), ("temperature", "1".to_string())]), json_escape),
+			r#"{"text": "a\"b: \"1\""}"#
+		);
+		assert_eq!(expand("https://example.com/?t={{tag_name}}", &variables(), url_encode), "https://example.com/?t=Bob%27s%20sauna");
+	}
+	
+	#[test]
+	fn tag_variables_without_an_alert() {
+		let tag = Tag {
+			temperature: Some(Metric::from_current(65.0)),
+			..Tag::test("DE:AD:BE:EF:00:01", "Sauna")
+		};
+		let variables = super::variables("open", None, Some(&tag), None, Utc::now());
+		assert_eq!(expand("https://grafana.example.com/d/{{tag_name}}?var-tag={{tag_id}}&t={{temperature}}", &variables, url_encode), "https://grafana.example.com/d/Sauna?var-tag=DE%3AAD%3ABE%3AEF%3A00%3A01&t=65.00");
+	}
+	
+	#[test]
+	fn webhook_body_has_every_variable() {
+		let variables = super::variables("cleared", None, None, None, Utc::now());
+		let body = serde_json::to_value(WebhookBody::new(&variables)).unwrap();
+		for name in variables.keys() {
+			assert!(body.get(name).is_some(), "{} missing from the webhook body", name);
+		}
+		assert_eq!(body["schema_version"], SCHEMA_VERSION);
+		assert_eq!(body["event"], "cleared");
+	}
+	
+	#[test]
+	fn notifications_and_rate_limits() {
+		let action: Action = toml::from_str(r#"
+			on = ["threshold", "cleared"]
+			ntfy = { topic = "cabin" }
+			rate_limit_minutes = 10
+		"#).unwrap();
+		let mut variables = HashMap::from([
+			("event", "threshold".to_string()),
+			("tag_name", "Fridge".to_string()),
+			("rule", "fridge_warm".to_string()),
+			("severity", "warning".to_string()),
+			("message", "temperature above 6.00".to_string()),
+		]);
+		assert_eq!(notification(&action, &variables).text, "Fridge: temperature above 6.00");
+		variables.insert("event", "cleared".into());
+		let cleared = notification(&action, &variables);
+		assert_eq!((cleared.text.as_str(), cleared.cleared), ("Fridge back within range", true));
+		let custom = Action { text: Some("{{tag_name}} ok".into()), ..action.clone() };
+		assert_eq!(notification(&custom, &variables).text, "Fridge ok");
+		
+		let mut actions = Actions::new(Client::new());
+		let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		assert!(actions.allowed(0, &action, "1 fridge_warm threshold", now));
+		assert!(!actions.allowed(0, &action, "1 fridge_warm threshold", now + Duration::minutes(9)));
+		assert!(actions.allowed(0, &action, "1 fridge_warm cleared", now + Duration::minutes(9)));
+		assert!(actions.allowed(0, &action, "1 fridge_warm threshold", now + Duration::minutes(10)));
+		assert!(actions.allowed(0, &Action { rate_limit_minutes: None, ..action }, "1 fridge_warm threshold", now));
+	}
+	
+	#[test]
+	fn unknown_variables_stay() {
+		assert_eq!(expand("{{nope}} {{tag_name", &variables(), json_escape), "{{nope}} {{tag_name");
+	}
+}
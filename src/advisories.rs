@@ -0,0 +1,99 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::history::History;
+use crate::model::ApiResponse;
+
+// Readings compared for the sag, long enough for a night outdoors or a few defrost cycles of a freezer.
+const WINDOW_HOURS: i64 = 48;
+// Below freezing the coin cell can't keep its voltage up under the load of the radio once it is weak.
+const COLD: f64 = 0.0;
+// Readings at least this much warmer than the coldest one are compared against it.
+const WARMER: f64 = 10.0;
+// A fresh cell sags less than this in the cold.
+const SAG: f64 = 0.15;
+
+/**
+ * Built-in advisory of a tag: something the readings tell about the tag itself rather than what it measures. Shown
+ * as an informational alert, which doesn't change the alert state or run the hooks.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+	pub tag_id: String,
+	pub rule: &'static str,
+	pub value: f64,
+	pub message: String,
+}
+
+/**
+ * The advisories of the tags from their recent history.
+*/
+pub fn check(data: &ApiResponse, history: &History, now: DateTime<Utc>) -> Vec<Advisory> {
+	return data.iter()
+		.filter_map(|tag| voltage_sag(history, &tag.id, now).map(|voltage| Advisory {
+			tag_id: tag.id.clone(),
+			rule: "voltage_sag",
+			value: voltage,
+			message: format!("voltage sag to {:.2} V at low temperature detected — battery weak", voltage),
+		}))
+		.collect();
+}
+
+/**
+ * The lowest voltage below freezing, if it is well below the voltage when much warmer. A weak CR2477 still shows a
+ * good voltage in the warm, but its internal resistance makes it sag in the cold, and the tag starts resetting or
+ * drops out long before the voltage alone would say the battery is low.
+*/
+fn voltage_sag(history: &History, tag_id: &str, now: DateTime<Utc>) -> Option<f64> {
+	let since = now - Duration::hours(WINDOW_HOURS);
+	let readings: Vec<(f64, f64)> = history.samples(tag_id)
+		.filter(|sample| sample.datetime >= since)
+		.filter_map(|sample| Some((sample.temperature?, sample.battery_voltage?)))
+		.collect();
+	
+	let coldest = readings.iter().map(|(temperature, _)| *temperature).reduce(f64::min).filter(|coldest| *coldest < COLD)?;
+	let cold_voltage = readings.iter()
+		.filter(|(temperature, _)| *temperature < COLD)
+		.map(|(_, voltage)| *voltage)
+		.reduce(f64::min)?;
+	let warm_voltage = readings.iter()
+		.filter(|(temperature, _)| *temperature >= coldest + WARMER)
+		.map(|(_, voltage)| *voltage)
+		.reduce(f64::max)?;
+	return (warm_voltage - cold_voltage >= SAG).then_some(cold_voltage);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::store::Reading;
+	
+	fn history(readings: &[(i64, f64, f64)], now: DateTime<Utc>) -> History {
+		let readings: Vec<Reading> = readings.iter()
+			.map(|(hours_ago, temperature, voltage)| Reading {
+				tag_id: "1".into(),
+				tag_name: String::new(),
+				datetime: now - Duration::hours(*hours_ago),
+				temperature: *temperature,
+				humidity: 80.0,
+				pressure: None,
+				battery_voltage: Some(*voltage),
+			})
+			.collect();
+		let mut history = History::new(Duration::days(7));
+		history.seed(&readings);
+		return history;
+	}
+	
+	#[test]
+	fn sag_in_the_cold() {
+		let now = "2024-01-10T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		// Day and night outdoors.
+		assert_eq!(voltage_sag(&history(&[(20, 8.0, 2.95), (10, -12.0, 2.72), (1, 6.0, 2.93)], now), "1", now), Some(2.72));
+		// A fresh cell barely sags.
+		assert_eq!(voltage_sag(&history(&[(20, 8.0, 3.0), (10, -12.0, 2.94)], now), "1", now), None);
+		// Never below freezing, or never warm enough to compare.
+		assert_eq!(voltage_sag(&history(&[(20, 25.0, 2.95), (10, 2.0, 2.7)], now), "1", now), None);
+		assert_eq!(voltage_sag(&history(&[(20, -15.0, 2.95), (10, -20.0, 2.7)], now), "1", now), None);
+		// Only the recent readings.
+		assert_eq!(voltage_sag(&history(&[(72, 8.0, 2.95), (10, -12.0, 2.72)], now), "1", now), None);
+	}
+}
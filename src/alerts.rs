@@ -0,0 +1,635 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::advisories::Advisory;
+use crate::clock::Zone;
+use crate::history::{History, Sample};
+use crate::metrics::DerivedMetric;
+use crate::model::{ApiResponse, Tag};
+use crate::ranges::{ColorRange, RangeColor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+	// Advisories, and rules only worth a look: don't change the alert state or run the hooks.
+	Info,
+	Warning,
+	Critical,
+}
+
+impl Severity {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Severity::Info => "info",
+			Severity::Warning => "warning",
+			Severity::Critical => "critical",
+		}
+	}
+	
+	pub fn from_name(name: &str) -> Option<Severity> {
+		match name {
+			"info" => Some(Severity::Info),
+			"warning" => Some(Severity::Warning),
+			"critical" => Some(Severity::Critical),
+			_ => None,
+		}
+	}
+}
+
+/**
+ * Hours of the day when the alerts keep quiet, e.g. "22:00-07:00" over the midnight: no bell and no push
+ * notifications, though the alerts are shown as usual. Critical alerts ring and notify anyway.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct QuietHours {
+	pub start: NaiveTime,
+	pub end: NaiveTime,
+}
+
+impl TryFrom<String> for QuietHours {
+	type Error = String;
+	
+	fn try_from(text: String) -> Result<QuietHours, String> {
+		let invalid = || format!("Invalid quiet hours \"{}\", use e.g. 22:00-07:00", text);
+		let (start, end) = text.split_once(['-', '–']).ok_or_else(invalid)?;
+		let time = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+		return Ok(QuietHours { start: time(start)?, end: time(end)? });
+	}
+}
+
+impl QuietHours {
+	/**
+	 * Is the time of the day within the quiet hours, from the start up to the end.
+	*/
+	pub fn contains(&self, time: NaiveTime) -> bool {
+		if self.start <= self.end {
+			return time >= self.start && time < self.end;
+		}
+		return time >= self.start || time < self.end;
+	}
+	
+	/**
+	 * Should an alert of the severity keep quiet at the moment in the zone. The network events have no severity.
+	*/
+	pub fn silences(&self, severity: Option<Severity>, now: DateTime<Utc>, zone: Zone) -> bool {
+		return severity != Some(Severity::Critical) && self.contains(zone.local(now).time());
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+	Temperature,
+	Humidity,
+	Pressure,
+	BatteryVoltage,
+	// Signal strength in dBm, e.g. below -90 when the tag was moved away from the gateway or something is in between.
+	Rssi,
+	// Dew point, absolute humidity, heat index or vapor pressure deficit, calculated from the temperature and humidity.
+	#[serde(untagged)]
+	Derived(DerivedMetric),
+}
+
+impl AlertMetric {
+	pub fn value(&self, tag: &Tag) -> Option<f64> {
+		match self {
+			AlertMetric::Temperature => tag.temperature(),
+			AlertMetric::Humidity => tag.humidity(),
+			AlertMetric::Pressure => tag.pressure(),
+			AlertMetric::BatteryVoltage => tag.battery_voltage,
+			AlertMetric::Rssi => tag.rssi.map(f64::from),
+			AlertMetric::Derived(derived) => Some(derived.value(tag.temperature()?, tag.humidity()?)),
+		}
+	}
+	
+	/**
+	 * The value in a sample of the history, None for the signal strength which isn't kept there.
+	*/
+	pub fn sample_value(&self, sample: &Sample) -> Option<f64> {
+		match self {
+			AlertMetric::Temperature => sample.temperature,
+			AlertMetric::Humidity => sample.humidity,
+			AlertMetric::Pressure => sample.pressure,
+			AlertMetric::BatteryVoltage => sample.battery_voltage,
+			AlertMetric::Rssi => None,
+			AlertMetric::Derived(derived) => Some(derived.value(sample.temperature?, sample.humidity?)),
+		}
+	}
+	
+	/**
+	 * Whether the values are temperatures, shown in the temperature unit.
+	*/
+	pub fn is_temperature(&self) -> bool {
+		return match self {
+			AlertMetric::Temperature => true,
+			AlertMetric::Derived(derived) => derived.is_temperature(),
+			_ => false,
+		};
+	}
+	
+	pub fn name(&self) -> &'static str {
+		match self {
+			AlertMetric::Temperature => "temperature",
+			AlertMetric::Humidity => "humidity",
+			AlertMetric::Pressure => "pressure",
+			AlertMetric::BatteryVoltage => "battery voltage",
+			AlertMetric::Rssi => "signal strength",
+			AlertMetric::Derived(derived) => derived.name(),
+		}
+	}
+}
+
+/**
+ * Threshold alert: the metric above or below the limit, or change alert: the metric rose or fell more than the amount
+ * within the minutes, e.g. a freezer door left open. Applies to the listed tags (IDs or names), or all tags.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRule {
+	pub name: String,
+	pub metric: AlertMetric,
+	pub above: Option<f64>,
+	pub below: Option<f64>,
+	// Color range of the metric whose open end is the above or below limit instead, so that the alert and the colors
+	// agree, e.g. "red" for above 28 °C.
+	pub range: Option<RangeColor>,
+	// Rise or fall from the lowest or the highest value within the minutes to the latest, from the history.
+	pub rise: Option<f64>,
+	pub fall: Option<f64>,
+	#[serde(default = "default_within_minutes")]
+	pub within_minutes: i64,
+	#[serde(default = "default_severity")]
+	pub severity: Severity,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	// Without data from the tag for this long an active alert is resolved as unknown, so that a dead tag doesn't
+	// hold it open forever.
+	#[serde(default)]
+	pub resolve_after_minutes: Option<i64>,
+}
+
+fn default_severity() -> Severity {
+	return Severity::Warning;
+}
+
+fn default_within_minutes() -> i64 {
+	return 30;
+}
+
+impl AlertRule {
+	/**
+	 * Take the above or below limit from the color range the rule refers to: the min of a range open at the top, or
+	 * the max of one open at the bottom. The ranges are the global ones of the metric.
+	*/
+	pub fn resolve_range(&mut self, ranges: &[ColorRange]) -> Result<(), String> {
+		let Some(color) = self.range else {
+			return Ok(());
+		};
+		if self.above.is_some() || self.below.is_some() {
+			return Err(format!("The alert rule \"{}\" has both a range and above or below", self.name));
+		}
+		let Some(range) = ranges.iter().find(|range| range.color == color) else {
+			return Err(format!("No {} range of {} for the alert rule \"{}\"", color.name(), self.metric.name(), self.name));
+		};
+		match (range.min, range.max) {
+			(Some(min), None) => self.above = Some(min),
+			(None, Some(max)) => self.below = Some(max),
+			_ => return Err(format!("The {} range of the alert rule \"{}\" isn't open at one end", color.name(), self.name)),
+		}
+		return Ok(());
+	}
+	
+	/**
+	 * Check that the rule has a limit to alert on and a positive window for the changes.
+	*/
+	pub fn validate(&self) -> Result<(), String> {
+		if self.above.is_none() && self.below.is_none() && self.rise.is_none() && self.fall.is_none() {
+			return Err(format!("The alert rule \"{}\" has none of above, below, rise or fall", self.name));
+		}
+		if self.within_minutes <= 0 {
+			return Err(format!("The within_minutes of the alert rule \"{}\" must be positive", self.name));
+		}
+		return Ok(());
+	}
+	
+	pub fn applies_to(&self, tag: &Tag) -> bool {
+		return self.tags.is_empty() || self.tags.iter().any(|key| *key == tag.id || *key == tag.name);
+	}
+	
+	/**
+	 * Whether the tag's state is unknown to the rule: no data for the resolve timeout.
+	*/
+	fn is_unknown(&self, tag: &Tag, now: DateTime<Utc>) -> bool {
+		return self.resolve_after_minutes.is_some_and(|minutes| {
+			tag.measured_at().is_some_and(|measured_at| now - measured_at >= Duration::minutes(minutes))
+		});
+	}
+	
+	/**
+	 * The value and a description of the breached threshold or change, and whether the breach is above it or a rise.
+	*/
+	fn breach(&self, tag: &Tag, history: &History) -> Option<(f64, String, bool)> {
+		let value = self.metric.value(tag)?;
+		if let Some(above) = self.above.filter(|above| value > *above) {
+			return Some((value, format!("{} above {:.2} ({})", self.metric.name(), above, self.name), true));
+		}
+		if let Some(below) = self.below.filter(|below| value < *below) {
+			return Some((value, format!("{} below {:.2} ({})", self.metric.name(), below, self.name), false));
+		}
+		if self.rise.is_none() && self.fall.is_none() {
+			return None;
+		}
+		// The window ends at the latest measurement rather than now, like the history, so that replayed data works too.
+		let since = tag.measured_at()? - Duration::minutes(self.within_minutes);
+		let (min, max) = history.range(&tag.id, since, |sample| self.metric.sample_value(sample))?;
+		if self.rise.is_some_and(|rise| value - min > rise) {
+			return Some((value, format!("{} rose {:.2} within {} min ({})", self.metric.name(), value - min, self.within_minutes, self.name), true));
+		}
+		if self.fall.is_some_and(|fall| max - value > fall) {
+			return Some((value, format!("{} fell {:.2} within {} min ({})", self.metric.name(), max - value, self.within_minutes, self.name), false));
+		}
+		return None;
+	}
+}
+
+/**
+ * An active alert of a tag.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+	pub tag_id: String,
+	pub tag_name: String,
+	// Built-in battery_low, battery_critical and unreachable, an advisory or the name of a threshold rule.
+	pub rule: String,
+	pub severity: Severity,
+	pub message: String,
+	// The metric value that started a threshold alert, and the most extreme value while it has been active.
+	pub value: Option<f64>,
+	pub peak: Option<f64>,
+	pub started_at: DateTime<Utc>,
+}
+
+impl Alert {
+	/**
+	 * Stable ID of the alert occurrence: tag, rule and start time. Stays the same across restarts
+	 * when the active alerts are restored from the store.
+	*/
+	pub fn id(&self) -> String {
+		return format!("{}:{}:{}", self.tag_id, self.rule, self.started_at.timestamp());
+	}
+}
+
+// The value and message of an alert condition that is on, and whether higher values are worse.
+type Breach = Option<(Option<f64>, String, bool)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertEvent {
+	Started(Alert),
+	Cleared(Alert),
+	// The tag stopped sending data while the alert was active: neither alerting nor clear.
+	Unknown(Alert),
+}
+
+/**
+ * Keeps track of the active alerts, turning the tag states and threshold rules into start and clear events.
+*/
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+	active: HashMap<(String, String), Alert>,
+}
+
+impl AlertEngine {
+	pub fn new() -> AlertEngine {
+		return AlertEngine::default();
+	}
+	
+	/**
+	 * Continue the alerts that were active before a restart. They keep their IDs and don't start again.
+	*/
+	pub fn restore(&mut self, alerts: Vec<Alert>) {
+		for alert in alerts {
+			self.active.insert((alert.tag_id.clone(), alert.rule.clone()), alert);
+		}
+	}
+	
+	/**
+	 * Evaluate the tags against the built-in and configured rules, and start informational alerts of the advisories.
+	 * The change rules look at the history. Returns what started and cleared.
+	*/
+	pub fn evaluate(&mut self, data: &ApiResponse, history: &History, rules: &[AlertRule], advisories: &[Advisory], now: DateTime<Utc>) -> Vec<AlertEvent> {
+		let mut events = Vec::new();
+		
+		for tag in data {
+			// Rule name, severity and the value and message if the alert condition is on.
+			let mut conditions: Vec<(String, Severity, Breach)> = vec![
+				("battery_low".into(), Severity::Warning, (tag.battery_low && !tag.battery_critical).then(|| (tag.battery_voltage, "battery low".into(), false))),
+				("battery_critical".into(), Severity::Critical, tag.battery_critical.then(|| (tag.battery_voltage, "battery critical".into(), false))),
+				("unreachable".into(), Severity::Warning, tag.unreachable.then(|| (None, "unreachable".into(), false))),
+			];
+			// The advisories that are off aren't listed, and clear like removed rules.
+			for advisory in advisories.iter().filter(|advisory| advisory.tag_id == tag.id) {
+				conditions.push((advisory.rule.into(), Severity::Info, Some((Some(advisory.value), advisory.message.clone(), false))));
+			}
+			// Rules that can't tell without data. Their alerts don't start or clear, but end as unknown.
+			let mut unknown = Vec::new();
+			for rule in rules.iter().filter(|rule| rule.applies_to(tag)) {
+				if rule.is_unknown(tag, now) {
+					unknown.push(rule.name.clone());
+					continue;
+				}
+				let breach = rule.breach(tag, history).map(|(value, message, above)| (Some(value), message, above));
+				conditions.push((rule.name.clone(), rule.severity, breach));
+			}
+			
+			// Alerts of rules that were removed from the config.
+			let removed: Vec<(String, String)> = self.active.keys()
+				.filter(|(tag_id, rule)| *tag_id == tag.id && !conditions.iter().any(|(name, _, _)| name == rule) && !unknown.contains(rule))
+				.cloned()
+				.collect();
+			for key in removed {
+				if let Some(alert) = self.active.remove(&key) {
+					events.push(AlertEvent::Cleared(alert));
+				}
+			}
+			
+			for rule in unknown {
+				if let Some(alert) = self.active.remove(&(tag.id.clone(), rule)) {
+					events.push(AlertEvent::Unknown(alert));
+				}
+			}
+			
+			for (rule, severity, breach) in conditions {
+				let key = (tag.id.clone(), rule.clone());
+				match (breach, self.active.get_mut(&key)) {
+					(Some((value, message, _)), None) => {
+						let alert = Alert {
+							tag_id: tag.id.clone(),
+							tag_name: tag.label().to_string(),
+							rule,
+							severity,
+							message,
+							value,
+							peak: value,
+							started_at: now,
+						};
+						self.active.insert(key, alert.clone());
+						events.push(AlertEvent::Started(alert));
+					},
+					(Some((Some(value), _, higher_is_worse)), Some(alert)) => {
+						alert.peak = Some(match alert.peak {
+							Some(peak) if higher_is_worse => peak.max(value),
+							Some(peak) => peak.min(value),
+							None => value,
+						});
+					},
+					(None, Some(_)) => {
+						if let Some(alert) = self.active.remove(&key) {
+							events.push(AlertEvent::Cleared(alert));
+						}
+					},
+					_ => {},
+				}
+			}
+		}
+		
+		return events;
+	}
+	
+	/**
+	 * Take the alerts active in the headless instance a TUI is attached to, instead of evaluating. Returns what started
+	 * and cleared since the previous alerts.
+	*/
+	pub fn sync(&mut self, alerts: Vec<Alert>) -> Vec<AlertEvent> {
+		let mut active: HashMap<(String, String), Alert> = alerts.into_iter()
+			.map(|alert| ((alert.tag_id.clone(), alert.rule.clone()), alert))
+			.collect();
+		let mut events: Vec<AlertEvent> = self.active.iter()
+			.filter(|(key, _)| !active.contains_key(*key))
+			.map(|(_, alert)| AlertEvent::Cleared(alert.clone()))
+			.collect();
+		events.extend(active.iter()
+			.filter(|(key, alert)| self.active.get(*key).is_none_or(|previous| previous.started_at != alert.started_at))
+			.map(|(_, alert)| AlertEvent::Started(alert.clone())));
+		std::mem::swap(&mut self.active, &mut active);
+		return events;
+	}
+	
+	pub fn active(&self) -> impl Iterator<Item = &Alert> {
+		return self.active.values();
+	}
+	
+	/**
+	 * The most severe active alert level, None if all is well. Informational alerts don't count.
+	*/
+	pub fn severity(&self) -> Option<Severity> {
+		return self.active.values().map(|alert| alert.severity).filter(|severity| *severity > Severity::Info).max();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::Metric;
+	
+	fn tag(temperature: f64, battery_low: bool) -> Tag {
+		return Tag {
+			temperature: Some(Metric::from_current(temperature)),
+			humidity: Some(Metric::from_current(40.0)),
+			battery_low,
+			..Tag::test("1", "Freezer")
+		};
+	}
+	
+	fn history() -> History {
+		return History::new(Duration::days(1));
+	}
+	
+	fn rules() -> Vec<AlertRule> {
+		return toml::from_str::<HashMap<String, Vec<AlertRule>>>(r#"
+			[[alerts]]
+			name = "freezer_warm"
+			metric = "temperature"
+			above = -15.0
+			severity = "critical"
+			tags = ["Freezer"]
+		"#).unwrap().remove("alerts").unwrap();
+	}
+	
+	#[test]
+	fn threshold_starts_and_clears() {
+		let mut engine = AlertEngine::new();
+		let now = Utc::now();
+		
+		assert!(engine.evaluate(&vec![tag(-18.0, false)], &history(), &rules(), &[], now).is_empty());
+		assert_eq!(engine.severity(), None);
+		
+		let events = engine.evaluate(&vec![tag(-12.0, false)], &history(), &rules(), &[], now);
+		assert!(matches!(&events[..], [AlertEvent::Started(alert)] if alert.rule == "freezer_warm"));
+		assert_eq!(engine.severity(), Some(Severity::Critical));
+		
+		// Still breaching, no new events.
+		assert!(engine.evaluate(&vec![tag(-10.0, false)], &history(), &rules(), &[], now).is_empty());
+		
+		let events = engine.evaluate(&vec![tag(-16.0, false)], &history(), &rules(), &[], now);
+		assert!(matches!(&events[..], [AlertEvent::Cleared(alert)] if alert.rule == "freezer_warm" && alert.peak == Some(-10.0)));
+		assert_eq!(engine.severity(), None);
+	}
+	
+	#[test]
+	fn restored_alerts_keep_their_id() {
+		let started_at = Utc::now() - chrono::Duration::hours(1);
+		let mut previous = AlertEngine::new();
+		previous.evaluate(&vec![tag(-12.0, false)], &history(), &rules(), &[], started_at);
+		let alerts: Vec<Alert> = previous.active.values().cloned().collect();
+		let id = alerts[0].id();
+		
+		let mut engine = AlertEngine::new();
+		engine.restore(alerts);
+		assert!(engine.evaluate(&vec![tag(-11.0, false)], &history(), &rules(), &[], Utc::now()).is_empty());
+		
+		let events = engine.evaluate(&vec![tag(-20.0, false)], &history(), &rules(), &[], Utc::now());
+		assert!(matches!(&events[..], [AlertEvent::Cleared(alert)] if alert.id() == id));
+	}
+	
+	#[test]
+	fn alerts_of_removed_rules_clear() {
+		let mut engine = AlertEngine::new();
+		engine.evaluate(&vec![tag(-12.0, false)], &history(), &rules(), &[], Utc::now());
+		
+		let events = engine.evaluate(&vec![tag(-12.0, false)], &history(), &[], &[], Utc::now());
+		assert!(matches!(&events[..], [AlertEvent::Cleared(alert)] if alert.rule == "freezer_warm"));
+	}
+	
+	#[test]
+	fn unknown_without_data() {
+		let mut rules = rules();
+		rules[0].resolve_after_minutes = Some(30);
+		let measured_at = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let stale = Tag { datetime: "2024-05-01T12:00:00Z".into(), ..tag(-12.0, false) };
+		
+		let mut engine = AlertEngine::new();
+		engine.evaluate(&vec![stale.clone()], &history(), &rules, &[], measured_at);
+		assert!(engine.evaluate(&vec![stale.clone()], &history(), &rules, &[], measured_at + Duration::minutes(29)).is_empty());
+		
+		let events = engine.evaluate(&vec![stale.clone()], &history(), &rules, &[], measured_at + Duration::minutes(30));
+		assert!(matches!(&events[..], [AlertEvent::Unknown(alert)] if alert.rule == "freezer_warm"));
+		assert_eq!(engine.severity(), None);
+		// And doesn't start again until there's data.
+		assert!(engine.evaluate(&vec![stale], &history(), &rules, &[], measured_at + Duration::hours(5)).is_empty());
+		
+		let fresh = Tag { datetime: "2024-05-01T17:00:00Z".into(), ..tag(-12.0, false) };
+		let events = engine.evaluate(&vec![fresh], &history(), &rules, &[], measured_at + Duration::hours(5));
+		assert!(matches!(&events[..], [AlertEvent::Started(_)]));
+	}
+	
+	#[test]
+	fn rules_on_derived_metrics() {
+		let rules = toml::from_str::<HashMap<String, Vec<AlertRule>>>(r#"
+			[[alerts]]
+			name = "damp"
+			metric = "absolute_humidity"
+			above = 8.0
+		"#).unwrap().remove("alerts").unwrap();
+		assert_eq!(rules[0].metric, AlertMetric::Derived(DerivedMetric::AbsoluteHumidity));
+		
+		// 40% is 6.9 g/m³ at 20°C and 9.1 g/m³ at 26°C.
+		let mut engine = AlertEngine::new();
+		assert!(engine.evaluate(&vec![tag(20.0, false)], &history(), &rules, &[], Utc::now()).is_empty());
+		let events = engine.evaluate(&vec![tag(26.0, false)], &history(), &rules, &[], Utc::now());
+		assert!(matches!(&events[..], [AlertEvent::Started(alert)] if alert.message.starts_with("absolute humidity above 8.00")));
+	}
+	
+	#[test]
+	fn rate_of_change() {
+		let rules = toml::from_str::<HashMap<String, Vec<AlertRule>>>(r#"
+			[[alerts]]
+			name = "door_open"
+			metric = "temperature"
+			rise = 5.0
+			within_minutes = 30
+		"#).unwrap().remove("alerts").unwrap();
+		let at = |minutes: i64, temperature: f64| Tag {
+			datetime: ("2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::minutes(minutes)).to_rfc3339(),
+			..tag(temperature, false)
+		};
+		let mut history = history();
+		let mut engine = AlertEngine::new();
+		let mut evaluate = |tag: Tag| {
+			history.add(&vec![tag.clone()]);
+			return engine.evaluate(&vec![tag.clone()], &history, &rules, &[], tag.measured_at().unwrap());
+		};
+		
+		assert!(evaluate(at(0, -18.0)).is_empty());
+		assert!(evaluate(at(10, -15.0)).is_empty());
+		let events = evaluate(at(20, -12.5));
+		assert!(matches!(&events[..], [AlertEvent::Started(alert)] if alert.message == "temperature rose 5.50 within 30 min (door_open)"));
+		assert!(evaluate(at(25, -11.0)).is_empty());
+		// Slowly enough once the -18 °C is out of the window.
+		assert!(matches!(&evaluate(at(35, -11.0))[..], [AlertEvent::Cleared(alert)] if alert.peak == Some(-11.0)));
+	}
+	
+	#[test]
+	fn rules_on_color_ranges() {
+		let rule = |limits: &str| toml::from_str::<AlertRule>(&format!("name = \"hot\"\nmetric = \"temperature\"\n{}", limits)).unwrap();
+		let mut hot = rule("range = \"red\"");
+		hot.resolve_range(crate::ranges::DEFAULT_TEMPERATURE).unwrap();
+		assert_eq!((hot.above, hot.below), (Some(28.0), None));
+		let mut freezing = rule("range = \"blue\"");
+		freezing.resolve_range(crate::ranges::DEFAULT_TEMPERATURE).unwrap();
+		assert_eq!((freezing.above, freezing.below), (None, Some(0.0)));
+		assert!(rule("range = \"green\"").resolve_range(crate::ranges::DEFAULT_TEMPERATURE).is_err());
+		assert!(rule("range = \"magenta\"").resolve_range(crate::ranges::DEFAULT_TEMPERATURE).is_err());
+		assert!(rule("range = \"red\"\nabove = 30.0").resolve_range(crate::ranges::DEFAULT_TEMPERATURE).is_err());
+	}
+	
+	#[test]
+	fn rules_need_a_limit_and_a_window() {
+		let rule = |limits: &str| toml::from_str::<AlertRule>(&format!("name = \"sauna\"\nmetric = \"temperature\"\n{}", limits)).unwrap();
+		assert!(rule("above = 80.0").validate().is_ok());
+		assert!(rule("fall = 5.0\nwithin_minutes = 10").validate().is_ok());
+		assert!(rule("").validate().is_err());
+		assert!(rule("rise = 5.0\nwithin_minutes = 0").validate().is_err());
+	}
+	
+	#[test]
+	fn most_severe_alert_wins() {
+		let mut engine = AlertEngine::new();
+		engine.evaluate(&vec![tag(-18.0, true)], &history(), &rules(), &[], Utc::now());
+		assert_eq!(engine.severity(), Some(Severity::Warning));
+		
+		engine.evaluate(&vec![tag(-5.0, true)], &history(), &rules(), &[], Utc::now());
+		assert_eq!(engine.severity(), Some(Severity::Critical));
+		assert_eq!(engine.active.len(), 2);
+	}
+	
+	#[test]
+	fn advisories_are_informational() {
+		let advisory = Advisory { tag_id: "1".into(), rule: "voltage_sag", value: 2.72, message: "battery weak".into() };
+		let mut engine = AlertEngine::new();
+		let events = engine.evaluate(&vec![tag(-18.0, false)], &history(), &rules(), &[advisory], Utc::now());
+		assert!(matches!(&events[..], [AlertEvent::Started(alert)] if alert.severity == Severity::Info && alert.value == Some(2.72)));
+		assert_eq!(engine.severity(), None);
+		
+		let events = engine.evaluate(&vec![tag(-18.0, false)], &history(), &rules(), &[], Utc::now());
+		assert!(matches!(&events[..], [AlertEvent::Cleared(_)]));
+	}
+	
+	#[test]
+	fn quiet_hours_over_the_midnight() {
+		let night = QuietHours::try_from("22:00-07:00".to_string()).unwrap();
+		let time = |text: &str| text.parse::<NaiveTime>().unwrap();
+		assert!(night.contains(time("23:30:00")) && night.contains(time("06:59:00")));
+		assert!(!night.contains(time("07:00:00")) && !night.contains(time("12:00:00")));
+		let lunch = QuietHours::try_from("12:00 – 13:00".to_string()).unwrap();
+		assert!(lunch.contains(time("12:30:00")) && !lunch.contains(time("13:30:00")));
+		assert!(QuietHours::try_from("22-07".to_string()).is_err());
+		
+		// 01:00 in Helsinki.
+		let now = "2024-05-01T22:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let zone = Zone(Some(chrono_tz::Europe::Helsinki));
+		assert!(night.silences(Some(Severity::Warning), now, zone));
+		assert!(night.silences(None, now, zone));
+		assert!(!night.silences(Some(Severity::Critical), now, zone));
+		assert!(!lunch.silences(Some(Severity::Warning), now, zone));
+	}
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::alerts::Alert;
+use crate::history::{History, Sample};
+use crate::model::ApiResponse;
+use crate::poller::Source;
+
+// A client that can't take a message in this long is dropped, so that a stuck SSH session doesn't hold up the others.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+// How often the reading stops to check whether the client is quitting.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+// Wait before connecting again, doubled after every failed connection up to the longest.
+const BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/**
+ * A line of the socket, JSON. An attaching client gets the history of each tag and the alerts active at the moment,
+ * and then the state after every refresh of the headless instance.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+	History {
+		tag_id: String,
+		samples: Vec<Sample>,
+	},
+	// The alerts that were already active, which the client takes without starting them again.
+	Active {
+		alerts: Vec<Alert>,
+	},
+	State {
+		tags: ApiResponse,
+		// The source of each tag by tag ID, for the pages of a source.
+		#[serde(default)]
+		sources: HashMap<String, Source>,
+		alerts: Vec<Alert>,
+		network_error: Option<String>,
+	},
+}
+
+/**
+ * The headless side: shares the tags, the history and the alerts on a Unix socket with the TUIs attached with
+ * --attach, so that any number of them, e.g. from SSH sessions, show the same without fetching on their own.
+*/
+pub struct Share {
+	listener: UnixListener,
+	path: PathBuf,
+	clients: Vec<UnixStream>,
+}
+
+impl Share {
+	/**
+	 * Listen on the socket. A socket file left behind by an instance that's no longer running is replaced.
+	*/
+	pub fn start(path: &Path) -> Result<Share, String> {
+		if path.exists() {
+			if UnixStream::connect(path).is_ok() {
+				return Err(format!("Another instance is already sharing on {}", path.display()));
+			}
+			let _ = fs::remove_file(path);
+		}
+		let listener = UnixListener::bind(path)
+			.and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+			.map_err(|error| format!("Could not listen on {}: {}", path.display(), error))?;
+		return Ok(Share { listener, path: path.to_path_buf(), clients: Vec::new() });
+	}
+	
+	/**
+	 * Take the clients that connected since the last call, giving them the history and the current state.
+	*/
+	pub fn accept(&mut self, history: &History, state: &Message, active: Vec<Alert>) {
+		while let Ok((client, _)) = self.listener.accept() {
+			if client.set_nonblocking(false).and_then(|_| client.set_write_timeout(Some(WRITE_TIMEOUT))).is_err() {
+				continue;
+			}
+			let mut messages: Vec<Message> = history.tag_ids()
+				.map(|tag_id| Message::History { tag_id: tag_id.clone(), samples: history.samples(tag_id).cloned().collect() })
+				.collect();
+			messages.push(Message::Active { alerts: active.clone() });
+			messages.push(state.clone());
+			let mut client = client;
+			if messages.iter().all(|message| write(&mut client, message)) {
+				self.clients.push(client);
+			}
+		}
+	}
+	
+	/**
+	 * Send the state to all the clients, dropping the ones that have gone.
+	*/
+	pub fn send(&mut self, state: &Message) {
+		self.clients.retain_mut(|client| write(client, state));
+	}
+}
+
+impl Drop for Share {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+fn write(client: &mut UnixStream, message: &Message) -> bool {
+	let Ok(mut line) = serde_json::to_string(message) else {
+		return false;
+	};
+	line.push('\n');
+	return client.write_all(line.as_bytes()).is_ok();
+}
+
+/**
+ * The TUI side of --attach: the messages of the headless instance's socket, received in a background thread. The
+ * connection is opened again when it drops, e.g. when the headless instance restarts.
+*/
+pub struct Attached {
+	messages: Arc<Mutex<Vec<Message>>>,
+	// Why there's no connection at the moment.
+	problem: Arc<Mutex<Option<String>>>,
+	stop: Arc<AtomicBool>,
+}
+
+impl Attached {
+	pub fn start(path: &Path) -> Attached {
+		let messages = Arc::new(Mutex::new(Vec::new()));
+		let problem = Arc::new(Mutex::new(None));
+		let stop = Arc::new(AtomicBool::new(false));
+		
+		let path = path.to_path_buf();
+		let (thread_messages, thread_problem, thread_stop) = (Arc::clone(&messages), Arc::clone(&problem), Arc::clone(&stop));
+		thread::spawn(move || {
+			let mut backoff = BACKOFF;
+			while !thread_stop.load(Ordering::Relaxed) {
+				let dropped = match UnixStream::connect(&path) {
+					Ok(stream) => {
+						backoff = BACKOFF;
+						*thread_problem.lock().unwrap() = None;
+						receive(stream, &thread_messages, &thread_stop)
+					},
+					Err(error) => Some(format!("Could not connect to {}: {}", path.display(), error)),
+				};
+				let Some(dropped) = dropped else {
+					return;
+				};
+				*thread_problem.lock().unwrap() = Some(dropped);
+				
+				let reconnect = Instant::now() + backoff;
+				while Instant::now() < reconnect && !thread_stop.load(Ordering::Relaxed) {
+					thread::sleep(Duration::from_millis(100));
+				}
+				backoff = (backoff * 2).min(MAX_BACKOFF);
+			}
+		});
+		
+		return Attached { messages, problem, stop };
+	}
+	
+	/**
+	 * Take the messages received since the last call, in order.
+	*/
+	pub fn take(&self) -> Vec<Message> {
+		return self.messages.lock().map(|mut messages| std::mem::take(&mut *messages)).unwrap_or_default();
+	}
+	
+	pub fn problem(&self) -> Option<String> {
+		return self.problem.lock().ok()?.clone();
+	}
+}
+
+impl Drop for Attached {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+/**
+ * Read the lines until the connection drops or the client stops. Returns why the connection dropped.
+*/
+fn receive(stream: UnixStream, messages: &Mutex<Vec<Message>>, stop: &AtomicBool) -> Option<String> {
+	if let Err(error) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+		return Some(error.to_string());
+	}
+	let mut reader = BufReader::new(stream);
+	let mut line = String::new();
+	loop {
+		if stop.load(Ordering::Relaxed) {
+			return None;
+		}
+		// A line cut by the timeout is continued on the next round.
+		match reader.read_line(&mut line) {
+			Ok(0) => return Some("The headless instance closed the connection".into()),
+			Ok(_) => {
+				match serde_json::from_str::<Message>(&line) {
+					Ok(message) => messages.lock().unwrap().push(message),
+					Err(error) => return Some(format!("Could not read the headless instance: {}", error)),
+				}
+				line.clear();
+			},
+			Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {},
+			Err(error) => return Some(error.to_string()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::Utc;
+	use crate::model::{Metric, Tag};
+	
+	#[test]
+	fn attached_clients_get_the_history_and_the_state() {
+		let path = std::env::temp_dir().join(format!("ruuvi-attach-{}.sock", std::process::id()));
+		let mut share = Share::start(&path).unwrap();
+		assert!(Share::start(&path).is_err());
+		
+		let tag = Tag {
+			datetime: Utc::now().to_rfc3339(),
+			temperature: Some(Metric::from_current(62.5)),
+			..Tag::test("1", "Sauna")
+		};
+		let mut history = History::new(chrono::Duration::days(1));
+		history.add(&vec![tag.clone()]);
+		let state = |network_error: Option<&str>| Message::State { tags: vec![tag.clone()], sources: HashMap::new(), alerts: Vec::new(), network_error: network_error.map(String::from) };
+		
+		let attached = Attached::start(&path);
+		let deadline = Instant::now() + Duration::from_secs(5);
+		let mut received = Vec::new();
+		while received.len() < 4 && Instant::now() < deadline {
+			share.accept(&history, &state(None), Vec::new());
+			share.send(&state(Some("API down")));
+			thread::sleep(Duration::from_millis(50));
+			received.extend(attached.take());
+		}
+		assert!(matches!(&received[0], Message::History { tag_id, samples } if tag_id == "1" && samples[0].temperature == Some(62.5)));
+		assert!(matches!(&received[1], Message::Active { alerts } if alerts.is_empty()));
+		assert!(matches!(&received[2], Message::State { tags, network_error: None, .. } if tags[0].name == "Sauna"));
+		assert!(matches!(&received[3], Message::State { network_error: Some(error), .. } if error == "API down"));
+		
+		drop(share);
+		assert!(!path.exists());
+	}
+}
@@ -0,0 +1,276 @@
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+use crossterm::{cursor, event, queue, style, terminal};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::{Attribute, Color};
+use crate::theme::{self, Style};
+use super::{Key, Screen};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+	character: char,
+	style: Option<Style>,
+}
+
+const BLANK: Cell = Cell { character: ' ', style: None };
+
+/**
+ * The frame is drawn into a buffer and refresh() sends only the rows that changed since the previous frame, the
+ * same way curses does.
+*/
+pub struct Crossterm {
+	stdout: Stdout,
+	height: i32,
+	width: i32,
+	cells: Vec<Vec<Cell>>,
+	// What the terminal shows, None when it must be repainted.
+	shown: Option<Vec<Vec<Cell>>>,
+	y: i32,
+	x: i32,
+	style: Option<Style>,
+	hide_cursor: bool,
+	// Event read ahead while looking for a double-click.
+	pending: Option<Event>,
+}
+
+impl Crossterm {
+	pub fn open(hide_cursor: bool, mouse: bool, focus_events: bool) -> io::Result<Crossterm> {
+		terminal::enable_raw_mode()?;
+		let mut stdout = io::stdout();
+		queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+		if mouse {
+			queue!(stdout, event::EnableMouseCapture)?;
+		}
+		if focus_events {
+			queue!(stdout, event::EnableFocusChange)?;
+		}
+		stdout.flush()?;
+		
+		let (width, height) = terminal::size()?;
+		let mut screen = Crossterm {
+			stdout,
+			height: height as i32,
+			width: width as i32,
+			cells: Vec::new(),
+			shown: None,
+			y: 0,
+			x: 0,
+			style: None,
+			hide_cursor,
+			pending: None,
+		};
+		screen.clear();
+		return Ok(screen);
+	}
+	
+	fn draw(&mut self) -> io::Result<()> {
+		queue!(self.stdout, cursor::Hide)?;
+		if self.shown.is_none() {
+			queue!(self.stdout, style::ResetColor, terminal::Clear(terminal::ClearType::All))?;
+		}
+		
+		for (y, row) in self.cells.iter().enumerate() {
+			if self.shown.as_ref().is_some_and(|shown| shown.get(y) == Some(row)) {
+				continue;
+			}
+			
+			queue!(self.stdout, cursor::MoveTo(0, y as u16))?;
+			let mut current = None;
+			for cell in row {
+				if cell.style != current {
+					queue!(self.stdout, style::SetAttribute(Attribute::Reset))?;
+					if let Some(style) = cell.style {
+						set_style(&mut self.stdout, style)?;
+					}
+					current = cell.style;
+				}
+				queue!(self.stdout, style::Print(cell.character))?;
+			}
+			queue!(self.stdout, style::SetAttribute(Attribute::Reset))?;
+		}
+		
+		if !self.hide_cursor {
+			queue!(self.stdout, cursor::MoveTo(self.x as u16, self.y.min(self.height - 1).max(0) as u16), cursor::Show)?;
+		}
+		self.shown = Some(self.cells.clone());
+		return self.stdout.flush();
+	}
+	
+	/**
+	 * The next event, the one read ahead first.
+	*/
+	fn event(&mut self) -> Option<Event> {
+		if let Some(event) = self.pending.take() {
+			return Some(event);
+		}
+		if !event::poll(Duration::ZERO).unwrap_or(false) {
+			return None;
+		}
+		return event::read().ok();
+	}
+	
+	/**
+	 * A click, or a double-click if another click at the same place is waiting right after it. The terminal doesn't
+	 * tell them apart, and the keys are read only once a second, so the second click of a double-click is already
+	 * there.
+	*/
+	fn click(&mut self, y: i32, x: i32) -> Key {
+		while let Some(event) = self.event() {
+			let Event::Mouse(mouse) = event else {
+				self.pending = Some(event);
+				break;
+			};
+			match mouse.kind {
+				MouseEventKind::Down(MouseButton::Left) if (mouse.row as i32, mouse.column as i32) == (y, x) => return Key::DoubleClick(y, x),
+				MouseEventKind::Up(_) | MouseEventKind::Moved | MouseEventKind::Drag(_) => {},
+				_ => {
+					self.pending = Some(event);
+					break;
+				},
+			}
+		}
+		return Key::Click(y, x);
+	}
+}
+
+impl Screen for Crossterm {
+	fn size(&self) -> (i32, i32) {
+		return (self.height, self.width);
+	}
+	
+	fn erase(&mut self) {
+		// Resize events can be missed while drawing, the size is checked for every frame.
+		if let Ok((width, height)) = terminal::size() {
+			if (height as i32, width as i32) != (self.height, self.width) {
+				(self.height, self.width) = (height as i32, width as i32);
+				self.shown = None;
+			}
+		}
+		
+		self.cells = vec![vec![BLANK; self.width as usize]; self.height as usize];
+		(self.y, self.x) = (0, 0);
+	}
+	
+	fn clear(&mut self) {
+		self.erase();
+		self.shown = None;
+	}
+	
+	fn mv(&mut self, y: i32, x: i32) {
+		(self.y, self.x) = (y.clamp(0, self.height), x.clamp(0, self.width));
+	}
+	
+	fn cursor(&self) -> (i32, i32) {
+		return (self.y, self.x);
+	}
+	
+	fn addstr(&mut self, text: &str) {
+		for character in text.chars() {
+			if character == '\n' {
+				(self.y, self.x) = (self.y + 1, 0);
+				continue;
+			}
+			if self.x >= self.width {
+				(self.y, self.x) = (self.y + 1, 0);
+			}
+			if let Some(cell) = self.cells.get_mut(self.y as usize).and_then(|row| row.get_mut(self.x as usize)) {
+				*cell = Cell { character, style: self.style };
+			}
+			self.x += 1;
+		}
+	}
+	
+	fn set_style(&mut self, style: Option<Style>) {
+		self.style = style;
+	}
+	
+	fn refresh(&mut self) {
+		// A failed write shows up as a stale screen, there's nowhere to report it.
+		let _ = self.draw();
+	}
+	
+	fn key(&mut self) -> Option<Key> {
+		while let Some(event) = self.event() {
+			match event {
+				Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+					KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Some(Key::Interrupt),
+					KeyCode::Char(character) => return Some(Key::Char(character)),
+					// Like curses, which reads Tab, Enter and Escape as characters.
+					KeyCode::Tab => return Some(Key::Char('\t')),
+					KeyCode::Enter => return Some(Key::Char('\n')),
+					KeyCode::Esc => return Some(Key::Char('\x1b')),
+					KeyCode::Backspace => return Some(Key::Char('\x7f')),
+					_ => {},
+				},
+				Event::Mouse(mouse) => match mouse.kind {
+					MouseEventKind::Down(MouseButton::Left) => return Some(self.click(mouse.row as i32, mouse.column as i32)),
+					MouseEventKind::ScrollUp => return Some(Key::ScrollUp),
+					MouseEventKind::ScrollDown => return Some(Key::ScrollDown),
+					_ => {},
+				},
+				Event::Resize(_, _) => return Some(Key::Resize),
+				Event::FocusGained => return Some(Key::FocusGained),
+				Event::FocusLost => return Some(Key::FocusLost),
+				_ => {},
+			}
+		}
+		return None;
+	}
+	
+	fn colors(&self) -> i32 {
+		// There's no terminfo to ask, go by what the terminal advertises.
+		let truecolor = std::env::var("COLORTERM").is_ok_and(|value| !value.is_empty());
+		if truecolor || std::env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+			return 256;
+		}
+		return 8;
+	}
+	
+	fn bell(&mut self) {
+		let _ = self.stdout.write_all(b"\x07").and_then(|_| self.stdout.flush());
+	}
+}
+
+fn set_style(stdout: &mut Stdout, style: Style) -> io::Result<()> {
+	for (flag, attribute) in [(theme::BOLD, Attribute::Bold), (theme::UNDERLINE, Attribute::Underlined), (theme::REVERSE, Attribute::Reverse), (theme::DIM, Attribute::Dim)] {
+		if style.attributes & flag != 0 {
+			queue!(stdout, style::SetAttribute(attribute))?;
+		}
+	}
+	if let Some(color) = color(style.foreground) {
+		queue!(stdout, style::SetForegroundColor(color))?;
+	}
+	if let Some(color) = color(style.background) {
+		queue!(stdout, style::SetBackgroundColor(color))?;
+	}
+	return Ok(());
+}
+
+/**
+ * The eight standard colors as the basic ANSI colors so that they work on any terminal, the rest from the 256 color
+ * palette. -1 is the terminal default.
+*/
+fn color(index: i16) -> Option<Color> {
+	match index {
+		theme::BLACK => Some(Color::Black),
+		theme::RED => Some(Color::DarkRed),
+		theme::GREEN => Some(Color::DarkGreen),
+		theme::YELLOW => Some(Color::DarkYellow),
+		theme::BLUE => Some(Color::DarkBlue),
+		theme::MAGENTA => Some(Color::DarkMagenta),
+		theme::CYAN => Some(Color::DarkCyan),
+		theme::WHITE => Some(Color::Grey),
+		8..=255 => Some(Color::AnsiValue(index as u8)),
+		_ => None,
+	}
+}
+
+/**
+ * Leave the alternate screen and raw mode with the cursor visible.
+*/
+pub fn restore() {
+	let mut stdout = io::stdout();
+	let _ = queue!(stdout, event::DisableMouseCapture, event::DisableFocusChange, style::ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+	let _ = stdout.flush();
+	let _ = terminal::disable_raw_mode();
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use pancurses::{chtype, Input, Window, A_BOLD, A_DIM, A_NORMAL, A_REVERSE, A_UNDERLINE, BUTTON1_CLICKED, BUTTON1_DOUBLE_CLICKED, BUTTON4_PRESSED, BUTTON5_PRESSED, COLOR_PAIR};
+use crate::theme::{self, Style};
+use super::{Key, Screen};
+
+// Escapes that turn the terminal's focus events on and off.
+const FOCUS_ON: &[u8] = b"\x1b[?1004h";
+const FOCUS_OFF: &[u8] = b"\x1b[?1004l";
+
+pub struct Curses {
+	window: Window,
+	colors: bool,
+	// Color pairs are set up as the color combinations are first used.
+	pairs: HashMap<(i16, i16), i16>,
+}
+
+impl Curses {
+	pub fn open(hide_cursor: bool, mouse: bool, focus_events: bool) -> Curses {
+		let window = pancurses::initscr();
+		let colors = pancurses::has_colors();
+		if colors {
+			pancurses::start_color();
+			pancurses::use_default_colors(); 	// This is needed. If not set, the background color will be forced black instead of terminal color.
+		}
+		
+		window.nodelay(true);
+		if hide_cursor {
+			pancurses::curs_set(0);
+		}
+		// The mouse events come as a key, which needs the keypad. Curses tells the double-clicks apart.
+		if mouse {
+			window.keypad(true);
+			pancurses::mousemask(BUTTON1_CLICKED | BUTTON1_DOUBLE_CLICKED | BUTTON4_PRESSED | BUTTON5_PRESSED, None);
+		}
+		// Curses has no focus events, the terminal is asked for them directly and they come as ESC [ I and ESC [ O.
+		if focus_events {
+			let _ = io::stdout().write_all(FOCUS_ON).and_then(|_| io::stdout().flush());
+		}
+		
+		return Curses { window, colors, pairs: HashMap::new() };
+	}
+	
+	/**
+	 * A focus event if the Escape starts one, the Escape otherwise with the rest left to read.
+	*/
+	fn escape(&mut self) -> Key {
+		let next = self.window.getch();
+		if next != Some(Input::Character('[')) {
+			next.inspect(|next| { self.window.ungetch(next); });
+			return Key::Char('\x1b');
+		}
+		match self.window.getch() {
+			Some(Input::Character('I')) => return Key::FocusGained,
+			Some(Input::Character('O')) => return Key::FocusLost,
+			// ungetch pushes onto a stack, the last one pushed is read first.
+			last => {
+				last.inspect(|last| { self.window.ungetch(last); });
+				self.window.ungetch(&Input::Character('['));
+				return Key::Char('\x1b');
+			},
+		}
+	}
+	
+	fn pair(&mut self, foreground: i16, background: i16) -> chtype {
+		if !self.colors || (foreground, background) == (-1, -1) {
+			return 0;
+		}
+		
+		let next = self.pairs.len() as i16 + 1;
+		let pair = *self.pairs.entry((foreground, background)).or_insert_with(|| {
+			pancurses::init_pair(next, foreground, background);
+			next
+		});
+		return COLOR_PAIR(pair as chtype);
+	}
+}
+
+impl Screen for Curses {
+	fn size(&self) -> (i32, i32) {
+		return self.window.get_max_yx();
+	}
+	
+	fn erase(&mut self) {
+		self.window.erase();
+	}
+	
+	fn clear(&mut self) {
+		self.window.clear();
+	}
+	
+	fn mv(&mut self, y: i32, x: i32) {
+		self.window.mv(y, x);
+	}
+	
+	fn cursor(&self) -> (i32, i32) {
+		return self.window.get_cur_yx();
+	}
+	
+	fn addstr(&mut self, text: &str) {
+		self.window.addstr(text);
+	}
+	
+	fn set_style(&mut self, style: Option<Style>) {
+		let Some(style) = style else {
+			self.window.attrset(A_NORMAL);
+			return;
+		};
+		
+		let mut attributes = self.pair(style.foreground, style.background);
+		for (flag, attribute) in [(theme::BOLD, A_BOLD), (theme::UNDERLINE, A_UNDERLINE), (theme::REVERSE, A_REVERSE), (theme::DIM, A_DIM)] {
+			if style.attributes & flag != 0 {
+				attributes |= attribute;
+			}
+		}
+		self.window.attrset(attributes);
+	}
+	
+	fn refresh(&mut self) {
+		self.window.refresh();
+	}
+	
+	fn key(&mut self) -> Option<Key> {
+		match self.window.getch()? {
+			Input::Character('\x1b') => Some(self.escape()),
+			Input::Character(character) => Some(Key::Char(character)),
+			Input::KeyBackspace => Some(Key::Char('\x7f')),
+			Input::KeyResize => {
+				pancurses::resize_term(0, 0);
+				Some(Key::Resize)
+			},
+			Input::KeyMouse => {
+				let event = pancurses::getmouse().ok()?;
+				let (y, x) = (event.y, event.x);
+				match event.bstate {
+					state if state & BUTTON1_DOUBLE_CLICKED != 0 => Some(Key::DoubleClick(y, x)),
+					state if state & BUTTON1_CLICKED != 0 => Some(Key::Click(y, x)),
+					state if state & BUTTON4_PRESSED != 0 => Some(Key::ScrollUp),
+					state if state & BUTTON5_PRESSED != 0 => Some(Key::ScrollDown),
+					_ => None,
+				}
+			},
+			_ => None,
+		}
+	}
+	
+	fn colors(&self) -> i32 {
+		return pancurses::COLORS();
+	}
+	
+	fn bell(&mut self) {
+		pancurses::beep();
+	}
+}
+
+/**
+ * Leave curses mode with the cursor visible.
+*/
+pub fn restore() {
+	let _ = io::stdout().write_all(FOCUS_OFF).and_then(|_| io::stdout().flush());
+	pancurses::curs_set(1);
+	pancurses::endwin();
+}
@@ -0,0 +1,102 @@
+use crate::table;
+use crate::theme::{self, Style};
+use super::{Key, Screen};
+
+/**
+ * Draws everything in reverse video, the blank screen too, e.g. light text on dark for an e-paper display. Newlines
+ * are turned into cursor moves, because curses clears the rest of the line in the normal colors on a newline.
+*/
+pub struct Inverted {
+	screen: Box<dyn Screen>,
+	y: i32,
+	x: i32,
+}
+
+impl Inverted {
+	pub fn new(screen: Box<dyn Screen>) -> Inverted {
+		return Inverted { screen, y: 0, x: 0 };
+	}
+	
+	/**
+	 * Fill the erased screen with reversed blanks.
+	*/
+	fn fill(&mut self) {
+		let (height, width) = self.screen.size();
+		let blank = " ".repeat(width.max(0) as usize);
+		self.screen.set_style(Some(invert(None)));
+		for y in 0..height {
+			self.screen.mv(y, 0);
+			self.screen.addstr(&blank);
+		}
+		// The text without a style is reversed too.
+		self.mv(0, 0);
+	}
+}
+
+/**
+ * The style with reverse video toggled, the terminal default reversed for None.
+*/
+fn invert(style: Option<Style>) -> Style {
+	let style = style.unwrap_or(Style::new(-1, -1, theme::NORMAL));
+	return Style { attributes: style.attributes ^ theme::REVERSE, ..style };
+}
+
+impl Screen for Inverted {
+	fn size(&self) -> (i32, i32) {
+		return self.screen.size();
+	}
+	
+	fn erase(&mut self) {
+		self.screen.erase();
+		self.fill();
+	}
+	
+	fn clear(&mut self) {
+		self.screen.clear();
+		self.fill();
+	}
+	
+	fn mv(&mut self, y: i32, x: i32) {
+		self.screen.mv(y, x);
+		(self.y, self.x) = (y, x);
+	}
+	
+	fn cursor(&self) -> (i32, i32) {
+		return self.screen.cursor();
+	}
+	
+	fn addstr(&mut self, text: &str) {
+		let width = self.screen.size().1;
+		for (index, line) in text.split('\n').enumerate() {
+			if index > 0 {
+				self.mv(self.y + 1, 0);
+			}
+			self.screen.addstr(line);
+			// Long lines wrap.
+			let end = self.x + table::columns(line) as i32;
+			if width > 0 {
+				(self.y, self.x) = (self.y + end / width, end % width);
+			}
+		}
+	}
+	
+	fn set_style(&mut self, style: Option<Style>) {
+		self.screen.set_style(Some(invert(style)));
+	}
+	
+	fn refresh(&mut self) {
+		self.screen.refresh();
+	}
+	
+	fn key(&mut self) -> Option<Key> {
+		return self.screen.key();
+	}
+	
+	fn colors(&self) -> i32 {
+		return self.screen.colors();
+	}
+	
+	fn bell(&mut self) {
+		self.screen.bell();
+	}
+}
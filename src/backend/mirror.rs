@@ -0,0 +1,145 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::theme::Style;
+use super::{Key, Screen, Snapshot};
+
+/**
+ * Passes the drawing on to the terminal and keeps a plain text copy of the frame, written into a file on every
+ * refresh that changed it. For other displays following the TUI, e.g. `watch cat`, conky or an e-ink script.
+*/
+pub struct Mirror {
+	screen: Box<dyn Screen>,
+	path: PathBuf,
+	frame: Snapshot,
+	// The text in the file, so that an unchanged frame isn't written again every second.
+	written: Option<String>,
+}
+
+impl Mirror {
+	/**
+	 * Mirror the screen into the file. The file is written right away so that a path that can't be written is an
+	 * error on startup.
+	*/
+	pub fn new(screen: Box<dyn Screen>, path: &Path) -> Result<Mirror, String> {
+		let frame = Snapshot::new(screen.size());
+		let mut mirror = Mirror { screen, path: path.to_path_buf(), frame, written: None };
+		mirror.erase();
+		mirror.write().map_err(|error| format!("Could not write the mirror file {}: {}", path.display(), error))?;
+		return Ok(mirror);
+	}
+	
+	fn write(&mut self) -> io::Result<()> {
+		let text = self.frame.text();
+		if self.written.as_ref() == Some(&text) {
+			return Ok(());
+		}
+		// Renamed over the file, so that the readers never see a half written frame.
+		let mut temporary = self.path.clone().into_os_string();
+		temporary.push(".tmp");
+		fs::write(&temporary, &text)?;
+		fs::rename(&temporary, &self.path)?;
+		self.written = Some(text);
+		return Ok(());
+	}
+}
+
+impl Screen for Mirror {
+	fn size(&self) -> (i32, i32) {
+		return self.screen.size();
+	}
+	
+	fn erase(&mut self) {
+		self.screen.erase();
+		// Sized after the terminal, which checks for a resize on erase.
+		self.frame.resize(self.screen.size());
+		self.frame.erase();
+	}
+	
+	fn clear(&mut self) {
+		self.screen.clear();
+		self.erase();
+	}
+	
+	fn mv(&mut self, y: i32, x: i32) {
+		self.screen.mv(y, x);
+		self.frame.mv(y, x);
+	}
+	
+	fn cursor(&self) -> (i32, i32) {
+		return self.screen.cursor();
+	}
+	
+	fn addstr(&mut self, text: &str) {
+		self.screen.addstr(text);
+		self.frame.addstr(text);
+	}
+	
+	fn set_style(&mut self, style: Option<Style>) {
+		self.screen.set_style(style);
+	}
+	
+	fn refresh(&mut self) {
+		self.screen.refresh();
+		// The path was writable on startup. A full disk or the like later shouldn't stop the TUI.
+		let _ = self.write();
+	}
+	
+	fn key(&mut self) -> Option<Key> {
+		return self.screen.key();
+	}
+	
+	fn colors(&self) -> i32 {
+		return self.screen.colors();
+	}
+	
+	fn bell(&mut self) {
+		self.screen.bell();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	/**
+	 * Terminal of the size that draws nothing.
+	*/
+	struct Blank(i32, i32);
+	
+	impl Screen for Blank {
+		fn size(&self) -> (i32, i32) {
+			return (self.0, self.1);
+		}
+		fn erase(&mut self) {}
+		fn clear(&mut self) {}
+		fn mv(&mut self, _: i32, _: i32) {}
+		fn cursor(&self) -> (i32, i32) {
+			return (0, 0);
+		}
+		fn addstr(&mut self, _: &str) {}
+		fn set_style(&mut self, _: Option<Style>) {}
+		fn refresh(&mut self) {}
+		fn key(&mut self) -> Option<Key> {
+			return None;
+		}
+		fn colors(&self) -> i32 {
+			return 8;
+		}
+	}
+	
+	#[test]
+	fn frames_are_written_as_text() {
+		let path = std::env::temp_dir().join(format!("ruuvi-tc-mirror-{}.txt", std::process::id()));
+		let mut mirror = Mirror::new(Box::new(Blank(5, 12)), &path).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap(), "");
+		
+		mirror.erase();
+		mirror.addstr("Sauna  65.0°C\nOutdoor\n");
+		mirror.mv(3, 4);
+		mirror.addstr("ok");
+		mirror.refresh();
+		assert_eq!(fs::read_to_string(&path).unwrap(), "Sauna  65.0°\nC\nOutdoor\n    ok\n");
+		fs::remove_file(&path).unwrap();
+	}
+}
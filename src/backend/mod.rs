@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use crate::theme::Style;
+
+#[cfg(feature = "crossterm")]
+mod crossterm;
+#[cfg(feature = "curses")]
+mod curses;
+mod inverted;
+mod mirror;
+mod pane;
+mod snapshot;
+
+pub use inverted::Inverted;
+pub use mirror::Mirror;
+pub use pane::Pane;
+pub use snapshot::Snapshot;
+
+/**
+ * Terminal library the TUI is drawn with. Each needs a build with its feature, curses is on by default.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackendName {
+	// pancurses: ncurses, or PDCurses on Windows.
+	Curses,
+	// crossterm: pure Rust, no C library needed.
+	Crossterm,
+}
+
+impl Default for BackendName {
+	fn default() -> BackendName {
+		if cfg!(feature = "curses") {
+			return BackendName::Curses;
+		}
+		return BackendName::Crossterm;
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+	Char(char),
+	Resize,
+	// Ctrl-C when the backend reads it as a key instead of the terminal sending SIGINT.
+	#[cfg_attr(not(feature = "crossterm"), allow(dead_code))]
+	Interrupt,
+	// Left button clicks at the row and column, and the scroll wheel. Only with the mouse enabled.
+	Click(i32, i32),
+	DoubleClick(i32, i32),
+	ScrollUp,
+	ScrollDown,
+	// The terminal window gained or lost the focus. Only with the focus events enabled.
+	FocusGained,
+	FocusLost,
+}
+
+/**
+ * What the renderers draw on. Text is added at the cursor like in curses: a newline moves to the start of the
+ * next row and long lines wrap. Nothing shows until refresh().
+*/
+pub trait Screen {
+	// Rows and columns.
+	fn size(&self) -> (i32, i32);
+	// Blank the screen for the next frame. Only the changed cells are sent on refresh.
+	fn erase(&mut self);
+	// Blank the screen and repaint all of it on the next refresh.
+	fn clear(&mut self);
+	fn mv(&mut self, y: i32, x: i32);
+	// Row and column where the next text is added.
+	fn cursor(&self) -> (i32, i32);
+	fn addstr(&mut self, text: &str);
+	// Style of the text added next, None for the terminal default.
+	fn set_style(&mut self, style: Option<Style>);
+	fn refresh(&mut self);
+	// A pending key press, without waiting.
+	fn key(&mut self) -> Option<Key>;
+	// Number of colors the terminal supports.
+	fn colors(&self) -> i32;
+	// Ring the terminal's bell, e.g. for a new alert.
+	fn bell(&mut self) {}
+}
+
+/**
+ * Take over the terminal with the backend. The cursor is hidden if asked. With the mouse, the terminal reports the
+ * clicks and the scroll wheel instead of selecting text. With the focus events, it reports the window gaining and
+ * losing the focus. Terminals without mouse or focus support just send nothing.
+*/
+#[cfg_attr(not(any(feature = "curses", feature = "crossterm")), allow(unused_variables))]
+pub fn open(name: BackendName, hide_cursor: bool, mouse: bool, focus_events: bool) -> Result<Box<dyn Screen>, String> {
+	match name {
+		#[cfg(feature = "curses")]
+		BackendName::Curses => Ok(Box::new(curses::Curses::open(hide_cursor, mouse, focus_events))),
+		#[cfg(feature = "crossterm")]
+		BackendName::Crossterm => crossterm::Crossterm::open(hide_cursor, mouse, focus_events)
+			.map(|screen| Box::new(screen) as Box<dyn Screen>)
+			.map_err(|error| format!("Could not set up the terminal: {}", error)),
+		#[allow(unreachable_patterns)]
+		name => Err(format!("The {0} backend needs a build with the {0} feature", feature(name))),
+	}
+}
+
+/**
+ * Give the terminal back in its normal mode with the cursor visible. Safe to call from the panic hook.
+*/
+pub fn restore(name: BackendName) {
+	match name {
+		#[cfg(feature = "curses")]
+		BackendName::Curses => curses::restore(),
+		#[cfg(feature = "crossterm")]
+		BackendName::Crossterm => crossterm::restore(),
+		#[allow(unreachable_patterns)]
+		_ => {},
+	}
+}
+
+#[allow(dead_code)]
+fn feature(name: BackendName) -> &'static str {
+	match name {
+		BackendName::Curses => "curses",
+		BackendName::Crossterm => "crossterm",
+	}
+}
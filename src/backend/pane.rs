@@ -0,0 +1,116 @@
+use unicode_width::UnicodeWidthChar;
+use crate::table;
+use crate::theme::Style;
+use super::{Key, Screen};
+
+/**
+ * Part of a screen that the renderers draw on as if it was the whole screen, for showing two of them side by side.
+ * The text wraps and ends at the edges of the pane, and refresh() is left to the whole screen.
+*/
+pub struct Pane<'a> {
+	screen: &'a mut dyn Screen,
+	// Top left corner on the screen, and the rows and columns.
+	top: i32,
+	left: i32,
+	size: (i32, i32),
+	y: i32,
+	x: i32,
+}
+
+impl<'a> Pane<'a> {
+	pub fn new(screen: &'a mut dyn Screen, top: i32, left: i32, size: (i32, i32)) -> Pane<'a> {
+		return Pane { screen, top, left, size, y: 0, x: 0 };
+	}
+	
+	/**
+	 * Add the text that fits on the current row. Below the pane it's left out.
+	*/
+	fn add_row(&mut self, text: &str) {
+		if self.y < self.size.0 {
+			self.screen.mv(self.top + self.y, self.left + self.x);
+			self.screen.addstr(text);
+		}
+		self.x += table::columns(text) as i32;
+	}
+}
+
+impl Screen for Pane<'_> {
+	fn size(&self) -> (i32, i32) {
+		return self.size;
+	}
+	
+	fn erase(&mut self) {
+		let blank = " ".repeat(self.size.1.max(0) as usize);
+		self.screen.set_style(None);
+		for y in 0..self.size.0 {
+			self.screen.mv(self.top + y, self.left);
+			self.screen.addstr(&blank);
+		}
+		self.mv(0, 0);
+	}
+	
+	fn clear(&mut self) {
+		self.erase();
+	}
+	
+	fn mv(&mut self, y: i32, x: i32) {
+		(self.y, self.x) = (y, x);
+	}
+	
+	fn cursor(&self) -> (i32, i32) {
+		return (self.y, self.x);
+	}
+	
+	fn addstr(&mut self, text: &str) {
+		let width = self.size.1.max(1);
+		let mut row = String::new();
+		for character in text.chars() {
+			if character == '\n' {
+				self.add_row(&std::mem::take(&mut row));
+				(self.y, self.x) = (self.y + 1, 0);
+				continue;
+			}
+			if self.x + (table::columns(&row) + character.width().unwrap_or(0)) as i32 > width {
+				self.add_row(&std::mem::take(&mut row));
+				(self.y, self.x) = (self.y + 1, 0);
+			}
+			row.push(character);
+		}
+		self.add_row(&row);
+	}
+	
+	fn set_style(&mut self, style: Option<Style>) {
+		self.screen.set_style(style);
+	}
+	
+	fn refresh(&mut self) {}
+	
+	fn key(&mut self) -> Option<Key> {
+		return None;
+	}
+	
+	fn colors(&self) -> i32 {
+		return self.screen.colors();
+	}
+	
+	fn bell(&mut self) {
+		self.screen.bell();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::Snapshot;
+	
+	#[test]
+	fn drawn_within_the_pane() {
+		let mut snapshot = Snapshot::new((4, 12));
+		snapshot.addstr("############\n############\n############\n############");
+		let mut pane = Pane::new(&mut snapshot, 1, 4, (2, 5));
+		pane.erase();
+		pane.addstr("Sauna\n65°C hot and more");
+		assert_eq!(pane.cursor(), (4, 2));
+		assert_eq!(snapshot.text(), "############\n####Sauna###\n####65°C ###\n############\n");
+	}
+}
@@ -0,0 +1,228 @@
+use std::fs;
+use std::path::Path;
+use crate::theme::{self, Style};
+use super::{Key, Screen};
+
+/**
+ * Screen that draws into memory, for saving a frame as text or as HTML with the colors to share the readings in
+ * a chat or an email.
+*/
+pub struct Snapshot {
+	size: (i32, i32),
+	// The characters with their styles, a row per screen row.
+	rows: Vec<Vec<(char, Option<Style>)>>,
+	y: usize,
+	x: usize,
+	style: Option<Style>,
+}
+
+impl Snapshot {
+	/**
+	 * Blank screen of the rows and columns.
+	*/
+	pub fn new(size: (i32, i32)) -> Snapshot {
+		let mut snapshot = Snapshot { size, rows: Vec::new(), y: 0, x: 0, style: None };
+		snapshot.erase();
+		return snapshot;
+	}
+	
+	/**
+	 * Resize on the next erase, e.g. to follow the terminal.
+	*/
+	pub fn resize(&mut self, size: (i32, i32)) {
+		self.size = size;
+	}
+	
+	/**
+	 * The rows up to the last one with text, without the trailing blanks.
+	*/
+	fn used_rows(&self) -> &[Vec<(char, Option<Style>)>] {
+		let blank = |row: &Vec<(char, Option<Style>)>| row.iter().all(|(character, _)| *character == ' ');
+		let length = self.rows.iter().rposition(|row| !blank(row)).map_or(0, |last| last + 1);
+		return &self.rows[..length];
+	}
+	
+	/**
+	 * The frame as text without the trailing spaces and empty rows.
+	*/
+	pub fn text(&self) -> String {
+		return self.used_rows().iter()
+			.map(|row| format!("{}\n", row.iter().map(|(character, _)| character).collect::<String>().trim_end()))
+			.collect();
+	}
+	
+	/**
+	 * The frame as an HTML page, light on dark like a terminal, with the styles as inline CSS.
+	*/
+	pub fn html(&self) -> String {
+		let mut body = String::new();
+		for row in self.used_rows() {
+			let length = row.iter().rposition(|(character, _)| *character != ' ').map_or(0, |last| last + 1);
+			let mut cells = row[..length].iter().peekable();
+			while let Some((character, style)) = cells.next() {
+				let mut text = escape(*character);
+				while let Some((next, _)) = cells.next_if(|(_, next_style)| next_style == style) {
+					text.push_str(&escape(*next));
+				}
+				match style.map(css).filter(|css| !css.is_empty()) {
+					Some(css) => body.push_str(&format!("<span style=\"{}\">{}</span>", css, text)),
+					None => body.push_str(&text),
+				}
+			}
+			body.push('\n');
+		}
+		return format!(
+			"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Ruuvi</title>\n</head>\n<body style=\"background: {}; color: {}\">\n<pre>\n{}</pre>\n</body>\n</html>\n",
+			BACKGROUND, FOREGROUND, body
+		);
+	}
+	
+	/**
+	 * Write the frame into the file, as HTML if the file name ends in .html or .htm and as text otherwise.
+	*/
+	pub fn save(&self, path: &Path) -> Result<(), String> {
+		let html = path.extension().is_some_and(|extension| extension == "html" || extension == "htm");
+		let contents = if html { self.html() } else { self.text() };
+		return fs::write(path, contents).map_err(|error| format!("Could not write the snapshot {}: {}", path.display(), error));
+	}
+}
+
+// Colors of the terminal defaults in the HTML.
+const FOREGROUND: &str = "#d0d0d0";
+const BACKGROUND: &str = "#000000";
+
+/**
+ * The style as CSS, e.g. "color: #cd0000; font-weight: bold". Empty for the terminal default.
+*/
+fn css(style: Style) -> String {
+	let (mut foreground, mut background) = (color(style.foreground), color(style.background));
+	if style.attributes & theme::REVERSE != 0 {
+		(foreground, background) = (background.or(Some(BACKGROUND.into())), foreground.or(Some(FOREGROUND.into())));
+	}
+	let mut properties = Vec::new();
+	properties.extend(foreground.map(|color| format!("color: {}", color)));
+	properties.extend(background.map(|color| format!("background: {}", color)));
+	if style.attributes & theme::BOLD != 0 {
+		properties.push("font-weight: bold".into());
+	}
+	if style.attributes & theme::UNDERLINE != 0 {
+		properties.push("text-decoration: underline".into());
+	}
+	if style.attributes & theme::DIM != 0 {
+		properties.push("opacity: 0.6".into());
+	}
+	return properties.join("; ");
+}
+
+/**
+ * The terminal color as CSS: the 16 basic colors as xterm shows them and the 256 color cube and grays. None for the
+ * default, -1.
+*/
+fn color(index: i16) -> Option<String> {
+	const BASIC: [&str; 16] = [
+		"#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+		"#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+	];
+	return match index {
+		0..=15 => Some(BASIC[index as usize].into()),
+		16..=231 => {
+			let level = |value: i16| if value == 0 { 0 } else { 55 + value * 40 };
+			let cube = index - 16;
+			Some(format!("#{:02x}{:02x}{:02x}", level(cube / 36), level(cube / 6 % 6), level(cube % 6)))
+		},
+		232..=255 => {
+			let gray = 8 + (index - 232) * 10;
+			Some(format!("#{:02x}{:02x}{:02x}", gray, gray, gray))
+		},
+		_ => None,
+	};
+}
+
+fn escape(character: char) -> String {
+	return match character {
+		'<' => "&lt;".into(),
+		'>' => "&gt;".into(),
+		'&' => "&amp;".into(),
+		character => character.to_string(),
+	};
+}
+
+impl Screen for Snapshot {
+	fn size(&self) -> (i32, i32) {
+		return self.size;
+	}
+	
+	fn erase(&mut self) {
+		let (height, width) = self.size;
+		self.rows = vec![vec![(' ', None); width.max(0) as usize]; height.max(0) as usize];
+		(self.y, self.x) = (0, 0);
+	}
+	
+	fn clear(&mut self) {
+		self.erase();
+	}
+	
+	fn mv(&mut self, y: i32, x: i32) {
+		(self.y, self.x) = (y.max(0) as usize, x.max(0) as usize);
+	}
+	
+	fn cursor(&self) -> (i32, i32) {
+		return (self.y as i32, self.x as i32);
+	}
+	
+	fn addstr(&mut self, text: &str) {
+		let width = self.rows.first().map_or(0, Vec::len);
+		for character in text.chars() {
+			if character == '\n' {
+				(self.y, self.x) = (self.y + 1, 0);
+				continue;
+			}
+			if self.x >= width {
+				(self.y, self.x) = (self.y + 1, 0);
+			}
+			if let Some(cell) = self.rows.get_mut(self.y).and_then(|row| row.get_mut(self.x)) {
+				*cell = (character, self.style);
+			}
+			self.x += 1;
+		}
+	}
+	
+	fn set_style(&mut self, style: Option<Style>) {
+		self.style = style;
+	}
+	
+	fn refresh(&mut self) {}
+	
+	fn key(&mut self) -> Option<Key> {
+		return None;
+	}
+	
+	fn colors(&self) -> i32 {
+		return 256;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn text_and_html() {
+		let mut snapshot = Snapshot::new((4, 20));
+		snapshot.set_style(Some(Style::new(theme::GREEN, -1, theme::BOLD)));
+		snapshot.addstr("Sauna");
+		snapshot.set_style(None);
+		snapshot.addstr(" 65.0°C <hot>\n");
+		snapshot.set_style(Some(Style::new(theme::WHITE, theme::RED, theme::NORMAL)));
+		snapshot.addstr("Network error");
+		
+		assert_eq!(snapshot.text(), "Sauna 65.0°C <hot>\nNetwork error\n");
+		let html = snapshot.html();
+		assert!(html.contains(concat!(
+			"<pre>\n<span style=\"color: #00cd00; font-weight: bold\">Sauna</span> 65.0°C &lt;hot&gt;\n",
+			"<span style=\"color: #e5e5e5; background: #cd0000\">Network error</span>\n</pre>",
+		)));
+		assert_eq!(color(196).as_deref(), Some("#ff0000"));
+		assert_eq!(color(244).as_deref(), Some("#808080"));
+	}
+}
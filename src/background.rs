@@ -0,0 +1,117 @@
+use serde::Deserialize;
+
+/**
+ * Whether the terminal shows light text on a dark background or dark text on a light one. The themes are made for
+ * dark terminals and adapted for light ones.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Background {
+	// Asked from the terminal, or from COLORFGBG if it doesn't answer. Dark if neither tells.
+	#[default]
+	Auto,
+	Dark,
+	Light,
+}
+
+impl Background {
+	/**
+	 * The detected background for Auto, the one set otherwise.
+	*/
+	pub fn or(self, detected: Background) -> Background {
+		return if self == Background::Auto { detected } else { self };
+	}
+	
+	/**
+	 * Dark or light. Auto asks the terminal, so it must be done before the backend takes it over, the answer would
+	 * come in as keys.
+	*/
+	pub fn detect(self) -> Background {
+		if self != Background::Auto {
+			return self;
+		}
+		return query().as_deref().and_then(parse_answer)
+			.or_else(|| std::env::var("COLORFGBG").ok().as_deref().and_then(parse_colorfgbg))
+			.unwrap_or(Background::Dark);
+	}
+}
+
+/**
+ * The terminal's answer to the OSC 11 query of its background color, e.g. "\x1b]11;rgb:ffff/ffff/ffff\x1b\\". None if
+ * there's no terminal or it didn't answer in time.
+*/
+#[cfg(unix)]
+fn query() -> Option<String> {
+	use std::fs::{File, OpenOptions};
+	use std::io::{Read, Write};
+	use std::process::Command;
+	
+	// Raw mode with stty, the reads give up after 0.2 seconds without input.
+	let stty = |tty: &File, settings: &[&str]| -> Option<String> {
+		let output = Command::new("stty").args(settings).stdin(tty.try_clone().ok()?).output().ok()?;
+		return output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string());
+	};
+	let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+	let saved = stty(&tty, &["-g"])?;
+	stty(&tty, &["raw", "-echo", "min", "0", "time", "2"])?;
+	
+	let mut answer = Vec::new();
+	if tty.write_all(b"\x1b]11;?\x1b\\").and_then(|_| tty.flush()).is_ok() {
+		let mut buffer = [0; 64];
+		// Ends with ST (ESC \) or BEL.
+		while !answer.ends_with(b"\x1b\\") && !answer.ends_with(b"\x07") && answer.len() < 256 {
+			match tty.read(&mut buffer) {
+				Ok(0) | Err(_) => break,
+				Ok(length) => answer.extend_from_slice(&buffer[..length]),
+			}
+		}
+	}
+	stty(&tty, &[&saved]);
+	return Some(String::from_utf8_lossy(&answer).into_owned()).filter(|answer| !answer.is_empty());
+}
+
+#[cfg(not(unix))]
+fn query() -> Option<String> {
+	return None;
+}
+
+/**
+ * Light or dark by the luminance of the color in the answer, given as 1 to 4 hex digits a component.
+*/
+fn parse_answer(answer: &str) -> Option<Background> {
+	let color = answer.split_once("rgb:")?.1;
+	let mut components = color.split('/').map(|component| {
+		let digits: String = component.chars().take_while(char::is_ascii_hexdigit).collect();
+		let value = u32::from_str_radix(&digits, 16).ok()?;
+		Some(value as f64 / ((1u32 << (4 * digits.len() as u32)) - 1) as f64)
+	});
+	let (red, green, blue) = (components.next()??, components.next()??, components.next()??);
+	let luminance = 0.2126 * red + 0.7152 * green + 0.0722 * blue;
+	return Some(if luminance > 0.5 { Background::Light } else { Background::Dark });
+}
+
+/**
+ * Light or dark by the background color of COLORFGBG, e.g. "15;0" or "0;default;15": the last field, a terminal color.
+*/
+fn parse_colorfgbg(value: &str) -> Option<Background> {
+	return match value.rsplit(';').next()?.parse::<u8>().ok()? {
+		7 | 9..=15 => Some(Background::Light),
+		_ => Some(Background::Dark),
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn answers() {
+		assert_eq!(parse_answer("\x1b]11;rgb:ffff/ffff/ffff\x1b\\"), Some(Background::Light));
+		assert_eq!(parse_answer("\x1b]11;rgb:1e1e/1e1e/2e2e\x07"), Some(Background::Dark));
+		assert_eq!(parse_answer("\x1b]11;rgb:fd/f6/e3\x1b\\"), Some(Background::Light));
+		assert_eq!(parse_answer("garbage"), None);
+		assert_eq!(parse_colorfgbg("15;0"), Some(Background::Dark));
+		assert_eq!(parse_colorfgbg("0;default;15"), Some(Background::Light));
+		assert_eq!(parse_colorfgbg("default"), None);
+	}
+}
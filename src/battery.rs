@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use crate::chart;
+use crate::config::Config;
+use crate::glyphs::Glyphs;
+use crate::model::ApiResponse;
+
+// The critical threshold of a model without one is this far below the low one, as in the ruuvitag model.
+const CRITICAL_BELOW_LOW: f64 = 0.2;
+
+/**
+ * Battery model of a tag generation: the low and critical thresholds and the discharge curve used
+ * for the percentage estimate. The curve is a list of [voltage, percentage] points. A cell shows a lower voltage
+ * while it's cold, so the thresholds can be lowered at and below temperatures with [temperature, low voltage] steps.
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BatteryModel {
+	pub low_voltage: f64,
+	// Without it, CRITICAL_BELOW_LOW below the low voltage.
+	#[serde(default)]
+	pub critical_voltage: Option<f64>,
+	pub curve: Vec<(f64, f64)>,
+	// The critical threshold is lowered by as much as the low one.
+	#[serde(default)]
+	pub cold_low_voltages: Vec<(f64, f64)>,
+}
+
+impl BatteryModel {
+	/**
+	 * RuuviTag with a CR2477 lithium coin cell. The voltage stays flat for most of the
+	 * battery life and drops quickly at the end. Below freezing it's temporarily lower, the same steps as in Ruuvi
+	 * Station.
+	*/
+	pub fn ruuvitag() -> BatteryModel {
+		return BatteryModel {
+			low_voltage: 2.5,
+			critical_voltage: Some(2.3),
+			curve: vec![(2.0, 0.0), (2.5, 10.0), (2.7, 30.0), (2.85, 60.0), (2.95, 90.0), (3.0, 100.0)],
+			cold_low_voltages: vec![(0.0, 2.3), (-20.0, 2.0)],
+		};
+	}
+	
+	/**
+	 * The low threshold at the temperature: the lowest of the steps at or above it. Without the temperature the
+	 * thresholds aren't lowered.
+	*/
+	fn low_threshold(&self, temperature: Option<f64>) -> f64 {
+		let Some(temperature) = temperature else {
+			return self.low_voltage;
+		};
+		return self.cold_low_voltages.iter()
+			.filter(|(step, _)| temperature <= *step)
+			.map(|(_, voltage)| *voltage)
+			.fold(self.low_voltage, f64::min);
+	}
+	
+	pub fn is_low(&self, voltage: f64, temperature: Option<f64>) -> bool {
+		return voltage <= self.low_threshold(temperature);
+	}
+	
+	pub fn critical_voltage(&self) -> f64 {
+		return self.critical_voltage.unwrap_or(self.low_voltage - CRITICAL_BELOW_LOW);
+	}
+	
+	pub fn is_critical(&self, voltage: f64, temperature: Option<f64>) -> bool {
+		return voltage <= self.critical_voltage() - (self.low_voltage - self.low_threshold(temperature));
+	}
+	
+	/**
+	 * Check that the critical threshold isn't above the low one.
+	*/
+	pub fn validate(&self, name: &str) -> Result<(), String> {
+		if self.critical_voltage() > self.low_voltage {
+			return Err(format!("The critical voltage of the battery model \"{}\" is above its low voltage", name));
+		}
+		return Ok(());
+	}
+	
+	/**
+	 * Estimate the remaining capacity by interpolating the discharge curve.
+	*/
+	pub fn percentage(&self, voltage: f64) -> f64 {
+		let mut curve = self.curve.clone();
+		curve.sort_by(|a, b| a.0.total_cmp(&b.0));
+		
+		let (Some(first), Some(last)) = (curve.first(), curve.last()) else {
+			return 0.0;
+		};
+		if voltage <= first.0 {
+			return first.1;
+		}
+		if voltage >= last.0 {
+			return last.1;
+		}
+		
+		for points in curve.windows(2) {
+			let ((low_v, low_p), (high_v, high_p)) = (points[0], points[1]);
+			if voltage <= high_v {
+				return low_p + (voltage - low_v) / (high_v - low_v) * (high_p - low_p);
+			}
+		}
+		
+		return last.1;
+	}
+}
+
+/**
+ * All known battery models: the built-in ones and the ones from the config.
+*/
+pub fn models(custom: &HashMap<String, BatteryModel>) -> HashMap<String, BatteryModel> {
+	let mut models = HashMap::from([("ruuvitag".to_string(), BatteryModel::ruuvitag())]);
+	models.extend(custom.clone());
+	return models;
+}
+
+/**
+ * Determine the battery low and critical flags from the voltages and the tags' own temperatures, so that outdoor
+ * tags aren't low all winter. Tags without a voltage keep the low flag from the source.
+*/
+pub fn apply(data: &mut ApiResponse, config: &Config) {
+	for tag in data.iter_mut() {
+		if let Some(voltage) = tag.battery_voltage {
+			let model = config.battery_model_for(tag);
+			tag.battery_low = model.is_low(voltage, tag.temperature());
+			tag.battery_critical = model.is_critical(voltage, tag.temperature());
+		}
+	}
+}
+
+// Recent days the discharge is fitted to, long enough to see through the swings with the temperature.
+const DISCHARGE_DAYS: i64 = 60;
+// Fewer days than this don't tell the discharge from the noise.
+const MINIMUM_DISCHARGE_DAYS: usize = 14;
+// Projections further than this are a battery that isn't discharging measurably.
+const MAXIMUM_PROJECTION_DAYS: f64 = 3650.0;
+
+/**
+ * Estimate when the battery reaches the empty voltage from a line fitted to the daily voltages of the last 60 days.
+ * The discharge in volts per day and the estimated day, or None if the voltage isn't falling or there are too few
+ * days. Rough: a coin cell falls faster and faster at the end, so the real date is earlier rather than later.
+*/
+pub fn depletion(days: &[(NaiveDate, f64)], empty: f64) -> Option<(f64, NaiveDate)> {
+	let (last, _) = days.last()?;
+	let recent: Vec<(f64, f64)> = days.iter()
+		.filter(|(day, _)| (*last - *day).num_days() < DISCHARGE_DAYS)
+		.map(|(day, voltage)| ((*day - *last).num_days() as f64, *voltage))
+		.collect();
+	if recent.len() < MINIMUM_DISCHARGE_DAYS {
+		return None;
+	}
+	
+	// Least squares, with the days counted from the last one.
+	let count = recent.len() as f64;
+	let mean_x = recent.iter().map(|(x, _)| x).sum::<f64>() / count;
+	let mean_y = recent.iter().map(|(_, y)| y).sum::<f64>() / count;
+	let covariance: f64 = recent.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+	let variance: f64 = recent.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+	let slope = covariance / variance;
+	if !slope.is_finite() || slope >= 0.0 {
+		return None;
+	}
+	let today = mean_y - slope * mean_x;
+	let remaining = ((empty - today) / slope).max(0.0);
+	if remaining > MAXIMUM_PROJECTION_DAYS {
+		return None;
+	}
+	return Some((slope, *last + chrono::Days::new(remaining.round() as u64)));
+}
+
+/**
+ * Bar chart of the daily voltages over months, for seeing the knee of the discharge curve before the tag dies. The
+ * days are averaged into at most width columns. Rows of the chart with the voltage axis on the left, and the first
+ * and last day below.
+*/
+pub fn chart(days: &[(NaiveDate, f64)], width: usize, height: usize, glyphs: Glyphs) -> Vec<String> {
+	let (Some((first, _)), Some((last, _))) = (days.first(), days.last()) else {
+		return Vec::new();
+	};
+	let per_column = days.len().div_ceil(width.max(1));
+	let columns: Vec<Option<f64>> = days.chunks(per_column)
+		.map(|chunk| Some(chunk.iter().map(|(_, voltage)| voltage).sum::<f64>() / chunk.len() as f64))
+		.collect();
+	
+	let mut rows = chart::bars(&columns, height, 0.1, glyphs, |voltage| format!("{:.2} V", voltage), 6);
+	rows.push(chart::ends(&first.to_string(), &last.to_string(), columns.len(), 6));
+	return rows;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::TagConfig;
+	use crate::model::{Metric, Tag};
+	
+	fn tag(id: &str, voltage: Option<f64>, battery_low: bool) -> Tag {
+		return Tag {
+			temperature: Some(Metric::from_current(20.0)),
+			humidity: Some(Metric::from_current(40.0)),
+			battery_voltage: voltage,
+			battery_low,
+			..Tag::test(id, id)
+		};
+	}
+	
+	#[test]
+	fn depletion_from_the_recent_slope() {
+		let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+		let days = |count: u64, voltage: &dyn Fn(f64) -> f64| -> Vec<(NaiveDate, f64)> {
+			return (0..count).map(|day| (start + chrono::Days::new(day), voltage(day as f64))).collect();
+		};
+		
+		// Flat for a year and then 5 mV a day for the last 30 days. The line through the flat and the falling days of
+		// the window falls 2.6 mV a day and reaches 2.3 V in 229 days.
+		let (slope, empty) = depletion(&days(395, &|day| if day < 365.0 { 3.0 } else { 3.0 - (day - 364.0) * 0.005 }), 2.3).unwrap();
+		assert!((slope + 0.0026).abs() < 0.0001);
+		assert_eq!(empty, start + chrono::Days::new(394 + 229));
+		
+		// A fresh battery, or too few days to tell.
+		assert_eq!(depletion(&days(100, &|_| 3.0), 2.3), None);
+		assert_eq!(depletion(&days(10, &|day| 3.0 - day * 0.01), 2.3), None);
+		
+		// Already below.
+		let (_, empty) = depletion(&days(30, &|day| 2.4 - day * 0.01), 2.3).unwrap();
+		assert_eq!(empty, start + chrono::Days::new(29));
+	}
+	
+	#[test]
+	fn percentage_interpolates_between_points() {
+		let model = BatteryModel { low_voltage: 2.5, critical_voltage: Some(2.2), curve: vec![(2.0, 0.0), (3.0, 100.0)], cold_low_voltages: vec![] };
+		assert_eq!(model.percentage(2.5), 50.0);
+		assert_eq!(model.percentage(2.75), 75.0);
+	}
+	
+	#[test]
+	fn percentage_is_clamped_to_curve() {
+		let model = BatteryModel::ruuvitag();
+		assert_eq!(model.percentage(3.3), 100.0);
+		assert_eq!(model.percentage(1.5), 0.0);
+	}
+	
+	#[test]
+	fn percentage_accepts_unsorted_curve() {
+		let model = BatteryModel { low_voltage: 2.5, critical_voltage: Some(2.2), curve: vec![(3.0, 100.0), (2.0, 0.0)], cold_low_voltages: vec![] };
+		assert_eq!(model.percentage(2.5), 50.0);
+	}
+	
+	#[test]
+	fn percentage_of_empty_curve_is_zero() {
+		let model = BatteryModel { low_voltage: 2.5, critical_voltage: Some(2.2), curve: vec![], cold_low_voltages: vec![] };
+		assert_eq!(model.percentage(3.0), 0.0);
+	}
+	
+	#[test]
+	fn low_threshold_is_inclusive() {
+		let model = BatteryModel::ruuvitag();
+		assert!(model.is_low(2.5, None));
+		assert!(!model.is_low(2.51, Some(20.0)));
+	}
+	
+	#[test]
+	fn critical_threshold_is_inclusive() {
+		let model = BatteryModel::ruuvitag();
+		assert!(model.is_critical(2.3, None));
+		assert!(!model.is_critical(2.31, Some(20.0)));
+	}
+	
+	#[test]
+	fn thresholds_are_lower_in_the_cold() {
+		let model = BatteryModel::ruuvitag();
+		assert!(!model.is_low(2.4, Some(0.0)));
+		assert!(model.is_low(2.4, Some(0.1)));
+		assert!(model.is_low(2.3, Some(-5.0)));
+		assert!(!model.is_low(2.1, Some(-25.0)));
+		assert!(!model.is_critical(2.15, Some(-5.0)));
+		assert!(model.is_critical(2.1, Some(5.0)));
+		assert!(model.is_critical(1.79, Some(-20.0)));
+		assert!(!model.is_critical(1.81, Some(-20.0)));
+	}
+	
+	#[test]
+	fn critical_voltage_defaults_below_the_low_one() {
+		let model: BatteryModel = toml::from_str("low_voltage = 2.3\ncurve = [[2.0, 0], [3.2, 100]]").unwrap();
+		assert!((model.critical_voltage() - 2.1).abs() < 1e-9);
+		assert!(model.validate("aa").is_ok());
+		let model: BatteryModel = toml::from_str("low_voltage = 2.3\ncritical_voltage = 2.5\ncurve = []").unwrap();
+		assert!(model.validate("aa").is_err());
+	}
+	
+	#[test]
+	fn custom_models_override_builtins() {
+		let custom = HashMap::from([("ruuvitag".to_string(), BatteryModel { low_voltage: 2.2, critical_voltage: Some(2.0), curve: vec![], cold_low_voltages: vec![] })]);
+		assert_eq!(models(&custom)["ruuvitag"].low_voltage, 2.2);
+		assert_eq!(models(&HashMap::new())["ruuvitag"], BatteryModel::ruuvitag());
+	}
+	
+	#[test]
+	fn apply_uses_voltage_and_keeps_source_flag_without_it() {
+		let config = Config::default();
+		let mut data = vec![
+			tag("a", Some(2.4), false),
+			tag("b", Some(2.9), true),
+			tag("c", None, true),
+			tag("d", None, false),
+			tag("e", Some(2.2), false),
+		];
+		
+		apply(&mut data, &config);
+		
+		let flags: Vec<(bool, bool)> = data.iter().map(|tag| (tag.battery_low, tag.battery_critical)).collect();
+		assert_eq!(flags, vec![(true, false), (false, false), (true, false), (false, false), (true, true)]);
+	}
+	
+	#[test]
+	fn chart_of_the_knee() {
+		let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+		// Flat for a month, then the knee: down 0.1 V a day.
+		let days: Vec<(NaiveDate, f64)> = (0..36)
+			.map(|day| (start + chrono::Days::new(day), if day < 30 { 3.0 } else { 3.0 - (day - 29) as f64 * 0.1 }))
+			.collect();
+		let rows = chart(&days, 12, 3, Glyphs::UNICODE);
+		assert_eq!(rows, vec![
+			"3.00 V │██████████  ",
+			"       │██████████▇ ",
+			"2.45 V │███████████▂",
+			"       └────────────",
+			"        2024-01-01 2024-02-05",
+		]);
+		assert_eq!(chart(&days, 12, 3, Glyphs::ASCII)[1], "       |########### ");
+		assert!(chart(&[], 12, 3, Glyphs::UNICODE).is_empty());
+	}
+	
+	#[test]
+	fn apply_uses_per_tag_model() {
+		let mut config = Config::default();
+		config.battery_models.insert("sensitive".into(), BatteryModel { low_voltage: 2.9, critical_voltage: Some(2.7), curve: vec![], cold_low_voltages: vec![] });
+		config.tags.insert("a".into(), TagConfig { battery_model: Some("sensitive".into()), ..TagConfig::default() });
+		let mut data = vec![tag("a", Some(2.8), false), tag("b", Some(2.8), false)];
+		
+		apply(&mut data, &config);
+		
+		assert!(data[0].battery_low);
+		assert!(!data[1].battery_low);
+	}
+}
@@ -0,0 +1,54 @@
+use crate::glyphs::Glyphs;
+
+/**
+ * Big 5 rows high digits made of block characters, for text that must be readable across the room.
+*/
+pub const HEIGHT: usize = 5;
+
+fn glyph(character: char) -> [&'static str; HEIGHT] {
+	match character {
+		'0' => ["███", "█ █", "█ █", "█ █", "███"],
+		'1' => [" █ ", "██ ", " █ ", " █ ", "███"],
+		'2' => ["███", "  █", "███", "█  ", "███"],
+		'3' => ["███", "  █", " ██", "  █", "███"],
+		'4' => ["█ █", "█ █", "███", "  █", "  █"],
+		'5' => ["███", "█  ", "███", "  █", "███"],
+		'6' => ["███", "█  ", "███", "█ █", "███"],
+		'7' => ["███", "  █", " █ ", " █ ", " █ "],
+		'8' => ["███", "█ █", "███", "█ █", "███"],
+		'9' => ["███", "█ █", "███", "  █", "███"],
+		'+' => ["   ", " █ ", "███", " █ ", "   "],
+		'-' => ["   ", "   ", "███", "   ", "   "],
+		'—' => ["     ", "     ", "█████", "     ", "     "],
+		'.' | ',' => [" ", " ", " ", " ", "█"],
+		'°' => ["█", " ", " ", " ", " "],
+		'%' => ["█  █", "  █ ", " █  ", "█   ", "   █"],
+		'C' => ["███", "█  ", "█  ", "█  ", "███"],
+		'F' => ["███", "█  ", "██ ", "█  ", "█  "],
+		'K' => ["█ █", "██ ", "█  ", "██ ", "█ █"],
+		_ => ["   ", "   ", "   ", "   ", "   "],
+	}
+}
+
+/**
+ * Render the text as big characters, one string per row. Of # instead of the blocks if the terminal can't show them.
+*/
+pub fn render(text: &str, glyphs: Glyphs) -> [String; HEIGHT] {
+	let mut rows: [String; HEIGHT] = Default::default();
+	
+	for (index, character) in text.chars().enumerate() {
+		for (row, part) in rows.iter_mut().zip(glyph(character)) {
+			if index > 0 {
+				row.push(' ');
+			}
+			row.push_str(part);
+		}
+	}
+	if !glyphs.blocks {
+		for row in &mut rows {
+			*row = row.replace('█', "#");
+		}
+	}
+	
+	return rows;
+}
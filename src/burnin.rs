@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use crate::alerts::QuietHours;
+use crate::clock::Zone;
+
+/**
+ * Burn-in protection for always-on OLED and plasma displays: everything drawn is shifted by a cell now and then, so
+ * that no pixel shows the same thing for days, and the colors are dimmed during the night.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BurnInConfig {
+	// Minutes between the shifts, 0 for none.
+	pub shift_minutes: i64,
+	// Most cells the screen is shifted right and down. The layouts get that much less room.
+	pub shift_cells: i32,
+	// Hours of the night when the colors are dimmed, e.g. "22:00-07:00".
+	pub dim_hours: Option<QuietHours>,
+}
+
+impl Default for BurnInConfig {
+	fn default() -> BurnInConfig {
+		return BurnInConfig {
+			shift_minutes: 5,
+			shift_cells: 2,
+			dim_hours: None,
+		};
+	}
+}
+
+impl BurnInConfig {
+	/**
+	 * Rows and columns the screen is shifted by at the moment. The shift goes a cell at a time back and forth over
+	 * the rows like a plough, and starts over from the top left corner.
+	*/
+	pub fn offset(&self, now: DateTime<Utc>) -> (i32, i32) {
+		if self.shift_minutes <= 0 || self.shift_cells <= 0 {
+			return (0, 0);
+		}
+		let positions = (self.shift_cells + 1) as i64;
+		let step = now.timestamp().div_euclid(60 * self.shift_minutes) % (positions * positions);
+		let (row, column) = (step / positions, step % positions);
+		let column = if row % 2 == 1 { positions - 1 - column } else { column };
+		return (row as i32, column as i32);
+	}
+	
+	/**
+	 * Are the colors dimmed at the moment in the zone.
+	*/
+	pub fn dims(&self, now: DateTime<Utc>, zone: Zone) -> bool {
+		return self.dim_hours.is_some_and(|hours| hours.contains(zone.local(now).time()));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+	
+	#[test]
+	fn shifts_a_cell_at_a_time() {
+		let config = BurnInConfig { shift_minutes: 1, shift_cells: 1, dim_hours: None };
+		let offsets: Vec<(i32, i32)> = (0..5).map(|minute| config.offset(Utc.timestamp_opt(minute * 60, 0).unwrap())).collect();
+		assert_eq!(offsets, [(0, 0), (0, 1), (1, 1), (1, 0), (0, 0)]);
+		
+		let off = BurnInConfig { shift_minutes: 0, ..BurnInConfig::default() };
+		assert_eq!(off.offset(Utc.timestamp_opt(600, 0).unwrap()), (0, 0));
+	}
+}
@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use crate::config::Config;
+use crate::model::{ApiResponse, Metric};
+
+/**
+ * Linear calibration of a metric: calibrated = raw * gain + offset. Alternatively two reference
+ * points [raw, actual] can be given, from which the gain and offset are calculated.
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Calibration {
+	pub offset: f64,
+	pub gain: f64,
+	pub points: Option<[(f64, f64); 2]>,
+}
+
+impl Default for Calibration {
+	fn default() -> Calibration {
+		return Calibration {
+			offset: 0.0,
+			gain: 1.0,
+			points: None,
+		};
+	}
+}
+
+impl Calibration {
+	/**
+	 * Gain and offset, from the reference points if given.
+	*/
+	fn coefficients(&self) -> (f64, f64) {
+		match self.points {
+			Some([(raw_a, actual_a), (raw_b, actual_b)]) if raw_a != raw_b => {
+				let gain = (actual_b - actual_a) / (raw_b - raw_a);
+				(gain, actual_a - raw_a * gain)
+			},
+			_ => (self.gain, self.offset),
+		}
+	}
+	
+	pub fn apply(&self, raw: f64) -> f64 {
+		let (gain, offset) = self.coefficients();
+		return raw * gain + offset;
+	}
+	
+	fn apply_metric(&self, metric: &mut Metric) {
+		metric.raw = Some(metric.current);
+		metric.current = self.apply(metric.current);
+		metric.min = metric.min.map(|min| self.apply(min));
+		metric.max = metric.max.map(|max| self.apply(max));
+		
+		// A negative gain would flip min and max.
+		if let (Some(min), Some(max)) = (metric.min, metric.max) {
+			if min > max {
+				metric.min = Some(max);
+				metric.max = Some(min);
+			}
+		}
+	}
+}
+
+/**
+ * Per-tag calibration for each metric.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TagCalibration {
+	pub temperature: Option<Calibration>,
+	pub humidity: Option<Calibration>,
+}
+
+/**
+ * Calibrate the freshly parsed data, before it's displayed, recorded or logged.
+*/
+pub fn apply(data: &mut ApiResponse, config: &Config) {
+	for tag in data.iter_mut() {
+		let Some(calibration) = config.tag(tag).and_then(|tag_config| tag_config.calibration.clone()) else {
+			continue;
+		};
+		
+		if let (Some(calibration), Some(temperature)) = (&calibration.temperature, &mut tag.temperature) {
+			calibration.apply_metric(temperature);
+		}
+		if let (Some(calibration), Some(humidity)) = (&calibration.humidity, &mut tag.humidity) {
+			calibration.apply_metric(humidity);
+			// Relative humidity can't go outside 0-100%.
+			humidity.current = humidity.current.clamp(0.0, 100.0);
+			humidity.min = humidity.min.map(|min| min.clamp(0.0, 100.0));
+			humidity.max = humidity.max.map(|max| max.clamp(0.0, 100.0));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn offset_and_gain() {
+		let calibration = Calibration { offset: -0.5, gain: 1.02, points: None };
+		assert!((calibration.apply(20.0) - 19.9).abs() < 1e-9);
+	}
+	
+	#[test]
+	fn default_is_identity() {
+		assert_eq!(Calibration::default().apply(21.37), 21.37);
+	}
+	
+	#[test]
+	fn two_points_override_offset_and_gain() {
+		let calibration = Calibration { offset: 5.0, gain: 3.0, points: Some([(0.4, 0.0), (100.6, 100.0)]) };
+		assert!((calibration.apply(0.4) - 0.0).abs() < 1e-9);
+		assert!((calibration.apply(100.6) - 100.0).abs() < 1e-9);
+		assert!((calibration.apply(50.5) - 50.0).abs() < 1e-9);
+	}
+	
+	#[test]
+	fn equal_points_fall_back_to_offset_and_gain() {
+		let calibration = Calibration { offset: 1.0, gain: 1.0, points: Some([(10.0, 0.0), (10.0, 5.0)]) };
+		assert_eq!(calibration.apply(10.0), 11.0);
+	}
+	
+	#[test]
+	fn metric_min_and_max_are_calibrated() {
+		let calibration = Calibration { offset: 0.0, gain: -1.0, points: None };
+		let mut metric = Metric { current: 1.0, min: Some(0.0), max: Some(2.0), trend: None, raw: None, delta: None };
+		calibration.apply_metric(&mut metric);
+		assert_eq!((metric.current, metric.min, metric.max, metric.raw), (-1.0, Some(-2.0), Some(0.0), Some(1.0)));
+	}
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use crate::config::TagConfig;
+use crate::model::{ApiResponse, Metric};
+
+/**
+ * Calm mode: the displayed numbers are only updated when the new value differs from the
+ * shown one by more than epsilon. Only the display is affected, the fetched data stays exact.
+ *
+ * The same epsilon is used for the temperature in °C and the humidity in %RH: the tags measure both to far less than
+ * a tenth, and in a still room both drift by about the same tenths, so one number calms both.
+*/
+pub struct CalmFilter {
+	epsilon: f64,
+	// Epsilons of single tags by tag ID or name, replacing the global one.
+	tags: HashMap<String, f64>,
+	shown: HashMap<String, (Option<Metric>, Option<Metric>)>,
+}
+
+impl CalmFilter {
+	pub fn new(epsilon: f64) -> CalmFilter {
+		return CalmFilter {
+			epsilon,
+			tags: HashMap::new(),
+			shown: HashMap::new(),
+		};
+	}
+	
+	/**
+	 * Use the calm_epsilon of the tags that set one in place of the global epsilon.
+	*/
+	pub fn with_tags(mut self, tags: &HashMap<String, TagConfig>) -> CalmFilter {
+		self.tags = tags.iter()
+			.filter_map(|(key, tag_config)| Some((key.clone(), tag_config.calm_epsilon?)))
+			.collect();
+		return self;
+	}
+	
+	/**
+	 * Return a copy of the data with the values that haven't moved enough replaced by the shown ones.
+	*/
+	pub fn apply(&mut self, data: &ApiResponse) -> ApiResponse {
+		let mut calmed = data.clone();
+		
+		for tag in calmed.iter_mut() {
+			let epsilon = *self.tags.get(&tag.id).or_else(|| self.tags.get(&tag.name)).unwrap_or(&self.epsilon);
+			if epsilon <= 0.0 {
+				continue;
+			}
+			
+			let (temperature, humidity) = self.shown
+				.entry(tag.id.clone())
+				.or_insert_with(|| (tag.temperature.clone(), tag.humidity.clone()));
+			
+			calm_metric(temperature, &tag.temperature, epsilon);
+			calm_metric(humidity, &tag.humidity, epsilon);
+			
+			tag.temperature = temperature.clone();
+			tag.humidity = humidity.clone();
+		}
+		
+		return calmed;
+	}
+}
+
+/**
+ * Update the shown metric values that have changed more than epsilon. Trend and the delta to the reference tag, which
+ * moves on its own, are always updated, and so is a metric going missing or coming back.
+*/
+fn calm_metric(shown: &mut Option<Metric>, new: &Option<Metric>, epsilon: f64) {
+	let (Some(shown), Some(new)) = (shown.as_mut(), new) else {
+		*shown = new.clone();
+		return;
+	};
+	if (new.current - shown.current).abs() > epsilon {
+		shown.current = new.current;
+		// The value the tag sent for the shown one.
+		shown.raw = new.raw;
+	}
+	if changed(shown.min, new.min, epsilon) {
+		shown.min = new.min;
+	}
+	if changed(shown.max, new.max, epsilon) {
+		shown.max = new.max;
+	}
+	shown.trend = new.trend;
+	shown.delta = new.delta;
+}
+
+fn changed(shown: Option<f64>, new: Option<f64>, epsilon: f64) -> bool {
+	match (shown, new) {
+		(Some(shown), Some(new)) => (new - shown).abs() > epsilon,
+		(shown, new) => shown.is_some() != new.is_some(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::Tag;
+	
+	fn tag(temperature: f64, delta: f64) -> Tag {
+		return Tag {
+			temperature: Some(Metric { delta: Some(delta), ..Metric::from_current(temperature) }),
+			..Tag::test("1", "Indoor")
+		};
+	}
+	
+	#[test]
+	fn epsilons_per_tag() {
+		let tags = HashMap::from([("Sauna".to_string(), TagConfig { calm_epsilon: Some(2.0), ..TagConfig::default() })]);
+		let mut calm = CalmFilter::new(0.0).with_tags(&tags);
+		let sauna = |temperature: f64| Tag { temperature: Some(Metric::from_current(temperature)), ..Tag::test("2", "Sauna") };
+		calm.apply(&vec![tag(21.0, 0.0), sauna(60.0)]);
+		let calmed = calm.apply(&vec![tag(21.2, 0.0), sauna(61.5)]);
+		assert_eq!(calmed[0].temperature.as_ref().unwrap().current, 21.2);
+		assert_eq!(calmed[1].temperature.as_ref().unwrap().current, 60.0);
+	}
+	
+	#[test]
+	fn raw_follows_the_shown_value() {
+		let sent = |raw: f64| vec![Tag {
+			temperature: Some(Metric { raw: Some(raw), ..Metric::from_current(raw + 0.5) }),
+			..Tag::test("1", "Indoor")
+		}];
+		let mut calm = CalmFilter::new(0.5);
+		calm.apply(&sent(20.0));
+		assert_eq!(calm.apply(&sent(20.25))[0].temperature.as_ref().unwrap().raw, Some(20.0));
+		let calmed = calm.apply(&sent(21.0));
+		let temperature = calmed[0].temperature.as_ref().unwrap();
+		assert_eq!((temperature.current, temperature.raw), (21.5, Some(21.0)));
+	}
+	
+	#[test]
+	fn delta_follows_the_reference() {
+		let mut calm = CalmFilter::new(0.5);
+		calm.apply(&vec![tag(21.0, 18.0)]);
+		// The tag barely moves but the reference outdoors cools down.
+		let calmed = calm.apply(&vec![tag(21.2, 21.2)]);
+		let temperature = calmed[0].temperature.as_ref().unwrap();
+		assert_eq!(temperature.current, 21.0);
+		assert_eq!(temperature.delta, Some(21.2));
+	}
+}
@@ -0,0 +1,48 @@
+use crate::glyphs::Glyphs;
+
+// Bars of the chart from empty to full, in eighths of a row.
+const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_BARS: [char; 9] = [' ', '.', '.', ':', ':', '+', '+', '#', '#'];
+
+/**
+ * Rows of a bar chart of the columns, scaled from a bit below the lowest to the highest but at least the span apart,
+ * with the top and bottom labels in the label width on the left and the axis line below. Columns without a value
+ * are left empty.
+*/
+pub fn bars(columns: &[Option<f64>], height: usize, span: f64, glyphs: Glyphs, label: impl Fn(f64) -> String, label_width: usize) -> Vec<String> {
+	let values = columns.iter().flatten().copied();
+	let (Some(min), Some(max)) = (values.clone().reduce(f64::min), values.reduce(f64::max)) else {
+		return Vec::new();
+	};
+	
+	// The lowest column still gets a bar.
+	let span = (max - min).max(span);
+	let (bottom, top) = (min - span * 0.1, min + span);
+	let bars = if glyphs.blocks { BARS } else { ASCII_BARS };
+	let eighths: Vec<usize> = columns.iter()
+		.map(|value| value.map_or(0, |value| ((value - bottom) / (top - bottom) * (height * 8) as f64).round() as usize))
+		.collect();
+	
+	let mut rows: Vec<String> = (0..height)
+		.map(|row| {
+			let label = match row {
+				0 => label(top),
+				_ if row == height - 1 => label(bottom),
+				_ => String::new(),
+			};
+			let level = (height - 1 - row) * 8;
+			let bars: String = eighths.iter().map(|eighths| bars[eighths.saturating_sub(level).min(8)]).collect();
+			format!("{:>width$} {}{}", label, glyphs.line("│", "|"), bars, width = label_width)
+		})
+		.collect();
+	rows.push(format!("{:>width$} {}{}", "", glyphs.line("└", "+"), glyphs.line("─", "-").repeat(columns.len()), width = label_width));
+	return rows;
+}
+
+/**
+ * The row below the chart with the first label under the first column and the last label ending at the last one.
+*/
+pub fn ends(first: &str, last: &str, columns: usize, label_width: usize) -> String {
+	let width = columns.saturating_sub(first.chars().count()).max(last.chars().count() + 1);
+	return format!("{:>indent$}{}{:>width$}", "", first, last, indent = label_width + 2, width = width);
+}
@@ -0,0 +1,448 @@
+use std::path::PathBuf;
+use chrono::{Duration, NaiveDate};
+use clap::{Parser, Subcommand};
+use crate::backend::BackendName;
+use crate::background::Background;
+use crate::config::{self, Config, Overrides};
+use crate::eink::EinkConfig;
+use crate::i18n::Lang;
+use crate::metrics::DerivedMetric;
+use crate::query::{Aggregate, QueryMetric};
+use crate::render::Layout;
+use crate::schema::SchemaName;
+use crate::status::StatusFormat;
+use crate::theme::ThemeName;
+use crate::units::TemperatureUnit;
+use crate::update;
+
+/**
+ * Command line arguments. These override the config file and ENV variables.
+*/
+#[derive(Debug, Parser)]
+#[command(version = update::VERSION, about = "Terminal client for Ruuvi tags", long_about = None)]
+pub struct Args {
+	/// Listen for Ruuvi Gateway pushes on this address, e.g. 0.0.0.0:8080.
+	#[arg(long)]
+	pub listen: Option<String>,
+	
+	/// Proxy for all requests, e.g. socks5://127.0.0.1:1080 for an SSH tunnel, or direct for none.
+	#[arg(long, global = true)]
+	pub proxy: Option<String>,
+	
+	/// Proxy for the API fetches only, e.g. socks5h://127.0.0.1:9050 for Tor, or direct for none.
+	#[arg(long)]
+	pub api_proxy: Option<String>,
+	
+	/// Record the readings into this SQLite database, e.g. ~/.local/share/ruuvi-tc/history.db.
+	#[arg(long, global = true)]
+	pub store: Option<PathBuf>,
+	
+	/// Generated or recorded data instead of the API and the listener: demo, or replay and a JSON or history store
+	/// file.
+	#[arg(long, num_args = 1..=2, value_names = ["KIND", "FILE"])]
+	pub source: Option<Vec<String>>,
+	
+	/// Speed of the replay, e.g. 60 for an hour a minute.
+	#[arg(long)]
+	pub replay_speed: Option<f64>,
+	
+	/// Save every raw payload from the API and the listener into this directory, named by the time received.
+	#[arg(long)]
+	pub record: Option<PathBuf>,
+	
+	/// Log the keys pressed, the view changes and the refreshes of the TUI into this file, for bug reports.
+	#[arg(long)]
+	pub session_log: Option<PathBuf>,
+	
+	/// Fetch at :00 of every minute instead of a minute after the previous fetch.
+	#[arg(long)]
+	pub align_refresh: bool,
+	
+	/// Run without the TUI, logging the readings and state changes.
+	#[arg(long)]
+	pub headless: bool,
+	
+	/// Log file for the headless mode. Defaults to stdout.
+	#[arg(long)]
+	pub log_file: Option<PathBuf>,
+	
+	/// Show the tags of the headless instance sharing them on the socket of the config file, instead of fetching.
+	#[arg(long, conflicts_with_all = ["headless", "source"])]
+	pub attach: bool,
+	
+	/// Write the TUI as plain text into this file on every refresh, e.g. for watch cat or an e-ink display.
+	#[arg(long)]
+	pub mirror: Option<PathBuf>,
+	
+	/// File the S key saves the screen into, as HTML with the colors if it ends in .html and as text otherwise.
+	#[arg(long)]
+	pub snapshot: Option<PathBuf>,
+	
+	/// Print the tags as a plain table to stdout instead of the TUI.
+	#[arg(long)]
+	pub plain: bool,
+	
+	/// With --plain, print the table again on every refresh, in place on a terminal.
+	#[arg(long, requires = "plain")]
+	pub watch: bool,
+	
+	/// Print each tag as a sentence for screen readers instead of the TUI, again only when its values change.
+	#[arg(long, conflicts_with = "plain")]
+	pub accessible: bool,
+	
+	/// Print the JSON Schema of the status command's JSON (the default), the webhook body or the forwarded lines, and exit.
+	#[arg(long, value_enum, num_args = 0..=1, default_missing_value = "status", value_name = "OUTPUT")]
+	pub schema: Option<SchemaName>,
+	
+	/// Terminal library to draw the TUI with. Needs a build with its feature.
+	#[arg(long, value_enum)]
+	pub backend: Option<BackendName>,
+	
+	/// Color theme.
+	#[arg(long, value_enum)]
+	pub theme: Option<ThemeName>,
+	
+	/// Profile of the config file to use, a [profile.NAME] table laid over the rest of the file.
+	#[arg(long)]
+	pub profile: Option<String>,
+	
+	/// Don't use colors.
+	#[arg(long)]
+	pub no_color: bool,
+	
+	/// Background of the terminal for the theme's colors. Asked from the terminal by default.
+	#[arg(long, value_enum)]
+	pub background: Option<Background>,
+	
+	/// Have the terminal report the focus, and redraw less often without it, see focus_events in the config.
+	#[arg(long)]
+	pub focus_events: bool,
+	
+	/// E-ink mode: monochrome, and redrawn in full only when the data changes, see [eink] in the config.
+	#[arg(long)]
+	pub eink: bool,
+	
+	/// User interface language. Defaults to the locale.
+	#[arg(long, value_enum)]
+	pub lang: Option<Lang>,
+	
+	/// Unit of the temperatures shown.
+	#[arg(long, value_enum)]
+	pub temperature_unit: Option<TemperatureUnit>,
+	
+	/// Show the values to the resolution of the tags, e.g. 0.005 °C, instead of rounding them.
+	#[arg(long)]
+	pub precise: bool,
+	
+	/// Layout of the tags.
+	#[arg(long, value_enum)]
+	pub layout: Option<Layout>,
+	
+	/// Show one tag at a time with big digits, for wall displays.
+	#[arg(long)]
+	pub kiosk: bool,
+	
+	/// Seconds each tag is shown in the kiosk mode.
+	#[arg(long)]
+	pub kiosk_cycle_seconds: Option<u64>,
+	
+	/// Rotate through the layouts, themes and tags on a timer, for demos.
+	#[arg(long)]
+	pub demo: bool,
+	
+	/// Derived metrics to show for all tags, comma separated.
+	#[arg(long, value_enum, value_delimiter = ',')]
+	pub derived: Option<Vec<DerivedMetric>>,
+	
+	#[command(subcommand)]
+	pub command: Option<Command>,
+}
+
+impl Args {
+	/**
+	 * Load the config with the profile of --profile, overridden by the arguments.
+	*/
+	pub fn load(&self) -> Result<Config, String> {
+		return self.load_profile(self.profile.as_deref());
+	}
+	
+	/**
+	 * Load the config with the profile, or without one, overridden by the arguments.
+	*/
+	pub fn load_profile(&self, profile: Option<&str>) -> Result<Config, String> {
+		let mut config = Config::load_profile(&self.overrides(), profile)?;
+		
+		if let Some(session_log) = &self.session_log {
+			config.session_log = Some(config::expand_home(session_log));
+		}
+		if let Some(mirror) = &self.mirror {
+			config.mirror = Some(config::expand_home(mirror));
+		}
+		if let Some(snapshot) = &self.snapshot {
+			config.snapshot = Some(config::expand_home(snapshot));
+		}
+		
+		if let Some(backend) = self.backend {
+			config.backend = backend;
+		}
+		if let Some(theme) = self.theme {
+			config.theme = theme;
+		}
+		if self.no_color {
+			config.no_color = true;
+		}
+		if let Some(background) = self.background {
+			config.background = background;
+		}
+		if self.focus_events {
+			config.focus_events = true;
+		}
+		if self.eink && config.eink.is_none() {
+			config.eink = Some(EinkConfig::default());
+		}
+		
+		if let Some(layout) = self.layout {
+			config.layout = layout;
+		}
+		if self.kiosk {
+			config.kiosk = true;
+		}
+		if let Some(seconds) = self.kiosk_cycle_seconds {
+			config.kiosk_cycle_seconds = seconds;
+		}
+		if self.demo {
+			config.demo = true;
+		}
+		
+		return Ok(config);
+	}
+	
+	/**
+	 * The arguments that the library's config loading takes.
+	*/
+	fn overrides(&self) -> Overrides {
+		return Overrides {
+			profile: self.profile.clone(),
+			listen: self.listen.clone(),
+			proxy: self.proxy.clone(),
+			api_proxy: self.api_proxy.clone(),
+			store: self.store.clone(),
+			record: self.record.clone(),
+			align_refresh: self.align_refresh,
+			headless: self.headless,
+			log_file: self.log_file.clone(),
+			source: self.source.clone(),
+			replay_speed: self.replay_speed,
+			attach: self.attach,
+			lang: self.lang,
+			temperature_unit: self.temperature_unit,
+			precise: self.precise,
+			derived: self.derived.clone(),
+			// Subcommands don't need a source.
+			without_source: self.command.is_some(),
+		};
+	}
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+	/// Inspect or prune the stored history.
+	History {
+		#[command(subcommand)]
+		action: HistoryAction,
+	},
+	/// Export or acknowledge the alerts in the alert log.
+	Alerts {
+		#[command(subcommand)]
+		action: AlertsAction,
+	},
+	/// Export the stored readings for spreadsheets and other analysis tools.
+	Export {
+		/// Only this tag (ID or name).
+		#[arg(long)]
+		tag: Option<String>,
+		/// First day of the readings, e.g. 2024-01-01. Defaults to the first reading.
+		#[arg(long)]
+		from: Option<NaiveDate>,
+		/// Last day of the readings, included. Defaults to today.
+		#[arg(long)]
+		to: Option<NaiveDate>,
+		/// Output format.
+		#[arg(long, value_enum, default_value = "csv")]
+		format: ExportFormat,
+	},
+	/// Cold chain compliance report of the tags with compliance limits: time within the limits and the excursions.
+	Report {
+		/// First day of the report, e.g. 2024-05-01.
+		#[arg(long)]
+		from: NaiveDate,
+		/// Last day of the report. Defaults to today.
+		#[arg(long)]
+		to: Option<NaiveDate>,
+		/// Only this tag (ID or name).
+		#[arg(long)]
+		tag: Option<String>,
+		/// Text for printing, or CSV with a row per excursion.
+		#[arg(long, value_enum, default_value = "text")]
+		format: ReportFormat,
+		/// Smallest unit of the humanized durations and ages in the CSV, rounded to it.
+		#[arg(long, value_enum, default_value = "minutes")]
+		age_precision: AgePrecision,
+	},
+	/// Answer a question from the stored readings, e.g. the highest temperature of the sauna in the last 6 hours.
+	/// The value picked by min, max and last is followed by its time, tab separated.
+	Query {
+		/// Tag (ID or name).
+		tag: String,
+		#[arg(long, value_enum, default_value = "temperature")]
+		metric: QueryMetric,
+		/// The period up to now, e.g. 6h or 7d.
+		#[arg(long, value_parser = parse_duration, default_value = "24h")]
+		last: Duration,
+		/// How the readings of the period are summed up.
+		#[arg(long, value_enum, default_value = "last")]
+		agg: Aggregate,
+	},
+	/// Print the growing degree days of the season per day, tab separated.
+	DegreeDays {
+		/// Tag (ID or name). The base and season start come from its config.
+		tag: String,
+	},
+	/// Chart the daily battery voltage of a tag over months, to see the battery running out before it does.
+	Battery {
+		/// Tag (ID or name).
+		tag: String,
+		/// Days of history to chart.
+		#[arg(long, default_value_t = 365)]
+		days: u64,
+		/// Columns of the chart. The days are averaged to fit.
+		#[arg(long, default_value_t = 72)]
+		width: usize,
+	},
+	/// Print the version and check GitHub for a newer release.
+	Version,
+	/// Fetch once and print the tags with their trends, for scripts and status bars like tmux and waybar.
+	Status {
+		/// A line per tag, one tmux status line, waybar JSON, or JSON for scripts.
+		#[arg(long, value_enum, default_value = "text")]
+		format: StatusFormat,
+	},
+	/// Check the terminal, the config, the sources and the directories, and print a report to attach to bug reports.
+	Doctor,
+	/// Validate the config and fetch once from each configured source, e.g. for provisioning scripts and systemd's
+	/// ExecStartPre. Exits with 1 if anything failed.
+	Check,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryAction {
+	/// Print the stored readings.
+	Dump {
+		/// Only this tag (ID or name).
+		#[arg(long)]
+		tag: Option<String>,
+		/// Only readings from the last period, e.g. 6h or 7d.
+		#[arg(long, value_parser = parse_duration)]
+		last: Option<Duration>,
+	},
+	/// List the longest periods without measurements per tag, tab separated.
+	Gaps {
+		/// Only this tag (ID or name).
+		#[arg(long)]
+		tag: Option<String>,
+		/// Only readings from the last period, e.g. 7d.
+		#[arg(long, value_parser = parse_duration)]
+		last: Option<Duration>,
+		/// Shortest gap to list, e.g. 30m. Defaults to the tag's unreachable limit.
+		#[arg(long, value_parser = parse_duration)]
+		longer_than: Option<Duration>,
+		/// Number of gaps to list.
+		#[arg(long, default_value_t = 10)]
+		top: usize,
+	},
+	/// Delete old readings.
+	Prune {
+		/// Delete readings older than this, e.g. 30d.
+		#[arg(long, value_parser = parse_duration)]
+		older_than: Duration,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AlertsAction {
+	/// Print the alert log: start, end, duration, peak value and who acknowledged each alert.
+	Export {
+		/// Output format.
+		#[arg(long, value_enum, default_value = "csv")]
+		format: ExportFormat,
+		/// Only this tag (ID or name).
+		#[arg(long)]
+		tag: Option<String>,
+		/// Only alerts started in the last period, e.g. 30d.
+		#[arg(long, value_parser = parse_duration)]
+		last: Option<Duration>,
+		/// Smallest unit of the humanized durations and ages, rounded to it.
+		#[arg(long, value_enum, default_value = "minutes")]
+		age_precision: AgePrecision,
+	},
+	/// Mark an alert acknowledged. The IDs are in the export and the headless log.
+	Ack {
+		id: String,
+		/// Who acknowledged it. Defaults to the user name.
+		#[arg(long)]
+		by: Option<String>,
+	},
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormat {
+	Csv,
+	Json,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ReportFormat {
+	Text,
+	Csv,
+}
+
+/**
+ * Smallest unit of humanized durations like "2d 3h 15m" in the exports.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AgePrecision {
+	Seconds,
+	Minutes,
+	Hours,
+	Days,
+}
+
+impl AgePrecision {
+	pub fn seconds(self) -> i64 {
+		match self {
+			AgePrecision::Seconds => 1,
+			AgePrecision::Minutes => 60,
+			AgePrecision::Hours => 3600,
+			AgePrecision::Days => 86400,
+		}
+	}
+}
+
+/**
+ * Parse durations like "90s", "15m", "6h", "30d" and "2w".
+*/
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+	let value = value.trim();
+	let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+	let (number, unit) = value.split_at(split);
+	let number: i64 = number.parse().map_err(|_| format!("Invalid duration \"{}\"", value))?;
+	
+	match unit {
+		"s" => Ok(Duration::seconds(number)),
+		"m" => Ok(Duration::minutes(number)),
+		"h" | "" => Ok(Duration::hours(number)),
+		"d" => Ok(Duration::days(number)),
+		"w" => Ok(Duration::weeks(number)),
+		_ => Err(format!("Invalid duration unit in \"{}\", use s, m, h, d or w", value)),
+	}
+}
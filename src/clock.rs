@@ -0,0 +1,218 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/**
+ * Source of the current time for the time-dependent logic (staleness, trends, min/max windows, alerts). The system
+ * clock normally, a simulated one in tests and replays.
+*/
+pub trait Clock: Send + Sync {
+	fn now(&self) -> DateTime<Utc>;
+	
+	/**
+	 * Time that stands still while the machine is suspended, so that the difference to now() tells a suspend from the
+	 * time passing. The same as now() for the clocks that can't tell.
+	*/
+	fn awake(&self) -> DateTime<Utc> {
+		return self.now();
+	}
+}
+
+/**
+ * Civil time of the site: the configured time zone, or the system's without one. The days start at the local
+ * midnight, so they are 23 or 25 hours long across the daylight saving changes.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Zone(pub Option<Tz>);
+
+impl Zone {
+	/**
+	 * Wall clock time of the moment.
+	*/
+	pub fn local(&self, datetime: DateTime<Utc>) -> NaiveDateTime {
+		return match self.0 {
+			Some(zone) => datetime.with_timezone(&zone).naive_local(),
+			None => datetime.with_timezone(&Local).naive_local(),
+		};
+	}
+	
+	pub fn date(&self, datetime: DateTime<Utc>) -> NaiveDate {
+		return self.local(datetime).date();
+	}
+	
+	/**
+	 * Start of the day. Where a daylight saving change skips midnight, the first moment of the day after it.
+	*/
+	pub fn midnight(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+		let midnight = date.and_hms_opt(0, 0, 0)?;
+		// The changes skip an hour at most, in some zones half an hour.
+		return (0..=4)
+			.map(|quarter| midnight + Duration::minutes(15 * quarter))
+			.find_map(|time| match self.0 {
+				Some(zone) => earliest(&zone, time),
+				None => earliest(&Local, time),
+			});
+	}
+}
+
+/**
+ * A datetime from a source: RFC 3339, also with a space for the T or the offset without a colon, UNIX seconds or
+ * milliseconds, or without an offset like "2024-05-01 12:00:00", which is in the zone, UTC without one.
+*/
+pub fn parse_datetime(text: &str, zone: Option<Tz>) -> Option<DateTime<Utc>> {
+	let text = text.trim();
+	if let Ok(datetime) = text.parse::<DateTime<Utc>>() {
+		return Some(datetime);
+	}
+	if !text.is_empty() && text.chars().all(|character| character.is_ascii_digit()) {
+		let number: i64 = text.parse().ok()?;
+		// Seconds would be past the year 33658.
+		return if number >= 1_000_000_000_000 { DateTime::from_timestamp_millis(number) } else { DateTime::from_timestamp(number, 0) };
+	}
+	let offset = ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z"].iter()
+		.find_map(|format| DateTime::parse_from_str(text, format).ok());
+	if let Some(datetime) = offset {
+		return Some(datetime.with_timezone(&Utc));
+	}
+	let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"].iter()
+		.find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())?;
+	return match zone {
+		Some(zone) => earliest(&zone, naive),
+		None => Some(naive.and_utc()),
+	};
+}
+
+fn earliest<Z: TimeZone>(zone: &Z, time: NaiveDateTime) -> Option<DateTime<Utc>> {
+	return time.and_local_timezone(zone.clone()).earliest().map(|time| time.with_timezone(&Utc));
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> DateTime<Utc> {
+		return Utc::now();
+	}
+	
+	/**
+	 * The monotonic clock from the first call on, which doesn't run during a suspend on Linux and macOS.
+	*/
+	fn awake(&self) -> DateTime<Utc> {
+		static STARTED: OnceLock<(Instant, DateTime<Utc>)> = OnceLock::new();
+		let (instant, started) = STARTED.get_or_init(|| (Instant::now(), Utc::now()));
+		return *started + Duration::from_std(instant.elapsed()).unwrap_or_default();
+	}
+}
+
+/**
+ * Clock running from a start time at a multiple of the real speed, for replays.
+*/
+pub struct ScaledClock {
+	start: DateTime<Utc>,
+	started: Instant,
+	speed: f64,
+}
+
+impl ScaledClock {
+	pub fn new(start: DateTime<Utc>, speed: f64) -> ScaledClock {
+		return ScaledClock { start, started: Instant::now(), speed };
+	}
+}
+
+impl Clock for ScaledClock {
+	fn now(&self) -> DateTime<Utc> {
+		return self.start + Duration::milliseconds((self.started.elapsed().as_millis() as f64 * self.speed) as i64);
+	}
+}
+
+/**
+ * Clock that only moves when told to.
+*/
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct SimulatedClock {
+	now: Mutex<DateTime<Utc>>,
+	// Time spent suspended, which the awake time leaves out.
+	suspended: Mutex<Duration>,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl SimulatedClock {
+	pub fn new(start: DateTime<Utc>) -> SimulatedClock {
+		return SimulatedClock { now: Mutex::new(start), suspended: Mutex::new(Duration::zero()) };
+	}
+	
+	pub fn set(&self, now: DateTime<Utc>) {
+		*self.now.lock().unwrap() = now;
+	}
+	
+	pub fn advance(&self, duration: Duration) {
+		*self.now.lock().unwrap() += duration;
+	}
+	
+	/**
+	 * Move on as if the machine was suspended for the duration.
+	*/
+	pub fn suspend(&self, duration: Duration) {
+		self.advance(duration);
+		*self.suspended.lock().unwrap() += duration;
+	}
+}
+
+impl Clock for SimulatedClock {
+	fn now(&self) -> DateTime<Utc> {
+		return *self.now.lock().unwrap();
+	}
+	
+	fn awake(&self) -> DateTime<Utc> {
+		return self.now() - *self.suspended.lock().unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn simulated_clock_moves_when_told() {
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let clock = SimulatedClock::new(start);
+		assert_eq!(clock.now(), start);
+		
+		clock.advance(Duration::minutes(90));
+		assert_eq!(clock.now(), start + Duration::minutes(90));
+		
+		clock.set(start);
+		assert_eq!(clock.now(), start);
+		
+		clock.suspend(Duration::hours(8));
+		assert_eq!(clock.now() - clock.awake(), Duration::hours(8));
+	}
+	
+	#[test]
+	fn days_across_daylight_saving() {
+		let zone = Zone(Some(chrono_tz::Europe::Helsinki));
+		let date = |text: &str| text.parse::<NaiveDate>().unwrap();
+		let day = |text: &str| zone.midnight(date(text).succ_opt().unwrap()).unwrap() - zone.midnight(date(text)).unwrap();
+		assert_eq!(day("2024-03-31"), Duration::hours(23));
+		assert_eq!(day("2024-10-27"), Duration::hours(25));
+		assert_eq!(zone.date("2024-10-26T21:30:00Z".parse().unwrap()), date("2024-10-27"));
+		
+		// Midnight doesn't exist when the clocks are turned forward at it.
+		let santiago = Zone(Some(chrono_tz::America::Santiago));
+		assert_eq!(santiago.midnight(date("2024-09-08")), Some("2024-09-08T04:00:00Z".parse().unwrap()));
+	}
+	
+	#[test]
+	fn datetimes_of_the_sources() {
+		let noon = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let helsinki = Some(chrono_tz::Europe::Helsinki);
+		for text in ["2024-05-01T12:00:00Z", "2024-05-01 15:00:00+03:00", "2024-05-01T15:00:00+0300", "1714564800", "1714564800000", "2024-05-01 12:00:00"] {
+			assert_eq!(parse_datetime(text, None), Some(noon), "{}", text);
+		}
+		assert_eq!(parse_datetime("2024-05-01 15:00:00", helsinki), Some(noon));
+		assert_eq!(parse_datetime("2024-05-01T15:00", helsinki), Some(noon));
+		assert_eq!(parse_datetime("2024-05-01T12:00:00Z", helsinki), Some(noon));
+		assert_eq!(parse_datetime("yesterday", None), None);
+		assert_eq!(parse_datetime("", None), None);
+	}
+}
@@ -0,0 +1,494 @@
+use std::sync::Arc;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use crate::cli::{AgePrecision, AlertsAction, Command, ExportFormat, HistoryAction, ReportFormat};
+use crate::battery;
+use crate::compliance::{self, ComplianceLimits};
+use crate::clock::SystemClock;
+use crate::config::{Config, TagConfig};
+use crate::degree_days;
+use crate::doctor;
+use crate::http;
+use crate::poller::Poller;
+use crate::query::{self, Aggregate, QueryMetric};
+use crate::staleness;
+use crate::status::{self, State, StatusFormat};
+use crate::store::{Reading, Store};
+use crate::units;
+use crate::update;
+
+/**
+ * Run a subcommand. Returns the exit code.
+*/
+pub fn run(command: &Command, config: &Config) -> i32 {
+	let result = match command {
+		Command::History { action } => history(action, config),
+		Command::Alerts { action } => alerts(action, config),
+		Command::Export { tag, from, to, format } => export(tag.as_deref(), *from, *to, *format, config),
+		Command::Report { from, to, tag, format, age_precision } => report(*from, *to, tag.as_deref(), *format, *age_precision, config),
+		Command::Query { tag, metric, last, agg } => query(tag, *metric, *last, *agg, config),
+		Command::DegreeDays { tag } => degree_days(tag, config),
+		Command::Battery { tag, days, width } => battery(tag, *days, *width, config),
+		Command::Version => version(config),
+		Command::Status { format } => return status(*format, config),
+		Command::Doctor => return doctor::run(config, None),
+		Command::Check => return doctor::check_sources(config, None),
+	};
+	
+	match result {
+		Ok(()) => 0,
+		Err(error) => {
+			eprintln!("{}", error);
+			1
+		}
+	}
+}
+
+fn open_store(config: &Config) -> Result<Store, Box<dyn std::error::Error>> {
+	let Some(path) = &config.store else {
+		return Err("No history store configured, use --store or store in the config file".into());
+	};
+	return Store::open(path);
+}
+
+fn history(action: &HistoryAction, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	
+	match action {
+		HistoryAction::Dump { tag, last } => {
+			let since = last.map(|last| Utc::now() - last);
+			for reading in store.readings(tag.as_deref(), since)? {
+				let voltage = reading.battery_voltage
+					.map(|voltage| format!("{:.3} V", voltage))
+					.unwrap_or_default();
+				println!(
+					"{}\t{}\t{:+.2}°C\t{:.2}%\t{}",
+					reading.datetime.to_rfc3339(),
+					reading.tag_name,
+					reading.temperature,
+					reading.humidity,
+					voltage
+				);
+			}
+		},
+		HistoryAction::Gaps { tag, last, longer_than, top } => {
+			let since = last.map(|last| Utc::now() - last);
+			let readings = store.readings(tag.as_deref(), since)?;
+			
+			// Tag name, start and end of each gap.
+			let mut gaps: Vec<(&str, DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+			let mut tag_ids: Vec<&str> = readings.iter().map(|reading| reading.tag_id.as_str()).collect();
+			tag_ids.sort();
+			tag_ids.dedup();
+			for tag_id in tag_ids {
+				let tag_readings: Vec<_> = readings.iter().filter(|reading| reading.tag_id == tag_id).collect();
+				let datetimes: Vec<DateTime<Utc>> = tag_readings.iter().map(|reading| reading.datetime).collect();
+				let limit = longer_than.unwrap_or_else(|| gap_limit(config, tag_readings[0], &datetimes));
+				
+				for (start, end) in staleness::gaps(&datetimes, limit) {
+					gaps.push((&tag_readings[0].tag_name, start, end));
+				}
+			}
+			
+			gaps.sort_by_key(|(_, start, end)| std::cmp::Reverse(*end - *start));
+			for (tag_name, start, end) in gaps.iter().take(*top) {
+				println!("{}\t{}\t{}\t{}", tag_name, start.to_rfc3339(), end.to_rfc3339(), humanize(*end - *start, AgePrecision::Minutes));
+			}
+		},
+		HistoryAction::Prune { older_than } => {
+			let deleted = store.prune(Utc::now() - *older_than)?;
+			println!("Deleted {} readings", deleted);
+		},
+	}
+	
+	return Ok(());
+}
+
+/**
+ * Shortest gap in the tag's readings that counts as an outage: its unreachable limit, by the configured or learned
+ * report interval.
+*/
+fn gap_limit(config: &Config, reading: &Reading, datetimes: &[DateTime<Utc>]) -> Duration {
+	let interval = config.tags.get(&reading.tag_id)
+		.or_else(|| config.tags.get(&reading.tag_name))
+		.and_then(|tag_config| tag_config.report_interval_seconds)
+		.map(Duration::seconds)
+		.or_else(|| staleness::learned_interval(datetimes.iter().copied()));
+	return staleness::limit(interval, config).unwrap_or(Duration::minutes(config.unreachable_after_minutes.max(15)));
+}
+
+fn report(from: NaiveDate, to: Option<NaiveDate>, tag: Option<&str>, format: ReportFormat, precision: AgePrecision, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	let now = Utc::now();
+	let zone = config.zone();
+	let to = to.unwrap_or_else(|| zone.date(now));
+	let (Some(start), Some(end)) = (zone.midnight(from), to.succ_opt().and_then(|to| zone.midnight(to))) else {
+		return Err("Invalid date range".into());
+	};
+	
+	let mut keys: Vec<(&String, &ComplianceLimits)> = config.tags.iter()
+		.filter_map(|(key, tag_config)| Some((key, tag_config.compliance.as_ref()?)))
+		.filter(|(key, _)| tag.is_none_or(|tag| tag == key.as_str()))
+		.collect();
+	keys.sort_by_key(|(key, _)| key.as_str());
+	if keys.is_empty() {
+		return Err("No tags with compliance limits, set compliance in the tag config".into());
+	}
+	
+	match format {
+		ReportFormat::Text => {
+			println!("Cold chain compliance report {} – {}", from, to);
+			println!("Generated {}", zone.local(now).format("%Y-%m-%d %H:%M"));
+		},
+		ReportFormat::Csv => println!("tag,kind,start,end,duration_minutes,peak,duration,end_age"),
+	}
+	
+	for (key, limits) in keys {
+		let readings: Vec<Reading> = store.readings(Some(key), Some(start))?
+			.into_iter()
+			.filter(|reading| reading.datetime < end)
+			.collect();
+		let Some(first) = readings.first() else {
+			if let ReportFormat::Text = format {
+				println!("\n{}\n  No readings", key);
+			}
+			continue;
+		};
+		let datetimes: Vec<DateTime<Utc>> = readings.iter().map(|reading| reading.datetime).collect();
+		let temperatures: Vec<(DateTime<Utc>, f64)> = readings.iter().map(|reading| (reading.datetime, reading.temperature)).collect();
+		let summary = compliance::check(&temperatures, limits, gap_limit(config, first, &datetimes));
+		
+		if let ReportFormat::Csv = format {
+			let name = csv_field(&serde_json::Value::String(first.tag_name.clone()));
+			for excursion in &summary.excursions {
+				println!(
+					"{},excursion,{},{},{},{:.2},{},{}",
+					name,
+					excursion.start.to_rfc3339(),
+					excursion.end.to_rfc3339(),
+					excursion.duration().num_minutes(),
+					excursion.peak,
+					humanize(excursion.duration(), precision),
+					age(excursion.end, now, precision)
+				);
+			}
+			for (start, end) in &summary.gaps {
+				println!(
+					"{},gap,{},{},{},,{},{}",
+					name,
+					start.to_rfc3339(),
+					end.to_rfc3339(),
+					(*end - *start).num_minutes(),
+					humanize(*end - *start, precision),
+					age(*end, now, precision)
+				);
+			}
+			continue;
+		}
+		
+		let limit = |limit: Option<f64>| limit.map(|limit| format!("{:.1}", limit)).unwrap_or("-".into());
+		let min = temperatures.iter().map(|(_, temperature)| *temperature).reduce(f64::min).unwrap_or_default();
+		let max = temperatures.iter().map(|(_, temperature)| *temperature).reduce(f64::max).unwrap_or_default();
+		let local = |datetime: &DateTime<Utc>| zone.local(*datetime).format("%Y-%m-%d %H:%M").to_string();
+		
+		println!("\n{} ({})", first.tag_name, first.tag_id);
+		println!("  Limits:         {} … {} °C", limit(limits.min), limit(limits.max));
+		println!("  Readings:       {}, {:.1} … {:.1} °C, average {:.1} °C", readings.len(), min, max, summary.average.unwrap_or_default());
+		println!(
+			"  Within limits:  {} of {} covered by readings",
+			summary.compliance().map(|compliance| format!("{:.2} %", compliance)).unwrap_or("-".into()),
+			humanize(summary.covered, AgePrecision::Minutes)
+		);
+		let total: Duration = summary.excursions.iter().map(|excursion| excursion.duration()).sum();
+		println!("  Excursions:     {}, {} in total", summary.excursions.len(), humanize(total, AgePrecision::Minutes));
+		for excursion in &summary.excursions {
+			println!(
+				"    {} – {}  {}  peak {:.1} °C{}",
+				local(&excursion.start),
+				local(&excursion.end),
+				humanize(excursion.duration(), AgePrecision::Minutes),
+				excursion.peak,
+				if excursion.ended { "" } else { "  (not ended)" }
+			);
+		}
+		let total: Duration = summary.gaps.iter().map(|(start, end)| *end - *start).sum();
+		println!("  Gaps:           {}, {} in total", summary.gaps.len(), humanize(total, AgePrecision::Minutes));
+		for (start, end) in &summary.gaps {
+			println!("    {} – {}  {}", local(start), local(end), humanize(*end - *start, AgePrecision::Minutes));
+		}
+	}
+	
+	return Ok(());
+}
+
+/**
+ * The readings of the local days as CSV with a header row or as a JSON array, oldest first.
+*/
+fn export(tag: Option<&str>, from: Option<NaiveDate>, to: Option<NaiveDate>, format: ExportFormat, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	let zone = config.zone();
+	let to = to.unwrap_or_else(|| zone.date(Utc::now()));
+	let start = match from {
+		Some(from) => Some(zone.midnight(from).ok_or("Invalid date range")?),
+		None => None,
+	};
+	let end = to.succ_opt().and_then(|to| zone.midnight(to)).ok_or("Invalid date range")?;
+	// To the precision of the metrics, or as measured when precise.
+	let precision = config.units.precision;
+	let round = |value: f64, decimals: usize| if config.units.precise { value } else { units::round(value, decimals) };
+	
+	let rows: Vec<serde_json::Value> = store.readings(tag, start)?
+		.into_iter()
+		.filter(|reading| reading.datetime < end)
+		.map(|reading| serde_json::json!({
+			"tag_id": reading.tag_id,
+			"tag_name": reading.tag_name,
+			"datetime": reading.datetime.to_rfc3339(),
+			"temperature": round(reading.temperature, precision.temperature),
+			"humidity": round(reading.humidity, precision.humidity),
+			"pressure": reading.pressure.map(|pressure| round(pressure, precision.pressure)),
+			"battery_voltage": reading.battery_voltage.map(|voltage| round(voltage, precision.battery_voltage)),
+		}))
+		.collect();
+	
+	match format {
+		ExportFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+		ExportFormat::Csv => {
+			println!("{}", READING_COLUMNS.join(","));
+			for row in &rows {
+				let fields: Vec<String> = READING_COLUMNS.iter().map(|column| csv_field(&row[column])).collect();
+				println!("{}", fields.join(","));
+			}
+		},
+	}
+	
+	return Ok(());
+}
+
+const READING_COLUMNS: [&str; 7] = ["tag_id", "tag_name", "datetime", "temperature", "humidity", "pressure", "battery_voltage"];
+
+fn alerts(action: &AlertsAction, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	
+	match action {
+		AlertsAction::Export { format, tag, last, age_precision } => {
+			let since = last.map(|last| Utc::now() - last);
+			let records = store.alert_log(tag.as_deref(), since)?;
+			let now = Utc::now();
+			
+			let rows: Vec<serde_json::Value> = records.iter().map(|record| {
+				let alert = &record.alert;
+				let duration = record.cleared_at.unwrap_or(now) - alert.started_at;
+				serde_json::json!({
+					"id": alert.id(),
+					"tag_id": alert.tag_id,
+					"tag_name": alert.tag_name,
+					"rule": alert.rule,
+					"severity": alert.severity.name(),
+					"state": record.state(),
+					"message": alert.message,
+					"started_at": alert.started_at.to_rfc3339(),
+					"started_age": age(alert.started_at, now, *age_precision),
+					"cleared_at": record.cleared_at.map(|cleared_at| cleared_at.to_rfc3339()),
+					"cleared_age": record.cleared_at.map(|cleared_at| age(cleared_at, now, *age_precision)),
+					// Active alerts count up to now.
+					"duration_seconds": duration.num_seconds(),
+					"duration": humanize(duration, *age_precision),
+					"value": alert.value,
+					"peak": alert.peak,
+					"acknowledged_by": record.acknowledged_by,
+					"acknowledged_at": record.acknowledged_at.map(|acknowledged_at| acknowledged_at.to_rfc3339()),
+				})
+			}).collect();
+			
+			match format {
+				ExportFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+				ExportFormat::Csv => {
+					println!("{}", CSV_COLUMNS.join(","));
+					for row in &rows {
+						let fields: Vec<String> = CSV_COLUMNS.iter().map(|column| csv_field(&row[column])).collect();
+						println!("{}", fields.join(","));
+					}
+				},
+			}
+		},
+		AlertsAction::Ack { id, by } => {
+			let by = by.clone()
+				.or_else(|| std::env::var("USER").ok())
+				.ok_or("Give the name with --by")?;
+			if !store.acknowledge(id, &by, Utc::now())? {
+				return Err(format!("No alert {}", id).into());
+			}
+			println!("Acknowledged {} by {}", id, by);
+		},
+	}
+	
+	return Ok(());
+}
+
+const CSV_COLUMNS: [&str; 17] = [
+	"id", "tag_id", "tag_name", "rule", "severity", "state", "message", "started_at", "started_age", "cleared_at",
+	"cleared_age", "duration_seconds", "duration", "value", "peak", "acknowledged_by", "acknowledged_at",
+];
+
+/**
+ * A JSON value as a CSV field, quoted if needed. Null is an empty field.
+*/
+fn csv_field(value: &serde_json::Value) -> String {
+	let text = match value {
+		serde_json::Value::Null => String::new(),
+		serde_json::Value::String(text) => text.clone(),
+		other => other.to_string(),
+	};
+	if text.contains([',', '"', '\n']) {
+		return format!("\"{}\"", text.replace('"', "\"\""));
+	}
+	return text;
+}
+
+/**
+ * Duration rounded to the precision, e.g. "2d 3h 15m" or "45s".
+*/
+fn humanize(duration: Duration, precision: AgePrecision) -> String {
+	let unit = precision.seconds();
+	let seconds = (duration.num_seconds().max(0) + unit / 2) / unit * unit;
+	let parts = [(seconds / 86400, "d"), (seconds / 3600 % 24, "h"), (seconds / 60 % 60, "m"), (seconds % 60, "s")];
+	let text: Vec<String> = parts.iter()
+		.filter(|(count, _)| *count > 0)
+		.map(|(count, unit)| format!("{}{}", count, unit))
+		.collect();
+	if text.is_empty() {
+		return match precision {
+			AgePrecision::Seconds => "0s".into(),
+			AgePrecision::Minutes => "0m".into(),
+			AgePrecision::Hours => "0h".into(),
+			AgePrecision::Days => "0d".into(),
+		};
+	}
+	return text.join(" ");
+}
+
+/**
+ * How long ago the time was, e.g. "3m ago", for people reading the exports next to the timestamps.
+*/
+fn age(datetime: DateTime<Utc>, now: DateTime<Utc>, precision: AgePrecision) -> String {
+	return format!("{} ago", humanize(now - datetime, precision));
+}
+
+fn query(tag: &str, metric: QueryMetric, last: Duration, aggregate: Aggregate, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	let readings = store.readings(Some(tag), Some(Utc::now() - last))?;
+	let values: Vec<(DateTime<Utc>, f64)> = readings.iter()
+		.filter_map(|reading| Some((reading.datetime, metric.value(reading)?)))
+		.collect();
+	
+	// No readings is an answer to the count, too.
+	if aggregate == Aggregate::Count {
+		println!("{}", values.len());
+		return Ok(());
+	}
+	let Some((value, datetime)) = query::aggregate(&values, aggregate) else {
+		return Err(format!("No readings of {} in the last {}", tag, humanize(last, AgePrecision::Minutes)).into());
+	};
+	match datetime {
+		Some(datetime) => println!("{}\t{}", metric.format(value, config), config.zone().local(datetime).format("%Y-%m-%d %H:%M")),
+		None => println!("{}", metric.format(value, config)),
+	}
+	return Ok(());
+}
+
+/**
+ * The ID and the config of the tag given to a command as its ID or name. The store knows both, so the config is found
+ * like for the tags shown, also when it's keyed by the ID and the name is given or the other way around.
+*/
+fn stored_tag<'a>(store: &Store, tag: &str, config: &'a Config) -> Result<(String, Option<&'a TagConfig>), Box<dyn std::error::Error>> {
+	let Some((id, name)) = store.tag(tag)? else {
+		return Err(format!("No readings of {} in the store", tag).into());
+	};
+	let tag_config = config.tag_config(&id, &name);
+	return Ok((id, tag_config));
+}
+
+fn degree_days(tag: &str, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	let (id, tag_config) = stored_tag(&store, tag, config)?;
+	let Some(degree_days) = tag_config.and_then(|tag_config| tag_config.degree_days.clone()) else {
+		return Err(format!("No degree_days in the config of {}", tag).into());
+	};
+	let Some(season_start) = degree_days.season_start(config.zone().date(Utc::now())) else {
+		return Err(format!("Invalid season start \"{}\", use MM-DD", degree_days.season_start).into());
+	};
+	
+	let days = store.daily_temperatures(&id, season_start, config.zone())?;
+	for (date, day, total) in degree_days::accumulate(&days, degree_days.base) {
+		println!("{}\t{:.1}\t{:.1}", date, day, total);
+	}
+	
+	return Ok(());
+}
+
+fn battery(tag: &str, days: u64, width: usize, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	let store = open_store(config)?;
+	let (id, tag_config) = stored_tag(&store, tag, config)?;
+	let since = config.zone().date(Utc::now()) - chrono::Days::new(days);
+	let voltages = store.daily_battery_voltages(&id, since, config.zone())?;
+	if voltages.is_empty() {
+		return Err(format!("No battery voltages of {} since {}", tag, since).into());
+	}
+	
+	println!("Battery voltage of {}, {} days", tag, voltages.len());
+	for row in battery::chart(&voltages, width, BATTERY_CHART_HEIGHT, config.glyphs()) {
+		println!("{}", row);
+	}
+	
+	let empty = config.battery_model_of(tag_config).critical_voltage();
+	match battery::depletion(&voltages, empty) {
+		Some((slope, date)) => println!(
+			"Discharging {:.0} mV a month lately, estimated to reach {:.2} V (critical) around {}",
+			-slope * 30.0 * 1000.0,
+			empty,
+			date
+		),
+		None => println!("No measurable discharge lately"),
+	}
+	return Ok(());
+}
+
+// Rows of the battery chart, enough for the knee in eighths of a row.
+const BATTERY_CHART_HEIGHT: usize = 10;
+
+fn version(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+	println!("ruuvi-terminal-client {}", update::VERSION);
+	
+	let client = http::client(&config.http)?;
+	match update::latest(&client).map_err(|error| format!("Could not check for updates: {}", error))? {
+		Some(latest) => println!("Update available: {}", latest),
+		None => println!("Up to date"),
+	}
+	return Ok(());
+}
+
+/**
+ * Fetch once, without the listener or the update check, and print the tags. Returns the exit code of the state,
+ * unknown if the fetch fails.
+*/
+fn status(format: StatusFormat, config: &Config) -> i32 {
+	let mut config = config.clone();
+	config.listen = None;
+	config.update_check = false;
+	let mut poller = match Poller::new(config, Arc::new(SystemClock)) {
+		Ok(poller) => poller,
+		Err(error) => {
+			eprintln!("{}", error);
+			return State::Unknown.exit_code();
+		}
+	};
+	poller.poll();
+	if let Some(error) = &poller.network_error {
+		eprintln!("{}", error);
+		return State::Unknown.exit_code();
+	}
+	
+	let state = State::of(poller.alerts.active(), &poller.data);
+	println!("{}", status::format(format, &poller.data, &poller.config, state, poller.api_format));
+	return state.exit_code();
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+/**
+ * Temperature limits a tag must stay within, e.g. 0…5 °C for a food fridge.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComplianceLimits {
+	pub min: Option<f64>,
+	pub max: Option<f64>,
+}
+
+impl ComplianceLimits {
+	fn breached(&self, temperature: f64) -> bool {
+		return self.min.is_some_and(|min| temperature < min) || self.max.is_some_and(|max| temperature > max);
+	}
+}
+
+/**
+ * A period outside the limits: from the first reading outside to the first reading back within them, or the
+ * last reading if the period hadn't ended. Peak is the temperature furthest outside the limits.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Excursion {
+	pub start: DateTime<Utc>,
+	pub end: DateTime<Utc>,
+	pub peak: f64,
+	pub ended: bool,
+}
+
+impl Excursion {
+	pub fn duration(&self) -> Duration {
+		return self.end - self.start;
+	}
+}
+
+/**
+ * Compliance over a period of readings.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+	pub excursions: Vec<Excursion>,
+	// Time covered by readings, gaps longer than the gap limit left out.
+	pub covered: Duration,
+	// Time outside the limits within the covered time.
+	pub outside: Duration,
+	// Gaps without readings longer than the gap limit.
+	pub gaps: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+	// Average temperature weighted by time, so that a tag reporting more often when warm doesn't pull the average up.
+	// Each reading counts for the interval to the next one, gaps left out. The plain mean if the readings cover no time.
+	pub average: Option<f64>,
+}
+
+impl Summary {
+	/**
+	 * Share of the covered time within the limits, as a percentage.
+	*/
+	pub fn compliance(&self) -> Option<f64> {
+		if self.covered <= Duration::zero() {
+			return None;
+		}
+		return Some(100.0 * (1.0 - self.outside.num_seconds() as f64 / self.covered.num_seconds() as f64));
+	}
+}
+
+/**
+ * Check the (time, temperature) readings, in order, against the limits. Gaps between readings longer than the
+ * gap limit count as neither compliant nor outside.
+*/
+pub fn check(readings: &[(DateTime<Utc>, f64)], limits: &ComplianceLimits, gap_limit: Duration) -> Summary {
+	let mut summary = Summary {
+		excursions: Vec::new(),
+		covered: Duration::zero(),
+		outside: Duration::zero(),
+		gaps: Vec::new(),
+		average: None,
+	};
+	let mut current: Option<Excursion> = None;
+	// Sum of the temperatures times their intervals in seconds.
+	let mut weighted = 0.0;
+	
+	for (index, (datetime, temperature)) in readings.iter().enumerate() {
+		let breached = limits.breached(*temperature);
+		match (&mut current, breached) {
+			(None, true) => current = Some(Excursion { start: *datetime, end: *datetime, peak: *temperature, ended: false }),
+			(Some(excursion), true) => {
+				excursion.end = *datetime;
+				if distance(*temperature, limits) > distance(excursion.peak, limits) {
+					excursion.peak = *temperature;
+				}
+			},
+			(Some(excursion), false) => {
+				excursion.end = *datetime;
+				excursion.ended = true;
+				summary.excursions.extend(current.take());
+			},
+			(None, false) => {},
+		}
+		
+		// The interval to the next reading is as compliant as this reading.
+		if let Some((next, _)) = readings.get(index + 1) {
+			let interval = *next - *datetime;
+			if interval > gap_limit {
+				summary.gaps.push((*datetime, *next));
+				continue;
+			}
+			summary.covered += interval;
+			weighted += temperature * interval.num_seconds() as f64;
+			if breached {
+				summary.outside += interval;
+			}
+		}
+	}
+	summary.excursions.extend(current);
+	
+	summary.average = match summary.covered.num_seconds() {
+		0 if readings.is_empty() => None,
+		0 => Some(readings.iter().map(|(_, temperature)| temperature).sum::<f64>() / readings.len() as f64),
+		seconds => Some(weighted / seconds as f64),
+	};
+	return summary;
+}
+
+/**
+ * How far outside the limits the temperature is.
+*/
+fn distance(temperature: f64, limits: &ComplianceLimits) -> f64 {
+	let below = limits.min.map_or(0.0, |min| min - temperature);
+	let above = limits.max.map_or(0.0, |max| temperature - max);
+	return below.max(above);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn readings(temperatures: &[f64]) -> Vec<(DateTime<Utc>, f64)> {
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		return temperatures.iter()
+			.enumerate()
+			.map(|(index, temperature)| (start + Duration::minutes(10 * index as i64), *temperature))
+			.collect();
+	}
+	
+	#[test]
+	fn excursions_with_durations() {
+		let limits = ComplianceLimits { min: Some(0.0), max: Some(5.0) };
+		let readings = readings(&[3.0, 6.0, 8.5, 4.0, 4.0, -1.0]);
+		let summary = check(&readings, &limits, Duration::minutes(30));
+		
+		assert_eq!(summary.excursions, vec![
+			Excursion { start: readings[1].0, end: readings[3].0, peak: 8.5, ended: true },
+			Excursion { start: readings[5].0, end: readings[5].0, peak: -1.0, ended: false },
+		]);
+		assert_eq!(summary.excursions[0].duration(), Duration::minutes(20));
+		assert_eq!(summary.covered, Duration::minutes(50));
+		assert_eq!(summary.outside, Duration::minutes(20));
+		assert_eq!(summary.compliance(), Some(60.0));
+	}
+	
+	#[test]
+	fn gaps_are_not_covered() {
+		let limits = ComplianceLimits { min: None, max: Some(5.0) };
+		let mut readings = readings(&[3.0, 3.0]);
+		readings.push((readings[1].0 + Duration::hours(2), 3.0));
+		let summary = check(&readings, &limits, Duration::minutes(30));
+		
+		assert_eq!(summary.covered, Duration::minutes(10));
+		assert_eq!(summary.gaps, vec![(readings[1].0, readings[2].0)]);
+		assert_eq!(summary.compliance(), Some(100.0));
+	}
+	
+	#[test]
+	fn average_is_weighted_by_time() {
+		let limits = ComplianceLimits { min: None, max: None };
+		// 2 °C for 30 minutes, then 8 °C reported every minute for 10 minutes.
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let readings: Vec<(DateTime<Utc>, f64)> = [(0, 2.0)].into_iter()
+			.chain((30..=40).map(|minute| (minute, 8.0)))
+			.map(|(minute, temperature)| (start + Duration::minutes(minute), temperature))
+			.collect();
+		assert_eq!(check(&readings, &limits, Duration::minutes(30)).average, Some(3.5));
+		assert_eq!(check(&readings[..1], &limits, Duration::minutes(30)).average, Some(2.0));
+		assert_eq!(check(&[], &limits, Duration::minutes(30)).average, None);
+	}
+}
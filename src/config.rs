@@ -0,0 +1,800 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use crate::actions::{Action, TagLink};
+use crate::alerts::{AlertMetric, AlertRule, QuietHours, Severity};
+#[cfg(feature = "tui")]
+use crate::backend::BackendName;
+#[cfg(feature = "tui")]
+use crate::background::Background;
+use crate::battery::{self, BatteryModel};
+#[cfg(feature = "tui")]
+use crate::burnin::BurnInConfig;
+use crate::calibration::TagCalibration;
+use crate::clock::Zone;
+use crate::compliance::ComplianceLimits;
+use crate::degree_days::DegreeDaysConfig;
+#[cfg(feature = "tui")]
+use crate::eink::EinkConfig;
+use crate::formatters;
+use crate::forward::ForwardConfig;
+use crate::frost::FrostConfig;
+use crate::glyphs::{Glyphs, GlyphsConfig};
+use crate::groups::GroupConfig;
+#[cfg(feature = "tui")]
+use crate::pages::PageConfig;
+use crate::profiles;
+use crate::hooks::HooksConfig;
+use crate::http::HttpConfig;
+use crate::i18n::{Lang, TrendVocabulary};
+use crate::metrics::{DerivedMetric, ExtraMetric};
+use crate::mqtt::MqttConfig;
+use crate::model::Tag;
+use crate::ranges::{self, ColorRange, RangeColor, Ranges};
+#[cfg(feature = "tui")]
+use crate::render::{Layout, MinMaxWindow, MinMaxWindows};
+#[cfg(feature = "tui")]
+use crate::table::TableConfig;
+use crate::targets::Targets;
+#[cfg(feature = "tui")]
+use crate::theme::ThemeName;
+use crate::simulation::Simulation;
+use crate::trend::TrendConfig;
+use crate::units::{TemperatureUnit, Units};
+use crate::wind::WindConfig;
+
+// The error when there is nothing to read the tags from, which starts the first start setup on a terminal.
+pub const NO_SOURCE: &str = "API URL must be set with the API_URL environment variable or in the config file, or a WebSocket URL, a listen address or a command given";
+
+/**
+ * Runtime configuration. Read from the config file first, ENV variables override it.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	pub api_url: String,
+	// Ruuvi Cloud API token, sent as a bearer token. The API URL defaults to the cloud when set.
+	pub cloud_token: Option<String>,
+	// Proxy of the API fetches instead of the [http] one, or "direct", e.g. an SSH tunnel to a remote gateway.
+	pub api_proxy: Option<String>,
+	// Ask the custom API only for the tags measured since the previous response, with ?since=<timestamp>.
+	pub api_incremental: bool,
+	// Timeouts, keep-alive and proxy of the HTTP requests.
+	pub http: HttpConfig,
+	// Minimum change before a displayed number is updated, the same for °C and %RH. 0 disables calm mode.
+	pub calm_epsilon: f64,
+	// Terminal library the TUI is drawn with.
+	#[cfg(feature = "tui")]
+	pub backend: BackendName,
+	// Clicks select the tags and the scroll wheel scrolls them, instead of the terminal selecting text.
+	pub mouse: bool,
+	// The terminal reports gaining and losing the focus, and the screen is redrawn less often without it.
+	pub focus_events: bool,
+	// Seconds between the redraws while the TUI can't be seen: without the focus, or in a hidden tmux pane.
+	pub hidden_redraw_seconds: u64,
+	// Color theme, and monochrome output regardless of the theme.
+	#[cfg(feature = "tui")]
+	pub theme: ThemeName,
+	pub no_color: bool,
+	// Whether the terminal's background is light or dark, for the colors of the theme.
+	#[cfg(feature = "tui")]
+	pub background: Background,
+	// E-ink mode for e-paper displays, off without the section.
+	#[cfg(feature = "tui")]
+	pub eink: Option<EinkConfig>,
+	// Burn-in protection for always-on OLED and plasma displays, off without the section.
+	#[cfg(feature = "tui")]
+	pub burn_in: Option<BurnInConfig>,
+	// User interface language. Detected from the locale if not set.
+	pub lang: Option<Lang>,
+	// Time zone of the site for the days, e.g. "Europe/Helsinki". The system's if not set.
+	pub timezone: Option<Tz>,
+	// Time zone of the datetimes the sources send without an offset, e.g. "2024-05-01 12:00:00". UTC if not set.
+	pub source_timezone: Option<Tz>,
+	// Absolute timestamps instead of the relative "Updated" times. Toggled with the t key.
+	pub absolute_time: bool,
+	// Min…max in parentheses after the current values instead of their own lines.
+	pub compact_min_max: bool,
+	// When the min and max were measured after them on the cards, when the history has it. The detail view always
+	// shows them.
+	pub min_max_times: bool,
+	// Terminals at least this wide show the selected tag's detail next to the tags. 0 never does.
+	pub split_width: i32,
+	// Summary line above the tags: warmest, coldest, indoor averages, tags in alert and the oldest data.
+	pub summary: bool,
+	// Shown dimmed in place of the values a tag doesn't report, e.g. the humidity of a dead sensor.
+	pub missing_value: String,
+	// Trend words and symbols in place of the language's and the arrows.
+	pub vocabulary: TrendVocabulary,
+	// Arrows, blocks and box drawing lines, or their ASCII stand-ins. Detected from the locale and the terminal if not set.
+	pub glyphs: GlyphsConfig,
+	// Check GitHub for a newer release on startup, in the background, and show a notice at the bottom if there is one.
+	pub update_check: bool,
+	// Temperature unit and the precise mode showing the values to the resolution of the tags.
+	pub units: Units,
+	// Period of the min/max on the cards: the source's own, today, 24h or 7d. Cycled with the m key.
+	#[cfg(feature = "tui")]
+	pub min_max_window: MinMaxWindow,
+	// Windows of single metrics instead, e.g. the temperature over the last 24 hours and the rest since midnight.
+	#[cfg(feature = "tui")]
+	pub min_max_windows: MinMaxWindows,
+	// Cards or a table, and the table's column widths and decimals.
+	#[cfg(feature = "tui")]
+	pub layout: Layout,
+	#[cfg(feature = "tui")]
+	pub table: TableConfig,
+	// ASCII floorplan for the floorplan layout, with placeholders like {Sauna} or {Sauna:humidity} for the values.
+	pub floorplan: Option<String>,
+	// Named sections of tags, collapsed and expanded with the number keys.
+	pub groups: Vec<GroupConfig>,
+	// Tabs of the TUI, switched with Tab and the number keys. The number keys toggle the groups without pages.
+	#[cfg(feature = "tui")]
+	pub pages: Vec<PageConfig>,
+	// Tags without new measurements for this long are shown as unreachable. 0 disables.
+	pub unreachable_after_minutes: i64,
+	// Tags with a known report interval are unreachable after missing this many reports instead. 0 disables.
+	pub unreachable_after_missed_reports: i32,
+	// Tag (ID or name) shown as a big widget at the top of the screen.
+	pub outdoor_tag: Option<String>,
+	// Kiosk mode: one tag at a time with big digits, cycling through the kiosk tags (all if empty).
+	pub kiosk: bool,
+	pub kiosk_tags: Vec<String>,
+	pub kiosk_cycle_seconds: u64,
+	// Demo mode: the layouts, themes and tags in turn, each for demo_cycle_seconds. Toggled with the d key.
+	pub demo: bool,
+	pub demo_cycle_seconds: u64,
+	// Wind speed source for the feels-like temperature of outdoor tags.
+	pub wind: Option<WindConfig>,
+	// Address to listen on for Ruuvi Gateway pushes, e.g. "0.0.0.0:8080".
+	pub listen: Option<String>,
+	// External executable as a source, run with sh, see the plugin module for the protocol.
+	pub command: Option<String>,
+	// WebSocket endpoint pushing the tags as they change, e.g. "wss://sensors.example.com/stream".
+	pub websocket: Option<String>,
+	// External executable the readings are written to, run with sh, see the plugin module for the protocol.
+	pub sink: Option<String>,
+	// Every refresh posted to another service, see the forward module.
+	pub forward: ForwardConfig,
+	// Every refresh published to an MQTT broker, see the mqtt module.
+	pub mqtt: MqttConfig,
+	// Bearer token the gateway must send. Without it all pushes are accepted.
+	pub listen_token: Option<String>,
+	// PEM certificate and private key to serve the listener over HTTPS.
+	pub listen_tls_cert: Option<PathBuf>,
+	pub listen_tls_key: Option<PathBuf>,
+	// Requests logged into this file, one line per request.
+	pub listen_access_log: Option<PathBuf>,
+	// Maximum requests per minute per client IP. 0 disables the limit.
+	pub listen_rate_limit: u32,
+	// Largest push body accepted in kilobytes, larger ones are rejected with 413 without reading them whole.
+	pub listen_max_body_kb: usize,
+	// Demo or replay data instead of the API and the listener, from --source only.
+	#[serde(skip)]
+	pub simulation: Option<Simulation>,
+	// Profile of the config file in use, from --profile or switched to while running, and the names of all of them.
+	#[serde(skip)]
+	pub profile: Option<String>,
+	#[serde(skip)]
+	pub profiles: Vec<String>,
+	// Replay speed, 1 for real time.
+	pub replay_speed: f64,
+	// Directory to save the raw payloads from the API and the listener into.
+	pub record: Option<PathBuf>,
+	// File to log the keys, the view changes and the refreshes of the TUI into, for bug reports. The readings only
+	// with session_log_values.
+	pub session_log: Option<PathBuf>,
+	pub session_log_values: bool,
+	// Fetch at :00 of every minute instead of a minute after the previous fetch.
+	pub align_refresh: bool,
+	// OTLP/HTTP collector to send traces of the refresh cycle to, e.g. "http://localhost:4318".
+	// Needs a build with the otel feature.
+	pub otlp_endpoint: Option<String>,
+	// SQLite database for the reading history.
+	pub store: Option<PathBuf>,
+	// Readings kept in memory per tag at most, 0 for no limit. The older ones are thinned out to make room.
+	pub history_samples: usize,
+	// Run without the TUI, logging the readings instead.
+	pub headless: bool,
+	// Log file for the headless mode. Logs to stdout if not set.
+	pub log_file: Option<PathBuf>,
+	// Unix socket the headless mode shares the tags, the history and the alerts on, and the TUI attaches to with
+	// --attach.
+	pub socket: Option<PathBuf>,
+	// The TUI shows what the headless instance on the socket shares, from --attach only.
+	#[serde(skip)]
+	pub attach: bool,
+	// File the TUI is mirrored into as plain text on every refresh.
+	pub mirror: Option<PathBuf>,
+	// File the S key saves the screen into, HTML or text by the extension. A timestamped text file if not set.
+	pub snapshot: Option<PathBuf>,
+	// Tag (ID or name) the temperatures of the other tags are compared to on the cards, e.g. "Outdoor".
+	pub reference: Option<String>,
+	// Metrics shown, in this order on the cards. Toggled with the T, H and P keys.
+	pub metrics: Vec<AlertMetric>,
+	// Derived metrics shown for all tags.
+	pub derived: Vec<DerivedMetric>,
+	// Labels, units and decimals of the extra numbers the custom API passes through, by field name.
+	pub extras: HashMap<String, ExtraMetric>,
+	// Battery model used for tags without their own.
+	pub battery_model: String,
+	// Additional battery models, or overrides for the built-in ones.
+	pub battery_models: HashMap<String, BatteryModel>,
+	// Trends calculated for sources that don't provide them.
+	pub trend: TrendConfig,
+	// Frost and condensation warnings of the outdoor tags.
+	pub frost: FrostConfig,
+	// Values are colored by the range they fall in.
+	pub ranges: Ranges,
+	// Threshold alert rules, in addition to the built-in battery and unreachable alerts.
+	pub alerts: Vec<AlertRule>,
+	// Built-in advisories about the tags themselves, like a battery that sags in the cold, as informational alerts.
+	pub advisories: bool,
+	// Ring the terminal's bell when an alert starts.
+	pub bell: bool,
+	// Time of the day without the bell and the push notifications, e.g. "22:00-07:00", except for critical alerts.
+	pub quiet_hours: Option<QuietHours>,
+	// Commands run when the overall alert state changes.
+	pub hooks: HooksConfig,
+	// Commands and webhooks run on alert and network events.
+	pub actions: Vec<Action>,
+	// Per-tag settings keyed by MAC address, tag ID or the name coming from the source.
+	pub tags: HashMap<String, TagConfig>,
+}
+
+impl Default for Config {
+	fn default() -> Config {
+		return Config {
+			api_url: String::new(),
+			api_proxy: None,
+			api_incremental: false,
+			cloud_token: None,
+			http: HttpConfig::default(),
+			calm_epsilon: 0.0,
+			#[cfg(feature = "tui")]
+			backend: BackendName::default(),
+			mouse: true,
+			focus_events: false,
+			hidden_redraw_seconds: 30,
+			#[cfg(feature = "tui")]
+			theme: ThemeName::Default,
+			no_color: false,
+			#[cfg(feature = "tui")]
+			background: Background::Auto,
+			#[cfg(feature = "tui")]
+			eink: None,
+			#[cfg(feature = "tui")]
+			burn_in: None,
+			lang: None,
+			timezone: None,
+			source_timezone: None,
+			absolute_time: false,
+			compact_min_max: false,
+			min_max_times: false,
+			split_width: 160,
+			summary: false,
+			missing_value: "—".into(),
+			vocabulary: TrendVocabulary::default(),
+			glyphs: GlyphsConfig::default(),
+			update_check: false,
+			units: Units::default(),
+			#[cfg(feature = "tui")]
+			min_max_window: MinMaxWindow::Source,
+			#[cfg(feature = "tui")]
+			min_max_windows: MinMaxWindows::default(),
+			#[cfg(feature = "tui")]
+			layout: Layout::Cards,
+			#[cfg(feature = "tui")]
+			table: TableConfig::default(),
+			floorplan: None,
+			groups: Vec::new(),
+			#[cfg(feature = "tui")]
+			pages: Vec::new(),
+			unreachable_after_minutes: 15,
+			unreachable_after_missed_reports: 3,
+			outdoor_tag: None,
+			kiosk: false,
+			kiosk_tags: Vec::new(),
+			kiosk_cycle_seconds: 10,
+			demo: false,
+			demo_cycle_seconds: 5,
+			wind: None,
+			listen: None,
+			command: None,
+			websocket: None,
+			reference: None,
+			sink: None,
+			forward: ForwardConfig::default(),
+			mqtt: MqttConfig::default(),
+			listen_token: None,
+			listen_tls_cert: None,
+			listen_tls_key: None,
+			listen_access_log: None,
+			listen_rate_limit: 60,
+			listen_max_body_kb: 1024,
+			simulation: None,
+			profile: None,
+			profiles: Vec::new(),
+			replay_speed: 1.0,
+			record: None,
+			session_log: None,
+			session_log_values: false,
+			align_refresh: false,
+			otlp_endpoint: None,
+			store: None,
+			history_samples: 10_000,
+			headless: false,
+			log_file: None,
+			socket: None,
+			attach: false,
+			mirror: None,
+			snapshot: None,
+			metrics: vec![AlertMetric::Temperature, AlertMetric::Humidity, AlertMetric::Pressure, AlertMetric::BatteryVoltage],
+			derived: Vec::new(),
+			extras: HashMap::new(),
+			battery_model: "ruuvitag".into(),
+			battery_models: HashMap::new(),
+			trend: TrendConfig::default(),
+			frost: FrostConfig::default(),
+			ranges: Ranges::default(),
+			alerts: Vec::new(),
+			advisories: true,
+			bell: false,
+			quiet_hours: None,
+			hooks: HooksConfig::default(),
+			actions: Vec::new(),
+			tags: HashMap::new(),
+		};
+	}
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TagConfig {
+	pub name: Option<String>,
+	pub order: Option<i32>,
+	pub icon: Option<String>,
+	// Overrides the global derived metrics for this tag.
+	pub derived: Option<Vec<DerivedMetric>>,
+	// Overrides the global calm_epsilon for this tag, e.g. a larger one for a sauna.
+	pub calm_epsilon: Option<f64>,
+	// Battery model (tag generation) name.
+	pub battery_model: Option<String>,
+	// Calibration per metric, applied before anything else uses the values.
+	pub calibration: Option<TagCalibration>,
+	// Expected seconds between measurements, e.g. 10 for gateway tags or 300 for cloud tags. Learned from the
+	// history if not set.
+	pub report_interval_seconds: Option<i64>,
+	// Outdoor tags get the weather features, e.g. the pressure tendency.
+	pub outdoor: bool,
+	// Overrides the global color ranges per metric.
+	pub ranges: Option<Ranges>,
+	// Growing degree days accumulated from the history store, for greenhouse and garden tags.
+	pub degree_days: Option<DegreeDaysConfig>,
+	// Temperature limits for the compliance report, e.g. of a fridge.
+	pub compliance: Option<ComplianceLimits>,
+	// Free text shown on the card, e.g. where the tag is.
+	pub note: Option<String>,
+	// Floor for the floors layout, e.g. "2" or "Basement". Overrides the floor from the source.
+	pub floor: Option<String>,
+	// Bands the values should stay in, shown on the card with how far outside them the values are.
+	pub target: Option<Targets>,
+	// Metrics not shown for the tag, e.g. the humidity of a freezer.
+	pub hidden: Vec<AlertMetric>,
+	// URL and/or command opened with the o key in the kiosk view, e.g. the room's dashboard.
+	pub link: Option<TagLink>,
+	// The other tag of a pair, e.g. the outside of the wall, whose temperature difference to this one is charted over
+	// the last day in the detail view.
+	pub pair: Option<String>,
+}
+
+/**
+ * What overrides the config file and the ENV variables, e.g. the command line arguments. The unset ones keep the
+ * loaded values.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+	// Profile of the config file laid over the rest of it.
+	pub profile: Option<String>,
+	pub listen: Option<String>,
+	pub proxy: Option<String>,
+	pub api_proxy: Option<String>,
+	pub store: Option<PathBuf>,
+	pub record: Option<PathBuf>,
+	pub align_refresh: bool,
+	pub headless: bool,
+	pub log_file: Option<PathBuf>,
+	// Simulated or replayed data instead of the sources, e.g. ["replay", "history.db"].
+	pub source: Option<Vec<String>>,
+	pub replay_speed: Option<f64>,
+	// Show the tags of the headless instance sharing them on the socket instead of running the sources.
+	pub attach: bool,
+	pub lang: Option<Lang>,
+	pub temperature_unit: Option<TemperatureUnit>,
+	pub precise: bool,
+	pub derived: Option<Vec<DerivedMetric>>,
+	// Load without a source, e.g. for a command reading only the store.
+	pub without_source: bool,
+}
+
+impl Config {
+	/**
+	 * Load the config file (if it exists) with the profile of the overrides and apply the ENV and the overrides.
+	*/
+	pub fn load(overrides: &Overrides) -> Result<Config, String> {
+		return Config::load_profile(overrides, overrides.profile.as_deref());
+	}
+	
+	/**
+	 * Load the config file with the profile laid over it, or without one, and apply the ENV and the overrides.
+	*/
+	pub fn load_profile(overrides: &Overrides, profile: Option<&str>) -> Result<Config, String> {
+		let path = config_path();
+		
+		let (mut config, profiles) = match path {
+			Some(path) if path.exists() => {
+				let contents = fs::read_to_string(&path)
+					.map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+				// Without the profile first, for the line numbers in the errors.
+				let config = toml::from_str::<Config>(&contents)
+					.map_err(|error| format!("Invalid config file {}: {}", path.display(), error))?;
+				let file = toml::from_str::<toml::Table>(&contents)
+					.map_err(|error| format!("Invalid config file {}: {}", path.display(), error))?;
+				let profiles = profiles::names(&file);
+				let config = match profile {
+					Some(name) => profiles::apply(file, name)?.try_into::<Config>()
+						.map_err(|error| format!("Invalid profile {} in {}: {}", name, path.display(), error))?,
+					None => config,
+				};
+				(config, profiles)
+			},
+			_ => match profile {
+				Some(name) => return Err(format!("No profile \"{}\" without a config file", name)),
+				None => (Config::default(), Vec::new()),
+			},
+		};
+		config.profile = profile.map(String::from);
+		config.profiles = profiles;
+		
+		if let Ok(api_url) = env::var("API_URL") {
+			config.api_url = api_url;
+		}
+		
+		if let Ok(token) = env::var("RUUVI_CLOUD_TOKEN") {
+			config.cloud_token = Some(token);
+		}
+		if config.cloud_token.is_some() && config.api_url.is_empty() {
+			config.api_url = formatters::cloud::URL.into();
+		}
+		
+		if let Ok(value) = env::var("CALM_EPSILON") {
+			config.calm_epsilon = value.parse::<f64>()
+				.map_err(|_| format!("CALM_EPSILON must be a number, got \"{}\"", value))?;
+		}
+		config.calm_epsilon = config.calm_epsilon.abs();
+		for tag_config in config.tags.values_mut() {
+			tag_config.calm_epsilon = tag_config.calm_epsilon.map(f64::abs);
+		}
+		
+		// https://no-color.org/
+		if env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+			config.no_color = true;
+		}
+		
+		if let Ok(endpoint) = env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+			config.otlp_endpoint = Some(endpoint);
+		}
+		
+		if let Ok(token) = env::var("LISTEN_TOKEN") {
+			config.listen_token = Some(token);
+		}
+		
+		if let Some(listen) = &overrides.listen {
+			config.listen = Some(listen.clone());
+		}
+		if let Some(proxy) = &overrides.proxy {
+			config.http.proxy = Some(proxy.clone());
+		}
+		if let Some(proxy) = &overrides.api_proxy {
+			config.api_proxy = Some(proxy.clone());
+		}
+		
+		if let Some(store) = &overrides.store {
+			config.store = Some(store.clone());
+		}
+		config.store = config.store.map(|path| expand_home(&path));
+		if let Some(record) = &overrides.record {
+			config.record = Some(record.clone());
+		}
+		config.record = config.record.map(|path| expand_home(&path));
+		config.session_log = config.session_log.map(|path| expand_home(&path));
+		if overrides.align_refresh {
+			config.align_refresh = true;
+		}
+		config.listen_tls_cert = config.listen_tls_cert.map(|path| expand_home(&path));
+		config.listen_tls_key = config.listen_tls_key.map(|path| expand_home(&path));
+		config.listen_access_log = config.listen_access_log.map(|path| expand_home(&path));
+		config.http.client_cert = config.http.client_cert.map(|path| expand_home(&path));
+		config.http.client_key = config.http.client_key.map(|path| expand_home(&path));
+		
+		if overrides.headless {
+			config.headless = true;
+		}
+		if let Some(log_file) = &overrides.log_file {
+			config.log_file = Some(log_file.clone());
+		}
+		config.log_file = config.log_file.map(|path| expand_home(&path));
+		config.socket = config.socket.map(|path| expand_home(&path));
+		config.mirror = config.mirror.map(|path| expand_home(&path));
+		config.snapshot = config.snapshot.map(|path| expand_home(&path));
+		
+		if let Some(lang) = overrides.lang {
+			config.lang = Some(lang);
+		}
+		config.lang = Some(config.lang.unwrap_or_else(Lang::detect));
+		config.glyphs = config.glyphs.or(Glyphs::detect());
+		
+		if let Some(unit) = overrides.temperature_unit {
+			config.units.temperature = unit;
+		}
+		if overrides.precise {
+			config.units.precise = true;
+		}
+		
+		if let Some(derived) = &overrides.derived {
+			config.derived = derived.clone();
+		}
+		
+		for (name, model) in &config.battery_models {
+			model.validate(name)?;
+		}
+		for (key, tag_config) in &config.tags {
+			if let Some(degree_days) = &tag_config.degree_days {
+				degree_days.validate(key)?;
+			}
+		}
+		for rule in &mut config.alerts {
+			let ranges = match rule.metric {
+				AlertMetric::Temperature => config.ranges.temperature.as_deref().unwrap_or(ranges::DEFAULT_TEMPERATURE),
+				AlertMetric::Humidity => config.ranges.humidity.as_deref().unwrap_or(ranges::DEFAULT_HUMIDITY),
+				_ => &[],
+			};
+			rule.resolve_range(ranges)?;
+			rule.validate()?;
+		}
+		let models = battery::models(&config.battery_models);
+		let model_names = std::iter::once(&config.battery_model)
+			.chain(config.tags.values().filter_map(|tag_config| tag_config.battery_model.as_ref()));
+		for name in model_names {
+			if !models.contains_key(name) {
+				return Err(format!("Unknown battery model \"{}\"", name));
+			}
+		}
+		
+		// The simulation replaces the real sources.
+		if let Some(values) = &overrides.source {
+			config.simulation = Some(Simulation::parse(values)?);
+			config.api_url.clear();
+			config.listen = None;
+		}
+		// The headless instance owns the sources, the store and the alerting, and runs the outputs and the actions.
+		if overrides.attach {
+			if config.socket.is_none() {
+				return Err("--attach needs the socket of the headless instance in the config file".into());
+			}
+			config.attach = true;
+			config.api_url.clear();
+			config.listen = None;
+			config.command = None;
+			config.websocket = None;
+			config.simulation = None;
+			config.wind = None;
+			config.store = None;
+			config.record = None;
+			config.sink = None;
+			config.forward.url = None;
+			config.mqtt.url = None;
+			config.hooks = HooksConfig::default();
+			config.actions.clear();
+		}
+		if let Some(speed) = overrides.replay_speed {
+			config.replay_speed = speed;
+		}
+		if config.replay_speed <= 0.0 {
+			return Err(format!("The replay speed must be positive, got {}", config.replay_speed));
+		}
+		
+		if !overrides.without_source && config.api_url.is_empty() && config.listen.is_none() && config.command.is_none() && config.websocket.is_none() && config.simulation.is_none() && !config.attach {
+			return Err(NO_SOURCE.into());
+		}
+		
+		return Ok(config);
+	}
+	
+	pub fn lang(&self) -> Lang {
+		return self.lang.unwrap_or_default();
+	}
+	
+	pub fn glyphs(&self) -> Glyphs {
+		return self.glyphs.glyphs();
+	}
+	
+	pub fn zone(&self) -> Zone {
+		return Zone(self.timezone);
+	}
+	
+	/**
+	 * Should the bell and the push notifications of an alert of the severity keep quiet at the moment.
+	*/
+	pub fn quiet(&self, severity: Option<Severity>, now: DateTime<Utc>) -> bool {
+		return self.quiet_hours.is_some_and(|quiet_hours| quiet_hours.silences(severity, now, self.zone()));
+	}
+	
+	/**
+	 * Find the per-tag settings by tag ID or the name coming from the source.
+	*/
+	pub fn tag(&self, tag: &Tag) -> Option<&TagConfig> {
+		return self.tag_config(&tag.id, &tag.name);
+	}
+	
+	/**
+	 * The config of the tag with the ID and the name, by its ID or else by its name.
+	*/
+	pub fn tag_config(&self, id: &str, name: &str) -> Option<&TagConfig> {
+		return self.tags.get(id).or_else(|| self.tags.get(name));
+	}
+	
+	/**
+	 * Is the metric shown for the tag: in the metrics and not hidden in the tag's config.
+	*/
+	pub fn shows(&self, tag: &Tag, metric: AlertMetric) -> bool {
+		return self.metrics.contains(&metric) && !self.tag(tag).is_some_and(|tag_config| tag_config.hidden.contains(&metric));
+	}
+	
+	/**
+	 * The profile after the one in use, from the last back to the first. None without profiles.
+	*/
+	pub fn next_profile(&self) -> Option<String> {
+		let next = match self.profile.as_ref().and_then(|profile| self.profiles.iter().position(|name| name == profile)) {
+			Some(index) => (index + 1) % self.profiles.len(),
+			None => 0,
+		};
+		return self.profiles.get(next).cloned();
+	}
+	
+	/**
+	 * Hide the metric, or show it again in its place in the configured order.
+	*/
+	pub fn toggle_metric(&mut self, metric: AlertMetric, order: &[AlertMetric]) {
+		if self.metrics.contains(&metric) {
+			self.metrics.retain(|shown| *shown != metric);
+			return;
+		}
+		self.metrics.push(metric);
+		self.metrics.sort_by_key(|shown| order.iter().position(|ordered| ordered == shown).unwrap_or(order.len()));
+	}
+	
+	/**
+	 * Is the tag the one shown in the outdoor widget.
+	*/
+	pub fn is_outdoor_tag(&self, tag: &Tag) -> bool {
+		return self.outdoor_tag.as_ref().is_some_and(|key| *key == tag.id || *key == tag.name);
+	}
+	
+	/**
+	 * Derived metrics to show for the tag.
+	*/
+	pub fn derived_for(&self, tag: &Tag) -> &[DerivedMetric] {
+		return self.tag(tag)
+			.and_then(|tag_config| tag_config.derived.as_deref())
+			.unwrap_or(&self.derived);
+	}
+	
+	/**
+	 * Color of the tag's temperature.
+	*/
+	pub fn temperature_color(&self, tag: &Tag) -> Option<RangeColor> {
+		let ranges = self.ranges_for(tag, |ranges| ranges.temperature.as_deref()).unwrap_or(ranges::DEFAULT_TEMPERATURE);
+		return ranges::color(ranges, tag.temperature()?);
+	}
+	
+	/**
+	 * Color of the tag's humidity.
+	*/
+	pub fn humidity_color(&self, tag: &Tag) -> Option<RangeColor> {
+		let ranges = self.ranges_for(tag, |ranges| ranges.humidity.as_deref()).unwrap_or(ranges::DEFAULT_HUMIDITY);
+		return ranges::color(ranges, tag.humidity()?);
+	}
+	
+	/**
+	 * Color ranges of a metric for the tag, the tag's own before the global ones.
+	*/
+	fn ranges_for<'a>(&'a self, tag: &Tag, metric: impl Fn(&'a Ranges) -> Option<&'a [ColorRange]>) -> Option<&'a [ColorRange]> {
+		return self.tag(tag)
+			.and_then(|tag_config| tag_config.ranges.as_ref())
+			.and_then(&metric)
+			.or_else(|| metric(&self.ranges));
+	}
+	
+	/**
+	 * Battery model for the tag. Unknown names fall back to the built-in RuuviTag model.
+	*/
+	pub fn battery_model_for(&self, tag: &Tag) -> BatteryModel {
+		return self.battery_model_of(self.tag(tag));
+	}
+	
+	/**
+	 * Battery model of the per-tag settings, the global one without them.
+	*/
+	pub fn battery_model_of(&self, tag_config: Option<&TagConfig>) -> BatteryModel {
+		let name = tag_config
+			.and_then(|tag_config| tag_config.battery_model.as_ref())
+			.unwrap_or(&self.battery_model);
+		return battery::models(&self.battery_models)
+			.remove(name)
+			.unwrap_or_else(BatteryModel::ruuvitag);
+	}
+}
+
+/**
+ * CONFIG_FILE or ~/.config/ruuvi-tc/config.toml.
+*/
+pub fn config_path() -> Option<PathBuf> {
+	if let Ok(path) = env::var("CONFIG_FILE") {
+		return Some(PathBuf::from(path));
+	}
+	
+	return dirs::config_dir().map(|dir| dir.join("ruuvi-tc").join("config.toml"));
+}
+
+/**
+ * Expand a leading ~ to the home directory.
+*/
+pub(crate) fn expand_home(path: &Path) -> PathBuf {
+	match (path.strip_prefix("~"), dirs::home_dir()) {
+		(Ok(rest), Some(home)) => home.join(rest),
+		_ => path.to_path_buf(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn metrics_are_shown_again_in_their_place() {
+		let order = [AlertMetric::Humidity, AlertMetric::Temperature, AlertMetric::Pressure];
+		let mut config = Config { metrics: order.to_vec(), ..Config::default() };
+		config.toggle_metric(AlertMetric::Humidity, &order);
+		config.toggle_metric(AlertMetric::Pressure, &order);
+		assert_eq!(config.metrics, [AlertMetric::Temperature]);
+		
+		config.toggle_metric(AlertMetric::Humidity, &order);
+		config.toggle_metric(AlertMetric::BatteryVoltage, &order);
+		assert_eq!(config.metrics, [AlertMetric::Humidity, AlertMetric::Temperature, AlertMetric::BatteryVoltage]);
+	}
+	
+	#[test]
+	fn profiles_switch_in_turn() {
+		let mut config = Config { profiles: vec!["cottage".into(), "home".into()], ..Config::default() };
+		assert_eq!(config.next_profile().as_deref(), Some("cottage"));
+		config.profile = Some("home".into());
+		assert_eq!(config.next_profile().as_deref(), Some("cottage"));
+		assert_eq!(Config::default().next_profile(), None);
+	}
+	
+	#[test]
+	fn websocket_is_a_source() {
+		let path = env::temp_dir().join(format!("ruuvi-tc-websocket-{}.toml", std::process::id()));
+		fs::write(&path, "websocket = \"wss://sensors.example.com/stream\"\n").unwrap();
+		env::set_var("CONFIG_FILE", &path);
+		env::remove_var("API_URL");
+		env::remove_var("RUUVI_CLOUD_TOKEN");
+		let config = Config::load(&Overrides::default());
+		fs::remove_file(&path).unwrap();
+		assert_eq!(config.unwrap().websocket.as_deref(), Some("wss://sensors.example.com/stream"));
+	}
+}
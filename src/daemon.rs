@@ -0,0 +1,160 @@
+use std::{thread, time};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use crate::alerts::AlertEvent;
+use crate::cli::Args;
+use crate::i18n::Lang;
+use crate::logger::{Logger, Repeats};
+use crate::poller::Poller;
+use crate::shutdown;
+
+// A warning that keeps repeating is written once in this window, then the count of the repeats.
+const REPEAT_WINDOW_HOURS: i64 = 1;
+
+/**
+ * Headless mode: poll the sources and log the readings and state changes without the TUI. SIGHUP reloads the config
+ * with the same arguments.
+*/
+pub fn run(poller: &mut Poller, logger: &mut Logger, args: &Args) {
+	let mut network_error = false;
+	let mut failures = 0;
+	let mut problems = Vec::new();
+	let mut repeats = Repeats::new(Duration::hours(REPEAT_WINDOW_HOURS));
+	let mut digest = Digest::new(poller.config.zone().date(poller.clock.now()));
+	
+	logger.info("Started in headless mode");
+	
+	while !shutdown::requested() {
+		if shutdown::reload_requested() {
+			match args.load().and_then(|config| poller.reload(config)) {
+				Ok(()) => logger.info("Reloaded the config"),
+				Err(error) => logger.warn(&format!("Could not reload the config, keeping the previous one: {}", error)),
+			}
+		}
+		let updated = poller.poll();
+		// The repeats of the warnings are counted in the real time, the days of the digest on the data's.
+		let now = poller.wall.now();
+		
+		if updated {
+			digest.readings += poller.data.len();
+			for tag in &poller.data {
+				let temperature = tag.temperature()
+					.map(|temperature| format!(" temperature={:+.2}°C", temperature))
+					.unwrap_or_default();
+				let humidity = tag.humidity()
+					.map(|humidity| format!(" humidity={:.2}%", humidity))
+					.unwrap_or_default();
+				let pressure = tag.pressure()
+					.map(|pressure| format!(" pressure={:.1}hPa", pressure))
+					.unwrap_or_default();
+				let voltage = tag.battery_voltage
+					.map(|voltage| format!(" battery={:.3}V", voltage))
+					.unwrap_or_default();
+				logger.info(&format!(
+					"{}{}{}{}{}",
+					tag.label(),
+					temperature,
+					humidity,
+					pressure,
+					voltage
+				));
+			}
+			
+			if poller.store_error {
+				warn(logger, &mut repeats, "Could not record the readings into the history store", now);
+			}
+		}
+		
+		// Alerts also change without new data, e.g. unreachable as the data gets older.
+		for event in &poller.alert_events {
+			match event {
+				AlertEvent::Started(alert) => {
+					digest.alerts += 1;
+					let value = alert.value.map(|value| format!(": {:.2}", value)).unwrap_or_default();
+					logger.warn(&format!("{} {}{} [{}]", alert.tag_name, alert.message, value, alert.id()));
+				},
+				AlertEvent::Cleared(alert) => logger.info(&format!("{} {} cleared [{}]", alert.tag_name, alert.message, alert.id())),
+				AlertEvent::Unknown(alert) => logger.info(&format!("{} {} unknown, no data [{}]", alert.tag_name, alert.message, alert.id())),
+			}
+		}
+		if poller.hook_error {
+			warn(logger, &mut repeats, "Could not run the alert hook command", now);
+			poller.hook_error = false;
+		}
+		if poller.action_error {
+			warn(logger, &mut repeats, "An action command or webhook failed", now);
+			poller.action_error = false;
+		}
+		if let Some(error) = poller.sink_error.take() {
+			warn(logger, &mut repeats, &error, now);
+		}
+		if let Some(error) = poller.output_error.take() {
+			warn(logger, &mut repeats, &error, now);
+		}
+		
+		// Every failed fetch is an occurrence of the error.
+		if let (Some(error), true) = (&poller.network_error, poller.scheduler.failures() > failures) {
+			warn(logger, &mut repeats, &format!("Network error: {}", error), now);
+		}
+		if let (None, true) = (&poller.network_error, network_error) {
+			logger.info("Network restored");
+		}
+		network_error = poller.network_error.is_some();
+		failures = poller.scheduler.failures();
+		
+		for problem in &poller.problems {
+			if !problems.contains(problem) {
+				logger.warn(&format!("Could not parse the data from the {}: {}", Lang::En.text(problem.0.text()), problem.1));
+			}
+		}
+		problems = poller.problems.clone();
+		
+		for line in repeats.flush(now) {
+			logger.warn(&line);
+		}
+		let today = poller.config.zone().date(poller.clock.now());
+		if today != digest.day {
+			logger.info(&digest.line(&repeats.take_totals()));
+			digest = Digest::new(today);
+		}
+		
+		thread::sleep(time::Duration::from_secs(1));
+	}
+	
+	logger.info("Stopped");
+}
+
+fn warn(logger: &mut Logger, repeats: &mut Repeats, message: &str, now: DateTime<Utc>) {
+	if let Some(line) = repeats.occurred(message, now) {
+		logger.warn(&line);
+	}
+}
+
+/**
+ * What happened during a day, logged when the day changes.
+*/
+struct Digest {
+	day: NaiveDate,
+	// Tag readings received.
+	readings: usize,
+	alerts: usize,
+}
+
+impl Digest {
+	fn new(day: NaiveDate) -> Digest {
+		return Digest { day, readings: 0, alerts: 0 };
+	}
+	
+	/**
+	 * E.g. "Daily digest 2024-05-01: 8640 readings, 2 alerts, errors: Network error: timeout (57 times)".
+	*/
+	fn line(&self, errors: &[(String, u32)]) -> String {
+		let errors = if errors.is_empty() {
+			"no errors".to_string()
+		}
+		else {
+			let counts: Vec<String> = errors.iter().map(|(message, count)| format!("{} ({} times)", message, count)).collect();
+			format!("errors: {}", counts.join("; "))
+		};
+		return format!("Daily digest {}: {} readings, {} alerts, {}", self.day, self.readings, self.alerts, errors);
+	}
+}
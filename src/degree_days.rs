@@ -0,0 +1,111 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+
+/**
+ * Growing degree days: each day adds the amount its mean temperature ((min + max) / 2) exceeds the
+ * base temperature, accumulated from the season start.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DegreeDaysConfig {
+	pub base: f64,
+	// Month and day the season starts, e.g. "04-01".
+	pub season_start: String,
+}
+
+impl Default for DegreeDaysConfig {
+	fn default() -> DegreeDaysConfig {
+		return DegreeDaysConfig {
+			base: 10.0,
+			season_start: "04-01".into(),
+		};
+	}
+}
+
+impl DegreeDaysConfig {
+	/**
+	 * Start of the current season: this year's start date, or last year's if it hasn't come yet.
+	*/
+	pub fn season_start(&self, today: NaiveDate) -> Option<NaiveDate> {
+		let (month, day) = self.season_start.split_once('-')?;
+		let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+		let start = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+		
+		if start <= today {
+			return Some(start);
+		}
+		return NaiveDate::from_ymd_opt(today.year() - 1, month, day);
+	}
+	
+	/**
+	 * Check that the season start is a day of every year, so that no season is silently left without degree days.
+	*/
+	pub fn validate(&self, tag: &str) -> Result<(), String> {
+		// Not a leap year, for February 29th.
+		if self.season_start(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()).is_none() {
+			return Err(format!("The degree days season_start of \"{}\" must be a month and day of every year, e.g. \"04-01\", got \"{}\"", tag, self.season_start));
+		}
+		return Ok(());
+	}
+}
+
+/**
+ * Degree days of a single day.
+*/
+pub fn daily(min: f64, max: f64, base: f64) -> f64 {
+	return ((min + max) / 2.0 - base).max(0.0);
+}
+
+/**
+ * Running total per day from the daily (date, min, max) temperatures.
+*/
+pub fn accumulate(days: &[(NaiveDate, f64, f64)], base: f64) -> Vec<(NaiveDate, f64, f64)> {
+	let mut total = 0.0;
+	return days.iter()
+		.map(|(date, min, max)| {
+			let day = daily(*min, *max, base);
+			total += day;
+			(*date, day, total)
+		})
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+		return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+	}
+	
+	#[test]
+	fn days_below_base_add_nothing() {
+		assert_eq!(daily(12.0, 20.0, 10.0), 6.0);
+		assert_eq!(daily(2.0, 12.0, 10.0), 0.0);
+	}
+	
+	#[test]
+	fn accumulates_running_total() {
+		let days = [
+			(date(2024, 5, 1), 8.0, 16.0),
+			(date(2024, 5, 2), 4.0, 10.0),
+			(date(2024, 5, 3), 14.0, 22.0),
+		];
+		let totals: Vec<f64> = accumulate(&days, 10.0).iter().map(|(_, _, total)| *total).collect();
+		assert_eq!(totals, vec![2.0, 2.0, 10.0]);
+	}
+	
+	#[test]
+	fn season_starts_last_year_before_the_start_date() {
+		let config = DegreeDaysConfig::default();
+		assert_eq!(config.season_start(date(2024, 6, 15)), Some(date(2024, 4, 1)));
+		assert_eq!(config.season_start(date(2024, 2, 1)), Some(date(2023, 4, 1)));
+		
+		let invalid = DegreeDaysConfig { season_start: "spring".into(), ..DegreeDaysConfig::default() };
+		assert_eq!(invalid.season_start(date(2024, 6, 15)), None);
+		assert!(invalid.validate("Greenhouse").is_err());
+		assert!(DegreeDaysConfig { season_start: "13-01".into(), ..DegreeDaysConfig::default() }.validate("Greenhouse").is_err());
+		assert!(DegreeDaysConfig { season_start: "02-29".into(), ..DegreeDaysConfig::default() }.validate("Greenhouse").is_err());
+		assert!(config.validate("Greenhouse").is_ok());
+	}
+}
@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+use clap::ValueEnum;
+use crate::config::Config;
+use crate::render::Layout;
+use crate::theme::ThemeName;
+
+/**
+ * What the demo shows at a step.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemoStep {
+	// None for one tag with the big digits, like the kiosk mode.
+	pub layout: Option<Layout>,
+	pub theme: ThemeName,
+	// Index of the tag shown with the big digits, wrapped around the number of tags.
+	pub tag: usize,
+}
+
+/**
+ * The step with the number: each round goes through the layouts and then one tag with the big digits, and the next
+ * round is in the next theme with the next tag.
+*/
+pub fn step(number: usize, floorplan: bool) -> DemoStep {
+	let mut layouts = vec![Layout::Cards, Layout::Table, Layout::Minimal, Layout::Floors];
+	if floorplan {
+		layouts.push(Layout::Floorplan);
+	}
+	
+	let round = number / (layouts.len() + 1);
+	let themes = ThemeName::value_variants();
+	return DemoStep {
+		layout: layouts.get(number % (layouts.len() + 1)).copied(),
+		theme: themes[round % themes.len()],
+		tag: round,
+	};
+}
+
+/**
+ * Demo mode for showing the client at meetups and for soak testing the rendering: rotates through the layouts, the
+ * themes and the tags on a timer. Toggled with the d key.
+*/
+pub struct Demo {
+	pub running: bool,
+	number: usize,
+	switched_at: Instant,
+}
+
+impl Demo {
+	pub fn new(running: bool) -> Demo {
+		return Demo {
+			running,
+			number: 0,
+			switched_at: Instant::now(),
+		};
+	}
+	
+	/**
+	 * Start over from the first step, or stop.
+	*/
+	pub fn toggle(&mut self) {
+		*self = Demo::new(!self.running);
+	}
+	
+	/**
+	 * The step to show, moving to the next one every demo_cycle_seconds. None when not running.
+	*/
+	pub fn current(&mut self, config: &Config) -> Option<DemoStep> {
+		if !self.running {
+			return None;
+		}
+		if self.switched_at.elapsed() >= Duration::from_secs(config.demo_cycle_seconds.max(1)) {
+			self.number += 1;
+			self.switched_at = Instant::now();
+		}
+		return Some(step(self.number, config.floorplan.is_some()));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn rounds_of_layouts() {
+		let layouts: Vec<Option<Layout>> = (0..5).map(|number| step(number, false).layout).collect();
+		assert_eq!(layouts, vec![Some(Layout::Cards), Some(Layout::Table), Some(Layout::Minimal), Some(Layout::Floors), None]);
+		assert_eq!(step(4, false), DemoStep { layout: None, theme: ThemeName::Default, tag: 0 });
+		assert_eq!(step(5, false), DemoStep { layout: Some(Layout::Cards), theme: ThemeName::HighContrast, tag: 1 });
+		
+		assert_eq!(step(4, true).layout, Some(Layout::Floorplan));
+		assert_eq!(step(6 * 4, true).theme, ThemeName::Default);
+	}
+}
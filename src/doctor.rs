@@ -0,0 +1,353 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::clock::SystemClock;
+use crate::config::{self, Config};
+use crate::listener::Listener;
+use crate::poller::Poller;
+use crate::update;
+
+// How long the command source gets to write its tags.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+	Ok,
+	// Works, but not as well as it could, e.g. a terminal without UTF-8.
+	Warning,
+	Failed,
+	// Not configured or not used.
+	Skipped,
+}
+
+impl Outcome {
+	fn label(self) -> &'static str {
+		match self {
+			Outcome::Ok => "ok",
+			Outcome::Warning => "warn",
+			Outcome::Failed => "FAIL",
+			Outcome::Skipped => "-",
+		}
+	}
+}
+
+/**
+ * Result of one check of the doctor.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+	pub outcome: Outcome,
+	pub name: String,
+	pub detail: String,
+}
+
+fn check(outcome: Outcome, name: &str, detail: impl Into<String>) -> Check {
+	return Check { outcome, name: name.into(), detail: detail.into() };
+}
+
+/**
+ * Check the terminal, the config, the sources and the directories written to, and print the report for bug reports.
+ * The config error, if the config couldn't be loaded, is reported instead of the checks that need the config. Returns
+ * the exit code: 1 if a check failed.
+*/
+pub fn run(config: &Config, config_error: Option<&str>) -> i32 {
+	let mut checks = vec![
+		check(Outcome::Ok, "Version", format!("ruuvi-terminal-client {} on {} {}", update::VERSION, env::consts::OS, env::consts::ARCH)),
+		unicode(),
+		colors(),
+		size(),
+		config_file(config_error),
+	];
+	if config_error.is_none() {
+		checks.extend([api(config), listener(config), bluetooth()]);
+		checks.extend(writable_dirs(config));
+	}
+	
+	println!("{}", report(&checks));
+	return checks.iter().any(|check| check.outcome == Outcome::Failed) as i32;
+}
+
+/**
+ * Check the config and fetch once from each configured source, without the terminal and system checks of the doctor.
+ * Returns the exit code: 1 if a check failed.
+*/
+pub fn check_sources(config: &Config, config_error: Option<&str>) -> i32 {
+	let mut checks = vec![config_file(config_error)];
+	if config_error.is_none() {
+		checks.extend([api(config), listener(config), command(config), websocket(config)]);
+	}
+	
+	println!("{}", report(&checks));
+	return checks.iter().any(|check| check.outcome == Outcome::Failed) as i32;
+}
+
+/**
+ * A line per check: the outcome, name and details, aligned.
+*/
+pub fn report(checks: &[Check]) -> String {
+	let width = checks.iter().map(|check| check.name.chars().count()).max().unwrap_or(0);
+	return checks.iter()
+		.map(|check| format!("{:<4}  {:<width$}  {}", check.outcome.label(), check.name, check.detail, width = width))
+		.collect::<Vec<_>>()
+		.join("\n");
+}
+
+/**
+ * UTF-8 locale, for the arrows, the box drawing of the floors layout and the big digits.
+*/
+fn unicode() -> Check {
+	let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+		.filter_map(|name| env::var(name).ok())
+		.find(|value| !value.is_empty())
+		.unwrap_or_default();
+	let utf8 = locale.to_lowercase().replace('-', "").contains("utf8");
+	let detail = format!("locale {}", if locale.is_empty() { "not set" } else { &locale });
+	return check(if utf8 { Outcome::Ok } else { Outcome::Warning }, "UTF-8", detail);
+}
+
+fn colors() -> Check {
+	let term = env::var("TERM").unwrap_or_default();
+	let colors = tput(&["colors"]).and_then(|colors| colors.parse::<i32>().ok());
+	let detail = format!("TERM={}, {} colors", term, colors.map(|colors| colors.to_string()).unwrap_or("unknown".into()));
+	let outcome = match colors {
+		Some(colors) if colors >= 8 => Outcome::Ok,
+		_ => Outcome::Warning,
+	};
+	return check(outcome, "Colors", detail);
+}
+
+/**
+ * Terminal size against the smallest the cards fit in.
+*/
+fn size() -> Check {
+	let (Some(columns), Some(rows)) = (tput(&["cols"]), tput(&["lines"])) else {
+		return check(Outcome::Warning, "Terminal size", "unknown");
+	};
+	let (Ok(columns), Ok(rows)) = (columns.parse::<i32>(), rows.parse::<i32>()) else {
+		return check(Outcome::Warning, "Terminal size", "unknown");
+	};
+	let outcome = if columns >= 40 && rows >= 12 { Outcome::Ok } else { Outcome::Warning };
+	return check(outcome, "Terminal size", format!("{}x{}, the cards need at least 40x12", columns, rows));
+}
+
+/**
+ * Output of tput with the terminal as its stderr, which it reads the size from.
+*/
+fn tput(args: &[&str]) -> Option<String> {
+	let output = Command::new("tput").args(args).stderr(Stdio::inherit()).output().ok()?;
+	return output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string());
+}
+
+fn config_file(config_error: Option<&str>) -> Check {
+	let path = config::config_path();
+	let detail = match &path {
+		Some(path) if path.exists() => path.display().to_string(),
+		Some(path) => format!("{} not found, using the defaults", path.display()),
+		None => "no config directory, using the defaults".into(),
+	};
+	return match config_error {
+		Some(error) => check(Outcome::Failed, "Config", error),
+		None => check(Outcome::Ok, "Config", detail),
+	};
+}
+
+/**
+ * Fetch from the API once, like the status command.
+*/
+pub fn api(config: &Config) -> Check {
+	if config.api_url.is_empty() {
+		return check(Outcome::Skipped, "API", "no api_url");
+	}
+	let mut config = config.clone();
+	config.listen = None;
+	config.command = None;
+	config.websocket = None;
+	config.sink = None;
+	config.forward.url = None;
+	config.mqtt.url = None;
+	config.update_check = false;
+	let mut poller = match Poller::new(config, Arc::new(SystemClock)) {
+		Ok(poller) => poller,
+		Err(error) => return check(Outcome::Failed, "API", error),
+	};
+	
+	let start = Instant::now();
+	poller.poll();
+	let elapsed = start.elapsed().as_millis();
+	if let Some(error) = &poller.network_error {
+		return check(Outcome::Failed, "API", format!("{}: {}", poller.config.api_url, error));
+	}
+	if let Some((_, problem)) = poller.problems.first() {
+		return check(Outcome::Failed, "API", format!("{}: {}", poller.config.api_url, problem));
+	}
+	let format = poller.api_format.map(|format| format!(" in the {} format", format.name())).unwrap_or_default();
+	return check(Outcome::Ok, "API", format!("{}: {} tags{} in {} ms", poller.config.api_url, poller.data.len(), format, elapsed));
+}
+
+/**
+ * Run the command source until it writes its first line, or for at most COMMAND_TIMEOUT.
+*/
+fn command(config: &Config) -> Check {
+	let Some(command) = &config.command else {
+		return check(Outcome::Skipped, "Command", "no command");
+	};
+	let mut config = config.clone();
+	config.api_url = String::new();
+	config.listen = None;
+	config.websocket = None;
+	config.sink = None;
+	config.forward.url = None;
+	config.mqtt.url = None;
+	config.update_check = false;
+	let mut poller = match Poller::new(config, Arc::new(SystemClock)) {
+		Ok(poller) => poller,
+		Err(error) => return check(Outcome::Failed, "Command", error),
+	};
+	
+	let start = Instant::now();
+	while start.elapsed() < COMMAND_TIMEOUT {
+		if poller.poll() {
+			return check(Outcome::Ok, "Command", format!("{}: {} tags in {} ms", command, poller.data.len(), start.elapsed().as_millis()));
+		}
+		if let Some((_, problem)) = poller.problems.first() {
+			return check(Outcome::Failed, "Command", format!("{}: {}", command, problem));
+		}
+		thread::sleep(Duration::from_millis(100));
+	}
+	return check(Outcome::Failed, "Command", format!("{}: no data in {} s", command, COMMAND_TIMEOUT.as_secs()));
+}
+
+/**
+ * Connect to the WebSocket source and wait for its first message, for at most COMMAND_TIMEOUT. A server that only
+ * sends changes may have nothing to send, so no data without a connection problem is only a warning.
+*/
+fn websocket(config: &Config) -> Check {
+	let Some(url) = &config.websocket else {
+		return check(Outcome::Skipped, "WebSocket", "no websocket");
+	};
+	let mut config = config.clone();
+	config.api_url = String::new();
+	config.listen = None;
+	config.command = None;
+	config.sink = None;
+	config.forward.url = None;
+	config.mqtt.url = None;
+	config.update_check = false;
+	let mut poller = match Poller::new(config, Arc::new(SystemClock)) {
+		Ok(poller) => poller,
+		Err(error) => return check(Outcome::Failed, "WebSocket", error),
+	};
+	
+	let start = Instant::now();
+	while start.elapsed() < COMMAND_TIMEOUT {
+		if poller.poll() {
+			return check(Outcome::Ok, "WebSocket", format!("{}: {} tags in {} ms", url, poller.data.len(), start.elapsed().as_millis()));
+		}
+		if let Some((_, problem)) = poller.problems.first() {
+			return check(Outcome::Failed, "WebSocket", format!("{}: {}", url, problem));
+		}
+		thread::sleep(Duration::from_millis(100));
+	}
+	return check(Outcome::Warning, "WebSocket", format!("{}: no messages in {} s", url, COMMAND_TIMEOUT.as_secs()));
+}
+
+/**
+ * Start the listener to see that the address is free and the TLS files can be read. It stops with the doctor.
+*/
+fn listener(config: &Config) -> Check {
+	let Some(address) = &config.listen else {
+		return check(Outcome::Skipped, "Listener", "no listen address");
+	};
+	return match Listener::start(address, config, None) {
+		Ok(_) => check(Outcome::Ok, "Listener", format!("listening on {}", address)),
+		Err(error) => check(Outcome::Failed, "Listener", error),
+	};
+}
+
+/**
+ * Bluetooth adapters, for bug reports about gateways on the same machine. The client itself reads the tags from the
+ * API and the gateway pushes, not over Bluetooth.
+*/
+fn bluetooth() -> Check {
+	let adapters: Vec<String> = fs::read_dir("/sys/class/bluetooth")
+		.map(|entries| entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect())
+		.unwrap_or_default();
+	if adapters.is_empty() {
+		return check(Outcome::Skipped, "Bluetooth", "no adapters found, not needed by the client");
+	}
+	return check(Outcome::Ok, "Bluetooth", format!("{}, not needed by the client", adapters.join(", ")));
+}
+
+/**
+ * The directories of the store, the logs and the recordings, created if needed like on startup.
+*/
+fn writable_dirs(config: &Config) -> Vec<Check> {
+	let parent = |path: &Option<PathBuf>| path.as_ref().and_then(|path| path.parent()).map(Path::to_path_buf);
+	let dirs = [
+		("History store", parent(&config.store)),
+		("Log file", parent(&config.log_file)),
+		("Access log", parent(&config.listen_access_log)),
+		("Recordings", config.record.clone()),
+	];
+	return dirs.into_iter()
+		.filter_map(|(name, dir)| Some((name, dir?)))
+		.map(|(name, dir)| match writable(&dir) {
+			Ok(()) => check(Outcome::Ok, name, format!("{} writable", dir.display())),
+			Err(error) => check(Outcome::Failed, name, format!("{}: {}", dir.display(), error)),
+		})
+		.collect();
+}
+
+fn writable(dir: &Path) -> Result<(), std::io::Error> {
+	// Relative paths like "history.db" have an empty parent.
+	let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+	fs::create_dir_all(dir)?;
+	let probe = dir.join(format!(".ruuvi-tc-doctor-{}", std::process::id()));
+	fs::write(&probe, "")?;
+	return fs::remove_file(&probe);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn aligned_report() {
+		let checks = [
+			check(Outcome::Ok, "UTF-8", "locale C.UTF-8"),
+			check(Outcome::Failed, "History store", "/root: Permission denied"),
+			check(Outcome::Skipped, "API", "no api_url"),
+		];
+		assert_eq!(report(&checks), [
+			"ok    UTF-8          locale C.UTF-8",
+			"FAIL  History store  /root: Permission denied",
+			"-     API            no api_url",
+		].join("\n"));
+	}
+	
+	#[test]
+	fn command_source_check() {
+		let config = |command: &str| Config { command: Some(command.into()), update_check: false, ..Config::default() };
+		let check = command(&config("read request; tr -d '\\n' < tests/fixtures/custom.json; echo"));
+		assert_eq!(check.outcome, Outcome::Ok);
+		assert!(check.detail.contains(": 2 tags in "));
+		
+		let check = command(&config("echo nonsense; cat > /dev/null"));
+		assert_eq!(check.outcome, Outcome::Failed);
+		assert!(check.detail.ends_with("in nonsense"));
+	}
+	
+	#[test]
+	fn writable_directories() {
+		let dir = env::temp_dir().join(format!("ruuvi-tc-doctor-test-{}", std::process::id()));
+		let config = Config { record: Some(dir.join("recordings")), ..Config::default() };
+		let checks = writable_dirs(&config);
+		fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(checks.len(), 1);
+		assert_eq!((checks[0].outcome, checks[0].name.as_str()), (Outcome::Ok, "Recordings"));
+	}
+}
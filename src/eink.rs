@@ -0,0 +1,106 @@
+use serde::Deserialize;
+use crate::model::{ApiResponse, Metric};
+
+/**
+ * E-ink mode for e-paper displays, which are slow to update and ghost: monochrome, the clock times instead of the
+ * ages, and the screen is redrawn in full only when the data changes by more than the threshold, not every second.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EinkConfig {
+	// Change in a temperature or humidity that redraws the screen, like calm_epsilon.
+	pub threshold: f64,
+	// Light text on a dark screen.
+	pub inverted: bool,
+}
+
+impl Default for EinkConfig {
+	fn default() -> EinkConfig {
+		return EinkConfig {
+			threshold: 0.5,
+			inverted: false,
+		};
+	}
+}
+
+/**
+ * What the screen shows of a tag. A redraw is needed when it changes.
+*/
+#[derive(Debug, Clone, PartialEq)]
+struct Shown {
+	id: String,
+	label: String,
+	temperature: Option<(f64, Option<i8>)>,
+	humidity: Option<(f64, Option<i8>)>,
+	// To the hPa, the decimals drift all the time.
+	pressure: Option<(i64, Option<i8>)>,
+	unreachable: bool,
+	battery_low: bool,
+	battery_critical: bool,
+}
+
+/**
+ * Decides when the e-ink screen is redrawn.
+*/
+#[derive(Default)]
+pub struct Eink {
+	shown: Option<(Vec<Shown>, Vec<String>)>,
+}
+
+impl Eink {
+	/**
+	 * Whether the calmed data or the errors differ from what was last drawn. Remembers them as drawn if so.
+	*/
+	pub fn changed(&mut self, data: &ApiResponse, errors: Vec<String>) -> bool {
+		let metric = |metric: &Option<Metric>| metric.as_ref().map(|metric| (metric.current, metric.trend));
+		let tags = data.iter()
+			.map(|tag| Shown {
+				id: tag.id.clone(),
+				label: tag.label().to_string(),
+				temperature: metric(&tag.temperature),
+				humidity: metric(&tag.humidity),
+				pressure: tag.pressure.as_ref().map(|pressure| (pressure.current.round() as i64, pressure.trend)),
+				unreachable: tag.unreachable,
+				battery_low: tag.battery_low,
+				battery_critical: tag.battery_critical,
+			})
+			.collect();
+		let shown = Some((tags, errors));
+		if shown == self.shown {
+			return false;
+		}
+		self.shown = shown;
+		return true;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::calm::CalmFilter;
+	use crate::model::Tag;
+	
+	fn tag(temperature: f64, pressure: f64) -> Tag {
+		return Tag {
+			temperature: Some(Metric::from_current(temperature)),
+			humidity: Some(Metric::from_current(40.0)),
+			pressure: Some(Metric::from_current(pressure)),
+			..Tag::test("1", "Balcony")
+		};
+	}
+	
+	#[test]
+	fn redraws_on_changes_beyond_the_threshold() {
+		let mut calm = CalmFilter::new(EinkConfig::default().threshold);
+		let mut eink = Eink::default();
+		let mut changed = |temperature, pressure, errors: &[&str]| {
+			eink.changed(&calm.apply(&vec![tag(temperature, pressure)]), errors.iter().map(|error| error.to_string()).collect())
+		};
+		assert!(changed(20.0, 1002.1, &[]));
+		assert!(!changed(20.3, 1002.3, &[]));
+		assert!(changed(20.6, 1002.3, &[]));
+		assert!(changed(20.6, 1002.6, &[]));
+		assert!(changed(20.6, 1002.6, &["Network error"]));
+		assert!(!changed(20.6, 1002.6, &["Network error"]));
+	}
+}
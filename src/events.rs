@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use chrono::{DateTime, Utc};
+use crate::alerts::{AlertEvent, Severity};
+
+// Entries kept in memory, the oldest are dropped first.
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+	AlertStarted,
+	AlertCleared,
+	// The alert ended without data.
+	AlertUnknown,
+	NetworkError,
+	NetworkRestored,
+}
+
+impl EventKind {
+	pub fn name(&self) -> &'static str {
+		match self {
+			EventKind::AlertStarted => "alert_started",
+			EventKind::AlertCleared => "alert_cleared",
+			EventKind::AlertUnknown => "alert_unknown",
+			EventKind::NetworkError => "network_error",
+			EventKind::NetworkRestored => "network_restored",
+		}
+	}
+	
+	pub fn from_name(name: &str) -> Option<EventKind> {
+		match name {
+			"alert_started" => Some(EventKind::AlertStarted),
+			"alert_cleared" => Some(EventKind::AlertCleared),
+			"alert_unknown" => Some(EventKind::AlertUnknown),
+			"network_error" => Some(EventKind::NetworkError),
+			"network_restored" => Some(EventKind::NetworkRestored),
+			_ => None,
+		}
+	}
+}
+
+/**
+ * Something that happened: an alert starting or ending, or the network going down or coming back.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+	pub time: DateTime<Utc>,
+	pub kind: EventKind,
+	// The alert's severity and tag, None for the network events.
+	pub severity: Option<Severity>,
+	pub tag_name: Option<String>,
+	// The alert message or the network error.
+	pub message: String,
+}
+
+impl LogEntry {
+	/**
+	 * Entries of the alert events and the network state change of a poll.
+	*/
+	pub fn of(alert_events: &[AlertEvent], network: Option<Result<(), &str>>, now: DateTime<Utc>) -> Vec<LogEntry> {
+		let mut entries: Vec<LogEntry> = alert_events.iter()
+			.map(|event| {
+				let (kind, alert) = match event {
+					AlertEvent::Started(alert) => (EventKind::AlertStarted, alert),
+					AlertEvent::Cleared(alert) => (EventKind::AlertCleared, alert),
+					AlertEvent::Unknown(alert) => (EventKind::AlertUnknown, alert),
+				};
+				return LogEntry {
+					time: now,
+					kind,
+					severity: Some(alert.severity),
+					tag_name: Some(alert.tag_name.clone()),
+					message: alert.message.clone(),
+				};
+			})
+			.collect();
+		
+		let network = match network {
+			Some(Err(error)) => Some((EventKind::NetworkError, error.to_string())),
+			Some(Ok(())) => Some((EventKind::NetworkRestored, String::new())),
+			None => None,
+		};
+		if let Some((kind, message)) = network {
+			entries.push(LogEntry { time: now, kind, severity: None, tag_name: None, message });
+		}
+		return entries;
+	}
+}
+
+/**
+ * What happened while nobody was watching, e.g. overnight, for the event log screen. Seeded from the history store
+ * if there is one.
+*/
+#[derive(Debug, Default)]
+pub struct EventLog {
+	entries: VecDeque<LogEntry>,
+}
+
+impl EventLog {
+	pub fn new(entries: Vec<LogEntry>) -> EventLog {
+		let mut log = EventLog::default();
+		log.add(entries);
+		return log;
+	}
+	
+	pub fn add(&mut self, entries: Vec<LogEntry>) {
+		self.entries.extend(entries);
+		while self.entries.len() > MAX_ENTRIES {
+			self.entries.pop_front();
+		}
+	}
+	
+	/**
+	 * The entries, the latest first.
+	*/
+	pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+		return self.entries.iter().rev();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::alerts::Alert;
+	
+	#[test]
+	fn entries_of_a_poll() {
+		let now = "2024-05-01T03:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let alert = Alert {
+			tag_id: "1".into(),
+			tag_name: "Freezer".into(),
+			rule: "too_warm".into(),
+			severity: Severity::Critical,
+			message: "temperature above -15.00 (too_warm)".into(),
+			value: Some(-12.0),
+			peak: Some(-12.0),
+			started_at: now,
+		};
+		let entries = LogEntry::of(&[AlertEvent::Started(alert)], Some(Err("timed out")), now);
+		assert_eq!(entries.len(), 2);
+		assert_eq!((entries[0].kind, entries[0].severity, entries[0].tag_name.as_deref()), (EventKind::AlertStarted, Some(Severity::Critical), Some("Freezer")));
+		assert_eq!((entries[1].kind, entries[1].message.as_str()), (EventKind::NetworkError, "timed out"));
+		
+		let mut log = EventLog::new(entries);
+		log.add(LogEntry::of(&[], Some(Ok(())), now));
+		let kinds: Vec<EventKind> = log.entries().map(|entry| entry.kind).collect();
+		assert_eq!(kinds, vec![EventKind::NetworkRestored, EventKind::NetworkError, EventKind::AlertStarted]);
+		
+		let latest = log.entries().next().unwrap().clone();
+		log.add(vec![latest; MAX_ENTRIES]);
+		assert_eq!(log.entries().count(), MAX_ENTRIES);
+	}
+}
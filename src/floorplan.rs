@@ -0,0 +1,88 @@
+/**
+ * A piece of a floorplan line: text drawn as is, or a placeholder like "{Sauna}" or "{Sauna:humidity}" where the
+ * value of the tag goes. The value is padded or cut to the width of the placeholder, braces included, so that the
+ * walls around it stay where they were drawn.
+*/
+#[derive(Debug, PartialEq)]
+pub enum Segment<'a> {
+	Text(&'a str),
+	Value {
+		// Tag ID or name.
+		tag: &'a str,
+		metric: PlanMetric,
+		width: usize,
+	},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanMetric {
+	Temperature,
+	Humidity,
+	Pressure,
+	Name,
+}
+
+/**
+ * Split a floorplan line into the text and the placeholders. Braces that don't form a placeholder are text.
+*/
+pub fn parse(line: &str) -> Vec<Segment<'_>> {
+	let mut segments = Vec::new();
+	let mut rest = line;
+	
+	while let Some(start) = rest.find('{') {
+		let Some(length) = rest[start..].find('}') else {
+			break;
+		};
+		let placeholder = &rest[start..start + length + 1];
+		let content = placeholder[1..placeholder.len() - 1].trim();
+		let (tag, metric) = match content.rsplit_once(':').and_then(|(tag, metric)| Some((tag, metric_named(metric.trim())?))) {
+			Some((tag, metric)) => (tag.trim(), metric),
+			None => (content, PlanMetric::Temperature),
+		};
+		if tag.is_empty() {
+			segments.push(Segment::Text(&rest[..start + length + 1]));
+		}
+		else {
+			if start > 0 {
+				segments.push(Segment::Text(&rest[..start]));
+			}
+			segments.push(Segment::Value { tag, metric, width: placeholder.chars().count() });
+		}
+		rest = &rest[start + length + 1..];
+	}
+	if !rest.is_empty() {
+		segments.push(Segment::Text(rest));
+	}
+	return segments;
+}
+
+/**
+ * The metric after the colon. Tag IDs are MAC addresses with colons too, so anything else is part of the tag.
+*/
+fn metric_named(name: &str) -> Option<PlanMetric> {
+	return match name {
+		"temperature" => Some(PlanMetric::Temperature),
+		"humidity" => Some(PlanMetric::Humidity),
+		"pressure" => Some(PlanMetric::Pressure),
+		"name" => Some(PlanMetric::Name),
+		_ => None,
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn placeholders_keep_their_width() {
+		assert_eq!(parse("| {Sauna}   | {DE:AD:BE:EF:00:01:humidity} |"), vec![
+			Segment::Text("| "),
+			Segment::Value { tag: "Sauna", metric: PlanMetric::Temperature, width: 7 },
+			Segment::Text("   | "),
+			Segment::Value { tag: "DE:AD:BE:EF:00:01", metric: PlanMetric::Humidity, width: 28 },
+			Segment::Text(" |"),
+		]);
+		assert_eq!(parse("{DE:AD:BE:EF:00:01}"), vec![Segment::Value { tag: "DE:AD:BE:EF:00:01", metric: PlanMetric::Temperature, width: 19 }]);
+		assert_eq!(parse("+--{}--{ --"), vec![Segment::Text("+--{}"), Segment::Text("--{ --")]);
+	}
+}
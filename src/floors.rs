@@ -0,0 +1,109 @@
+use crate::config::Config;
+use crate::model::Tag;
+
+/**
+ * The tags on a floor. The name is None for the tags with no floor known.
+*/
+pub struct Floor<'a> {
+	pub name: Option<&'a str>,
+	pub tags: Vec<&'a Tag>,
+}
+
+/**
+ * The floor of the tag: from the config, or from the source's metadata.
+*/
+pub fn of<'a>(tag: &'a Tag, config: &'a Config) -> Option<&'a str> {
+	return config.tag(tag)
+		.and_then(|tag_config| tag_config.floor.as_deref())
+		.or(tag.metadata.floor.as_deref());
+}
+
+/**
+ * Stack the tags by floor like a building: the top floor first and the basements last, tags in the data order on
+ * each floor. Tags with no floor come after the basements.
+*/
+pub fn stack<'a>(data: impl Iterator<Item = &'a Tag>, config: &'a Config) -> Vec<Floor<'a>> {
+	let mut floors: Vec<Floor> = Vec::new();
+	let mut unknown = Floor { name: None, tags: Vec::new() };
+	
+	for tag in data {
+		match of(tag, config) {
+			Some(name) => match floors.iter_mut().find(|floor| floor.name == Some(name)) {
+				Some(floor) => floor.tags.push(tag),
+				None => floors.push(Floor { name: Some(name), tags: vec![tag] }),
+			},
+			None => unknown.tags.push(tag),
+		}
+	}
+	
+	// Stable, so floors that can't be ordered stay in the data order.
+	floors.sort_by(|a, b| level(b.name.unwrap_or_default()).total_cmp(&level(a.name.unwrap_or_default())));
+	if !unknown.tags.is_empty() {
+		floors.push(unknown);
+	}
+	return floors;
+}
+
+/**
+ * Height order of a floor name: the number of "2" or "-1", basements like "B1" and "Basement" below the ground
+ * floor, attics on top. Other names are at the ground level.
+*/
+fn level(name: &str) -> f64 {
+	let name = name.trim().to_lowercase();
+	if let Ok(number) = name.parse::<f64>() {
+		return number;
+	}
+	if let Some(Ok(number)) = name.strip_prefix('b').map(|number| number.parse::<f64>()) {
+		return -number;
+	}
+	if ["basement", "cellar", "kellari"].contains(&name.as_str()) {
+		return -1.0;
+	}
+	if ["attic", "ullakko"].contains(&name.as_str()) {
+		return f64::MAX;
+	}
+	return 0.0;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::TagConfig;
+	use crate::model::{Metric, TagMetadata};
+	
+	fn tag(id: &str, floor: Option<&str>) -> Tag {
+		return Tag {
+			temperature: Some(Metric::from_current(20.0)),
+			humidity: Some(Metric::from_current(40.0)),
+			metadata: TagMetadata { floor: floor.map(String::from), ..TagMetadata::default() },
+			..Tag::test(id, id)
+		};
+	}
+	
+	#[test]
+	fn top_floor_first() {
+		let data = [
+			tag("kitchen", Some("1")),
+			tag("cellar", Some("Basement")),
+			tag("bedroom", Some("2")),
+			tag("outdoor", None),
+			tag("attic", Some("Attic")),
+			tag("hall", Some("1")),
+			tag("garage", None),
+		];
+		let mut config = Config::default();
+		config.tags.insert("garage".into(), TagConfig { floor: Some("B2".into()), ..TagConfig::default() });
+		
+		let floors: Vec<(Option<&str>, Vec<&str>)> = stack(data.iter(), &config).iter()
+			.map(|floor| (floor.name, floor.tags.iter().map(|tag| tag.id.as_str()).collect()))
+			.collect();
+		assert_eq!(floors, vec![
+			(Some("Attic"), vec!["attic"]),
+			(Some("2"), vec!["bedroom"]),
+			(Some("1"), vec!["kitchen", "hall"]),
+			(Some("Basement"), vec!["cellar"]),
+			(Some("B2"), vec!["garage"]),
+			(None, vec!["outdoor"]),
+		]);
+	}
+}
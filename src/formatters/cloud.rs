@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use crate::model::{ApiResponse, Metric, Tag, TagMetadata};
+use crate::ruuvi_decoder;
+
+/**
+ * Sensors and their latest measurements from the Ruuvi Cloud API (the sensors-dense endpoint). The measurements
+ * are the raw broadcasts of the tags.
+*/
+pub const URL: &str = "https://network.ruuvi.com/sensors-dense?measurements=true&sharedToMe=true";
+
+#[derive(Debug, Deserialize)]
+struct CloudResponse {
+	data: CloudData,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudData {
+	sensors: Vec<CloudSensor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CloudSensor {
+	sensor: String,
+	#[serde(default)]
+	name: String,
+	#[serde(default)]
+	measurements: Vec<CloudMeasurement>,
+	// Calibration offsets set in the Ruuvi app, pressure in Pa.
+	#[serde(default)]
+	offset_temperature: f64,
+	#[serde(default)]
+	offset_humidity: f64,
+	#[serde(default)]
+	offset_pressure: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudMeasurement {
+	timestamp: i64,
+	data: String,
+	// Of the gateway that received the measurement.
+	#[serde(default)]
+	rssi: Option<i32>,
+}
+
+pub fn matches(value: &Value) -> bool {
+	return value.pointer("/data/sensors").is_some_and(|sensors| sensors.is_array());
+}
+
+pub fn parse(value: &Value) -> Result<ApiResponse, Box<dyn std::error::Error>> {
+	let response = CloudResponse::deserialize(value)?;
+	
+	return Ok(response.data.sensors.into_iter().filter_map(|sensor| {
+		// Sensors without a measurement, or in a format that can't be decoded, are left out.
+		let latest = sensor.measurements.iter().max_by_key(|measurement| measurement.timestamp)?;
+		let measurement = ruuvi_decoder::decode_text(&latest.data).ok()?;
+		let name = if sensor.name.is_empty() { sensor.sensor.clone() } else { sensor.name };
+		
+		Some(Tag {
+			id: sensor.sensor,
+			name,
+			alias: None,
+			datetime: DateTime::<Utc>::from_timestamp(latest.timestamp, 0).map(|datetime| datetime.to_rfc3339()).unwrap_or_default(),
+			temperature: measurement.temperature.map(|temperature| Metric::from_current(temperature + sensor.offset_temperature)),
+			humidity: measurement.humidity.map(|humidity| Metric::from_current(humidity + sensor.offset_humidity)),
+			pressure: measurement.pressure.map(|pressure| Metric::from_current((pressure + sensor.offset_pressure) / 100.0)), // Pa to hPa.
+			battery_voltage: measurement.battery_voltage,
+			battery_low: false, // Determined from the voltage by the battery model.
+			battery_critical: false,
+			movement_counter: measurement.movement_counter,
+			measurement_sequence: measurement.measurement_sequence,
+			rssi: latest.rssi,
+			unreachable: false,
+			metadata: TagMetadata::default(),
+			extras: BTreeMap::new(),
+		})
+	}).collect());
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::formatters::FormatError;
+use crate::model::{ApiResponse, Metric, Tag, TagMetadata};
+
+/**
+ * The custom Ruuvi API format: an array of tags with min/max and trends already calculated.
+*/
+#[derive(Debug, Deserialize)]
+struct CustomTag {
+	tag_id: u32,
+	datetime: String,
+	// Missing or null for a broken sensor.
+	#[serde(default)]
+	temperature: Option<Metric>,
+	#[serde(default)]
+	humidity: Option<Metric>,
+	#[serde(default)]
+	pressure: Option<Metric>,
+	#[serde(default)]
+	battery_voltage: Option<f64>,
+	#[serde(default)]
+	rssi: Option<i32>,
+	battery_low: bool,
+	unreachable: bool,
+	#[serde(default, deserialize_with = "empty_string_if_null")]
+	tag_name: String,
+	// Optional location, floor and description.
+	#[serde(flatten)]
+	metadata: TagMetadata,
+	// Any other fields, e.g. co2 or lux of a sensor next to the tag. The numbers are passed through.
+	#[serde(flatten)]
+	extras: BTreeMap<String, Value>,
+}
+
+pub fn matches(value: &Value) -> bool {
+	return value.is_array();
+}
+
+/**
+ * The tags one by one, so that a tag the API sends in an unexpected shape doesn't lose the others. The skipped tags
+ * are returned as a problem with the first of them. An error if no tag could be parsed.
+*/
+pub fn parse(value: &Value) -> Result<(ApiResponse, Option<FormatError>), Box<dyn std::error::Error>> {
+	let entries = value.as_array().ok_or("Expected an array of tags")?;
+	let mut tags = Vec::new();
+	let mut skipped = Vec::new();
+	for entry in entries {
+		match CustomTag::deserialize(entry) {
+			Ok(tag) => tags.push(tag),
+			Err(error) => skipped.push((error, entry)),
+		}
+	}
+	if tags.is_empty() && !skipped.is_empty() {
+		return Err(skipped.remove(0).0.into());
+	}
+	let problem = skipped.first().map(|(error, entry)| {
+		FormatError::new(format!("Skipped {} of {} tags: {}", skipped.len(), entries.len(), error), &entry.to_string())
+	});
+	
+	let tags = tags.into_iter().map(|tag| Tag {
+		id: tag.tag_id.to_string(),
+		name: tag.tag_name,
+		alias: None,
+		datetime: tag.datetime,
+		temperature: tag.temperature,
+		humidity: tag.humidity,
+		pressure: tag.pressure,
+		battery_voltage: tag.battery_voltage,
+		battery_low: tag.battery_low,
+		battery_critical: false,
+		movement_counter: None,
+		measurement_sequence: None,
+		rssi: tag.rssi,
+		unreachable: tag.unreachable,
+		metadata: tag.metadata,
+		extras: tag.extras.into_iter().filter_map(|(field, value)| Some((field, value.as_f64()?))).collect(),
+	}).collect();
+	return Ok((tags, problem));
+}
+
+/**
+ * Helper function for Serde to convert null values to empty strings that are coming from the API.
+ * This is 100% generated by ChatGPT but seems to do the trick.
+ * TODO: Refactor to something more human.
+ *
+*/
+fn empty_string_if_null<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	let opt = Option::<String>::deserialize(deserializer)?;
+	Ok(opt.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn metadata_is_optional() {
+		let value = serde_json::json!([
+			{
+				"tag_id": 1, "datetime": "2024-05-01T12:00:00Z", "temperature": { "current": 21.5 }, "humidity": { "current": 40.0 },
+				"battery_low": false, "unreachable": false, "tag_name": "Sauna",
+				"location": "Main house", "floor": 1, "description": "Above the stove"
+			},
+			{
+				"tag_id": 2, "datetime": "2024-05-01T12:00:00Z", "temperature": { "current": 4.0 }, "humidity": { "current": 80.0 },
+				"battery_low": false, "unreachable": false, "tag_name": null, "floor": "B1"
+			}
+		]);
+		let (tags, _) = parse(&value).unwrap();
+		
+		assert_eq!(tags[0].metadata.summary("floor"), Some("Main house, floor 1 — Above the stove".into()));
+		assert_eq!(tags[1].metadata.summary("floor"), Some("floor B1".into()));
+		assert_eq!(TagMetadata::default().summary("floor"), None);
+	}
+	
+	#[test]
+	fn extra_numbers_are_passed_through() {
+		let value = serde_json::json!([
+			{
+				"tag_id": 1, "datetime": "2024-05-01T12:00:00Z", "temperature": { "current": 21.5 }, "humidity": { "current": 40.0 },
+				"battery_low": false, "unreachable": false, "tag_name": "Office", "location": "Main house",
+				"co2": 612, "lux": 340.5, "sensor_model": "SCD30", "noise": null
+			}
+		]);
+		let (tags, _) = parse(&value).unwrap();
+		
+		assert_eq!(tags[0].extras, BTreeMap::from([("co2".to_string(), 612.0), ("lux".to_string(), 340.5)]));
+		assert_eq!(tags[0].metadata.location.as_deref(), Some("Main house"));
+	}
+	
+	#[test]
+	fn malformed_tags_are_skipped() {
+		let value = serde_json::json!([
+			{ "tag_id": 1, "datetime": "2024-05-01T12:00:00Z", "temperature": { "current": 21.5 }, "battery_low": false, "unreachable": false, "tag_name": "Sauna" },
+			{ "tag_id": "2", "datetime": "2024-05-01T12:00:00Z", "battery_low": false, "unreachable": false, "tag_name": "Cellar" },
+			{ "tag_id": 3, "datetime": "2024-05-01T12:00:00Z", "temperature": "21.5", "battery_low": false, "unreachable": false, "tag_name": "Attic" }
+		]);
+		let (tags, problem) = parse(&value).unwrap();
+		let problem = problem.unwrap();
+		
+		assert_eq!(tags.len(), 1);
+		assert_eq!(tags[0].name, "Sauna");
+		assert!(problem.message.starts_with("Skipped 2 of 3 tags: invalid type: string \"2\""), "{}", problem.message);
+		assert!(problem.snippet.contains("\"tag_name\":\"Cellar\""));
+		
+		assert!(parse(&serde_json::json!([{ "tag_id": "x" }])).is_err());
+		assert!(parse(&serde_json::json!([])).unwrap().0.is_empty());
+	}
+}
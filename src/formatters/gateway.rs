@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use crate::formatters::FormatError;
+use crate::model::{ApiResponse, Metric, Tag, TagMetadata};
+use crate::ruuvi_decoder::{self, Measurement};
+
+/**
+ * The Ruuvi Gateway format (the /history endpoint and HTTP pushes). Tags are keyed by MAC address and only have the
+ * current values. The raw broadcast in the data field fills in what the gateway didn't decode, and is all there is
+ * without decoding enabled in the gateway.
+*/
+#[derive(Debug, Deserialize)]
+struct GatewayResponse {
+	data: GatewayData,
+}
+
+#[derive(Debug, Deserialize)]
+struct GatewayData {
+	tags: BTreeMap<String, GatewayTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GatewayTag {
+	// When the gateway received the broadcast. The gateway's own timestamp next to the tags is when it sent them, not
+	// when the tags measured, so a tag without one has no measurement time.
+	#[serde(default)]
+	timestamp: Option<String>,
+	#[serde(default)]
+	temperature: Option<f64>,
+	#[serde(default)]
+	humidity: Option<f64>,
+	#[serde(default)]
+	pressure: Option<f64>,
+	#[serde(default)]
+	voltage: Option<f64>,
+	#[serde(default)]
+	rssi: Option<i32>,
+	// The raw broadcast as hex.
+	#[serde(default)]
+	data: Option<String>,
+}
+
+pub fn matches(value: &Value) -> bool {
+	return value.pointer("/data/tags").is_some_and(|tags| tags.is_object());
+}
+
+/**
+ * The tags of the Ruuvi devices. Broadcasts of other devices, like iBeacons when the gateway scans for all, are left
+ * out. A Ruuvi broadcast that can't be decoded and that the gateway didn't decode either is skipped, and returned as a
+ * problem with the first of them.
+*/
+pub fn parse(value: &Value) -> Result<(ApiResponse, Option<FormatError>), Box<dyn std::error::Error>> {
+	let response = GatewayResponse::deserialize(value)?;
+	let mut skipped = Vec::new();
+	let mut tags = Vec::new();
+	for (mac, tag) in response.data.tags {
+		let decoded = tag.temperature.is_some() || tag.humidity.is_some() || tag.pressure.is_some() || tag.voltage.is_some();
+		let raw = match tag.data.as_deref() {
+			Some(data) if !decoded && !ruuvi_decoder::is_ruuvi_text(data) => continue,
+			Some(data) => match ruuvi_decoder::decode_text(data) {
+				Ok(raw) => Some(raw),
+				// The gateway's own fields are enough.
+				Err(_) if decoded => None,
+				Err(error) => {
+					skipped.push((error, mac));
+					continue;
+				},
+			},
+			None if decoded => None,
+			None => continue,
+		};
+		tags.push(tag_of(mac, tag, raw));
+	}
+	let problem = skipped.first().map(|(error, mac)| {
+		let entry = value.pointer("/data/tags").and_then(|tags| tags.get(mac)).map(Value::to_string).unwrap_or_default();
+		FormatError::new(format!("Skipped {} of {} tags: {}", skipped.len(), tags.len() + skipped.len(), error), &entry)
+	});
+	return Ok((tags, problem));
+}
+
+fn tag_of(mac: String, tag: GatewayTag, raw: Option<Measurement>) -> Tag {
+	let raw_value = |value: fn(&Measurement) -> Option<f64>| raw.as_ref().and_then(value);
+	return Tag {
+		id: mac.clone(),
+		name: mac,
+		alias: None,
+		datetime: tag.timestamp.as_deref().map(unix_to_rfc3339).unwrap_or_default(),
+		temperature: tag.temperature.or(raw_value(|raw| raw.temperature)).map(Metric::from_current),
+		humidity: tag.humidity.or(raw_value(|raw| raw.humidity)).map(Metric::from_current),
+		pressure: tag.pressure.or(raw_value(|raw| raw.pressure)).map(|pressure| Metric::from_current(pressure / 100.0)), // Pa to hPa.
+		battery_voltage: tag.voltage.or(raw_value(|raw| raw.battery_voltage)),
+		battery_low: false, // Determined from the voltage by the battery model.
+		battery_critical: false,
+		movement_counter: raw.as_ref().and_then(|raw| raw.movement_counter),
+		measurement_sequence: raw.as_ref().and_then(|raw| raw.measurement_sequence),
+		rssi: tag.rssi,
+		unreachable: false,
+		metadata: TagMetadata::default(),
+		extras: BTreeMap::new(),
+	};
+}
+
+/**
+ * The gateway sends UNIX timestamps as strings.
+*/
+fn unix_to_rfc3339(timestamp: &str) -> String {
+	return timestamp.parse::<i64>()
+		.ok()
+		.and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+		.map(|datetime| datetime.to_rfc3339())
+		.unwrap_or_default();
+}
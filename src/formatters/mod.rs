@@ -0,0 +1,136 @@
+use std::fmt;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use crate::model::ApiResponse;
+
+pub mod cloud;
+mod custom;
+mod gateway;
+
+// Longest JSON snippet kept with a format error, in characters.
+const SNIPPET_LENGTH: usize = 120;
+
+/**
+ * Data that couldn't be parsed, with the start of the offending JSON so that the user can see what the source sent.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatError {
+	pub message: String,
+	pub snippet: String,
+}
+
+impl FormatError {
+	pub fn new(message: impl ToString, json: &str) -> FormatError {
+		let mut snippet: String = json.chars().take(SNIPPET_LENGTH).collect();
+		if json.chars().nth(SNIPPET_LENGTH).is_some() {
+			snippet.push('…');
+		}
+		return FormatError { message: message.to_string(), snippet };
+	}
+}
+
+impl fmt::Display for FormatError {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		return write!(formatter, "{} in {}", self.message, self.snippet);
+	}
+}
+
+impl std::error::Error for FormatError {}
+
+/**
+ * The supported data formats.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+	Custom,
+	Gateway,
+	Cloud,
+}
+
+impl Format {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Format::Custom => "custom",
+			Format::Gateway => "gateway",
+			Format::Cloud => "cloud",
+		}
+	}
+	
+	/**
+	 * The format the data is in, from its shape.
+	*/
+	pub fn detect(value: &Value) -> Option<Format> {
+		if custom::matches(value) {
+			return Some(Format::Custom);
+		}
+		if gateway::matches(value) {
+			return Some(Format::Gateway);
+		}
+		if cloud::matches(value) {
+			return Some(Format::Cloud);
+		}
+		return None;
+	}
+}
+
+/**
+ * Tags parsed from the data, with the format the data was in.
+*/
+#[derive(Debug)]
+pub struct Parsed {
+	pub format: Format,
+	pub data: ApiResponse,
+	// The tags that were skipped because they couldn't be parsed, if any.
+	pub skipped: Option<FormatError>,
+}
+
+/**
+ * Convert the raw API JSON into tags. The format is detected from the shape of the data. Tags that can't be parsed
+ * are left out.
+*/
+pub fn parse(value: &Value) -> Result<ApiResponse, FormatError> {
+	return parse_detected(value).map(|parsed| parsed.data);
+}
+
+/**
+ * Like parse(), with the format the data was in and the tags that were left out.
+*/
+pub fn parse_detected(value: &Value) -> Result<Parsed, FormatError> {
+	let Some(format) = Format::detect(value) else {
+		return Err(FormatError::new("Unknown API data format", &value.to_string()));
+	};
+	let result = match format {
+		Format::Custom => custom::parse(value),
+		Format::Gateway => gateway::parse(value),
+		Format::Cloud => cloud::parse(value).map(|data| (data, None)),
+	};
+	let (data, skipped) = result.map_err(|error| FormatError::new(error, &value.to_string()))?;
+	return Ok(Parsed { format, data, skipped });
+}
+
+/**
+ * Like parse_detected(), from the text of a response, a push or a line of a command. Invalid JSON is a format error
+ * too.
+*/
+pub fn parse_text(text: &str) -> Result<Parsed, FormatError> {
+	let json: Value = serde_json::from_str(text).map_err(|error| FormatError::new(error, text))?;
+	return parse_detected(&json);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn errors_keep_the_start_of_the_json() {
+		let error = parse(&serde_json::json!({ "unexpected": "x".repeat(200) })).unwrap_err();
+		assert_eq!(error.message, "Unknown API data format");
+		assert_eq!(error.snippet.chars().count(), SNIPPET_LENGTH + 1);
+		assert!(error.snippet.starts_with("{\"unexpected\":\"xxx"));
+		
+		let error = parse(&serde_json::json!([{ "tag_id": "not a number" }])).unwrap_err();
+		assert_eq!(error.snippet, "[{\"tag_id\":\"not a number\"}]");
+	}
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::blocking::Client;
+use reqwest::Url;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use crate::model::ApiResponse;
+use crate::output::Output;
+use crate::schema::SCHEMA_VERSION;
+use crate::status::{self, TagJson};
+
+// Refreshes queued for the background thread. More means the service is stuck, and they're dropped.
+const QUEUE: usize = 100;
+// Wait before the first retry of a failed request, doubled for every retry.
+const RETRY: Duration = Duration::from_secs(1);
+
+/**
+ * Forwarding of every refresh to another service, so that it can mirror the normalized tags without polling the
+ * sources itself.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ForwardConfig {
+	// URL the refreshes are posted to as JSON lines. Not forwarded without.
+	pub url: Option<String>,
+	// Bearer token sent with the requests.
+	pub token: Option<String>,
+	// Refreshes sent in one request, or fewer when the first of them is this many seconds old.
+	pub batch: usize,
+	pub batch_seconds: u64,
+	// Tries again after 1, 2, 4… seconds before leaving the lines for the next batch.
+	pub retries: u32,
+	// Lines kept while the service is down. The oldest are dropped beyond.
+	pub buffer: usize,
+}
+
+impl Default for ForwardConfig {
+	fn default() -> ForwardConfig {
+		return ForwardConfig {
+			url: None,
+			token: None,
+			batch: 1,
+			batch_seconds: 60,
+			retries: 3,
+			buffer: 10_000,
+		};
+	}
+}
+
+/**
+ * A line of the request body: a tag as in the status command's JSON, with the time of the refresh it's from.
+*/
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ForwardLine {
+	/// Version of this schema.
+	pub schema_version: u32,
+	/// RFC 3339, the same on all the tags of a refresh.
+	pub refreshed_at: String,
+	#[serde(flatten)]
+	pub tag: TagJson,
+}
+
+/**
+ * Posts the refreshes from a background thread, so that a slow service doesn't hold up the UI. The body is
+ * application/x-ndjson with a line per tag per refresh, the refreshes in the order they happened.
+*/
+pub struct Forwarder {
+	sender: SyncSender<Vec<String>>,
+	// Why a batch couldn't be sent since the last call.
+	failure: Arc<Mutex<Option<String>>>,
+}
+
+impl Forwarder {
+	pub fn start(url: &str, config: &ForwardConfig, client: Client) -> Result<Forwarder, String> {
+		let url = Url::parse(url).map_err(|error| format!("Invalid forward URL \"{}\": {}", url, error))?;
+		let (sender, receiver) = mpsc::sync_channel::<Vec<String>>(QUEUE);
+		let failure = Arc::new(Mutex::new(None));
+		
+		let config = config.clone();
+		let thread_failure = Arc::clone(&failure);
+		thread::spawn(move || {
+			let mut pending = VecDeque::new();
+			let mut refreshes = 0;
+			let mut deadline: Option<Instant> = None;
+			loop {
+				let received = match deadline {
+					Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+					None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+				};
+				match received {
+					Ok(lines) => {
+						pending.extend(lines);
+						refreshes += 1;
+						let deadline = *deadline.get_or_insert_with(|| Instant::now() + Duration::from_secs(config.batch_seconds));
+						if refreshes < config.batch && Instant::now() < deadline {
+							continue;
+						}
+					},
+					Err(RecvTimeoutError::Timeout) => {},
+					// The client quit.
+					Err(RecvTimeoutError::Disconnected) => return,
+				}
+				
+				while pending.len() > config.buffer {
+					pending.pop_front();
+				}
+				match post(&client, &url, &config, &pending) {
+					Ok(()) => pending.clear(),
+					Err(error) => *thread_failure.lock().unwrap() = Some(error),
+				}
+				(refreshes, deadline) = (0, None);
+			}
+		});
+		
+		return Ok(Forwarder { sender, failure });
+	}
+}
+
+impl Output for Forwarder {
+	/**
+	 * Queue the tags of a refresh.
+	*/
+	fn send(&mut self, data: &ApiResponse, now: DateTime<Utc>) {
+		let refreshed_at = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+		let lines = status::tags_json(data).into_iter()
+			.map(|tag| ForwardLine { schema_version: SCHEMA_VERSION, refreshed_at: refreshed_at.clone(), tag })
+			.filter_map(|line| serde_json::to_string(&line).ok())
+			.collect();
+		let _ = self.sender.try_send(lines);
+	}
+	
+	fn take_failure(&self) -> Option<String> {
+		return self.failure.lock().ok()?.take();
+	}
+}
+
+/**
+ * Post the lines, trying again after a while until the retries run out. The error is the last try's.
+*/
+fn post(client: &Client, url: &Url, config: &ForwardConfig, lines: &VecDeque<String>) -> Result<(), String> {
+	let body: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+	let mut attempt = 0;
+	loop {
+		let mut request = client.post(url.clone()).header("Content-Type", "application/x-ndjson").body(body.clone());
+		if let Some(token) = &config.token {
+			request = request.bearer_auth(token);
+		}
+		let error = match request.send() {
+			Ok(response) if response.status().is_success() => return Ok(()),
+			Ok(response) => format!("Forwarding to {} failed: {}", url, response.status()),
+			Err(error) => format!("Forwarding to {} failed: {}", url, error),
+		};
+		if attempt >= config.retries {
+			return Err(error);
+		}
+		thread::sleep(RETRY * 2u32.pow(attempt.min(16)));
+		attempt += 1;
+	}
+}
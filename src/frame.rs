@@ -0,0 +1,43 @@
+use crate::demo::DemoStep;
+use crate::i18n::Text;
+use crate::model::ApiResponse;
+use crate::poller::Poller;
+use crate::render::{self, View};
+
+/**
+ * What a frame of the screen depends on besides the keys pressed. Rendering every string again once a second is most
+ * of the CPU time on a slow computer like a Pi Zero, so the screen is redrawn only when this changes: the data, the
+ * errors, the size of the terminal, the demo step or the tag shown alone, and the clock. The clock is the "Updated"
+ * times as shown, which change every second while a reading is under a minute old and less often after, and the
+ * minute for the rest of the times on the screen.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+	size: (i32, i32),
+	// The id, the time and the unreachable flag of each tag, the values change only with the time.
+	tags: Vec<(String, String, bool)>,
+	errors: Vec<Text>,
+	paused: bool,
+	step: Option<DemoStep>,
+	// Id of the tag in the kiosk mode.
+	alone: Option<String>,
+	updated: Vec<String>,
+	minute: i64,
+}
+
+impl Frame {
+	pub fn new(data: &ApiResponse, poller: &Poller, view: &View, size: (i32, i32), step: Option<DemoStep>, alone: Option<&str>) -> Frame {
+		let now = poller.clock.now();
+		let lang = poller.config.lang();
+		return Frame {
+			size,
+			tags: data.iter().map(|tag| (tag.id.clone(), tag.datetime.clone(), tag.unreachable)).collect(),
+			errors: poller.errors(),
+			paused: poller.scheduler.paused,
+			step,
+			alone: alone.map(String::from),
+			updated: data.iter().map(|tag| render::format_updated(&tag.datetime, view, lang, now)).collect(),
+			minute: now.timestamp().div_euclid(60),
+		};
+	}
+}
@@ -0,0 +1,104 @@
+use chrono::Duration;
+use serde::Deserialize;
+use crate::history::History;
+use crate::i18n::Text;
+use crate::metrics;
+use crate::model::Tag;
+use crate::trend;
+
+/**
+ * Thresholds of the frost and condensation warnings of the outdoor tags.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FrostConfig {
+	pub enabled: bool,
+	// Warn when the temperature is at or below this, or falling fast enough to reach it within the hours, in °C.
+	pub temperature: f64,
+	pub hours: f64,
+	// How far back the fall is calculated from.
+	pub window_minutes: i64,
+	// Warn of condensation when the temperature is within this many degrees of the dew point. Not warned of without.
+	pub condensation_spread: Option<f64>,
+}
+
+impl Default for FrostConfig {
+	fn default() -> FrostConfig {
+		return FrostConfig {
+			enabled: true,
+			temperature: 0.0,
+			hours: 6.0,
+			window_minutes: 120,
+			condensation_spread: None,
+		};
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+	Frost,
+	Condensation,
+}
+
+impl Warning {
+	pub fn text(self) -> Text {
+		return match self {
+			Warning::Frost => Text::FrostRisk,
+			Warning::Condensation => Text::CondensationRisk,
+		};
+	}
+}
+
+/**
+ * The warning for an outdoor tag, frost before condensation. The frost is warned of as soon as the evening's fall
+ * heads for the threshold within the hours, so that the plants can be covered before the night.
+*/
+pub fn check(tag: &Tag, history: &History, config: &FrostConfig) -> Option<Warning> {
+	let temperature = tag.temperature().filter(|_| config.enabled)?;
+	let points: Vec<_> = history.samples(&tag.id).filter_map(|sample| Some((sample.datetime, sample.temperature?))).collect();
+	let falling = trend::slope_per_hour(&points, Duration::minutes(config.window_minutes)).filter(|slope| *slope < 0.0);
+	if temperature <= config.temperature || falling.is_some_and(|slope| temperature + slope * config.hours <= config.temperature) {
+		return Some(Warning::Frost);
+	}
+	
+	let (Some(spread), Some(humidity)) = (config.condensation_spread, tag.humidity()) else {
+		return None;
+	};
+	return (temperature - metrics::dew_point(temperature, humidity) <= spread).then_some(Warning::Condensation);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::{DateTime, Utc};
+	use crate::model::Metric;
+	
+	fn tag(datetime: DateTime<Utc>, temperature: f64, humidity: f64) -> Tag {
+		return Tag {
+			datetime: datetime.to_rfc3339(),
+			temperature: Some(Metric::from_current(temperature)),
+			humidity: Some(Metric::from_current(humidity)),
+			..Tag::test("1", "Garden")
+		};
+	}
+	
+	#[test]
+	fn frost_ahead_and_condensation() {
+		let start = "2024-05-01T17:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let config = FrostConfig::default();
+		let mut history = History::new(Duration::days(7));
+		// Falling a degree an hour from 8 °C: 2 °C in six hours.
+		for minutes in (0..=120).step_by(10) {
+			history.add(&vec![tag(start + Duration::minutes(minutes), 8.0 - minutes as f64 / 60.0, 60.0)]);
+		}
+		let now = tag(start + Duration::hours(2), 6.0, 60.0);
+		assert_eq!(check(&now, &history, &config), None);
+		assert_eq!(check(&now, &history, &FrostConfig { temperature: 1.0, ..config.clone() }), Some(Warning::Frost));
+		assert_eq!(check(&tag(start, -0.5, 60.0), &History::new(Duration::days(7)), &config), Some(Warning::Frost));
+		
+		// 6 °C at 90% has the dew point 1.6 degrees below.
+		let humid = tag(start + Duration::hours(2), 6.0, 90.0);
+		assert_eq!(check(&humid, &history, &FrostConfig { condensation_spread: Some(2.0), ..config.clone() }), Some(Warning::Condensation));
+		assert_eq!(check(&humid, &history, &FrostConfig { enabled: false, ..config }), None);
+	}
+}
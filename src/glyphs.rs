@@ -0,0 +1,112 @@
+use std::env;
+use serde::Deserialize;
+
+/**
+ * The kinds of glyphs the terminal can show. Each falls back to ASCII on its own, so that e.g. the Linux console,
+ * which has the block and box drawing characters but not the small triangles, keeps the big digits and the frames.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+	// Trend triangles, barometer arrows, the target check mark and the group header triangles.
+	pub arrows: bool,
+	// Block characters of the big digits and the battery chart.
+	pub blocks: bool,
+	// Box drawing lines of the floors layout and the battery chart axes.
+	pub boxes: bool,
+}
+
+impl Glyphs {
+	pub const UNICODE: Glyphs = Glyphs { arrows: true, blocks: true, boxes: true };
+	pub const ASCII: Glyphs = Glyphs { arrows: false, blocks: false, boxes: false };
+	
+	/**
+	 * What the locale and the terminal of the ENVs support.
+	*/
+	pub fn detect() -> Glyphs {
+		let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+			.filter_map(|name| env::var(name).ok())
+			.find(|value| !value.is_empty());
+		return Glyphs::of(locale.as_deref(), &env::var("TERM").unwrap_or_default());
+	}
+	
+	/**
+	 * What a terminal with the locale and TERM supports. Without a locale, e.g. on Windows, UTF-8 is assumed there
+	 * and ASCII elsewhere, where it means the C locale.
+	*/
+	pub fn of(locale: Option<&str>, term: &str) -> Glyphs {
+		let locale = locale.unwrap_or(if cfg!(windows) { "UTF-8" } else { "C" }).to_lowercase();
+		if !locale.contains("utf-8") && !locale.contains("utf8") {
+			return Glyphs::ASCII;
+		}
+		return match term {
+			// The console font has the blocks and the lines of code page 437, but not the other symbols.
+			"linux" => Glyphs { arrows: false, ..Glyphs::UNICODE },
+			"dumb" | "vt52" | "vt100" | "vt102" => Glyphs::ASCII,
+			_ => Glyphs::UNICODE,
+		};
+	}
+	
+	/**
+	 * One of the glyphs, or its ASCII stand-in if the terminal can't show it.
+	*/
+	pub fn arrow(&self, glyph: &'static str, ascii: &'static str) -> &'static str {
+		return if self.arrows { glyph } else { ascii };
+	}
+	
+	pub fn line(&self, glyph: &'static str, ascii: &'static str) -> &'static str {
+		return if self.boxes { glyph } else { ascii };
+	}
+}
+
+/**
+ * Override of the detected glyphs per kind, e.g. arrows = false for a font without them. Unset kinds are detected.
+*/
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct GlyphsConfig {
+	pub arrows: Option<bool>,
+	pub blocks: Option<bool>,
+	pub boxes: Option<bool>,
+}
+
+impl GlyphsConfig {
+	/**
+	 * The overrides, with the unset kinds from the detected ones.
+	*/
+	pub fn or(self, detected: Glyphs) -> GlyphsConfig {
+		return GlyphsConfig {
+			arrows: self.arrows.or(Some(detected.arrows)),
+			blocks: self.blocks.or(Some(detected.blocks)),
+			boxes: self.boxes.or(Some(detected.boxes)),
+		};
+	}
+	
+	/**
+	 * The glyphs to use, Unicode for the kinds that weren't set or detected.
+	*/
+	pub fn glyphs(&self) -> Glyphs {
+		return Glyphs {
+			arrows: self.arrows.unwrap_or(true),
+			blocks: self.blocks.unwrap_or(true),
+			boxes: self.boxes.unwrap_or(true),
+		};
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn glyphs_per_terminal() {
+		assert_eq!(Glyphs::of(Some("fi_FI.UTF-8"), "xterm-256color"), Glyphs::UNICODE);
+		assert_eq!(Glyphs::of(Some("en_US.utf8"), "linux"), Glyphs { arrows: false, blocks: true, boxes: true });
+		assert_eq!(Glyphs::of(Some("en_US.UTF-8"), "vt100"), Glyphs::ASCII);
+		assert_eq!(Glyphs::of(Some("fi_FI.ISO-8859-15"), "xterm"), Glyphs::ASCII);
+		assert_eq!(Glyphs::of(Some("C"), "xterm"), Glyphs::ASCII);
+		
+		let config = GlyphsConfig { boxes: Some(true), ..GlyphsConfig::default() };
+		assert_eq!(config.or(Glyphs::ASCII).glyphs(), Glyphs { arrows: false, blocks: false, boxes: true });
+		assert_eq!(GlyphsConfig::default().glyphs(), Glyphs::UNICODE);
+	}
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::config::Config;
+use crate::history::History;
+use crate::model::{ApiResponse, Tag};
+
+/**
+ * The other tag of the tag's pair in the config, e.g. the outside of a wall for the inside, if it's in the data.
+*/
+pub fn pair<'a>(tag: &Tag, data: &'a ApiResponse, config: &Config) -> Option<&'a Tag> {
+	let key = config.tag(tag)?.pair.as_ref()?;
+	return data.iter().find(|other| other.id != tag.id && (other.id == *key || other.name == *key));
+}
+
+/**
+ * The temperature of the tag minus the pair's over the window up to now, split into the columns. Each column is the
+ * difference of the two tags' averages within it, so that tags measuring at different times still pair up, and None
+ * where either has no samples.
+*/
+pub fn differences(history: &History, tag_id: &str, pair_id: &str, now: DateTime<Utc>, window: Duration, columns: usize) -> Vec<Option<f64>> {
+	if columns == 0 {
+		return Vec::new();
+	}
+	let start = now - window;
+	let averages = |id: &str| {
+		let mut sums = vec![(0.0, 0); columns];
+		for sample in history.samples(id).filter(|sample| sample.datetime > start && sample.datetime <= now) {
+			let Some(temperature) = sample.temperature else {
+				continue;
+			};
+			let elapsed = (sample.datetime - start).num_milliseconds() as f64 / window.num_milliseconds() as f64;
+			let column = ((elapsed * columns as f64).ceil() as usize).saturating_sub(1).min(columns - 1);
+			sums[column].0 += temperature;
+			sums[column].1 += 1;
+		}
+		return sums.into_iter().map(|(sum, count)| (count > 0).then(|| sum / count as f64)).collect::<Vec<_>>();
+	};
+	return averages(tag_id).into_iter().zip(averages(pair_id))
+		.map(|(tag, pair)| Some(tag? - pair?))
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::TagConfig;
+	use crate::model::Metric;
+	
+	fn tag(id: &str, datetime: &str, temperature: f64) -> Tag {
+		return Tag {
+			datetime: datetime.into(),
+			temperature: Some(Metric::from_current(temperature)),
+			..Tag::test(id, id)
+		};
+	}
+	
+	#[test]
+	fn differences_of_the_pair() {
+		let mut config = Config::default();
+		config.tags.insert("Inside".into(), TagConfig { pair: Some("Outside".into()), ..TagConfig::default() });
+		let data = vec![tag("Inside", "2024-01-10T12:00:00Z", 21.0), tag("Outside", "2024-01-10T12:00:00Z", -5.0)];
+		assert_eq!(pair(&data[0], &data, &config).map(|tag| tag.id.as_str()), Some("Outside"));
+		assert!(pair(&data[1], &data, &config).is_none());
+		
+		let mut history = History::new(Duration::days(7));
+		history.add(&vec![tag("Inside", "2024-01-10T10:10:00Z", 20.0), tag("Outside", "2024-01-10T10:20:00Z", -4.0)]);
+		history.add(&vec![tag("Inside", "2024-01-10T10:40:00Z", 22.0)]);
+		history.add(&vec![tag("Inside", "2024-01-10T11:30:00Z", 21.0)]);
+		history.add(&vec![tag("Inside", "2024-01-10T12:00:00Z", 21.5), tag("Outside", "2024-01-10T11:50:00Z", -5.0)]);
+		let now = "2024-01-10T12:00:00Z".parse().unwrap();
+		// Nothing in the first hour, the outside's single sample against the inside's average in the others.
+		assert_eq!(differences(&history, "Inside", "Outside", now, Duration::hours(3), 3), [None, Some(25.0), Some(26.25)]);
+		assert_eq!(differences(&history, "Inside", "Outside", now, Duration::hours(2), 2), [Some(25.0), Some(26.25)]);
+	}
+}
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use crate::model::Tag;
+
+/**
+ * Named section of tags (IDs or names), e.g. "Indoor" or "Fridges", and the tags the source places in the location.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct GroupConfig {
+	pub name: String,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	#[serde(default)]
+	pub location: Option<String>,
+}
+
+impl GroupConfig {
+	fn contains(&self, tag: &Tag) -> bool {
+		return self.tags.iter().any(|key| *key == tag.id || *key == tag.name)
+			|| self.location.as_ref().is_some_and(|location| tag.metadata.location.as_ref() == Some(location));
+	}
+}
+
+/**
+ * The tags of a group in the data order. None is the section of the tags in no group.
+*/
+pub struct Group<'a> {
+	pub name: Option<&'a str>,
+	pub tags: Vec<&'a Tag>,
+}
+
+impl Group<'_> {
+	pub fn average_temperature(&self) -> Option<f64> {
+		let temperatures: Vec<f64> = self.tags.iter().filter_map(|tag| tag.temperature()).collect();
+		if temperatures.is_empty() {
+			return None;
+		}
+		return Some(temperatures.iter().sum::<f64>() / temperatures.len() as f64);
+	}
+}
+
+/**
+ * Split the tags into the configured groups, in the config order. A tag listed in several groups goes to the first.
+ * Tags in no group come last in a section without a name. Without groups all tags are in that section.
+*/
+pub fn split<'a>(data: impl Iterator<Item = &'a Tag>, groups: &'a [GroupConfig]) -> Vec<Group<'a>> {
+	let mut sections: Vec<Group> = groups.iter().map(|group| Group { name: Some(&group.name), tags: Vec::new() }).collect();
+	let mut ungrouped = Group { name: None, tags: Vec::new() };
+	
+	for tag in data {
+		match groups.iter().position(|group| group.contains(tag)) {
+			Some(index) => sections[index].tags.push(tag),
+			None => ungrouped.tags.push(tag),
+		}
+	}
+	
+	sections.retain(|group| !group.tags.is_empty());
+	if !ungrouped.tags.is_empty() {
+		sections.push(ungrouped);
+	}
+	return sections;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::Metric;
+	
+	fn tag(id: &str, temperature: f64) -> Tag {
+		return Tag {
+			temperature: Some(Metric::from_current(temperature)),
+			humidity: Some(Metric::from_current(40.0)),
+			..Tag::test(id, id)
+		};
+	}
+	
+	#[test]
+	fn tags_split_in_config_order() {
+		let data = [tag("a", 20.0), tag("b", -18.0), tag("c", 22.0), tag("d", 4.0)];
+		let groups = vec![
+			GroupConfig { name: "Fridges".into(), tags: vec!["b".into(), "d".into()], location: None },
+			GroupConfig { name: "Empty".into(), tags: vec!["x".into()], location: None },
+			GroupConfig { name: "Indoor".into(), tags: vec!["a".into(), "b".into()], location: None },
+		];
+		
+		let sections = split(data.iter(), &groups);
+		let names: Vec<(Option<&str>, Vec<&str>)> = sections.iter()
+			.map(|group| (group.name, group.tags.iter().map(|tag| tag.id.as_str()).collect()))
+			.collect();
+		assert_eq!(names, vec![(Some("Fridges"), vec!["b", "d"]), (Some("Indoor"), vec!["a"]), (None, vec!["c"])]);
+		assert_eq!(sections[0].average_temperature(), Some(-7.0));
+	}
+	
+	#[test]
+	fn tags_grouped_by_location() {
+		let mut data = [tag("a", 20.0), tag("b", 5.0), tag("c", 22.0)];
+		data[1].metadata.location = Some("Cellar".into());
+		let groups = vec![GroupConfig { name: "Downstairs".into(), tags: vec!["c".into()], location: Some("Cellar".into()) }];
+		
+		let sections = split(data.iter(), &groups);
+		let ids: Vec<&str> = sections[0].tags.iter().map(|tag| tag.id.as_str()).collect();
+		assert_eq!(ids, vec!["b", "c"]);
+	}
+}
@@ -0,0 +1,283 @@
+use std::collections::{HashMap, VecDeque};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use crate::model::{ApiResponse, Metric};
+use crate::store::Reading;
+
+/**
+ * In-memory history of recent readings per tag. Seeded from the store on startup when one is used, which keeps all of
+ * the readings at their full resolution for the reports and exports.
+*/
+pub struct History {
+	tags: HashMap<String, VecDeque<Sample>>,
+	max_age: Duration,
+	// Samples kept per tag at most, 0 for no limit. When full, the older half is thinned out so that the memory stays
+	// bounded but the whole max age is still covered, coarser the older it is.
+	capacity: usize,
+}
+
+/**
+ * The lowest or the highest value of a metric, and when it was measured if that's known. The min/max of the source
+ * come without the time.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extreme {
+	pub value: f64,
+	pub datetime: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+	pub datetime: DateTime<Utc>,
+	pub temperature: Option<f64>,
+	pub humidity: Option<f64>,
+	pub pressure: Option<f64>,
+	pub battery_voltage: Option<f64>,
+}
+
+impl History {
+	pub fn new(max_age: Duration) -> History {
+		return History {
+			tags: HashMap::new(),
+			max_age,
+			capacity: 0,
+		};
+	}
+	
+	/**
+	 * Keep at most this many samples per tag from now on, 0 for no limit.
+	*/
+	pub fn set_capacity(&mut self, capacity: usize) {
+		self.capacity = capacity;
+		for samples in self.tags.values_mut() {
+			compact(samples, capacity);
+		}
+	}
+	
+	/**
+	 * Add stored readings, e.g. from the store on startup.
+	*/
+	pub fn seed(&mut self, readings: &[Reading]) {
+		for reading in readings {
+			self.push(&reading.tag_id, Sample {
+				datetime: reading.datetime,
+				temperature: Some(reading.temperature),
+				humidity: Some(reading.humidity),
+				pressure: reading.pressure,
+				battery_voltage: reading.battery_voltage,
+			});
+		}
+	}
+	
+	/**
+	 * Add freshly fetched data.
+	*/
+	pub fn add(&mut self, data: &ApiResponse) {
+		for tag in data {
+			let Some(datetime) = tag.measured_at() else {
+				continue;
+			};
+			
+			self.push(&tag.id, Sample {
+				datetime,
+				temperature: tag.temperature(),
+				humidity: tag.humidity(),
+				pressure: tag.pressure(),
+				battery_voltage: tag.battery_voltage,
+			});
+		}
+	}
+	
+	fn push(&mut self, tag_id: &str, sample: Sample) {
+		let samples = self.tags.entry(tag_id.to_string()).or_default();
+		
+		// Skip the same measurement fetched again.
+		if samples.back().is_some_and(|last| last.datetime >= sample.datetime) {
+			return;
+		}
+		// Relative to the newest sample rather than the clock, so that replayed and old data is kept as well.
+		let oldest = sample.datetime - self.max_age;
+		samples.push_back(sample);
+		
+		while samples.front().is_some_and(|first| first.datetime < oldest) {
+			samples.pop_front();
+		}
+		compact(samples, self.capacity);
+	}
+	
+	/**
+	 * Add the samples of a tag, e.g. from the headless instance a TUI attached to.
+	*/
+	pub fn extend(&mut self, tag_id: &str, samples: Vec<Sample>) {
+		for sample in samples {
+			self.push(tag_id, sample);
+		}
+	}
+	
+	pub fn samples(&self, tag_id: &str) -> impl Iterator<Item = &Sample> {
+		return self.tags.get(tag_id).into_iter().flatten();
+	}
+	
+	pub fn tag_ids(&self) -> impl Iterator<Item = &String> {
+		return self.tags.keys();
+	}
+	
+	/**
+	 * Min and max temperature since the given time.
+	*/
+	pub fn temperature_range(&self, tag_id: &str, since: DateTime<Utc>) -> Option<(f64, f64)> {
+		return self.range(tag_id, since, |sample| sample.temperature);
+	}
+	
+	/**
+	 * Min and max of a value of the samples since the given time.
+	*/
+	pub fn range(&self, tag_id: &str, since: DateTime<Utc>, value: impl Fn(&Sample) -> Option<f64>) -> Option<(f64, f64)> {
+		return self.extremes(tag_id, since, value).map(|(min, max)| (min.value, max.value));
+	}
+	
+	/**
+	 * Min and max of a value of the samples since the given time with when they were measured, the first time for a
+	 * value measured more than once.
+	*/
+	pub fn extremes(&self, tag_id: &str, since: DateTime<Utc>, value: impl Fn(&Sample) -> Option<f64>) -> Option<(Extreme, Extreme)> {
+		let mut extremes: Option<(Extreme, Extreme)> = None;
+		for sample in self.samples(tag_id).filter(|sample| sample.datetime >= since) {
+			let Some(value) = value(sample) else {
+				continue;
+			};
+			let extreme = Extreme { value, datetime: Some(sample.datetime) };
+			extremes = match extremes {
+				None => Some((extreme, extreme)),
+				Some((min, max)) => Some((
+					if value < min.value { extreme } else { min },
+					if value > max.value { extreme } else { max },
+				)),
+			};
+		}
+		return extremes;
+	}
+	
+	/**
+	 * Fill in the min/max values the source didn't provide from the history.
+	*/
+	pub fn fill_min_max(&self, data: &mut ApiResponse) {
+		for tag in data.iter_mut() {
+			if let Some(temperature) = &mut tag.temperature {
+				let temperatures: Vec<f64> = self.samples(&tag.id).filter_map(|sample| sample.temperature).collect();
+				fill_metric(temperature, &temperatures);
+			}
+			if let Some(humidity) = &mut tag.humidity {
+				let humidities: Vec<f64> = self.samples(&tag.id).filter_map(|sample| sample.humidity).collect();
+				fill_metric(humidity, &humidities);
+			}
+			if let Some(pressure) = &mut tag.pressure {
+				let pressures: Vec<f64> = self.samples(&tag.id).filter_map(|sample| sample.pressure).collect();
+				fill_metric(pressure, &pressures);
+			}
+		}
+	}
+}
+
+// Samples of the older half thinned out at a time. At most six of them hold an extreme, so at least two are dropped.
+const BUCKET: usize = 8;
+
+/**
+ * Thin out the older half of the samples until there are at most the capacity of them. Of each bucket only the samples
+ * holding the lowest or the highest temperature, humidity or pressure are kept, as they were, so that the min/max,
+ * when they were measured and the changes between the samples stay true instead of being averaged away.
+*/
+fn compact(samples: &mut VecDeque<Sample>, capacity: usize) {
+	if capacity == 0 {
+		return;
+	}
+	// At least a full bucket in the older half is needed to make room.
+	let capacity = capacity.max(2 * BUCKET);
+	while samples.len() > capacity {
+		let older = samples.len() / 2 / BUCKET * BUCKET;
+		let kept: Vec<Sample> = samples.drain(..older).collect::<Vec<_>>().chunks(BUCKET).flat_map(extremes_of).collect();
+		for sample in kept.into_iter().rev() {
+			samples.push_front(sample);
+		}
+	}
+}
+
+/**
+ * The samples of the bucket holding its min or max of a metric, the first of the equal ones, in order.
+*/
+fn extremes_of(bucket: &[Sample]) -> Vec<Sample> {
+	let metrics: [fn(&Sample) -> Option<f64>; 3] = [|sample| sample.temperature, |sample| sample.humidity, |sample| sample.pressure];
+	let mut kept = vec![false; bucket.len()];
+	for value in metrics {
+		let values: Vec<(usize, f64)> = bucket.iter().enumerate().filter_map(|(index, sample)| Some((index, value(sample)?))).collect();
+		let min = values.iter().fold(None, |min: Option<(usize, f64)>, &(index, value)| match min {
+			Some((_, lowest)) if lowest <= value => min,
+			_ => Some((index, value)),
+		});
+		let max = values.iter().fold(None, |max: Option<(usize, f64)>, &(index, value)| match max {
+			Some((_, highest)) if highest >= value => max,
+			_ => Some((index, value)),
+		});
+		for (index, _) in min.into_iter().chain(max) {
+			kept[index] = true;
+		}
+	}
+	return bucket.iter().zip(kept).filter(|(_, kept)| *kept).map(|(sample, _)| sample.clone()).collect();
+}
+
+fn fill_metric(metric: &mut Metric, values: &[f64]) {
+	let values = values.iter().copied().chain(std::iter::once(metric.current));
+	
+	if metric.min.is_none() {
+		metric.min = values.clone().reduce(f64::min);
+	}
+	if metric.max.is_none() {
+		metric.max = values.reduce(f64::max);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::store::Reading;
+	
+	#[test]
+	fn compacted_to_the_capacity() {
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let temperature = |minute: i64| match minute {
+			3 => -18.0,
+			5 => -10.0,
+			7 => 30.0,
+			_ => 20.0 + (minute % 3) as f64,
+		};
+		let readings: Vec<Reading> = (0..40).map(|minute| Reading {
+			tag_id: "1".into(),
+			tag_name: String::new(),
+			datetime: start + Duration::minutes(minute),
+			temperature: temperature(minute),
+			humidity: 50.0 + (minute % 4) as f64,
+			pressure: None,
+			battery_voltage: None,
+		}).collect();
+		let mut history = History::new(Duration::days(7));
+		history.set_capacity(16);
+		history.seed(&readings);
+		
+		// The older half was thinned out, the samples kept are as they were measured and the newest are all there.
+		let minutes: Vec<i64> = history.samples("1").map(|sample| (sample.datetime - start).num_minutes()).collect();
+		assert!(minutes.len() <= 16);
+		assert_eq!(minutes[minutes.len() - 8..], [32, 33, 34, 35, 36, 37, 38, 39]);
+		assert!(history.samples("1").all(|sample| sample.temperature == Some(temperature((sample.datetime - start).num_minutes()))));
+		
+		// The extremes survive with when they were measured.
+		let (min, max) = history.extremes("1", start, |sample| sample.temperature).unwrap();
+		assert_eq!((min.value, min.datetime), (-18.0, Some(start + Duration::minutes(3))));
+		assert_eq!((max.value, max.datetime), (30.0, Some(start + Duration::minutes(7))));
+		assert_eq!(history.range("1", start, |sample| sample.humidity), Some((50.0, 53.0)));
+		
+		history.set_capacity(0);
+		let count = minutes.len();
+		history.seed(&[Reading { datetime: start + Duration::minutes(40), ..readings[39].clone() }]);
+		assert_eq!(history.samples("1").count(), count + 1);
+	}
+}
@@ -0,0 +1,61 @@
+use std::io;
+use std::process::Command;
+use std::thread;
+use serde::Deserialize;
+use crate::alerts::Severity;
+
+/**
+ * Shell commands run when the overall alert state changes, e.g. to turn a kiosk display on for critical alerts.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+	pub critical: Option<String>,
+	pub warning: Option<String>,
+	// No active alerts.
+	pub ok: Option<String>,
+}
+
+/**
+ * Runs the hook of the overall alert state whenever it changes, and once at startup.
+*/
+#[derive(Debug, Default)]
+pub struct Hooks {
+	state: Option<Option<Severity>>,
+}
+
+impl Hooks {
+	pub fn new() -> Hooks {
+		return Hooks::default();
+	}
+	
+	/**
+	 * Returns true if a hook was run.
+	*/
+	pub fn update(&mut self, severity: Option<Severity>, config: &HooksConfig) -> io::Result<bool> {
+		if self.state == Some(severity) {
+			return Ok(false);
+		}
+		self.state = Some(severity);
+		
+		let command = match severity {
+			Some(Severity::Critical) => &config.critical,
+			Some(Severity::Warning) => &config.warning,
+			Some(Severity::Info) | None => &config.ok,
+		};
+		let Some(command) = command else {
+			return Ok(false);
+		};
+		
+		let mut child = Command::new("sh")
+			.arg("-c")
+			.arg(command)
+			.env("RUUVI_ALERT_STATE", severity.map_or("ok", |severity| severity.name()))
+			.spawn()?;
+		
+		// Reap the process without blocking the UI.
+		thread::spawn(move || child.wait());
+		
+		return Ok(true);
+	}
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use reqwest::blocking::Client;
+use reqwest::{Identity, Proxy};
+use serde::Deserialize;
+
+/**
+ * Settings of the HTTP client shared by the API fetches, the wind fetches and the webhooks.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+	// Time to connect to the server, and for the whole request including reading the response. 0 waits forever.
+	pub connect_timeout_seconds: u64,
+	pub timeout_seconds: u64,
+	// How long idle connections are kept open for the next request. 0 opens a new connection every time.
+	pub keep_alive_seconds: u64,
+	// Proxy for all requests, e.g. http://proxy.local:3128 or socks5://localhost:1080, or "direct" for none. Without
+	// it the HTTP_PROXY, HTTPS_PROXY and NO_PROXY ENVs are used.
+	pub proxy: Option<String>,
+	// Client certificate for servers that require one (mTLS), e.g. a reverse proxy in front of the gateway: a PEM
+	// certificate with its PKCS#8 PEM key, or a PKCS#12 bundle without the key and with its password, if any.
+	pub client_cert: Option<PathBuf>,
+	pub client_key: Option<PathBuf>,
+	pub client_cert_password: Option<String>,
+}
+
+// Proxy value for no proxy, not even from the ENVs.
+pub const DIRECT: &str = "direct";
+
+impl Default for HttpConfig {
+	fn default() -> HttpConfig {
+		return HttpConfig {
+			connect_timeout_seconds: 5,
+			timeout_seconds: 15,
+			keep_alive_seconds: 90,
+			proxy: None,
+			client_cert: None,
+			client_key: None,
+			client_cert_password: None,
+		};
+	}
+}
+
+/**
+ * Build the client once. It keeps a pool of open connections, so it should be cloned (cheap) rather than rebuilt.
+*/
+pub fn client(config: &HttpConfig) -> Result<Client, String> {
+	return client_via(config, config.proxy.as_deref());
+}
+
+/**
+ * Client through the proxy instead of the configured one, for a source with its own proxy.
+*/
+pub fn client_via(config: &HttpConfig, proxy: Option<&str>) -> Result<Client, String> {
+	let seconds = |seconds: u64| (seconds > 0).then_some(Duration::from_secs(seconds));
+	
+	let mut builder = Client::builder()
+		.connect_timeout(seconds(config.connect_timeout_seconds))
+		.timeout(seconds(config.timeout_seconds))
+		.pool_idle_timeout(seconds(config.keep_alive_seconds));
+	if config.keep_alive_seconds == 0 {
+		builder = builder.pool_max_idle_per_host(0);
+	}
+	match proxy {
+		Some(DIRECT) => builder = builder.no_proxy(),
+		Some(proxy) => builder = builder.proxy(Proxy::all(proxy).map_err(|error| format!("Invalid proxy \"{}\": {}", proxy, error))?),
+		None => {},
+	}
+	if let Some(identity) = identity(config)? {
+		builder = builder.identity(identity);
+	}
+	
+	return builder.build().map_err(|error| format!("Could not create the HTTP client: {}", error));
+}
+
+/**
+ * The client certificate and key, read on startup so that unreadable files stop the client right away.
+*/
+fn identity(config: &HttpConfig) -> Result<Option<Identity>, String> {
+	let read = |path: &PathBuf, what: &str| fs::read(path)
+		.map_err(|error| format!("Could not read the client {} {}: {}", what, path.display(), error));
+	let invalid = |error: reqwest::Error| format!("Invalid client certificate: {}", error);
+	
+	return match (&config.client_cert, &config.client_key) {
+		(Some(cert), Some(key)) => {
+			let identity = Identity::from_pkcs8_pem(&read(cert, "certificate")?, &read(key, "key")?).map_err(invalid)?;
+			Ok(Some(identity))
+		},
+		(Some(cert), None) => {
+			let password = config.client_cert_password.as_deref().unwrap_or("");
+			let identity = Identity::from_pkcs12_der(&read(cert, "certificate")?, password).map_err(invalid)?;
+			Ok(Some(identity))
+		},
+		(None, Some(_)) => Err("client_key needs the client_cert it belongs to".into()),
+		(None, None) => Ok(None),
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn unreadable_client_certificate() {
+		let config = HttpConfig { client_cert: Some("/nonexistent/client.pem".into()), client_key: Some("/nonexistent/client.key".into()), ..HttpConfig::default() };
+		let error = client(&config).unwrap_err();
+		assert!(error.starts_with("Could not read the client certificate /nonexistent/client.pem:"), "{}", error);
+		
+		let config = HttpConfig { client_key: Some("client.key".into()), ..HttpConfig::default() };
+		assert!(client(&config).is_err());
+		assert!(client(&HttpConfig::default()).is_ok());
+	}
+	
+	#[test]
+	fn proxies() {
+		let config = HttpConfig::default();
+		assert!(client_via(&config, Some("socks5h://127.0.0.1:9050")).is_ok());
+		assert!(client_via(&config, Some(DIRECT)).is_ok());
+		assert!(client_via(&config, Some("")).unwrap_err().starts_with("Invalid proxy \"\""));
+	}
+}
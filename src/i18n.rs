@@ -0,0 +1,534 @@
+use std::env;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use crate::glyphs::Glyphs;
+
+/**
+ * User interface language. Decides the texts, the decimal separator and the clock.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Lang {
+	#[default]
+	En,
+	Fi,
+}
+
+/**
+ * Translated texts.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+	Updated,
+	Measured,
+	Fetched,
+	Battery,
+	Raw,
+	BatteryLow,
+	BatteryCritical,
+	FrostRisk,
+	LatestAlerts,
+	CondensationRisk,
+	Unreachable,
+	Unknown,
+	FeelsLike,
+	Wind,
+	Today,
+	Last24Hours,
+	Last7Days,
+	Min,
+	Max,
+	At,
+	GrowingDegreeDays,
+	Base,
+	WaitingForData,
+	NetworkError,
+	DataError,
+	Paused,
+	SourceApi,
+	SourceListener,
+	SourceCommand,
+	SourceWebSocket,
+	SourceProblems,
+	SourceModified,
+	SourceNotModified,
+	UpdateAvailable,
+	SnapshotSaved,
+	StoreError,
+	RecordError,
+	HookError,
+	ActionError,
+	SinkError,
+	OutputError,
+	ReloadError,
+	TerminalTooSmall,
+	Average,
+	Target,
+	Floor,
+	FloorNumber,
+	NoFloor,
+	DewPoint,
+	AbsoluteHumidity,
+	HeatIndex,
+	VaporPressureDeficit,
+	Warmest,
+	Coldest,
+	Indoors,
+	InAlert,
+	Oldest,
+	Above,
+	Below,
+	SaveThreshold,
+	RisingFast,
+	Rising,
+	Steady,
+	Falling,
+	FallingFast,
+	TrendRising,
+	TrendSteady,
+	TrendFalling,
+	ActiveAlerts,
+	NoActiveAlerts,
+	AlertLog,
+	EventLog,
+	NoEvents,
+	AlertStarted,
+	AlertCleared,
+	NetworkRestored,
+	Legend,
+	ConfigInUse,
+	NoConfigFile,
+	Temperature,
+	Humidity,
+	Missing,
+	AlertRules,
+	Pressure,
+	Degrees,
+	Kelvins,
+	Percent,
+	Hectopascals,
+}
+
+/**
+ * Words and symbols of the trends in the TUI and the status bar outputs. The words default to the language's and the
+ * symbols to arrows, either can be overridden, e.g. for a status bar font without the arrows.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TrendVocabulary {
+	pub rising: Option<String>,
+	pub steady: Option<String>,
+	pub falling: Option<String>,
+	pub rising_symbol: Option<String>,
+	pub steady_symbol: Option<String>,
+	pub falling_symbol: Option<String>,
+}
+
+impl TrendVocabulary {
+	pub fn word(&self, trend: i8, lang: Lang) -> &str {
+		let word = match trend {
+			1 => &self.rising,
+			-1 => &self.falling,
+			_ => &self.steady,
+		};
+		return word.as_deref().unwrap_or(lang.trend(trend));
+	}
+	
+	pub fn symbol(&self, trend: i8, glyphs: Glyphs) -> &str {
+		return match trend {
+			1 => self.rising_symbol.as_deref().unwrap_or(glyphs.arrow("▴", "^")),
+			-1 => self.falling_symbol.as_deref().unwrap_or(glyphs.arrow("▾", "v")),
+			_ => self.steady_symbol.as_deref().unwrap_or(glyphs.arrow("▸", ">")),
+		};
+	}
+}
+
+impl Lang {
+	/**
+	 * The language of the locale ENVs (LC_ALL, LC_MESSAGES, LANG), English if not supported.
+	*/
+	pub fn detect() -> Lang {
+		let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+			.filter_map(|name| env::var(name).ok())
+			.find(|value| !value.is_empty())
+			.unwrap_or_default();
+		
+		if locale.starts_with("fi") {
+			return Lang::Fi;
+		}
+		return Lang::En;
+	}
+	
+	pub fn text(self, text: Text) -> &'static str {
+		match self {
+			Lang::En => match text {
+				Text::Updated => "Updated",
+				Text::Measured => "Measured",
+				Text::Fetched => "Fetched",
+				Text::Battery => "Battery",
+				Text::Raw => "Raw",
+				Text::BatteryLow => "Battery low",
+				Text::BatteryCritical => "Battery critical",
+				Text::FrostRisk => "Frost risk",
+				Text::LatestAlerts => "Latest alerts",
+				Text::CondensationRisk => "Condensation risk",
+				Text::Unreachable => "Unreachable",
+				Text::Unknown => "unknown",
+				Text::FeelsLike => "Feels like",
+				Text::Wind => "wind",
+				Text::Today => "Today",
+				Text::Last24Hours => "24 h",
+				Text::Last7Days => "7 days",
+				Text::Min => "min",
+				Text::Max => "max",
+				Text::At => "at",
+				Text::GrowingDegreeDays => "Growing degree days",
+				Text::Base => "base",
+				Text::WaitingForData => "Waiting for data…",
+				Text::NetworkError => "Network error",
+				Text::DataError => "Some data could not be read",
+				Text::Paused => "PAUSED — p resumes, r refreshes",
+				Text::SourceApi => "API",
+				Text::SourceListener => "Gateway push",
+				Text::SourceCommand => "Command",
+				Text::SourceWebSocket => "WebSocket",
+				Text::SourceProblems => "Source problems",
+				Text::SourceModified => "Source: updated",
+				Text::SourceNotModified => "Source: not modified (cached)",
+				Text::UpdateAvailable => "Update available",
+				Text::SnapshotSaved => "Screen saved into",
+				Text::StoreError => "History store error",
+				Text::RecordError => "Could not save the payload",
+				Text::HookError => "Hook command failed",
+				Text::ActionError => "Action failed",
+				Text::SinkError => "Output command failed",
+				Text::OutputError => "Forwarding failed",
+				Text::ReloadError => "Could not reload the config (ruuvi-terminal-client doctor tells why)",
+				Text::TerminalTooSmall => "Terminal too small",
+				Text::Average => "avg",
+				Text::Target => "Target",
+				Text::Floor => "floor",
+				Text::FloorNumber => "Floor",
+				Text::NoFloor => "Elsewhere",
+				Text::DewPoint => "Dew point",
+				Text::AbsoluteHumidity => "Abs. humidity",
+				Text::HeatIndex => "Heat index",
+				Text::VaporPressureDeficit => "VPD",
+				Text::Warmest => "Warmest",
+				Text::Coldest => "Coldest",
+				Text::Indoors => "Indoors",
+				Text::InAlert => "in alert",
+				Text::Oldest => "Oldest data",
+				Text::Above => "above",
+				Text::Below => "below",
+				Text::SaveThreshold => "y saves into the config, n cancels",
+				Text::RisingFast => "rising fast — clearing",
+				Text::Rising => "rising — fair weather",
+				Text::Steady => "steady — no change",
+				Text::Falling => "falling — rain likely",
+				Text::FallingFast => "falling fast — storm likely",
+				Text::TrendRising => "rising",
+				Text::TrendSteady => "steady",
+				Text::TrendFalling => "falling",
+				Text::ActiveAlerts => "Active alerts",
+				Text::NoActiveAlerts => "No active alerts",
+				Text::AlertLog => "Alert log, 24 h",
+				Text::EventLog => "Event log — a closes",
+				Text::NoEvents => "Nothing has happened yet",
+				Text::Legend => "Legend — ? closes",
+				Text::ConfigInUse => "Config in use, secrets hidden — c closes",
+				Text::NoConfigFile => "No config file, the defaults with the ENVs and options",
+				Text::Temperature => "Temperature",
+				Text::Humidity => "Humidity",
+				Text::Missing => "missing",
+				Text::AlertRules => "Alert rules",
+				Text::Pressure => "Pressure",
+				Text::Degrees => "degrees",
+				Text::Kelvins => "kelvins",
+				Text::Percent => "percent",
+				Text::Hectopascals => "hectopascals",
+				Text::AlertStarted => "started",
+				Text::AlertCleared => "cleared",
+				Text::NetworkRestored => "Network restored",
+			},
+			Lang::Fi => match text {
+				Text::Updated => "Päivitetty",
+				Text::Measured => "Mitattu",
+				Text::Fetched => "Haettu",
+				Text::Battery => "Paristo",
+				Text::Raw => "Kalibroimaton",
+				Text::BatteryLow => "Paristo vähissä",
+				Text::BatteryCritical => "Paristo loppumassa",
+				Text::FrostRisk => "Hallanvaara",
+				Text::LatestAlerts => "Viimeisimmät hälytykset",
+				Text::CondensationRisk => "Tiivistymisvaara",
+				Text::Unreachable => "Ei yhteyttä",
+				Text::Unknown => "tuntematon",
+				Text::FeelsLike => "Tuntuu kuin",
+				Text::Wind => "tuuli",
+				Text::Today => "Tänään",
+				Text::Last24Hours => "24 h",
+				Text::Last7Days => "7 vrk",
+				Text::Min => "alin",
+				Text::Max => "ylin",
+				Text::At => "klo",
+				Text::GrowingDegreeDays => "Tehoisa lämpösumma",
+				Text::Base => "kynnys",
+				Text::WaitingForData => "Odotetaan tietoja…",
+				Text::NetworkError => "Verkkovirhe",
+				Text::DataError => "Osaa tiedoista ei voitu lukea",
+				Text::Paused => "TAUKO — p jatkaa, r päivittää",
+				Text::SourceApi => "API",
+				Text::SourceListener => "Gateway-lähetys",
+				Text::SourceCommand => "Komento",
+				Text::SourceWebSocket => "WebSocket",
+				Text::SourceProblems => "Tietolähteiden ongelmat",
+				Text::SourceModified => "Lähde: päivittynyt",
+				Text::SourceNotModified => "Lähde: ei muutoksia (välimuisti)",
+				Text::UpdateAvailable => "Päivitys saatavilla",
+				Text::SnapshotSaved => "Näkymä tallennettu tiedostoon",
+				Text::StoreError => "Historiatietokannan virhe",
+				Text::RecordError => "Viestin tallennus epäonnistui",
+				Text::HookError => "Komennon suoritus epäonnistui",
+				Text::ActionError => "Toiminto epäonnistui",
+				Text::SinkError => "Tulostekomento epäonnistui",
+				Text::OutputError => "Välitys epäonnistui",
+				Text::ReloadError => "Asetuksia ei voitu ladata uudelleen (ruuvi-terminal-client doctor kertoo syyn)",
+				Text::TerminalTooSmall => "Pääte on liian pieni",
+				Text::Average => "ka.",
+				Text::Target => "Tavoite",
+				Text::Floor => "kerros",
+				Text::FloorNumber => "Kerros",
+				Text::NoFloor => "Muualla",
+				Text::DewPoint => "Kastepiste",
+				Text::AbsoluteHumidity => "Abs. kosteus",
+				Text::HeatIndex => "Lämpöindeksi",
+				Text::VaporPressureDeficit => "Höyrynpainevaje",
+				Text::Warmest => "Lämpimin",
+				Text::Coldest => "Kylmin",
+				Text::Indoors => "Sisällä",
+				Text::InAlert => "hälytystilassa",
+				Text::Oldest => "Vanhin tieto",
+				Text::Above => "yli",
+				Text::Below => "alle",
+				Text::SaveThreshold => "y tallentaa asetuksiin, n peruu",
+				Text::RisingFast => "nousee nopeasti — selkenee",
+				Text::Rising => "nousee — poutaa",
+				Text::Steady => "vakaa — ei muutosta",
+				Text::Falling => "laskee — sade todennäköinen",
+				Text::FallingFast => "laskee nopeasti — myrsky todennäköinen",
+				Text::TrendRising => "nousussa",
+				Text::TrendSteady => "vakaa",
+				Text::TrendFalling => "laskussa",
+				Text::ActiveAlerts => "Aktiiviset hälytykset",
+				Text::NoActiveAlerts => "Ei aktiivisia hälytyksiä",
+				Text::AlertLog => "Hälytysloki, 24 h",
+				Text::EventLog => "Tapahtumaloki — a sulkee",
+				Text::NoEvents => "Ei tapahtumia",
+				Text::Legend => "Selitteet — ? sulkee",
+				Text::ConfigInUse => "Käytössä olevat asetukset, salaisuudet piilotettu — c sulkee",
+				Text::NoConfigFile => "Ei asetustiedostoa, oletukset ENV-muuttujilla ja valitsimilla",
+				Text::Temperature => "Lämpötila",
+				Text::Humidity => "Kosteus",
+				Text::Missing => "puuttuu",
+				Text::AlertRules => "Hälytyssäännöt",
+				Text::Pressure => "Ilmanpaine",
+				Text::Degrees => "astetta",
+				Text::Kelvins => "kelviniä",
+				Text::Percent => "prosenttia",
+				Text::Hectopascals => "hehtopascalia",
+				Text::AlertStarted => "alkoi",
+				Text::AlertCleared => "päättyi",
+				Text::NetworkRestored => "Verkkoyhteys palautui",
+			},
+		}
+	}
+	
+	/**
+	 * The shorter form of the label for narrow screens, the label itself if it has none.
+	*/
+	pub fn abbreviation(self, text: Text) -> &'static str {
+		return match (self, text) {
+			(Lang::En, Text::Updated) => "Upd.",
+			(Lang::En, Text::Battery) => "Batt.",
+			(Lang::Fi, Text::Updated) => "Päiv.",
+			(Lang::Fi, Text::Battery) => "Par.",
+			_ => self.text(text),
+		};
+	}
+	
+	/**
+	 * The number with the language's decimal separator.
+	*/
+	pub fn number(self, value: f64, decimals: usize) -> String {
+		return self.decimal_separator(format!("{:.*}", decimals, value));
+	}
+	
+	/**
+	 * Like number() but always with the sign, for temperatures.
+	*/
+	pub fn signed(self, value: f64, decimals: usize) -> String {
+		return self.decimal_separator(format!("{:+.*}", decimals, value));
+	}
+	
+	fn decimal_separator(self, number: String) -> String {
+		match self {
+			Lang::En => number,
+			Lang::Fi => number.replace('.', ","),
+		}
+	}
+	
+	/**
+	 * Word for the trend of a value: 1 rising, -1 falling, anything else steady.
+	*/
+	pub fn trend(self, trend: i8) -> &'static str {
+		return self.text(match trend {
+			1 => Text::TrendRising,
+			-1 => Text::TrendFalling,
+			_ => Text::TrendSteady,
+		});
+	}
+	
+	/**
+	 * Wall clock time, 12-hour in English and 24-hour in Finnish.
+	*/
+	pub fn clock(self, time: &NaiveDateTime) -> String {
+		match self {
+			Lang::En => time.format("%-I:%M %p").to_string(),
+			Lang::Fi => time.format("%-H.%M").to_string(),
+		}
+	}
+	
+	/**
+	 * Date and time, the date only if it isn't today.
+	*/
+	pub fn timestamp(self, time: &NaiveDateTime, today: NaiveDate) -> String {
+		if time.date() == today {
+			return self.clock(time);
+		}
+		
+		match self {
+			Lang::En => format!("{} {}", time.format("%b %-d"), self.clock(time)),
+			Lang::Fi => format!("{} {}", time.format("%-d.%-m."), self.clock(time)),
+		}
+	}
+	
+	/**
+	 * Relative time like "5 minutes ago" from the age in seconds. Negative ages are in the future, e.g. when the
+	 * clocks of the source and this machine differ. Ages of a few seconds either way are "just now".
+	*/
+	pub fn time_ago(self, seconds: i64) -> String {
+		if seconds.abs() < 5 {
+			return match self {
+				Lang::En => "just now".into(),
+				Lang::Fi => "juuri nyt".into(),
+			};
+		}
+		
+		let future = seconds < 0;
+		let seconds = seconds.abs();
+		// Count, singular, plural and, for Finnish, the genitive used in the future form.
+		let (count, singular, plural, genitive) = match self {
+			Lang::En if seconds < 60 => (seconds, "second", "seconds", ""),
+			Lang::En if seconds < 3600 => (seconds / 60, "minute", "minutes", ""),
+			Lang::En if seconds < 86400 => (seconds / 3600, "hour", "hours", ""),
+			Lang::En => (seconds / 86400, "day", "days", ""),
+			Lang::Fi if seconds < 60 => (seconds, "sekunti", "sekuntia", "sekunnin"),
+			Lang::Fi if seconds < 3600 => (seconds / 60, "minuutti", "minuuttia", "minuutin"),
+			Lang::Fi if seconds < 86400 => (seconds / 3600, "tunti", "tuntia", "tunnin"),
+			Lang::Fi => (seconds / 86400, "päivä", "päivää", "päivän"),
+		};
+		let unit = if count == 1 { singular } else { plural };
+		
+		match (self, future) {
+			(Lang::En, false) => format!("{} {} ago", count, unit),
+			(Lang::En, true) => format!("in {} {}", count, unit),
+			(Lang::Fi, false) => format!("{} {} sitten", count, unit),
+			(Lang::Fi, true) => format!("{} {} päästä", count, genitive),
+		}
+	}
+	
+	/**
+	 * Start of a period at a clock time, e.g. "since 2:05 PM".
+	*/
+	pub fn since(self, time: &NaiveDateTime) -> String {
+		match self {
+			Lang::En => format!("since {}", self.clock(time)),
+			Lang::Fi => format!("klo {} alkaen", self.clock(time)),
+		}
+	}
+	
+	/**
+	 * Number of active alerts, e.g. "2 alerts".
+	*/
+	pub fn alerts(self, count: usize) -> String {
+		match (self, count) {
+			(Lang::En, 1) => "1 alert".into(),
+			(Lang::En, _) => format!("{} alerts", count),
+			(Lang::Fi, 1) => "1 hälytys".into(),
+			(Lang::Fi, _) => format!("{} hälytystä", count),
+		}
+	}
+	
+	/**
+	 * Size needed and the current size as columns x rows.
+	*/
+	pub fn terminal_size(self, need: (i32, i32), have: (i32, i32)) -> String {
+		match self {
+			Lang::En => format!("need {}x{}, have {}x{}", need.0, need.1, have.0, have.1),
+			Lang::Fi => format!("tarvitaan {}x{}, nyt {}x{}", need.0, need.1, have.0, have.1),
+		}
+	}
+	
+	pub fn unknown(self) -> &'static str {
+		match self {
+			Lang::En => "unknown",
+			Lang::Fi => "tuntematon",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn decimal_separator() {
+		assert_eq!(Lang::En.signed(21.456, 2), "+21.46");
+		assert_eq!(Lang::Fi.signed(-3.2, 1), "-3,2");
+		assert_eq!(Lang::Fi.number(45.0, 0), "45");
+	}
+	
+	#[test]
+	fn trend_vocabulary() {
+		let vocabulary = TrendVocabulary { rising: Some("up".into()), falling_symbol: Some("v".into()), ..TrendVocabulary::default() };
+		assert_eq!(vocabulary.word(1, Lang::Fi), "up");
+		assert_eq!(vocabulary.word(-1, Lang::Fi), "laskussa");
+		assert_eq!(vocabulary.word(0, Lang::En), "steady");
+		assert_eq!(vocabulary.symbol(-1, Glyphs::UNICODE), "v");
+		assert_eq!(vocabulary.symbol(1, Glyphs::UNICODE), "▴");
+		assert_eq!(vocabulary.symbol(0, Glyphs::ASCII), ">");
+	}
+	
+	#[test]
+	fn relative_time() {
+		assert_eq!(Lang::En.time_ago(1), "just now");
+		assert_eq!(Lang::En.time_ago(-3), "just now");
+		assert_eq!(Lang::En.time_ago(45), "45 seconds ago");
+		assert_eq!(Lang::En.time_ago(150), "2 minutes ago");
+		assert_eq!(Lang::En.time_ago(-3600), "in 1 hour");
+		assert_eq!(Lang::Fi.time_ago(60), "1 minuutti sitten");
+		assert_eq!(Lang::Fi.time_ago(7200), "2 tuntia sitten");
+		assert_eq!(Lang::Fi.time_ago(3 * 86400), "3 päivää sitten");
+		assert_eq!(Lang::Fi.time_ago(-120), "2 minuutin päästä");
+	}
+	
+	#[test]
+	fn clock() {
+		let time = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(14, 5, 0).unwrap();
+		assert_eq!(Lang::En.clock(&time), "2:05 PM");
+		assert_eq!(Lang::Fi.clock(&time), "14.05");
+		assert_eq!(Lang::Fi.timestamp(&time, time.date()), "14.05");
+		assert_eq!(Lang::En.timestamp(&time, time.date().succ_opt().unwrap()), "May 1 2:05 PM");
+	}
+}
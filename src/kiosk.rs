@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+use crate::config::Config;
+use crate::model::{ApiResponse, Tag};
+
+/**
+ * Cycles through the kiosk tags, one at a time.
+*/
+pub struct Kiosk {
+	index: usize,
+	switched_at: Instant,
+}
+
+impl Default for Kiosk {
+	fn default() -> Kiosk {
+		return Kiosk::new();
+	}
+}
+
+impl Kiosk {
+	pub fn new() -> Kiosk {
+		return Kiosk {
+			index: 0,
+			switched_at: Instant::now(),
+		};
+	}
+	
+	/**
+	 * The tag to show, moving to the next one every kiosk_cycle_seconds. Without selected
+	 * kiosk tags all tags are cycled through.
+	*/
+	pub fn current<'a>(&mut self, data: &'a ApiResponse, config: &Config) -> Option<&'a Tag> {
+		let tags: Vec<&Tag> = if config.kiosk_tags.is_empty() {
+			data.iter().collect()
+		}
+		else {
+			config.kiosk_tags.iter()
+				.filter_map(|key| data.iter().find(|tag| *key == tag.id || *key == tag.name))
+				.collect()
+		};
+		if tags.is_empty() {
+			return None;
+		}
+		
+		if self.switched_at.elapsed() >= Duration::from_secs(config.kiosk_cycle_seconds.max(1)) {
+			self.index += 1;
+			self.switched_at = Instant::now();
+		}
+		
+		return Some(tags[self.index % tags.len()]);
+	}
+}
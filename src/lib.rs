@@ -0,0 +1,179 @@
+#![allow(clippy::needless_return, clippy::tabs_in_doc_comments)]
+
+/*!
+ * Ruuvi tag sources, formats and the state around them, and the terminal client built on them.
+ *
+ * [`poller::Poller`] owns the sources (an API, the Ruuvi Cloud, Gateway pushes or a simulation) and keeps the latest
+ * tags with their history, trends and alerts, all as set in a [`config::Config`], built in code or loaded from the
+ * config file and the ENV with [`config::Config::load`]. [`formatters::parse`] turns a raw payload of any supported
+ * format into [`model::Tag`]s on its own. For a GUI or a bot, depend on the crate without the default features so
+ * that no terminal library is needed:
+ *
+ * ```toml
+ * ruuvi-terminal-client = { version = "0.1", default-features = false }
+ * ```
+ *
+ * ```no_run
+ * use std::sync::Arc;
+ * use ruuvi_terminal_client::clock::SystemClock;
+ * use ruuvi_terminal_client::config::Config;
+ * use ruuvi_terminal_client::poller::Poller;
+ *
+ * let config = Config { api_url: "http://gateway.local/history".into(), ..Config::default() };
+ * let mut poller = Poller::new(config, Arc::new(SystemClock))?;
+ * poller.poll();
+ * for tag in &poller.data {
+ * 	if let Some(temperature) = tag.temperature() {
+ * 		println!("{}: {:+.1}°C", tag.label(), temperature);
+ * 	}
+ * }
+ * # Ok::<(), String>(())
+ * ```
+*/
+
+pub mod actions;
+pub mod advisories;
+pub mod alerts;
+#[cfg(unix)]
+pub mod attach;
+pub mod battery;
+pub mod calibration;
+pub mod chart;
+pub mod clock;
+pub mod compliance;
+pub mod config;
+pub mod degree_days;
+pub mod formatters;
+pub mod forward;
+pub mod frost;
+pub mod glyphs;
+pub mod gradient;
+pub mod events;
+pub mod groups;
+pub mod history;
+pub mod hooks;
+pub mod http;
+pub mod i18n;
+pub mod inspect;
+pub mod listener;
+pub mod logger;
+pub mod metrics;
+pub mod model;
+pub mod mqtt;
+pub mod notifiers;
+pub mod output;
+pub mod plugin;
+pub mod poller;
+pub mod profiles;
+pub mod query;
+pub mod ranges;
+pub mod recorder;
+pub mod reference;
+pub mod ruuvi_decoder;
+pub mod scheduler;
+pub mod schema;
+pub mod simulation;
+pub mod staleness;
+pub mod status;
+pub mod store;
+pub mod targets;
+pub mod tags;
+pub mod trend;
+pub mod units;
+pub mod update;
+pub mod weather;
+pub mod websocket;
+pub mod wind;
+
+// OTLP tracing of the poller.
+#[doc(hidden)]
+pub mod telemetry;
+
+// The terminal client, built with a backend. Public for the binary, not part of the library API.
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod accessible;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod backend;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod background;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod bigdigits;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod burnin;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod calm;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod cli;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod commands;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod daemon;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod demo;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod doctor;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod eink;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod floorplan;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod frame;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod floors;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod kiosk;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod nudge;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod pages;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod palette;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod plain;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod render;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod responsive;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod session;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod shutdown;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod summary;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod table;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod theme;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod visibility;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod wizard;
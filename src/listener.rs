@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use tiny_http::{Header, Method, Request, Response, Server, SslConfig};
+use crate::config::Config;
+use crate::formatters::{self, FormatError};
+use crate::logger::Logger;
+use crate::model::ApiResponse;
+use crate::recorder::Recorder;
+
+/**
+ * HTTP listener for Ruuvi Gateway pushes. The gateway is configured to send the data to a
+ * custom HTTP server, optionally with a bearer token.
+*/
+pub struct Listener {
+	latest: Arc<Mutex<Option<ApiResponse>>>,
+	// Why the latest push was rejected, cleared by the next accepted one.
+	problem: Arc<Mutex<Option<FormatError>>>,
+	// Unblocked on drop, so that the thread ends and the port is free for the next listener.
+	server: Arc<Server>,
+}
+
+impl Listener {
+	/**
+	 * Start listening in a background thread. Serves HTTPS if a certificate and a key are configured.
+	*/
+	pub fn start(address: &str, config: &Config, recorder: Option<Recorder>) -> Result<Listener, String> {
+		let server = match (&config.listen_tls_cert, &config.listen_tls_key) {
+			(Some(cert), Some(key)) => {
+				let certificate = fs::read(cert)
+					.map_err(|error| format!("Could not read the TLS certificate {}: {}", cert.display(), error))?;
+				let private_key = fs::read(key)
+					.map_err(|error| format!("Could not read the TLS key {}: {}", key.display(), error))?;
+				Server::https(address, SslConfig { certificate, private_key })
+			},
+			(None, None) => Server::http(address),
+			_ => return Err("Both listen_tls_cert and listen_tls_key must be set for TLS".into()),
+		}.map_err(|error| format!("Could not listen on {}: {}", address, error))?;
+		let server = Arc::new(server);
+		let thread_server = Arc::clone(&server);
+		
+		let mut access_log = match &config.listen_access_log {
+			Some(path) => Some(Logger::open(Some(path))
+				.map_err(|error| format!("Could not open the access log {}: {}", path.display(), error))?),
+			None => None,
+		};
+		let mut rate_limiter = RateLimiter::new(config.listen_rate_limit);
+		let max_body = config.listen_max_body_kb * 1024;
+		let token = config.listen_token.clone();
+		let latest = Arc::new(Mutex::new(None));
+		let thread_latest = Arc::clone(&latest);
+		let problem = Arc::new(Mutex::new(None));
+		let thread_problem = Arc::clone(&problem);
+		
+		thread::spawn(move || {
+			for request in thread_server.incoming_requests() {
+				let client = request.remote_addr().map(|address| address.ip());
+				let line = format!("{} {} {}", client.map(|ip| ip.to_string()).unwrap_or("-".into()), request.method(), request.url());
+				
+				let status = match client {
+					Some(ip) if !rate_limiter.allow(ip) => {
+						let _ = request.respond(Response::from_string("Too many requests").with_status_code(429));
+						429
+					},
+					_ => handle(request, token.as_deref(), max_body, recorder.as_ref(), &thread_latest, &thread_problem),
+				};
+				
+				if let Some(access_log) = &mut access_log {
+					access_log.info(&format!("{} {}", line, status));
+				}
+			}
+		});
+		
+		return Ok(Listener { latest, problem, server });
+	}
+	
+	/**
+	 * Take the data received since the last call, if any.
+	*/
+	pub fn take(&self) -> Option<ApiResponse> {
+		return self.latest.lock().ok()?.take();
+	}
+	
+	pub fn problem(&self) -> Option<FormatError> {
+		return self.problem.lock().ok()?.clone();
+	}
+}
+
+impl Drop for Listener {
+	fn drop(&mut self) {
+		self.server.unblock();
+	}
+}
+
+/**
+ * Handle one request. Returns the response status code for the access log.
+*/
+fn handle(mut request: Request, token: Option<&str>, max_body: usize, recorder: Option<&Recorder>, latest: &Mutex<Option<ApiResponse>>, problem: &Mutex<Option<FormatError>>) -> u16 {
+	if *request.method() != Method::Post {
+		let _ = request.respond(Response::from_string("Method not allowed").with_status_code(405));
+		return 405;
+	}
+	
+	if let Some(token) = token {
+		if !is_authorized(&request, token) {
+			let header = Header::from_bytes("WWW-Authenticate", "Bearer").unwrap();
+			let _ = request.respond(Response::from_string("Unauthorized").with_status_code(401).with_header(header));
+			return 401;
+		}
+	}
+	
+	// The announced length is checked first, so that a too large body isn't read at all.
+	if request.body_length().is_some_and(|length| length > max_body) {
+		let _ = request.respond(Response::from_string("Payload too large").with_status_code(413));
+		return 413;
+	}
+	let body = match read_body(request.as_reader(), max_body) {
+		Ok(body) => body,
+		Err(status) => {
+			let message = if status == 413 { "Payload too large" } else { "Could not read body" };
+			let _ = request.respond(Response::from_string(message).with_status_code(status));
+			return status;
+		},
+	};
+	if let Some(recorder) = recorder {
+		recorder.record("listener", Utc::now(), &body);
+	}
+	
+	match formatters::parse_text(&body) {
+		Ok(parsed) => {
+			if let Ok(mut latest) = latest.lock() {
+				*latest = Some(parsed.data);
+			}
+			if let Ok(mut problem) = problem.lock() {
+				*problem = parsed.skipped;
+			}
+			let _ = request.respond(Response::from_string("OK"));
+			return 200;
+		},
+		Err(error) => {
+			let _ = request.respond(Response::from_string(&error.message).with_status_code(400));
+			if let Ok(mut problem) = problem.lock() {
+				*problem = Some(error);
+			}
+			return 400;
+		}
+	}
+}
+
+/**
+ * The body as text, at most the given number of bytes. Err with the status code if it's larger (413), e.g. chunked
+ * without a length, or can't be read (400).
+*/
+fn read_body(reader: &mut dyn Read, max_body: usize) -> Result<String, u16> {
+	let mut body = String::new();
+	reader.take(max_body as u64 + 1).read_to_string(&mut body).map_err(|_| 400u16)?;
+	if body.len() > max_body {
+		return Err(413);
+	}
+	return Ok(body);
+}
+
+/**
+ * Check the "Authorization: Bearer <token>" header.
+*/
+fn is_authorized(request: &Request, token: &str) -> bool {
+	return request.headers().iter()
+		.filter(|header| header.field.equiv("Authorization"))
+		.filter_map(|header| header.value.as_str().strip_prefix("Bearer "))
+		.any(|given| constant_time_eq(given.trim().as_bytes(), token.as_bytes()));
+}
+
+/**
+ * Compare without leaking the position of the first difference through timing.
+*/
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	return a.iter().zip(b).fold(0, |difference, (x, y)| difference | (x ^ y)) == 0;
+}
+
+/**
+ * Fixed window rate limiter: at most the given number of requests per client IP per minute.
+*/
+struct RateLimiter {
+	per_minute: u32,
+	clients: HashMap<IpAddr, (Instant, u32)>,
+}
+
+impl RateLimiter {
+	fn new(per_minute: u32) -> RateLimiter {
+		return RateLimiter {
+			per_minute,
+			clients: HashMap::new(),
+		};
+	}
+	
+	fn allow(&mut self, client: IpAddr) -> bool {
+		// 0 disables the limit.
+		if self.per_minute == 0 {
+			return true;
+		}
+		
+		let now = Instant::now();
+		let window = Duration::from_secs(60);
+		self.clients.retain(|_, (start, _)| now.duration_since(*start) < window);
+		
+		let (_, count) = self.clients.entry(client).or_insert((now, 0));
+		*count += 1;
+		return *count <= self.per_minute;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn body_is_read_up_to_the_limit() {
+		assert_eq!(read_body(&mut "{\"data\": {}}".as_bytes(), 13), Ok("{\"data\": {}}".to_string()));
+		assert_eq!(read_body(&mut "x".repeat(14).as_bytes(), 13), Err(413));
+		assert_eq!(read_body(&mut [0xff, 0xfe].as_slice(), 13), Err(400));
+	}
+}
@@ -0,0 +1,132 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+
+/**
+ * Minimal line logger for the headless mode. Writes to a file or stdout.
+*/
+pub struct Logger {
+	file: Option<File>,
+}
+
+impl Logger {
+	pub fn open(path: Option<&Path>) -> io::Result<Logger> {
+		let file = match path {
+			Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+			None => None,
+		};
+		return Ok(Logger { file });
+	}
+	
+	pub fn info(&mut self, message: &str) {
+		self.write("INFO", message);
+	}
+	
+	pub fn warn(&mut self, message: &str) {
+		self.write("WARN", message);
+	}
+	
+	fn write(&mut self, level: &str, message: &str) {
+		let line = format!("{} {} {}\n", Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true), level, message);
+		
+		// There's nowhere to report a failing log, so errors are ignored.
+		let _ = match &mut self.file {
+			Some(file) => file.write_all(line.as_bytes()),
+			None => io::stdout().write_all(line.as_bytes()),
+		};
+	}
+}
+
+/**
+ * Collapses a warning that keeps repeating, e.g. every fetch of a source that is down, so that it doesn't fill the
+ * log: the first one is written, the repeats within the window are only counted, and the count is written when the
+ * window ends as "same error repeated 57 times in 1 h". All occurrences are also counted for the daily digest.
+*/
+pub struct Repeats {
+	window: Duration,
+	// Warnings written within the window: message, when written and the repeats since.
+	open: Vec<(String, DateTime<Utc>, u32)>,
+	// Occurrences per message since the totals were last taken.
+	totals: Vec<(String, u32)>,
+}
+
+impl Repeats {
+	pub fn new(window: Duration) -> Repeats {
+		return Repeats { window, open: Vec::new(), totals: Vec::new() };
+	}
+	
+	/**
+	 * The warning happened again. Returns it if it should be written.
+	*/
+	pub fn occurred(&mut self, message: &str, now: DateTime<Utc>) -> Option<String> {
+		match self.totals.iter_mut().find(|(total_message, _)| total_message == message) {
+			Some((_, count)) => *count += 1,
+			None => self.totals.push((message.to_string(), 1)),
+		}
+		
+		let open = self.open.iter_mut().find(|(open_message, written, _)| open_message == message && now - *written < self.window);
+		if let Some((_, _, repeats)) = open {
+			*repeats += 1;
+			return None;
+		}
+		self.open.retain(|(open_message, _, _)| open_message != message);
+		self.open.push((message.to_string(), now, 0));
+		return Some(message.to_string());
+	}
+	
+	/**
+	 * Close the windows that have ended. Returns the lines to write for the warnings that repeated in them.
+	*/
+	pub fn flush(&mut self, now: DateTime<Utc>) -> Vec<String> {
+		let mut lines = Vec::new();
+		let window = self.window;
+		self.open.retain(|(message, written, repeats)| {
+			let ended = now - *written >= window;
+			if ended && *repeats > 0 {
+				lines.push(format!("{} (same error repeated {} times in {})", message, repeats, format_window(window)));
+			}
+			return !ended;
+		});
+		return lines;
+	}
+	
+	/**
+	 * The occurrences per message since the previous call, most frequent first.
+	*/
+	pub fn take_totals(&mut self) -> Vec<(String, u32)> {
+		let mut totals = std::mem::take(&mut self.totals);
+		totals.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+		return totals;
+	}
+}
+
+fn format_window(window: Duration) -> String {
+	if window.num_minutes() % 60 == 0 {
+		return format!("{} h", window.num_hours());
+	}
+	return format!("{} min", window.num_minutes());
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn repeats_are_collapsed() {
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let mut repeats = Repeats::new(Duration::hours(1));
+		assert_eq!(repeats.occurred("Network error: timeout", start).as_deref(), Some("Network error: timeout"));
+		for minute in 1..=57 {
+			assert_eq!(repeats.occurred("Network error: timeout", start + Duration::minutes(minute)), None);
+		}
+		assert_eq!(repeats.occurred("Store error", start).as_deref(), Some("Store error"));
+		assert!(repeats.flush(start + Duration::minutes(59)).is_empty());
+		
+		assert_eq!(repeats.flush(start + Duration::hours(1)), vec!["Network error: timeout (same error repeated 57 times in 1 h)"]);
+		assert_eq!(repeats.occurred("Network error: timeout", start + Duration::hours(2)).as_deref(), Some("Network error: timeout"));
+		
+		assert_eq!(repeats.take_totals(), vec![("Network error: timeout".into(), 59), ("Store error".into(), 1)]);
+		assert!(repeats.take_totals().is_empty());
+	}
+}
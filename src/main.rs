@@ -1,216 +1,444 @@
-use pancurses::{Input, Window, COLOR_PAIR, COLOR_GREEN, COLOR_WHITE, COLOR_RED, A_BOLD};
-use reqwest;
-use serde::Deserialize;
-use chrono::{DateTime, Utc};
-use std::{thread, time};
-use std::env;
-
-#[derive(Debug, Deserialize)]
-struct Metric {
-	current: f64,
-	min: f64,
-	max: f64,
-	trend: i8,
-}
+#![allow(clippy::needless_return, clippy::tabs_in_doc_comments)]
 
-#[derive(Debug, Deserialize)]
-struct Tag {
-	tag_id: u32,
-	datetime: String,
-	temperature: Metric,
-	humidity: Metric,
-	battery_low: bool,
-	unreachable: bool,
-	#[serde(default, deserialize_with = "empty_string_if_null")]
-	tag_name: String,
-}
-
-type ApiResponse = Vec<Tag>;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{thread, time};
+use clap::Parser;
+use ruuvi_terminal_client::{accessible, backend, commands, daemon, doctor, inspect, plain, render, schema, shutdown, telemetry, wizard};
+use ruuvi_terminal_client::alerts::AlertMetric;
+use ruuvi_terminal_client::backend::{Inverted, Key, Mirror, Pane, Screen, Snapshot};
+use ruuvi_terminal_client::background::Background;
+use ruuvi_terminal_client::calm::CalmFilter;
+use ruuvi_terminal_client::cli::{Args, Command};
+use ruuvi_terminal_client::clock::SystemClock;
+use ruuvi_terminal_client::config::{self, Config};
+use ruuvi_terminal_client::demo::Demo;
+use ruuvi_terminal_client::eink::Eink;
+use ruuvi_terminal_client::frame::Frame;
+use ruuvi_terminal_client::kiosk::Kiosk;
+use ruuvi_terminal_client::logger::Logger;
+use ruuvi_terminal_client::nudge::Nudge;
+use ruuvi_terminal_client::pages::PageContent;
+use ruuvi_terminal_client::palette::{Action, Outcome, Palette};
+use ruuvi_terminal_client::poller::Poller;
+use ruuvi_terminal_client::render::{Layout, View};
+use ruuvi_terminal_client::session::SessionLog;
+use ruuvi_terminal_client::theme::{Theme, ThemeName};
+use ruuvi_terminal_client::visibility::Visibility;
 
 /**
  * Wrapper for setting up the terminal.
 */
-fn setup_terminal() -> Window {
-	let window = pancurses::initscr();
-	pancurses::start_color();
-	pancurses::use_default_colors(); 	// This is needed. If not set, the background color will be forced black instead of terminal color.
-	pancurses::init_pair(1, COLOR_WHITE, -1);
-	pancurses::init_pair(2, COLOR_GREEN, -1);
-	pancurses::init_pair(3, COLOR_RED, -1);
+fn setup_terminal(config: &Config) -> Result<(Box<dyn Screen>, Theme, Background), String> {
+	// Before the backend, which would read the terminal's answer as keys.
+	let background = config.background.detect();
+	let screen = backend::open(config.backend, config.kiosk, config.mouse, config.focus_events)?;
+	let screen: Box<dyn Screen> = match &config.eink {
+		Some(eink) if eink.inverted => Box::new(Inverted::new(screen)),
+		_ => screen,
+	};
+	let screen: Box<dyn Screen> = match &config.mirror {
+		Some(path) => Box::new(Mirror::new(screen, path).inspect_err(|_| backend::restore(config.backend))?),
+		None => screen,
+	};
+	let theme = theme(config, screen.as_ref(), background);
 	
-	window.nodelay(true);
-	
-	return window;
+	return Ok((screen, theme, background));
 }
 
 /**
- * Get data from the API.
+ * The theme of the config for the background, the detected one unless the config sets it.
 */
-fn fetch_data(api_url: &str) -> Result<ApiResponse, Box<dyn std::error::Error>> {
-	let response = reqwest::blocking::get(api_url)?;
-	let mut data: ApiResponse = response.json()?;
-	return Ok(data);
+fn theme(config: &Config, screen: &dyn Screen, detected: Background) -> Theme {
+	let theme_name = if config.no_color || config.eink.is_some() { ThemeName::Monochrome } else { config.theme };
+	return Theme::named(theme_name, screen.colors()).on(config.background.or(detected), screen.colors());
 }
 
-/**
- * Helper function for Serde to convert null values to empty strings that are coming from the API.
- * This is 100% generated by ChatGPT but seems to do the trick.
- * TODO: Refactor to something more human.
- *
-*/
-fn empty_string_if_null<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-	D: serde::Deserializer<'de>,
-{
-	let opt = Option::<String>::deserialize(deserializer)?;
-	Ok(opt.unwrap_or_default())
+fn calm_filter(config: &Config) -> CalmFilter {
+	return CalmFilter::new(config.eink.as_ref().map_or(config.calm_epsilon, |eink| eink.threshold.max(config.calm_epsilon)))
+		.with_tags(&config.tags);
 }
 
 /**
- * The main render function.
+ * Main.
 */
-fn render(window: &Window, data: &ApiResponse, network_error: bool) {
-	window.clear();
-	
-	for tag in data {
-		// Title row.
-		window.attron(COLOR_PAIR(2) | A_BOLD);
-		window.addstr(if tag.tag_name.is_empty() { "Unknown tag" } else { &tag.tag_name }); // If the tag name is empty.
-		window.attroff(COLOR_PAIR(2) | A_BOLD);
-		
-		// Battery low indicator.
-		if (tag.battery_low) {
-			window.addstr(" ");
-			window.attron(COLOR_PAIR(3) | A_BOLD);
-			window.addstr("Battery low");
-			window.attroff(COLOR_PAIR(3) | A_BOLD);
-		}
-		else if (tag.unreachable) {
-			window.addstr(" ");
-			window.attron(COLOR_PAIR(3) | A_BOLD);
-			window.addstr("Unreachable");
-			window.attroff(COLOR_PAIR(3) | A_BOLD);
-		}
-		
-		window.addstr("\n");
-
-		// Temperature and humidity.
-		window.attron(COLOR_PAIR(1) | A_BOLD);
-		window.addstr(&format!("{:+.2}°C", tag.temperature.current));
-		window.attroff(COLOR_PAIR(1) | A_BOLD);
-
-		window.attron(COLOR_PAIR(2) | A_BOLD);
-		window.addstr(trend_arrow(tag.temperature.trend));
-		window.attroff(COLOR_PAIR(2) | A_BOLD);
-
-		window.attron(COLOR_PAIR(1) | A_BOLD);
-		window.addstr(&format!(" {:.2}%", tag.humidity.current));
-		window.attroff(COLOR_PAIR(1) | A_BOLD);
-
-		window.attron(COLOR_PAIR(2) | A_BOLD);
-		window.addstr(trend_arrow(tag.humidity.trend));
-		window.attroff(COLOR_PAIR(2) | A_BOLD);
-
-		window.addstr("\n");
-
-		// Temperature min/max.
-		window.addstr(&format!(
-			"{:+.2}…{:+.2}°C\n",
-			tag.temperature.min,
-			tag.temperature.max
-		));
-		
-		// Updated string.
-		window.addstr("Updated: ");
-		window.addstr(&format_time_ago(&tag.datetime));
-		
-		window.addstr("\n\n");
+fn main() {
+	// First load the config file and ENVs.
+	let mut args = Args::parse();
+	// Needs no config.
+	if let Some(name) = args.schema {
+		println!("{}", schema::json_schema(name));
+		return;
 	}
+	let mut config = match args.load() {
+		Ok(config) => config,
+		// The doctor and the check report a broken config among their other checks.
+		Err(error) if matches!(args.command, Some(Command::Doctor)) => {
+			std::process::exit(doctor::run(&Config::default(), Some(&error)));
+		},
+		Err(error) if matches!(args.command, Some(Command::Check)) => {
+			std::process::exit(doctor::check_sources(&Config::default(), Some(&error)));
+		},
+		// Set up a source on the first start rather than just failing.
+		Err(error) if error == config::NO_SOURCE && wizard::available(&args, config::config_path().as_deref()) => {
+			let Some(setup) = config::config_path().and_then(|path| wizard::run(&path)) else {
+				std::process::exit(1);
+			};
+			setup.apply(&mut args);
+			args.load().unwrap_or_else(|error| {
+				eprintln!("{}", error);
+				std::process::exit(1);
+			})
+		},
+		Err(error) => {
+			eprintln!("{}", error);
+			std::process::exit(1);
+		}
+	};
 	
-	if (network_error) {
-		window.attron(COLOR_PAIR(3) | A_BOLD);
-		window.addstr("Network error\n");
-		window.attroff(COLOR_PAIR(3) | A_BOLD);
+	if let Some(command) = &args.command {
+		std::process::exit(commands::run(command, &config));
 	}
-
-	window.refresh();
-}
-
-/**
- * Helper function for helper arrow mapping.
-*/
-fn trend_arrow(trend: i8) -> &'static str {
-	match trend {
-		1 => "▴",
-		-1 => "▾",
-		_ => "▸",
+	
+	if let Err(error) = shutdown::install(!args.plain && !args.accessible).and_then(|_| telemetry::init(&config)) {
+		eprintln!("{}", error);
+		std::process::exit(1);
 	}
-}
-
-/**
- * Get human readable time ago.
-*/
-fn format_time_ago(datetime: &str) -> String {
-	if let Ok(parsed) = datetime.parse::<DateTime<Utc>>() {
-		let now = Utc::now();
-		let seconds = (now - parsed).num_seconds();
-
-		if (seconds < 60) {
-			return format!("{} seconds ago", seconds)
-		}
-		else if seconds < 3600 {
-			return format!("{} minutes ago", seconds / 60)
-		}
-		else if seconds < 86400 {
-			return format!("{} hours ago", seconds / 3600)
-		}
-		else {
-			return format!("{} days ago", seconds / 86400)
+	
+	let mut poller = match Poller::new(config.clone(), Arc::new(SystemClock)) {
+		Ok(poller) => poller,
+		Err(error) => {
+			eprintln!("{}", error);
+			std::process::exit(1);
 		}
+	};
+	
+	if args.plain {
+		plain::run(&mut poller, args.watch);
+		telemetry::shutdown();
+		return;
 	}
-	else {
-		return "unknown".into() // &str to String.
+	if args.accessible {
+		accessible::run(&mut poller);
+		telemetry::shutdown();
+		return;
 	}
-}
-
-/**
- * Main.
-*/
-fn main() {
-	// First check the API URL ENV.
-	let api_url = env::var("API_URL")
-		.expect("Environment variable API_URL must be set");
-	let mut network_error = false;
-	let mut last_refresh = Utc::now() - chrono::Duration::minutes(1);
-	let mut data: ApiResponse = Vec::new();
 	
-	let window = setup_terminal();
-
+	if config.headless {
+		let mut logger = match Logger::open(config.log_file.as_deref()) {
+			Ok(logger) => logger,
+			Err(error) => {
+				eprintln!("Could not open the log file: {}", error);
+				std::process::exit(1);
+			}
+		};
+		daemon::run(&mut poller, &mut logger, &args);
+		telemetry::shutdown();
+		return;
+	}
+	
+	let mut calm = calm_filter(&config);
+	let mut eink = config.eink.as_ref().map(|_| Eink::default());
+	// Something else than the data changed the view, e.g. a key press.
+	let mut dirty = true;
+	// What the last frame showed, the screen is redrawn when it changes.
+	let mut drawn: Option<Frame> = None;
+	let mut drawn_at = time::Instant::now();
+	let mut visibility = Visibility::new();
+	// Id of the tag shown alone in the last frame, for the keys that act on it.
+	let mut shown_id: Option<String> = None;
+	let mut kiosk = Kiosk::new();
+	let mut demo = Demo::new(config.demo);
+	let mut view = View::new(&config);
+	// Reload the config file on the next round, asked for with the R key or SIGHUP, with this profile.
+	let mut reload = false;
+	let mut profile = args.profile.clone();
+	let mut session = match config.session_log.as_deref().map(|path| SessionLog::open(path, config.session_log_values)).transpose() {
+		Ok(session) => session,
+		Err(error) => {
+			eprintln!("{}", error);
+			std::process::exit(1);
+		}
+	};
+	let (mut screen, mut configured_theme, background) = match setup_terminal(&config) {
+		Ok(terminal) => terminal,
+		Err(error) => {
+			eprintln!("{}", error);
+			std::process::exit(1);
+		}
+	};
+	
+	// Restore the terminal before the panic message is printed, otherwise it's lost in curses mode.
+	let backend = config.backend;
+	let default_hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(move |info| {
+		backend::restore(backend);
+		default_hook(info);
+	}));
+	
 	// Main loop.
-	loop {
-		let now = Utc::now();
-		if (now - last_refresh).num_seconds() >= 60 {
-			match fetch_data(&api_url) {
-				Ok(new_data) => {
-					data = new_data;
-					last_refresh = now;
-					network_error = false;
+	while !shutdown::requested() {
+		// A SIGHUP is also sent when the terminal goes away, which ends the session instead.
+		if shutdown::reload_requested() {
+			if shutdown::hung_up() {
+				break;
+			}
+			reload = true;
+		}
+		if reload {
+			reload = false;
+			match args.load_profile(profile.as_deref()).and_then(|new| poller.reload(new.clone()).map(|_| new)) {
+				Ok(new) => {
+					// Another profile starts from its own layout and pages.
+					if new.profile != config.profile {
+						view = View::new(&new);
+					}
+					config = new;
+					configured_theme = theme(&config, screen.as_ref(), background);
+					calm = calm_filter(&config);
+					view.zone = config.zone();
+					screen.clear();
+					dirty = true;
+				},
+				Err(error) => {
+					poller.reload_error = Some(error);
+					profile = config.profile.clone();
 				},
-				Err(_) => {
-					network_error = true;
+			}
+		}
+		
+		let updated = poller.poll();
+		if poller.rings() {
+			screen.bell();
+		}
+		if let (Some(session), true) = (&mut session, updated) {
+			let errors: Vec<&str> = poller.errors().into_iter().map(|error| config.lang().text(error)).collect();
+			session.refresh(&poller.data, &errors, poller.wall.now());
+		}
+		
+		let data = calm.apply(&poller.data);
+		let page = view.page(&config);
+		let data = page.map(|page| page.filter(&data, &poller.sources)).unwrap_or(data);
+		let step = demo.current(&config);
+		if let Some(layout) = step.and_then(|step| step.layout) {
+			view.layout = layout;
+		}
+		// Without colors the demo stays monochrome.
+		let theme = match step {
+			Some(step) if !config.no_color => Theme::named(step.theme, screen.colors()).on(config.background.or(background), screen.colors()),
+			_ => configured_theme.clone(),
+		};
+		let theme = match &config.burn_in {
+			Some(burn_in) if burn_in.dims(poller.wall.now(), config.zone()) => theme.dimmed(),
+			_ => theme,
+		};
+		let alone = if config.kiosk && step.is_none() { kiosk.current(&data, &config) } else { None };
+		// An e-ink screen is redrawn only when what it shows changes, and then in full to clear the ghosting. Others
+		// also when the clock on the screen moves.
+		let redraw = match &mut eink {
+			Some(eink) => {
+				let errors = poller.errors().into_iter().map(|error| config.lang().text(error).to_string()).collect();
+				let changed = eink.changed(&data, errors) || dirty;
+				if changed {
+					screen.clear();
 				}
+				changed
+			},
+			None => {
+				let frame = Frame::new(&data, &poller, &view, screen.size(), step, alone.map(|tag| tag.id.as_str()));
+				let changed = drawn.as_ref() != Some(&frame) || dirty || updated;
+				// Unseen it's redrawn only now and then, except into the mirror file that others follow.
+				let throttled = !visibility.visible() && config.mirror.is_none()
+					&& drawn_at.elapsed() < time::Duration::from_secs(config.hidden_redraw_seconds);
+				if changed && !throttled {
+					(drawn, drawn_at) = (Some(frame), time::Instant::now());
+				}
+				changed && !throttled
+			},
+		};
+		
+		// Returns the tag shown alone in the kiosk or detail view, for the o key.
+		let draw = |screen: &mut dyn Screen| {
+			view.regions.borrow_mut().clear();
+			if let Some(step) = step.filter(|step| step.layout.is_none()) {
+				let tag = data.get(step.tag % data.len().max(1));
+				render::render_kiosk(screen, &theme, tag, &poller, &view);
+				return tag;
 			}
+			if config.kiosk {
+				render::render_kiosk(screen, &theme, alone, &poller, &view);
+				return alone;
+			}
+			if view.inspect {
+				render::render_inspect(screen, &theme, &poller, &view);
+				return None;
+			}
+			if view.legend {
+				render::render_legend(screen, &theme, &poller);
+				return None;
+			}
+			if view.event_log {
+				render::render_event_log(screen, &theme, &poller);
+				return None;
+			}
+			if let Some(tag) = data.iter().find(|tag| view.detail && view.selected.as_ref() == Some(&tag.id)) {
+				render::render_kiosk(screen, &theme, Some(tag), &poller, &view);
+				return Some(tag);
+			}
+			if page.is_some_and(|page| page.content == PageContent::Alerts) {
+				render::render_alerts(screen, &theme, &poller, &view);
+				return None;
+			}
+			let list = |screen: &mut dyn Screen| match render::resolve_layout(view.layout, screen.size(), &data, &poller) {
+				Layout::Table => render::render_table(screen, &theme, &data, &poller, &view),
+				Layout::Minimal => render::render_minimal(screen, &theme, &data, &poller, &view),
+				Layout::Floors => render::render_floors(screen, &theme, &data, &poller, &view),
+				Layout::Floorplan => render::render_floorplan(screen, &theme, &data, &poller, &view),
+				_ => render::render(screen, &theme, &data, &poller, &view),
+			};
+			// The selected tag's detail next to the tags if there's room.
+			let selected = data.iter().find(|tag| view.selected.as_ref() == Some(&tag.id));
+			if let (Some(tag), true) = (selected, config.split_width > 0 && screen.size().1 >= config.split_width) {
+				render::render_split(screen, &theme, list, tag, &poller, &view);
+				return Some(tag);
+			}
+			list(screen);
+			return None;
+		};
+		// Against burn-in drawn into a pane that moves around on the screen, as much smaller as it moves.
+		let shifted = |screen: &mut dyn Screen| {
+			let Some(burn_in) = config.burn_in.as_ref().filter(|burn_in| burn_in.shift_minutes > 0 && burn_in.shift_cells > 0) else {
+				return draw(screen);
+			};
+			let (top, left) = burn_in.offset(poller.wall.now());
+			let (height, width) = screen.size();
+			screen.erase();
+			let shown = draw(&mut Pane::new(screen, top, left, (height - burn_in.shift_cells, width - burn_in.shift_cells)));
+			screen.refresh();
+			return shown;
+		};
+		// Only renders of new data are traced, the redraws for the clock in between would be noise.
+		let shown = match (redraw, updated) {
+			(false, _) => shown_id.as_ref().and_then(|id| data.iter().find(|tag| tag.id == *id)),
+			(true, true) => telemetry::span("render", || shifted(screen.as_mut())),
+			(true, false) => shifted(screen.as_mut()),
+		};
+		shown_id = shown.map(|tag| tag.id.clone());
+		if let (true, Some(nudge)) = (redraw, &view.nudge) {
+			render::render_nudge(screen.as_mut(), &theme, &poller, nudge);
 		}
-
-		render(&window, &data, network_error);
-
-		match window.getch() {
-			Some(Input::Character('q')) => break,
-			_ => {}
+		if let (true, Some(saved)) = (redraw, &view.snapshot) {
+			render::render_snapshot_saved(screen.as_mut(), &theme, config.lang(), saved);
+		}
+		if let (true, Some(palette)) = (redraw, &view.palette) {
+			render::render_palette(screen.as_mut(), &theme, &data, &poller, palette);
+		}
+		
+		let key = screen.key();
+		// A change held back while throttled is drawn later.
+		dirty = key.is_some() || (dirty && !redraw);
+		if let (Some(session), Some(key)) = (&mut session, key) {
+			session.key(key, poller.wall.now());
+		}
+		// The open palette takes the typed keys, until a command is run or it's closed.
+		let mut palette = view.palette.clone();
+		let mut action = None;
+		let key = match (&mut palette, key) {
+			(Some(open), Some(Key::Char(typed))) => {
+				match open.key(typed, &poller.config, &data) {
+					Outcome::Typing => {},
+					Outcome::Cancel => palette = None,
+					Outcome::Run(run) => (palette, action) = (None, Some(run)),
+				}
+				None
+			},
+			_ => key,
+		};
+		// The frame drawn again into memory, without the messages over it.
+		let snapshot = (key == Some(Key::Char('S')) || action == Some(Action::Snapshot)).then(|| {
+			let mut snapshot = Snapshot::new(screen.size());
+			draw(&mut snapshot);
+			snapshot
+		});
+		view.palette = palette;
+		if key.is_some() || action.is_some() {
+			view.snapshot = snapshot.map(|snapshot| {
+				let path = config.snapshot.clone().unwrap_or_else(|| {
+					PathBuf::from(format!("ruuvi-snapshot-{}.txt", config.zone().local(poller.wall.now()).format("%Y%m%d-%H%M%S")))
+				});
+				snapshot.save(&path).map(|_| path)
+			});
+		}
+		match key {
+			Some(Key::Char('q') | Key::Interrupt) => break,
+			Some(Key::Char('a')) => view.event_log = !view.event_log,
+			Some(Key::Char('?')) => view.legend = !view.legend,
+			Some(Key::Char('c')) => view.inspect = !view.inspect,
+			Some(Key::Char('j')) if view.inspect => view.scroll_inspect(1, inspect::lines(&poller.config).len()),
+			Some(Key::Char('k')) if view.inspect => view.scroll_inspect(-1, inspect::lines(&poller.config).len()),
+			Some(Key::ScrollDown) if view.inspect => view.scroll_inspect(3, inspect::lines(&poller.config).len()),
+			Some(Key::ScrollUp) if view.inspect => view.scroll_inspect(-3, inspect::lines(&poller.config).len()),
+			Some(Key::Char('d')) => {
+				demo.toggle();
+				view.layout = config.layout;
+			},
+			Some(Key::Char('t')) => view.absolute_time = !view.absolute_time,
+			Some(Key::Char('l')) => view.layout = view.layout.next(config.floorplan.is_some()),
+			Some(Key::Char('m')) => view.cycle_min_max_window(),
+			Some(Key::Char('T')) => poller.config.toggle_metric(AlertMetric::Temperature, &config.metrics),
+			Some(Key::Char('H')) => poller.config.toggle_metric(AlertMetric::Humidity, &config.metrics),
+			Some(Key::Char('P')) => poller.config.toggle_metric(AlertMetric::Pressure, &config.metrics),
+			Some(Key::Char('M')) => view.min_max_reset = Some(poller.clock.now()),
+			Some(Key::Char('R')) => reload = true,
+			Some(Key::Char('f')) if !config.profiles.is_empty() => (profile, reload) = (config.next_profile(), true),
+			Some(Key::Char(':')) => view.palette = Some(Palette::new()),
+			Some(Key::Char('o')) => shown.into_iter().for_each(|tag| poller.open_link(tag)),
+			Some(Key::Char('p')) => poller.scheduler.toggle_pause(),
+			Some(Key::Char('r')) => poller.scheduler.refresh(),
+			Some(Key::Char('\t')) => view.next_page(&config),
+			Some(Key::Char('\n')) => view.detail = view.selected.is_some(),
+			Some(Key::Char('\x1b' | 'n')) if view.nudge.is_some() => view.nudge = None,
+			Some(Key::Char('\x1b')) => view.detail = false,
+			// The threshold of the tag shown alone or the selected one.
+			Some(Key::Char(key @ ('+' | '-'))) => {
+				let tag = shown.or_else(|| data.iter().find(|tag| view.selected.as_ref() == Some(&tag.id)));
+				if view.nudge.is_none() {
+					view.nudge = tag.and_then(|tag| Nudge::start(&poller.config.alerts, tag));
+				}
+				if let Some(nudge) = &mut view.nudge {
+					nudge.by(if key == '+' { 1 } else { -1 }, poller.config.alerts[nudge.rule].metric);
+				}
+			},
+			Some(Key::Char('y')) if view.nudge.is_some() => view.nudge = view.nudge.take().and_then(|nudge| nudge.confirm(&mut poller.config.alerts, poller.config.profile.as_deref())),
+			Some(Key::Click(y, x)) => view.click(y, x, false),
+			Some(Key::DoubleClick(y, x)) => view.click(y, x, true),
+			Some(Key::ScrollUp) => view.scroll_by(-1, data.len()),
+			Some(Key::ScrollDown) => view.scroll_by(1, data.len()),
+			Some(Key::Char(key @ '1'..='9')) if !config.pages.is_empty() => view.select_page(key as usize - '0' as usize, &config),
+			Some(Key::Char(key @ '1'..='9')) => view.toggle_group(key as usize - '0' as usize, &config),
+			// Repaint everything on the next refresh, the old screen contents can't be trusted after a resize.
+			Some(Key::Resize) => screen.clear(),
+			Some(Key::FocusGained) => visibility.focus(true),
+			Some(Key::FocusLost) => visibility.focus(false),
+			_ => {},
+		}
+		match action {
+			Some(Action::Profile(name)) => (profile, reload) = (Some(name), true),
+			Some(Action::Page(number)) => view.select_page(number, &config),
+			Some(Action::Layout(layout)) => view.layout = layout,
+			Some(Action::Interval(seconds)) => poller.scheduler.set_interval(chrono::Duration::seconds(seconds), poller.wall.now()),
+			Some(Action::Tag(id)) => (view.selected, view.detail) = (Some(id), true),
+			Some(Action::Toggle(metric)) => poller.config.toggle_metric(metric, &config.metrics),
+			Some(Action::Snapshot) | None => {},
+		}
+		if let Some(session) = &mut session {
+			session.view(&view, screen.size(), poller.wall.now());
 		}
 		
-		thread::sleep(time::Duration::from_secs(1));
+		// Faster while typing into the palette, the keys are read one per round.
+		thread::sleep(time::Duration::from_millis(if view.palette.is_some() { 50 } else { 1000 }));
 	}
-
-	pancurses::endwin();
-}
\ No newline at end of file
+	
+	backend::restore(config.backend);
+	telemetry::shutdown();
+}
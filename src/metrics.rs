@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::i18n::{Lang, Text};
+use crate::model::Tag;
+use crate::units::Units;
+
+/**
+ * Metrics derived from temperature and relative humidity.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedMetric {
+	DewPoint,
+	AbsoluteHumidity,
+	HeatIndex,
+	// VPD, how much drier the air is than saturated, for greenhouses and grow tents.
+	VaporPressureDeficit,
+}
+
+impl DerivedMetric {
+	pub fn label(&self, lang: Lang) -> &'static str {
+		match self {
+			DerivedMetric::DewPoint => lang.text(Text::DewPoint),
+			DerivedMetric::AbsoluteHumidity => lang.text(Text::AbsoluteHumidity),
+			DerivedMetric::HeatIndex => lang.text(Text::HeatIndex),
+			DerivedMetric::VaporPressureDeficit => lang.text(Text::VaporPressureDeficit),
+		}
+	}
+	
+	pub fn name(&self) -> &'static str {
+		match self {
+			DerivedMetric::DewPoint => "dew point",
+			DerivedMetric::AbsoluteHumidity => "absolute humidity",
+			DerivedMetric::HeatIndex => "heat index",
+			DerivedMetric::VaporPressureDeficit => "vapor pressure deficit",
+		}
+	}
+	
+	/**
+	 * Calculate the metric: °C, g/m³ or kPa.
+	*/
+	pub fn value(&self, temperature: f64, humidity: f64) -> f64 {
+		match self {
+			DerivedMetric::DewPoint => dew_point(temperature, humidity),
+			DerivedMetric::AbsoluteHumidity => absolute_humidity(temperature, humidity),
+			DerivedMetric::HeatIndex => heat_index(temperature, humidity),
+			DerivedMetric::VaporPressureDeficit => vapor_pressure_deficit(temperature, humidity),
+		}
+	}
+	
+	/**
+	 * Whether the metric is a temperature, in °C.
+	*/
+	pub fn is_temperature(&self) -> bool {
+		return matches!(self, DerivedMetric::DewPoint | DerivedMetric::HeatIndex);
+	}
+	
+	/**
+	 * Calculate and format the metric for display.
+	*/
+	pub fn format(&self, temperature: f64, humidity: f64, units: &Units, lang: Lang) -> String {
+		return self.format_value(self.value(temperature, humidity), units, lang);
+	}
+	
+	/**
+	 * Format a value of the metric, e.g. an alert limit, with the unit.
+	*/
+	pub fn format_value(&self, value: f64, units: &Units, lang: Lang) -> String {
+		match self {
+			DerivedMetric::DewPoint | DerivedMetric::HeatIndex => units.temperature(value, 2, lang),
+			DerivedMetric::AbsoluteHumidity => format!("{} g/m³", lang.number(value, 2)),
+			DerivedMetric::VaporPressureDeficit => format!("{} kPa", lang.number(value, 2)),
+		}
+	}
+}
+
+/**
+ * How an extra number the source passes through is shown, e.g. the co2 of a sensor next to the tag.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExtraMetric {
+	// Shown instead of the field name, e.g. "CO₂".
+	pub label: Option<String>,
+	// E.g. "ppm", after the value.
+	pub unit: Option<String>,
+	pub decimals: usize,
+}
+
+impl Default for ExtraMetric {
+	fn default() -> ExtraMetric {
+		return ExtraMetric {
+			label: None,
+			unit: None,
+			decimals: 1,
+		};
+	}
+}
+
+impl ExtraMetric {
+	/**
+	 * The field's value with the label and the unit, e.g. "CO₂ 612 ppm".
+	*/
+	pub fn format(&self, field: &str, value: f64, lang: Lang) -> String {
+		let unit = self.unit.as_ref().map(|unit| format!(" {}", unit)).unwrap_or_default();
+		return format!("{} {}{}", self.label.as_deref().unwrap_or(field), lang.number(value, self.decimals), unit);
+	}
+}
+
+/**
+ * The extra numbers of the tag on one line, e.g. "CO₂ 612 ppm  lux 340.0". Fields without a config are shown by
+ * their name with one decimal.
+*/
+pub fn format_extras(tag: &Tag, extras: &HashMap<String, ExtraMetric>, lang: Lang) -> Option<String> {
+	let default = ExtraMetric::default();
+	let values: Vec<String> = tag.extras.iter()
+		.map(|(field, value)| extras.get(field).unwrap_or(&default).format(field, *value, lang))
+		.collect();
+	return (!values.is_empty()).then(|| values.join("  "));
+}
+
+/**
+ * Saturation vapor pressure (hPa) over water with the Magnus formula.
+*/
+fn saturation_vapor_pressure(temperature: f64) -> f64 {
+	return 6.112 * ((17.62 * temperature) / (243.12 + temperature)).exp();
+}
+
+/**
+ * Dew point (°C) with the Magnus formula.
+*/
+pub fn dew_point(temperature: f64, humidity: f64) -> f64 {
+	let gamma = (humidity.max(0.01) / 100.0).ln() + (17.62 * temperature) / (243.12 + temperature);
+	return 243.12 * gamma / (17.62 - gamma);
+}
+
+/**
+ * Absolute humidity (g/m³).
+*/
+pub fn absolute_humidity(temperature: f64, humidity: f64) -> f64 {
+	let vapor_pressure = saturation_vapor_pressure(temperature) * humidity / 100.0;
+	return 216.7 * vapor_pressure / (273.15 + temperature);
+}
+
+/**
+ * Vapor pressure deficit (kPa): the saturation vapor pressure less the actual one.
+*/
+pub fn vapor_pressure_deficit(temperature: f64, humidity: f64) -> f64 {
+	return saturation_vapor_pressure(temperature) * (1.0 - humidity / 100.0) / 10.0;
+}
+
+/**
+ * Wind chill (°C) with the North American / UK formula. Wind speed in m/s.
+ * The formula is only defined for cold and windy conditions, otherwise the temperature is returned.
+*/
+pub fn wind_chill(temperature: f64, wind_speed: f64) -> f64 {
+	let wind = wind_speed * 3.6; // km/h
+	if temperature > 10.0 || wind <= 4.8 {
+		return temperature;
+	}
+	
+	let factor = wind.powf(0.16);
+	return 13.12 + 0.6215 * temperature - 11.37 * factor + 0.3965 * temperature * factor;
+}
+
+/**
+ * Heat index (°C) with the NOAA algorithm (Rothfusz regression with adjustments).
+*/
+pub fn heat_index(temperature: f64, humidity: f64) -> f64 {
+	let t = temperature * 9.0 / 5.0 + 32.0;
+	let rh = humidity;
+	
+	// The simple formula is good enough below 80°F.
+	let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+	let mut index = (simple + t) / 2.0;
+	
+	if index >= 80.0 {
+		index = -42.379 + 2.04901523 * t + 10.14333127 * rh
+			- 0.22475541 * t * rh - 0.00683783 * t * t
+			- 0.05481717 * rh * rh + 0.00122874 * t * t * rh
+			+ 0.00085282 * t * rh * rh - 0.00000199 * t * t * rh * rh;
+		
+		if rh < 13.0 && (80.0..=112.0).contains(&t) {
+			index -= ((13.0 - rh) / 4.0) * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+		}
+		else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+			index += ((rh - 85.0) / 10.0) * ((87.0 - t) / 5.0);
+		}
+	}
+	
+	return (index - 32.0) * 5.0 / 9.0;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+		assert!((actual - expected).abs() <= tolerance, "expected {} ± {}, got {}", expected, tolerance, actual);
+	}
+	
+	#[test]
+	fn extras_with_and_without_a_config() {
+		let co2 = ExtraMetric { label: Some("CO₂".into()), unit: Some("ppm".into()), decimals: 0 };
+		assert_eq!(co2.format("co2", 612.4, Lang::En), "CO₂ 612 ppm");
+		assert_eq!(ExtraMetric::default().format("lux", 340.0, Lang::Fi), "lux 340,0");
+	}
+	
+	#[test]
+	fn dew_point_reference_values() {
+		assert_close(dew_point(20.0, 50.0), 9.3, 0.1);
+		assert_close(dew_point(30.0, 80.0), 26.2, 0.1);
+		assert_close(dew_point(-10.0, 70.0), -14.5, 0.2);
+	}
+	
+	#[test]
+	fn dew_point_equals_temperature_when_saturated() {
+		assert_close(dew_point(15.0, 100.0), 15.0, 0.01);
+	}
+	
+	#[test]
+	fn dew_point_handles_zero_humidity() {
+		assert!(dew_point(20.0, 0.0).is_finite());
+	}
+	
+	#[test]
+	fn absolute_humidity_reference_values() {
+		assert_close(absolute_humidity(20.0, 50.0), 8.6, 0.1);
+		assert_close(absolute_humidity(30.0, 100.0), 30.4, 0.2);
+		assert_close(absolute_humidity(0.0, 100.0), 4.8, 0.1);
+	}
+	
+	#[test]
+	fn vapor_pressure_deficit_reference_values() {
+		assert_close(vapor_pressure_deficit(25.0, 60.0), 1.27, 0.01);
+		assert_close(vapor_pressure_deficit(20.0, 100.0), 0.0, 0.001);
+	}
+	
+	#[test]
+	fn wind_chill_reference_values() {
+		// Environment Canada table: -20°C and 30 km/h => -33.
+		assert_close(wind_chill(-20.0, 30.0 / 3.6), -32.6, 0.2);
+		// -5°C and 20 km/h => -12.
+		assert_close(wind_chill(-5.0, 20.0 / 3.6), -11.6, 0.1);
+	}
+	
+	#[test]
+	fn wind_chill_outside_formula_range_is_temperature() {
+		assert_eq!(wind_chill(15.0, 10.0), 15.0);
+		assert_eq!(wind_chill(-5.0, 1.0), -5.0);
+	}
+	
+	#[test]
+	fn heat_index_is_close_to_temperature_when_mild() {
+		assert_close(heat_index(20.0, 50.0), 20.0, 0.5);
+	}
+	
+	#[test]
+	fn heat_index_reference_values() {
+		// NOAA table: 90°F and 70% RH => 106°F.
+		assert_close(heat_index(32.22, 70.0), 41.1, 0.3);
+		// NOAA table: 100°F and 40% RH => 109°F.
+		assert_close(heat_index(37.78, 40.0), 42.8, 0.3);
+	}
+}
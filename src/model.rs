@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metric {
+	pub current: f64,
+	// Not all sources provide min/max. Missing ones are filled in from the local history.
+	#[serde(default)]
+	pub min: Option<f64>,
+	#[serde(default)]
+	pub max: Option<f64>,
+	// Calculated from the local history when the source doesn't provide it.
+	#[serde(default)]
+	pub trend: Option<i8>,
+	// The value the tag sent, when it has been calibrated.
+	#[serde(skip)]
+	pub raw: Option<f64>,
+	// Difference to the reference tag's value, when one is configured.
+	#[serde(skip)]
+	pub delta: Option<f64>,
+}
+
+impl Metric {
+	/**
+	 * Metric with only the current value known.
+	*/
+	pub fn from_current(current: f64) -> Metric {
+		return Metric {
+			current,
+			min: None,
+			max: None,
+			trend: None,
+			raw: None,
+			delta: None,
+		};
+	}
+}
+
+/**
+ * Normalized tag data, independent of the source format. Serialized only between the headless instance and the
+ * attached TUIs, see the attach module.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+	pub id: String,
+	pub name: String,
+	// Friendly name from the config, shown instead of the name.
+	pub alias: Option<String>,
+	pub datetime: String,
+	// None when the source doesn't send the value, e.g. of a broken sensor. Missing values are never made up.
+	pub temperature: Option<Metric>,
+	pub humidity: Option<Metric>,
+	// Pressure in hPa, not all sources have it.
+	pub pressure: Option<Metric>,
+	pub battery_voltage: Option<f64>,
+	pub battery_low: bool,
+	// Only known when the voltage is, see the battery model.
+	pub battery_critical: bool,
+	// From the raw broadcast of sources that send it: the movements the accelerometer has detected and the number
+	// of the measurement, both wrapping around.
+	pub movement_counter: Option<u8>,
+	pub measurement_sequence: Option<u16>,
+	// Signal strength of the latest broadcast where the gateway received it, in dBm.
+	pub rssi: Option<i32>,
+	pub unreachable: bool,
+	pub metadata: TagMetadata,
+	// Other numbers the source sends by field name, e.g. co2 or lux of a sensor next to the tag.
+	pub extras: BTreeMap<String, f64>,
+}
+
+/**
+ * Where the tag is and what it measures, from sources that know it.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagMetadata {
+	pub location: Option<String>,
+	// A string, as buildings name their floors differently ("2", "B1", "Attic").
+	#[serde(deserialize_with = "string_or_number")]
+	pub floor: Option<String>,
+	pub description: Option<String>,
+}
+
+impl TagMetadata {
+	/**
+	 * Location, floor and description on one line, e.g. "Main house, floor 2 — Above the stove".
+	*/
+	pub fn summary(&self, floor_text: &str) -> Option<String> {
+		let floor = self.floor.as_ref().map(|floor| format!("{} {}", floor_text, floor));
+		let place: Vec<String> = self.location.iter().cloned().chain(floor).collect();
+		let parts: Vec<String> = (!place.is_empty()).then(|| place.join(", ")).into_iter().chain(self.description.clone()).collect();
+		return (!parts.is_empty()).then(|| parts.join(" — "));
+	}
+}
+
+pub(crate) fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	return Ok(match Option::<serde_json::Value>::deserialize(deserializer)? {
+		Some(serde_json::Value::String(text)) => Some(text),
+		Some(serde_json::Value::Number(number)) => Some(number.to_string()),
+		_ => None,
+	});
+}
+
+impl Tag {
+	/**
+	 * A tag with only the ID and the name, for the tests to set what they check on with struct update syntax.
+	*/
+	#[cfg(test)]
+	pub fn test(id: &str, name: &str) -> Tag {
+		return Tag {
+			id: id.into(),
+			name: name.into(),
+			alias: None,
+			datetime: String::new(),
+			temperature: None,
+			humidity: None,
+			pressure: None,
+			battery_voltage: None,
+			battery_low: false,
+			battery_critical: false,
+			movement_counter: None,
+			measurement_sequence: None,
+			rssi: None,
+			unreachable: false,
+			metadata: TagMetadata::default(),
+			extras: BTreeMap::new(),
+		};
+	}
+	
+	/**
+	 * The measurement time, if the datetime from the source is valid.
+	*/
+	pub fn measured_at(&self) -> Option<DateTime<Utc>> {
+		return self.datetime.parse().ok();
+	}
+	
+	pub fn temperature(&self) -> Option<f64> {
+		return self.temperature.as_ref().map(|temperature| temperature.current);
+	}
+	
+	pub fn humidity(&self) -> Option<f64> {
+		return self.humidity.as_ref().map(|humidity| humidity.current);
+	}
+	
+	pub fn pressure(&self) -> Option<f64> {
+		return self.pressure.as_ref().map(|pressure| pressure.current);
+	}
+	
+	/**
+	 * Drop the values that aren't finite numbers, so that they show as missing instead of NaN.
+	*/
+	pub fn drop_invalid(&mut self) {
+		for metric in [&mut self.temperature, &mut self.humidity, &mut self.pressure] {
+			if metric.as_ref().is_some_and(|metric| !metric.current.is_finite()) {
+				*metric = None;
+			}
+		}
+		self.battery_voltage = self.battery_voltage.filter(|voltage| voltage.is_finite());
+	}
+	
+	/**
+	 * The name shown in the UI.
+	*/
+	pub fn label(&self) -> &str {
+		match &self.alias {
+			Some(alias) => alias,
+			None if self.name.is_empty() => "Unknown tag",
+			None => &self.name,
+		}
+	}
+}
+
+pub type ApiResponse = Vec<Tag>;
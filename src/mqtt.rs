@@ -0,0 +1,417 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use crate::model::ApiResponse;
+use crate::output::Output;
+use crate::status::{self, TagJson};
+use crate::units::Precision;
+
+// Refreshes queued for the background thread. More means the broker is stuck, and they're dropped.
+const QUEUE: usize = 100;
+const TIMEOUT: Duration = Duration::from_secs(10);
+// The broker drops a client it hasn't heard from in one and a half of this, so a ping is sent when there's been nothing
+// to publish for half of it, e.g. with a slow interval.
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+/**
+ * Republishing of the tags to an MQTT broker, which makes the client a bridge from e.g. a custom HTTP API to Home
+ * Assistant.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+	// Broker as mqtt://host:1883 or mqtts://host:8883, with the user and the password in the URL if needed. Not
+	// published without.
+	pub url: Option<String>,
+	pub client_id: String,
+	// Start of the topics: <topic_prefix>/<tag>/temperature, where the tag is its ID without the colons.
+	pub topic_prefix: String,
+	// Announce the tags to Home Assistant under <discovery_prefix>/sensor/… and <discovery_prefix>/binary_sensor/….
+	pub discovery: bool,
+	pub discovery_prefix: String,
+}
+
+impl Default for MqttConfig {
+	fn default() -> MqttConfig {
+		return MqttConfig {
+			url: None,
+			client_id: "ruuvi-terminal-client".into(),
+			topic_prefix: "ruuvi-tc".into(),
+			discovery: true,
+			discovery_prefix: "homeassistant".into(),
+		};
+	}
+}
+
+/**
+ * A retained message to publish. Once is for the discovery, sent again only after a reconnect.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Message {
+	topic: String,
+	payload: String,
+	once: bool,
+}
+
+trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+/**
+ * Publishes the values of the tags on every refresh from a background thread, as retained messages at QoS 0:
+ * <topic_prefix>/<tag>/<metric> with the value as a plain number, <topic_prefix>/<tag>/availability as online or
+ * offline by whether the tag is reachable, and <topic_prefix>/status as online while connected (offline as the last
+ * will). With discovery, Home Assistant finds each metric as a sensor of a device per tag.
+*/
+pub struct Mqtt {
+	config: MqttConfig,
+	// Decimals Home Assistant is told to show.
+	precision: Precision,
+	sender: SyncSender<Vec<Message>>,
+	// Why publishing failed since the last call.
+	failure: Arc<Mutex<Option<String>>>,
+}
+
+impl Mqtt {
+	pub fn start(url: &str, config: &MqttConfig, precision: Precision) -> Result<Mqtt, String> {
+		let url = Url::parse(url).map_err(|error| format!("Invalid MQTT URL: {}", error))?;
+		if !["mqtt", "mqtts"].contains(&url.scheme()) || url.host_str().is_none() {
+			return Err("Invalid MQTT URL: use mqtt://host:port or mqtts://host:port".into());
+		}
+		// For the errors, without the password.
+		let broker = format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default());
+		let (sender, receiver) = mpsc::sync_channel::<Vec<Message>>(QUEUE);
+		let failure = Arc::new(Mutex::new(None));
+		
+		let thread_config = config.clone();
+		let thread_failure = Arc::clone(&failure);
+		thread::spawn(move || {
+			let mut connection: Option<Box<dyn Connection>> = None;
+			// The discovery messages sent on this connection.
+			let mut announced = HashSet::new();
+			let mut last_sent = Instant::now();
+			loop {
+				let messages = match receiver.recv_timeout(KEEP_ALIVE / 2) {
+					Ok(messages) => messages,
+					Err(RecvTimeoutError::Timeout) => {
+						let Some(stream) = connection.as_mut().filter(|_| last_sent.elapsed() >= KEEP_ALIVE / 2) else {
+							continue;
+						};
+						match ping(stream) {
+							Ok(()) => last_sent = Instant::now(),
+							Err(error) => {
+								*thread_failure.lock().unwrap() = Some(format!("MQTT to {} failed: {}", broker, error));
+								connection = None;
+							},
+						}
+						continue;
+					},
+					Err(RecvTimeoutError::Disconnected) => break,
+				};
+				if connection.is_none() {
+					announced.clear();
+					match connect(&url, &thread_config) {
+						Ok(connected) => {
+							connection = Some(connected);
+							last_sent = Instant::now();
+						},
+						Err(error) => {
+							*thread_failure.lock().unwrap() = Some(format!("MQTT to {} failed: {}", broker, error));
+							continue;
+						},
+					}
+				}
+				let Some(stream) = connection.as_mut() else {
+					continue;
+				};
+				let sent = messages.iter()
+					.filter(|message| !message.once || !announced.contains(*message))
+					.try_for_each(|message| stream.write_all(&publish(&message.topic, &message.payload)));
+				match sent.and_then(|_| stream.flush()) {
+					Ok(()) => {
+						last_sent = Instant::now();
+						announced.extend(messages.into_iter().filter(|message| message.once));
+					},
+					Err(error) => {
+						*thread_failure.lock().unwrap() = Some(format!("MQTT to {} failed: {}", broker, error));
+						connection = None;
+					},
+				}
+			}
+			// The client quit.
+			if let Some(stream) = connection.as_mut() {
+				let _ = stream.write_all(&[0xe0, 0]);
+			}
+		});
+		
+		return Ok(Mqtt { config: config.clone(), precision, sender, failure });
+	}
+}
+
+impl Output for Mqtt {
+	fn send(&mut self, data: &ApiResponse, _now: DateTime<Utc>) {
+		let messages = status::tags_json(data).iter().flat_map(|tag| messages(&self.config, self.precision, tag)).collect();
+		let _ = self.sender.try_send(messages);
+	}
+	
+	fn take_failure(&self) -> Option<String> {
+		return self.failure.lock().ok()?.take();
+	}
+}
+
+/**
+ * A metric of a tag: the last part of the topic, the value and how Home Assistant shows it.
+*/
+struct Published {
+	metric: &'static str,
+	value: Option<String>,
+	// Sensor, or binary_sensor for ON and OFF.
+	component: &'static str,
+	unit: Option<&'static str>,
+	device_class: &'static str,
+	precision: Option<usize>,
+	// Of the tag itself rather than of what it measures.
+	diagnostic: bool,
+}
+
+/**
+ * The messages of a tag: its values and availability, and with discovery the config of each metric for Home
+ * Assistant.
+*/
+fn messages(config: &MqttConfig, precision: Precision, tag: &TagJson) -> Vec<Message> {
+	let node = topic_part(&tag.id);
+	let base = format!("{}/{}", config.topic_prefix, node);
+	let availability = format!("{}/availability", base);
+	let number = |value: Option<f64>| value.map(|value| value.to_string());
+	let published = [
+		Published { metric: "temperature", value: number(tag.temperature.as_ref().map(|metric| metric.value)), component: "sensor", unit: Some("°C"), device_class: "temperature", precision: Some(precision.temperature), diagnostic: false },
+		Published { metric: "humidity", value: number(tag.humidity.as_ref().map(|metric| metric.value)), component: "sensor", unit: Some("%"), device_class: "humidity", precision: Some(precision.humidity), diagnostic: false },
+		Published { metric: "pressure", value: number(tag.pressure.as_ref().map(|metric| metric.value)), component: "sensor", unit: Some("hPa"), device_class: "pressure", precision: Some(precision.pressure), diagnostic: false },
+		Published { metric: "battery_voltage", value: number(tag.battery_voltage), component: "sensor", unit: Some("V"), device_class: "voltage", precision: Some(precision.battery_voltage), diagnostic: true },
+		Published { metric: "battery_low", value: Some(if tag.battery_low { "ON" } else { "OFF" }.into()), component: "binary_sensor", unit: None, device_class: "battery", precision: None, diagnostic: true },
+	];
+	
+	let mut messages = vec![Message {
+		topic: availability.clone(),
+		payload: if tag.unreachable { "offline" } else { "online" }.into(),
+		once: false,
+	}];
+	for published in published {
+		let Some(value) = published.value else {
+			continue;
+		};
+		let state_topic = format!("{}/{}", base, published.metric);
+		if config.discovery {
+			let unique_id = format!("ruuvi_tc_{}_{}", node, published.metric);
+			let mut discovery = json!({
+				"name": published.metric.replace('_', " "),
+				"unique_id": unique_id,
+				"state_topic": state_topic,
+				"device_class": published.device_class,
+				// Unavailable when the client is gone or the tag unreachable.
+				"availability": [{ "topic": status_topic(config) }, { "topic": availability }],
+				"availability_mode": "all",
+				"device": { "identifiers": [format!("ruuvi_tc_{}", node)], "name": tag.name, "manufacturer": "Ruuvi", "model": "RuuviTag" },
+				"origin": { "name": "ruuvi-terminal-client", "sw_version": env!("CARGO_PKG_VERSION"), "support_url": "https://github.com/joonaskokko/ruuvi-terminal-client" },
+			});
+			if let Some(unit) = published.unit {
+				discovery["unit_of_measurement"] = unit.into();
+				discovery["state_class"] = "measurement".into();
+			}
+			if let Some(precision) = published.precision {
+				discovery["suggested_display_precision"] = precision.into();
+			}
+			if published.diagnostic {
+				discovery["entity_category"] = "diagnostic".into();
+			}
+			messages.push(Message {
+				topic: format!("{}/{}/{}/config", config.discovery_prefix, published.component, unique_id),
+				payload: discovery.to_string(),
+				once: true,
+			});
+		}
+		messages.push(Message { topic: state_topic, payload: value, once: false });
+	}
+	return messages;
+}
+
+/**
+ * The tag ID as a part of a topic, lowercase without the colons, e.g. "c12a3b4c5d6e".
+*/
+fn topic_part(id: &str) -> String {
+	return id.chars().filter(char::is_ascii_alphanumeric).map(|character| character.to_ascii_lowercase()).collect();
+}
+
+fn status_topic(config: &MqttConfig) -> String {
+	return format!("{}/status", config.topic_prefix);
+}
+
+/**
+ * Connect to the broker and say that the bridge is online. The last will says that it's offline.
+*/
+fn connect(url: &Url, config: &MqttConfig) -> Result<Box<dyn Connection>, String> {
+	let tls = url.scheme() == "mqtts";
+	let host = url.host_str().unwrap_or_default();
+	let port = url.port().unwrap_or(if tls { 8883 } else { 1883 });
+	let tcp = TcpStream::connect((host, port)).map_err(|error| error.to_string())?;
+	tcp.set_read_timeout(Some(TIMEOUT)).and_then(|_| tcp.set_write_timeout(Some(TIMEOUT))).map_err(|error| error.to_string())?;
+	let mut stream: Box<dyn Connection> = match tls {
+		true => {
+			let connector = native_tls::TlsConnector::new().map_err(|error| error.to_string())?;
+			Box::new(connector.connect(host, tcp).map_err(|error| error.to_string())?)
+		},
+		false => Box::new(tcp),
+	};
+	
+	// The URL keeps them percent-encoded, e.g. %40 for an @ in the password.
+	let decode = |value: &str| percent_decode_str(value).decode_utf8().map(|value| value.into_owned())
+		.map_err(|_| "the user or the password isn't UTF-8".to_string());
+	let user = Some(decode(url.username())?).filter(|user| !user.is_empty());
+	let password = url.password().map(decode).transpose()?;
+	stream.write_all(&connect_packet(&config.client_id, &status_topic(config), user.as_deref(), password.as_deref())).map_err(|error| error.to_string())?;
+	stream.flush().map_err(|error| error.to_string())?;
+	let mut connack = [0; 4];
+	stream.read_exact(&mut connack).map_err(|error| error.to_string())?;
+	match connack {
+		[0x20, 2, _, 0] => {},
+		[0x20, 2, _, 4 | 5] => return Err("not authorized".into()),
+		[0x20, 2, _, code] => return Err(format!("connection refused ({})", code)),
+		_ => return Err("not an MQTT broker".into()),
+	}
+	stream.write_all(&publish(&status_topic(config), "online")).map_err(|error| error.to_string())?;
+	return Ok(stream);
+}
+
+/**
+ * Ping the broker and wait for its answer, so that a connection that has silently gone is noticed.
+*/
+fn ping(stream: &mut Box<dyn Connection>) -> Result<(), String> {
+	stream.write_all(&[0xc0, 0]).and_then(|_| stream.flush()).map_err(|error| error.to_string())?;
+	let mut pingresp = [0; 2];
+	stream.read_exact(&mut pingresp).map_err(|error| error.to_string())?;
+	if pingresp != [0xd0, 0] {
+		return Err("unexpected answer to a ping".into());
+	}
+	return Ok(());
+}
+
+/**
+ * MQTT 3.1.1 CONNECT with a clean session, the keep-alive and the offline status as the retained last will.
+*/
+fn connect_packet(client_id: &str, will_topic: &str, user: Option<&str>, password: Option<&str>) -> Vec<u8> {
+	// Clean session, the will, and the will retained.
+	let mut flags = 0x02 | 0x04 | 0x20;
+	let mut body = string(b"MQTT");
+	body.push(4);
+	let flags_at = body.len();
+	body.push(0);
+	body.extend((KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+	body.extend(string(client_id.as_bytes()));
+	body.extend(string(will_topic.as_bytes()));
+	body.extend(string(b"offline"));
+	if let Some(user) = user {
+		flags |= 0x80;
+		body.extend(string(user.as_bytes()));
+	}
+	if let Some(password) = password {
+		flags |= 0x40;
+		body.extend(string(password.as_bytes()));
+	}
+	body[flags_at] = flags;
+	return packet(0x10, body);
+}
+
+/**
+ * MQTT PUBLISH of a retained message at QoS 0.
+*/
+fn publish(topic: &str, payload: &str) -> Vec<u8> {
+	let mut body = string(topic.as_bytes());
+	body.extend(payload.as_bytes());
+	return packet(0x31, body);
+}
+
+/**
+ * The packet with its type and flags and the remaining length, 7 bits a byte.
+*/
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+	let mut packet = vec![header];
+	let mut length = body.len();
+	loop {
+		let byte = (length % 128) as u8;
+		length /= 128;
+		packet.push(if length > 0 { byte | 0x80 } else { byte });
+		if length == 0 {
+			break;
+		}
+	}
+	packet.extend(body);
+	return packet;
+}
+
+/**
+ * A string or bytes with the length in front.
+*/
+fn string(bytes: &[u8]) -> Vec<u8> {
+	let mut string = (bytes.len() as u16).to_be_bytes().to_vec();
+	string.extend(bytes);
+	return string;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn packets() {
+		assert_eq!(publish("a/b", "21.5"), [0x31, 9, 0, 3, b'a', b'/', b'b', b'2', b'1', b'.', b'5']);
+		assert_eq!(&packet(0x31, vec![0; 200])[..3], [0x31, 0xc8, 0x01]);
+		let connect = connect_packet("id", "s", Some("u"), Some("p"));
+		assert_eq!(&connect[..12], [0x10, 32, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x80 | 0x40 | 0x20 | 0x04 | 0x02, 0, 60]);
+		assert_eq!(topic_part("C1:2A:3B:4C:5D:6E"), "c12a3b4c5d6e");
+	}
+	
+	#[test]
+	fn discovery() {
+		let tag = TagJson {
+			id: "C1:2A:3B:4C:5D:6E".into(),
+			name: "Sauna".into(),
+			measured_at: None,
+			unreachable: true,
+			temperature: Some(status::MetricJson { value: 65.25, trend: status::TrendJson::Steady }),
+			humidity: None,
+			pressure: None,
+			battery_voltage: Some(2.5),
+			battery_low: true,
+			battery_critical: false,
+		};
+		let published = messages(&MqttConfig::default(), Precision::default(), &tag);
+		let payload = |topic: &str| published.iter().find(|message| message.topic == topic).map(|message| message.payload.clone());
+		assert_eq!(payload("ruuvi-tc/c12a3b4c5d6e/availability").as_deref(), Some("offline"));
+		assert_eq!(payload("ruuvi-tc/c12a3b4c5d6e/temperature").as_deref(), Some("65.25"));
+		assert_eq!(payload("ruuvi-tc/c12a3b4c5d6e/battery_low").as_deref(), Some("ON"));
+		assert_eq!(payload("ruuvi-tc/c12a3b4c5d6e/humidity"), None);
+		
+		let config = |topic: &str| serde_json::from_str::<serde_json::Value>(&payload(topic).unwrap()).unwrap();
+		let temperature = config("homeassistant/sensor/ruuvi_tc_c12a3b4c5d6e_temperature/config");
+		assert_eq!(temperature["unit_of_measurement"], "°C");
+		assert_eq!(temperature["device_class"], "temperature");
+		assert_eq!(temperature["suggested_display_precision"], 1);
+		assert_eq!(temperature["availability"][1]["topic"], "ruuvi-tc/c12a3b4c5d6e/availability");
+		assert_eq!(temperature.get("entity_category"), None);
+		let battery = config("homeassistant/binary_sensor/ruuvi_tc_c12a3b4c5d6e_battery_low/config");
+		assert_eq!(battery["entity_category"], "diagnostic");
+		assert_eq!(battery.get("unit_of_measurement"), None);
+		
+		let without = messages(&MqttConfig { discovery: false, ..MqttConfig::default() }, Precision::default(), &tag);
+		assert!(without.iter().all(|message| !message.once));
+	}
+}
@@ -0,0 +1,103 @@
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+use crate::model::string_or_number;
+
+/**
+ * Push notification through ntfy, https://ntfy.sh or a self-hosted server, to the phones subscribed to the topic.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ntfy {
+	#[serde(default = "default_ntfy_server")]
+	pub server: String,
+	pub topic: String,
+	// Access token of a protected topic.
+	pub token: Option<String>,
+}
+
+fn default_ntfy_server() -> String {
+	return "https://ntfy.sh".into();
+}
+
+/**
+ * Message from a Telegram bot into a chat: the bot's token from @BotFather and the ID of the chat, a number or the
+ * @name of a public channel.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct Telegram {
+	pub token: String,
+	#[serde(deserialize_with = "string_or_number")]
+	pub chat_id: Option<String>,
+}
+
+/**
+ * What a notification says and how urgent it is.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+	pub title: String,
+	pub text: String,
+	// info, warning or critical of a starting alert, empty for a cleared one and the network events.
+	pub severity: String,
+	pub cleared: bool,
+}
+
+impl Ntfy {
+	pub fn request(&self, client: &Client, notification: &Notification) -> RequestBuilder {
+		// The priorities and the emoji shortcodes of the tags are ntfy's.
+		let (priority, emoji) = match (notification.cleared, notification.severity.as_str()) {
+			(true, _) => ("default", "white_check_mark"),
+			(false, "critical") => ("urgent", "rotating_light"),
+			(false, "warning") => ("high", "warning"),
+			_ => ("default", "information_source"),
+		};
+		let mut request = client.post(format!("{}/{}", self.server.trim_end_matches('/'), self.topic))
+			.header("Title", &notification.title)
+			.header("Priority", priority)
+			.header("Tags", emoji)
+			.body(notification.text.clone());
+		if let Some(token) = &self.token {
+			request = request.bearer_auth(token);
+		}
+		return request;
+	}
+}
+
+impl Telegram {
+	pub fn request(&self, client: &Client, notification: &Notification) -> RequestBuilder {
+		// The text already names the tag, which is all the title would say.
+		let body = json!({ "chat_id": self.chat_id, "text": notification.text });
+		return client.post(format!("https://api.telegram.org/bot{}/sendMessage", self.token)).json(&body);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn requests() {
+		let client = Client::new();
+		let notification = Notification {
+			title: "Fridge".into(),
+			text: "Temperature above 6.0: 7.20".into(),
+			severity: "critical".into(),
+			cleared: false,
+		};
+		
+		let ntfy: Ntfy = toml::from_str(r#"topic = "cabin-fridge"
+			token = "tk_secret""#).unwrap();
+		let request = ntfy.request(&client, &notification).build().unwrap();
+		assert_eq!(request.url().as_str(), "https://ntfy.sh/cabin-fridge");
+		assert_eq!(request.headers()["Priority"], "urgent");
+		assert_eq!(request.headers()["Authorization"], "Bearer tk_secret");
+		assert_eq!(request.body().and_then(|body| body.as_bytes()), Some("Temperature above 6.0: 7.20".as_bytes()));
+		
+		let telegram: Telegram = toml::from_str(r#"token = "123:abc"
+			chat_id = -1001234"#).unwrap();
+		let request = telegram.request(&client, &notification).build().unwrap();
+		assert_eq!(request.url().as_str(), "https://api.telegram.org/bot123:abc/sendMessage");
+		let body: serde_json::Value = serde_json::from_slice(request.body().and_then(|body| body.as_bytes()).unwrap()).unwrap();
+		assert_eq!(body, json!({ "chat_id": "-1001234", "text": "Temperature above 6.0: 7.20" }));
+	}
+}
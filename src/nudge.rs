@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item};
+use crate::alerts::{AlertMetric, AlertRule, Severity};
+use crate::config;
+use crate::model::Tag;
+
+/**
+ * A warning threshold of a tag being tuned from the dashboard with the + and - keys while watching the values. The
+ * change takes effect and is written into the config file only when confirmed.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nudge {
+	// Index of the rule in the alerts config, and whether its above or below limit is nudged.
+	pub rule: usize,
+	pub above: bool,
+	pub from: f64,
+	pub to: f64,
+	// Why saving failed, to try again or cancel.
+	pub error: Option<String>,
+}
+
+impl Nudge {
+	/**
+	 * Start nudging the first warning rule of the tag that has a limit, the above limit if it has both.
+	*/
+	pub fn start(alerts: &[AlertRule], tag: &Tag) -> Option<Nudge> {
+		return alerts.iter().enumerate()
+			// The limit of a range is in the [ranges].
+			.filter(|(_, rule)| rule.severity == Severity::Warning && rule.range.is_none() && rule.applies_to(tag))
+			.find_map(|(index, rule)| {
+				let (above, limit) = match (rule.above, rule.below) {
+					(Some(above), _) => (true, above),
+					(None, Some(below)) => (false, below),
+					(None, None) => return None,
+				};
+				Some(Nudge { rule: index, above, from: limit, to: limit, error: None })
+			});
+	}
+	
+	/**
+	 * Move the limit by steps of the metric, up for positive steps.
+	*/
+	pub fn by(&mut self, steps: i32, metric: AlertMetric) {
+		let per_unit = steps_per_unit(metric);
+		// Counted in whole steps and divided, so that the limit is e.g. 2.6 and not 2.6000000000000005 in the file.
+		self.to = ((self.to * per_unit).round() + steps as f64) / per_unit;
+		self.error = None;
+	}
+	
+	/**
+	 * Set the nudged limit in the rules.
+	*/
+	pub fn apply(&self, alerts: &mut [AlertRule]) {
+		let Some(rule) = alerts.get_mut(self.rule) else {
+			return;
+		};
+		if self.above {
+			rule.above = Some(self.to);
+		}
+		else {
+			rule.below = Some(self.to);
+		}
+	}
+	
+	/**
+	 * Save the limit into the config file and take it into use. Gives the nudge back with the error if saving failed.
+	*/
+	pub fn confirm(mut self, alerts: &mut [AlertRule], profile: Option<&str>) -> Option<Nudge> {
+		let saved = match config::config_path() {
+			Some(path) => self.save(&path, profile),
+			None => Err("No config file to save into".into()),
+		};
+		if let Err(error) = saved {
+			self.error = Some(error);
+			return Some(self);
+		}
+		self.apply(alerts);
+		return None;
+	}
+	
+	/**
+	 * Write the nudged limit into the rule in the config file, keeping the rest of the file as it is. The rules come
+	 * from the profile in use when it has alerts, as they replace the ones at the top as a whole.
+	*/
+	pub fn save(&self, path: &Path, profile: Option<&str>) -> Result<(), String> {
+		let contents = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+		let mut document = contents.parse::<DocumentMut>().map_err(|error| format!("Invalid config file {}: {}", path.display(), error))?;
+		let key = if self.above { "above" } else { "below" };
+		
+		let in_profile = profile.filter(|name| document.get("profile").and_then(|profiles| profiles.get(name))
+			.is_some_and(|profile| profile.get("alerts").is_some()));
+		let alerts = match in_profile {
+			Some(name) => document.get_mut("profile").and_then(|profiles| profiles.get_mut(name))
+				.and_then(|profile| profile.get_mut("alerts")),
+			None => document.get_mut("alerts"),
+		};
+		// The rules as [[alerts]] tables or as an array of inline tables.
+		let found = match alerts {
+			Some(Item::ArrayOfTables(tables)) => tables.get_mut(self.rule).map(|table| table[key] = toml_edit::value(self.to)),
+			Some(Item::Value(value)) => value.as_array_mut()
+				.and_then(|array| array.get_mut(self.rule))
+				.and_then(|rule| rule.as_inline_table_mut())
+				.map(|table| {
+					table.insert(key, self.to.into());
+				}),
+			_ => None,
+		};
+		if found.is_none() {
+			return Err(format!("The alert rule isn't in {}", path.display()));
+		}
+		
+		// Renamed over the file, so that a failed write doesn't leave half a config.
+		let mut temporary = path.as_os_str().to_owned();
+		temporary.push(".tmp");
+		fs::write(&temporary, document.to_string())
+			.and_then(|_| fs::rename(&temporary, path))
+			.map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+		return Ok(());
+	}
+}
+
+/**
+ * Decimals that show the steps of the metric.
+*/
+pub fn decimals(metric: AlertMetric) -> usize {
+	return steps_per_unit(metric).log10().ceil() as usize;
+}
+
+/**
+ * Presses of + or - that move a limit of the metric by one unit: 0.5 °C, 1 %, 1 hPa, 1 dBm, 0.05 V or 0.1 of a
+ * derived metric a press.
+*/
+fn steps_per_unit(metric: AlertMetric) -> f64 {
+	return match metric {
+		AlertMetric::Temperature => 2.0,
+		AlertMetric::Humidity | AlertMetric::Pressure | AlertMetric::Rssi => 1.0,
+		AlertMetric::BatteryVoltage => 20.0,
+		AlertMetric::Derived(_) => 10.0,
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn nudged_limit_is_written_into_the_config() {
+		let path = std::env::temp_dir().join(format!("ruuvi-nudge-{}.toml", std::process::id()));
+		let contents = "# Alerts\n[[alerts]]\nname = \"sauna_hot\"\nmetric = \"temperature\"\nabove = 80.0\nseverity = \"critical\"\n\n[[alerts]]\nname = \"cellar_cold\"  # pipes\nmetric = \"temperature\"\nbelow = 4.0\ntags = [\"Cellar\"]\n";
+		fs::write(&path, contents).unwrap();
+		let alerts: Vec<AlertRule> = toml::from_str::<toml::Table>(contents).unwrap()["alerts"].clone().try_into().unwrap();
+		let tag = Tag::test("2", "Cellar");
+		
+		let mut nudge = Nudge::start(&alerts, &tag).unwrap();
+		assert_eq!((nudge.rule, nudge.above), (1, false));
+		nudge.by(-3, AlertMetric::Temperature);
+		assert_eq!(nudge.to, 2.5);
+		nudge.by(2, AlertMetric::BatteryVoltage);
+		assert_eq!(nudge.to, 2.6);
+		nudge.by(-2, AlertMetric::BatteryVoltage);
+		nudge.save(&path, None).unwrap();
+		
+		let saved = fs::read_to_string(&path).unwrap();
+		assert_eq!(saved, contents.replace("below = 4.0", "below = 2.5"));
+		
+		// A profile without alerts uses the ones at the top, a profile with them has its own.
+		let profiles = format!("{}\n[profile.home]\ntheme = \"nord\"\n\n[profile.cottage]\nalerts = [{{ metric = \"temperature\", below = 0.0 }}]\n", saved);
+		fs::write(&path, &profiles).unwrap();
+		nudge.to = 3.0;
+		nudge.save(&path, Some("home")).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap(), profiles.replace("below = 2.5", "below = 3.0"));
+		fs::write(&path, &profiles).unwrap();
+		nudge.rule = 0;
+		nudge.save(&path, Some("cottage")).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap(), profiles.replace("below = 0.0", "below = 3.0"));
+		nudge.rule = 1;
+		assert!(nudge.save(&path, Some("cottage")).is_err());
+		fs::remove_file(&path).unwrap();
+	}
+}
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use crate::model::ApiResponse;
+
+/**
+ * Where the tags go besides the screen, e.g. another service or an MQTT broker. The poller hands every refresh to the
+ * outputs of the config, which send them on in the background and tell when that failed.
+*/
+pub trait Output: Send {
+	/**
+	 * The tags of a refresh as shown, with the staleness applied.
+	*/
+	fn send(&mut self, data: &ApiResponse, now: DateTime<Utc>);
+	
+	/**
+	 * Why sending failed since the last call, if it did.
+	*/
+	fn take_failure(&self) -> Option<String>;
+}
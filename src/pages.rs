@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::model::{ApiResponse, Tag};
+use crate::poller::Source;
+use crate::render::Layout;
+
+/**
+ * What a page shows.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PageContent {
+	// The member tags in the page's layout.
+	#[default]
+	Tags,
+	// The active alerts and the alert log of the last 24 hours.
+	Alerts,
+}
+
+/**
+ * Tab of the TUI, e.g. "House" for the tags of one source and "Cottage" for another's. The members are tags (IDs
+ * or names), the tags the source places in the location, like the groups, and the tags of a source. A page without
+ * any of them has all tags.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct PageConfig {
+	pub name: String,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	#[serde(default)]
+	pub location: Option<String>,
+	#[serde(default)]
+	pub source: Option<Source>,
+	// Layout when switching to the page, the configured layout if not set.
+	#[serde(default)]
+	pub layout: Option<Layout>,
+	#[serde(default)]
+	pub content: PageContent,
+}
+
+impl PageConfig {
+	fn contains(&self, tag: &Tag, sources: &HashMap<String, Source>) -> bool {
+		if self.tags.is_empty() && self.location.is_none() && self.source.is_none() {
+			return true;
+		}
+		return self.tags.iter().any(|key| *key == tag.id || *key == tag.name)
+			|| self.location.as_ref().is_some_and(|location| tag.metadata.location.as_ref() == Some(location))
+			|| self.source.is_some_and(|source| sources.get(&tag.id) == Some(&source));
+	}
+	
+	/**
+	 * The member tags in the data order, with the source of each tag by tag ID.
+	*/
+	pub fn filter(&self, data: &ApiResponse, sources: &HashMap<String, Source>) -> ApiResponse {
+		return data.iter().filter(|tag| self.contains(tag, sources)).cloned().collect();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::{Metric, TagMetadata};
+	
+	fn tag(id: &str, location: Option<&str>) -> Tag {
+		return Tag {
+			temperature: Some(Metric::from_current(20.0)),
+			humidity: Some(Metric::from_current(40.0)),
+			metadata: TagMetadata { location: location.map(String::from), ..TagMetadata::default() },
+			..Tag::test(id, id)
+		};
+	}
+	
+	#[test]
+	fn page_members() {
+		let data = vec![tag("a", Some("House")), tag("b", Some("Cottage")), tag("c", None)];
+		let sources = HashMap::from([("a".to_string(), Source::Listener), ("b".to_string(), Source::Api), ("c".to_string(), Source::Api)]);
+		let page = |tags: &[&str], location: Option<&str>, source: Option<Source>| PageConfig {
+			name: String::new(),
+			tags: tags.iter().map(|tag| tag.to_string()).collect(),
+			location: location.map(String::from),
+			source,
+			layout: None,
+			content: PageContent::Tags,
+		};
+		let ids = |page: PageConfig| page.filter(&data, &sources).into_iter().map(|tag| tag.id).collect::<Vec<_>>();
+		
+		assert_eq!(ids(page(&["c"], Some("House"), None)), vec!["a", "c"]);
+		assert_eq!(ids(page(&[], Some("Cottage"), None)), vec!["b"]);
+		assert_eq!(ids(page(&[], None, Some(Source::Api))), vec!["b", "c"]);
+		assert_eq!(ids(page(&[], None, None)), vec!["a", "b", "c"]);
+	}
+}
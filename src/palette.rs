@@ -0,0 +1,185 @@
+use crate::alerts::AlertMetric;
+use crate::config::Config;
+use crate::model::ApiResponse;
+use crate::render::Layout;
+
+// Refresh intervals offered without typing a number, in seconds.
+const INTERVALS: [i64; 4] = [10, 30, 60, 300];
+
+/**
+ * What a command of the palette does.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+	Profile(String),
+	// Number of the page, 1 for the first.
+	Page(usize),
+	Layout(Layout),
+	// Seconds between the fetches.
+	Interval(i64),
+	Snapshot,
+	// Id of the tag to show alone.
+	Tag(String),
+	Toggle(AlertMetric),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+	pub label: String,
+	pub action: Action,
+}
+
+/**
+ * What a key typed into the palette led to.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+	Typing,
+	Cancel,
+	Run(Action),
+}
+
+/**
+ * The command palette opened with the : key, for the actions that have no key of their own. The typed text picks the
+ * commands by fuzzy matching, e.g. "sau" or "tsa" for "tag Sauna", Tab moves to the next match and Enter runs it.
+*/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Palette {
+	pub input: String,
+	// Index among the matches.
+	pub selected: usize,
+}
+
+impl Palette {
+	pub fn new() -> Palette {
+		return Palette::default();
+	}
+	
+	/**
+	 * Type the key: Escape or Backspace on an empty line closes the palette, Enter runs the selected match.
+	*/
+	pub fn key(&mut self, key: char, config: &Config, data: &ApiResponse) -> Outcome {
+		match key {
+			'\x1b' => return Outcome::Cancel,
+			'\x7f' | '\x08' if self.input.is_empty() => return Outcome::Cancel,
+			'\x7f' | '\x08' => {
+				self.input.pop();
+				self.selected = 0;
+			},
+			'\t' => self.selected = (self.selected + 1) % self.matches(config, data).len().max(1),
+			'\n' => return match self.matches(config, data).into_iter().nth(self.selected) {
+				Some(command) => Outcome::Run(command.action),
+				None => Outcome::Typing,
+			},
+			key if !key.is_control() => {
+				self.input.push(key);
+				self.selected = 0;
+			},
+			_ => {},
+		}
+		return Outcome::Typing;
+	}
+	
+	/**
+	 * The commands matching the input, the best first and in the order of commands() among equals.
+	*/
+	pub fn matches(&self, config: &Config, data: &ApiResponse) -> Vec<Command> {
+		let mut matches: Vec<(i32, Command)> = commands(config, data, &self.input).into_iter()
+			.filter_map(|command| score(&self.input, &command.label).map(|score| (score, command)))
+			.collect();
+		matches.sort_by_key(|(score, _)| -score);
+		return matches.into_iter().map(|(_, command)| command).collect();
+	}
+}
+
+/**
+ * The commands available: the profiles, the pages, the layouts, the refresh intervals (also the number typed in the input), the
+ * snapshot, the tags shown and the metrics to hide or show.
+*/
+pub fn commands(config: &Config, data: &ApiResponse, input: &str) -> Vec<Command> {
+	let command = |label: String, action| Command { label, action };
+	let mut commands: Vec<Command> = config.profiles.iter()
+		.map(|name| command(format!("profile {}", name), Action::Profile(name.clone())))
+		.collect();
+	commands.extend(config.pages.iter().enumerate()
+		.map(|(index, page)| command(format!("page {}", page.name), Action::Page(index + 1))));
+	let layouts = [Layout::Cards, Layout::Table, Layout::Minimal, Layout::Floors, Layout::Floorplan, Layout::Auto];
+	commands.extend(layouts.into_iter()
+		.filter(|layout| *layout != Layout::Floorplan || config.floorplan.is_some())
+		.map(|layout| command(format!("layout {}", layout_name(layout)), Action::Layout(layout))));
+	let typed = input.split_whitespace().filter_map(|word| word.parse::<i64>().ok()).find(|seconds| *seconds > 0);
+	commands.extend(typed.into_iter().chain(INTERVALS.into_iter().filter(|seconds| Some(*seconds) != typed))
+		.map(|seconds| command(format!("interval {} s", seconds), Action::Interval(seconds))));
+	commands.push(command("snapshot".into(), Action::Snapshot));
+	commands.extend(data.iter().map(|tag| command(format!("tag {}", tag.name), Action::Tag(tag.id.clone()))));
+	commands.extend(config.metrics.iter().map(|metric| command(format!("toggle {}", metric.name()), Action::Toggle(*metric))));
+	return commands;
+}
+
+fn layout_name(layout: Layout) -> &'static str {
+	return match layout {
+		Layout::Cards => "cards",
+		Layout::Table => "table",
+		Layout::Minimal => "minimal",
+		Layout::Floors => "floors",
+		Layout::Floorplan => "floorplan",
+		Layout::Auto => "auto",
+	};
+}
+
+/**
+ * How well the label matches the typed pattern, None if it doesn't have the characters of the pattern in order. Runs
+ * of characters and characters at the start of words count more than scattered ones. Case and spaces are ignored.
+*/
+fn score(pattern: &str, label: &str) -> Option<i32> {
+	let label: Vec<char> = label.to_lowercase().chars().collect();
+	let mut score = 0;
+	let mut position = 0;
+	let mut previous: Option<usize> = None;
+	for character in pattern.to_lowercase().chars().filter(|character| !character.is_whitespace()) {
+		let found = position + label[position..].iter().position(|candidate| *candidate == character)?;
+		score += 1;
+		if found > 0 && previous == Some(found - 1) {
+			score += 2;
+		}
+		if found == 0 || label[found - 1] == ' ' {
+			score += 3;
+		}
+		previous = Some(found);
+		position = found + 1;
+	}
+	return Some(score);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::Tag;
+	
+	fn tag(id: &str, name: &str) -> Tag {
+		return Tag::test(id, name);
+	}
+	
+	#[test]
+	fn fuzzy_matching() {
+		let config = Config::default();
+		let data = vec![tag("a", "Sauna"), tag("b", "Storage")];
+		let labels = |input: &str| -> Vec<String> {
+			let palette = Palette { input: input.into(), selected: 0 };
+			return palette.matches(&config, &data).into_iter().map(|command| command.label).collect();
+		};
+		assert_eq!(labels("sau")[0], "tag Sauna");
+		assert_eq!(labels("tsto")[0], "tag Storage");
+		assert_eq!(labels("int 45")[0], "interval 45 s");
+		assert!(labels("xyz").is_empty());
+		assert_eq!(score("", "snapshot"), Some(0));
+		
+		let mut palette = Palette::new();
+		for key in "snap".chars() {
+			assert_eq!(palette.key(key, &config, &data), Outcome::Typing);
+		}
+		assert_eq!(palette.key('\n', &config, &data), Outcome::Run(Action::Snapshot));
+		palette.input.clear();
+		assert_eq!(palette.key('\x7f', &config, &data), Outcome::Cancel);
+	}
+}
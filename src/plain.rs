@@ -0,0 +1,150 @@
+use std::io::{self, IsTerminal, Write};
+use std::{thread, time};
+use chrono::{DateTime, Utc};
+use crate::alerts::AlertMetric;
+use crate::config::Config;
+use crate::i18n::Text;
+use crate::model::{Metric, Tag};
+use crate::poller::Poller;
+use crate::ranges::RangeColor;
+use crate::shutdown;
+use crate::table;
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+// Back to the top left and clear the screen, for the next table in the watch mode.
+const CLEAR: &str = "\x1b[H\x1b[2J";
+
+/**
+ * Plain mode: print the tags as a table to stdout, once or with --watch on every refresh, without curses. For tmux
+ * panes, serial consoles and logging into files. On a terminal each table replaces the previous one and the values
+ * are colored with ANSI escapes, otherwise the tables follow each other with the time above them.
+*/
+pub fn run(poller: &mut Poller, watch: bool) {
+	let terminal = io::stdout().is_terminal();
+	let colors = terminal && !poller.config.no_color;
+	
+	poller.poll();
+	print(poller, if watch && terminal { CLEAR } else { "" }, colors);
+	
+	while watch && !shutdown::requested() {
+		thread::sleep(time::Duration::from_secs(1));
+		// Alerts also change without new data, e.g. unreachable as the data gets older.
+		if !poller.poll() && poller.alert_events.is_empty() {
+			continue;
+		}
+		let separator = if terminal { CLEAR.to_string() } else { format!("\n{}\n", poller.config.zone().local(poller.clock.now()).format("%Y-%m-%d %H:%M:%S")) };
+		print(poller, &separator, colors);
+	}
+}
+
+fn print(poller: &Poller, separator: &str, colors: bool) {
+	let lang = poller.config.lang();
+	let mut text = format!("{}{}", separator, table(&poller.data, &poller.config, poller.clock.now(), colors));
+	for error in poller.errors() {
+		text.push_str(&format!("\n{}", lang.text(error)));
+	}
+	// Nobody to tell if stdout is gone, e.g. the pager quit.
+	let mut stdout = io::stdout().lock();
+	let _ = writeln!(stdout, "{}", text).and_then(|_| stdout.flush());
+}
+
+/**
+ * The tags as an aligned table: the name column as wide as the longest name up to the name width of the table, then
+ * the temperature, humidity, pressure and battery voltage right aligned with their trends, and when they were
+ * updated.
+*/
+pub fn table(data: &[Tag], config: &Config, now: DateTime<Utc>, colors: bool) -> String {
+	let lang = config.lang();
+	let table = &config.table;
+	let width = data.iter().map(|tag| table::columns(tag.label())).max().unwrap_or(0).min(table.name_width);
+	let paint = |color: Option<&str>, text: String| match color {
+		Some(color) if colors => format!("{}{}{}", color, text, RESET),
+		_ => text,
+	};
+	
+	// Right aligned over the numbers, the trends after them.
+	let column = |title: &str| format!("{:>width$} ", table::fit(title, table.value_width).trim_end(), width = table.value_width);
+	let header = [table::fit("", width + 1), column(config.units.temperature_symbol().trim()), column("%"), column("hPa"), column("V")].concat();
+	let mut lines = vec![format!("{} {}", header, lang.text(Text::Updated))];
+	
+	for tag in data {
+		let missing = &config.missing_value;
+		let precision = config.units.precision;
+		let value = |metric: AlertMetric, value: Option<f64>, decimals: usize, trend: &str, color: Option<RangeColor>| {
+			if !config.shows(tag, metric) {
+				return " ".repeat(table.value_width + 1);
+			}
+			let color = color.map(|color| format!("\x1b[3{}m", color.index()));
+			return format!("{}{}", paint(color.as_deref(), table.number(value, decimals, missing, lang)), trend);
+		};
+		// Colors of values that may not be true anymore would mislead.
+		let fresh = |color: Option<RangeColor>| color.filter(|_| !tag.unreachable);
+		
+		let temperature = tag.temperature().map(|temperature| config.units.convert(temperature));
+		let battery = match (tag.battery_low, tag.battery_critical) {
+			(_, true) => Some(RangeColor::Red),
+			(true, _) => Some(RangeColor::Yellow),
+			_ => None,
+		};
+		let updated = match tag.datetime.parse::<DateTime<Utc>>() {
+			_ if tag.unreachable => lang.text(Text::Unreachable).to_string(),
+			Ok(datetime) => lang.time_ago((now - datetime).num_seconds()),
+			Err(_) => lang.unknown().to_string(),
+		};
+		let row = [
+			table::ellipsize(tag.label(), width),
+			" ".into(),
+			value(AlertMetric::Temperature, temperature, precision.temperature, trend(&tag.temperature, config), fresh(config.temperature_color(tag))),
+			value(AlertMetric::Humidity, tag.humidity(), precision.humidity, trend(&tag.humidity, config), fresh(config.humidity_color(tag))),
+			value(AlertMetric::Pressure, tag.pressure(), precision.pressure, trend(&tag.pressure, config), None),
+			value(AlertMetric::BatteryVoltage, tag.battery_voltage, precision.battery_voltage, "", battery),
+			"  ".into(),
+			updated,
+		].concat();
+		lines.push(if tag.unreachable { paint(Some(DIM), row) } else { row });
+	}
+	
+	return lines.join("\n");
+}
+
+/**
+ * The trend symbol after a value, a space for a missing one.
+*/
+fn trend<'a>(metric: &Option<Metric>, config: &'a Config) -> &'a str {
+	return match metric {
+		Some(metric) => config.vocabulary.symbol(metric.trend.unwrap_or(0), config.glyphs()),
+		None => " ",
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn tag(name: &str, temperature: f64, unreachable: bool) -> Tag {
+		return Tag {
+			datetime: "2024-05-01T11:58:00Z".into(),
+			temperature: Some(Metric::from_current(temperature)),
+			humidity: Some(Metric::from_current(40.0)),
+			battery_voltage: Some(2.9),
+			unreachable,
+			..Tag::test(name, name)
+		};
+	}
+	
+	#[test]
+	fn columns_fit_the_longest_name() {
+		let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let config = Config::default();
+		let data = [tag("Sauna", 65.04, false), tag("Living room", -1.0, true)];
+		assert_eq!(table(&data, &config, now, false), [
+			"                  °C        %      hPa        V  Updated",
+			"Sauna           65.0▸      40▸       —     2.90  2 minutes ago",
+			"Living room     -1.0▸      40▸       —     2.90  Unreachable",
+		].join("\n"));
+		
+		let colored = table(&data, &config, now, true);
+		assert!(colored.lines().nth(2).unwrap().starts_with("\x1b[2mLiving room"));
+	}
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use crate::formatters::{self, FormatError};
+use crate::model::{ApiResponse, Tag};
+use crate::recorder::Recorder;
+
+// How often the plugin is asked for the data, like the API is fetched.
+const REFRESH: Duration = Duration::from_secs(60);
+// Wait before starting a plugin that exited again, so that a broken one doesn't run in a loop.
+const RESTART: Duration = Duration::from_secs(10);
+// Lines waiting for a slow sink. The newer ones are dropped when it's full rather than blocking the polling.
+const SINK_BUFFER: usize = 1000;
+
+/**
+ * External executable as a data source, for backends the crate doesn't know. The command is run with sh and talks
+ * JSON lines over stdin and stdout:
+ *
+ * - The client writes {"type": "refresh"} on start and then every minute. Plugins that fetch on request answer it,
+ *   the others can ignore it.
+ * - The plugin writes the tags as a line of JSON in any supported format, e.g. the custom API's array, whenever it
+ *   has them. Each line replaces the data, like a gateway push.
+ * - stdin is closed when the client quits, and the plugin should exit then. A plugin that exits on its own is
+ *   started again.
+ *
+ * The last line of stderr is shown with the problem if the plugin exits.
+*/
+pub struct Plugin {
+	latest: Arc<Mutex<Option<ApiResponse>>>,
+	// Why the latest line was rejected or the plugin exited, cleared by the next accepted line.
+	problem: Arc<Mutex<Option<FormatError>>>,
+	stop: Arc<AtomicBool>,
+}
+
+impl Plugin {
+	/**
+	 * Start the command and keep it running in a background thread.
+	*/
+	pub fn start(command: &str, recorder: Option<Recorder>) -> Result<Plugin, String> {
+		let latest = Arc::new(Mutex::new(None));
+		let problem = Arc::new(Mutex::new(None));
+		let stop = Arc::new(AtomicBool::new(false));
+		
+		// The first start is checked here so that a command that can't be run is an error on startup.
+		let child = spawn(command).map_err(|error| format!("Could not start the command {}: {}", command, error))?;
+		let command = command.to_string();
+		let (thread_latest, thread_problem, thread_stop) = (Arc::clone(&latest), Arc::clone(&problem), Arc::clone(&stop));
+		thread::spawn(move || {
+			let mut child = Some(child);
+			while !thread_stop.load(Ordering::Relaxed) {
+				let exited = match child.take().map_or_else(|| spawn(&command), Ok) {
+					Ok(child) => supervise(child, recorder.as_ref(), &thread_latest, &thread_problem, &thread_stop),
+					Err(error) => Some(FormatError::new(format!("Could not start the command: {}", error), &command)),
+				};
+				if let Some(exited) = exited {
+					*thread_problem.lock().unwrap() = Some(exited);
+				}
+				thread::sleep(RESTART);
+			}
+		});
+		
+		return Ok(Plugin { latest, problem, stop });
+	}
+	
+	/**
+	 * Take the data received since the last call, if any.
+	*/
+	pub fn take(&self) -> Option<ApiResponse> {
+		return self.latest.lock().ok()?.take();
+	}
+	
+	pub fn problem(&self) -> Option<FormatError> {
+		return self.problem.lock().ok()?.clone();
+	}
+}
+
+impl Drop for Plugin {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+fn spawn(command: &str) -> std::io::Result<Child> {
+	return Command::new("sh")
+		.arg("-c")
+		.arg(command)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn();
+}
+
+/**
+ * Ask for the data every minute and read the lines until the plugin exits or the client stops. Returns why the
+ * plugin exited, with the last line it wrote to stderr.
+*/
+fn supervise(mut child: Child, recorder: Option<&Recorder>, latest: &Arc<Mutex<Option<ApiResponse>>>, problem: &Arc<Mutex<Option<FormatError>>>, stop: &AtomicBool) -> Option<FormatError> {
+	let mut stdin = child.stdin.take();
+	let stdout = child.stdout.take().map(BufReader::new);
+	let stderr = child.stderr.take().map(BufReader::new);
+	
+	let (latest, problem, recorder) = (Arc::clone(latest), Arc::clone(problem), recorder.cloned());
+	let reader = thread::spawn(move || {
+		for line in stdout.into_iter().flat_map(|stdout| stdout.lines()).map_while(Result::ok) {
+			if line.trim().is_empty() {
+				continue;
+			}
+			if let Some(recorder) = &recorder {
+				recorder.record("command", Utc::now(), &line);
+			}
+			match formatters::parse_text(&line) {
+				Ok(parsed) => {
+					*latest.lock().unwrap() = Some(parsed.data);
+					*problem.lock().unwrap() = parsed.skipped;
+				},
+				Err(error) => *problem.lock().unwrap() = Some(error),
+			}
+		}
+	});
+	let last_error = Arc::new(Mutex::new(String::new()));
+	let thread_last_error = Arc::clone(&last_error);
+	let error_reader = thread::spawn(move || {
+		for line in stderr.into_iter().flat_map(|stderr| stderr.lines()).map_while(Result::ok) {
+			*thread_last_error.lock().unwrap() = line;
+		}
+	});
+	
+	let mut refreshed: Option<Instant> = None;
+	let status = loop {
+		if stop.load(Ordering::Relaxed) {
+			let _ = child.kill();
+			let _ = child.wait();
+			return None;
+		}
+		if let Ok(Some(status)) = child.try_wait() {
+			break status;
+		}
+		if refreshed.is_none_or(|refreshed| refreshed.elapsed() >= REFRESH) {
+			refreshed = Some(Instant::now());
+			// A plugin that doesn't read stdin may have closed it.
+			if stdin.as_mut().is_some_and(|stdin| refresh(stdin).is_err()) {
+				stdin = None;
+			}
+		}
+		thread::sleep(Duration::from_millis(100));
+	};
+	
+	// The lines written before exiting.
+	let _ = reader.join();
+	let _ = error_reader.join();
+	let last_error = last_error.lock().unwrap().clone();
+	return Some(FormatError::new(format!("The command exited with {}", status), &last_error));
+}
+
+/**
+ * External executable as an output, for storing or forwarding the readings somewhere the crate doesn't know. The
+ * command is run with sh and gets the readings on stdin as JSON lines, one per new measurement of a tag:
+ *
+ * {"tag_id": "…", "tag_name": "…", "datetime": "…", "temperature": 21.5, "humidity": 40.0, "pressure": 1002.1,
+ *  "battery_voltage": 2.9, "movement_counter": 12, "measurement_sequence": 345, "rssi": -65}
+ *
+ * The values are calibrated like in the store and missing ones are null. The lines are also a replay file
+ * when wrapped into an array. stdin is closed when the client quits. A sink that exits is started again with the next
+ * reading after a while, and the readings in between are dropped.
+*/
+pub struct Sink {
+	sender: SyncSender<String>,
+	// The measurement time last sent per tag ID, so that an unchanged reading isn't sent again on every fetch.
+	sent: HashMap<String, String>,
+	// Why the sink failed since the last call, with the last line it wrote to stderr.
+	failure: Arc<Mutex<Option<String>>>,
+}
+
+impl Sink {
+	/**
+	 * Start the command and feed it from a background thread.
+	*/
+	pub fn start(command: &str) -> Result<Sink, String> {
+		let child = spawn_sink(command).map_err(|error| format!("Could not start the sink command {}: {}", command, error))?;
+		let (sender, receiver) = mpsc::sync_channel(SINK_BUFFER);
+		let failure = Arc::new(Mutex::new(None));
+		
+		let command = command.to_string();
+		let thread_failure = Arc::clone(&failure);
+		thread::spawn(move || {
+			let mut child = Some(child);
+			loop {
+				let child = match child.take().map_or_else(|| spawn_sink(&command), Ok) {
+					Ok(child) => child,
+					Err(error) => {
+						*thread_failure.lock().unwrap() = Some(format!("Could not start the sink command: {}", error));
+						thread::sleep(RESTART);
+						continue;
+					},
+				};
+				match feed(child, &receiver) {
+					Some(exited) => *thread_failure.lock().unwrap() = Some(exited),
+					// The client quit.
+					None => return,
+				}
+				thread::sleep(RESTART);
+				// Readings that arrived while the sink was down are old by now.
+				while receiver.try_recv().is_ok() {}
+			}
+		});
+		
+		return Ok(Sink { sender, sent: HashMap::new(), failure });
+	}
+	
+	/**
+	 * Queue the new readings of the data for the sink.
+	*/
+	pub fn send(&mut self, data: &ApiResponse) {
+		for tag in data {
+			if tag.measured_at().is_none() || self.sent.get(&tag.id) == Some(&tag.datetime) {
+				continue;
+			}
+			self.sent.insert(tag.id.clone(), tag.datetime.clone());
+			// A full buffer means the sink is stuck, and its failure is reported when it exits.
+			let _ = self.sender.try_send(reading(tag).to_string());
+		}
+	}
+	
+	/**
+	 * Why the sink failed since the last call, if it did.
+	*/
+	pub fn take_failure(&self) -> Option<String> {
+		return self.failure.lock().ok()?.take();
+	}
+}
+
+fn spawn_sink(command: &str) -> std::io::Result<Child> {
+	return Command::new("sh")
+		.arg("-c")
+		.arg(command)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::null())
+		.stderr(Stdio::piped())
+		.spawn();
+}
+
+/**
+ * Write the lines to the sink until it exits or the client stops. Returns why the sink exited, with the last line it
+ * wrote to stderr.
+*/
+fn feed(mut child: Child, receiver: &Receiver<String>) -> Option<String> {
+	let mut stdin = child.stdin.take()?;
+	let stderr = child.stderr.take().map(BufReader::new);
+	let error_reader = thread::spawn(move || {
+		return stderr.into_iter().flat_map(|stderr| stderr.lines()).map_while(Result::ok).last().unwrap_or_default();
+	});
+	
+	let written = loop {
+		let Ok(line) = receiver.recv() else {
+			// Closing stdin tells the sink to finish.
+			drop(stdin);
+			let _ = child.wait();
+			return None;
+		};
+		if let Err(error) = writeln!(stdin, "{}", line).and_then(|_| stdin.flush()) {
+			break error;
+		}
+	};
+	
+	drop(stdin);
+	let status = child.wait().map(|status| status.to_string()).unwrap_or_else(|_| written.to_string());
+	let last_error = error_reader.join().unwrap_or_default();
+	if last_error.is_empty() {
+		return Some(format!("The sink command exited with {}", status));
+	}
+	return Some(format!("The sink command exited with {}: {}", status, last_error));
+}
+
+/**
+ * The normalized reading of a tag as a line for the sink.
+*/
+fn reading(tag: &Tag) -> serde_json::Value {
+	return serde_json::json!({
+		"tag_id": tag.id,
+		"tag_name": tag.label(),
+		"datetime": tag.datetime,
+		"temperature": tag.temperature(),
+		"humidity": tag.humidity(),
+		"pressure": tag.pressure(),
+		"battery_voltage": tag.battery_voltage,
+		"movement_counter": tag.movement_counter,
+		"measurement_sequence": tag.measurement_sequence,
+		"rssi": tag.rssi,
+	});
+}
+
+fn refresh(stdin: &mut ChildStdin) -> std::io::Result<()> {
+	writeln!(stdin, "{}", serde_json::json!({"type": "refresh"}))?;
+	return stdin.flush();
+}
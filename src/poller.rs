@@ -0,0 +1,826 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK};
+use reqwest::blocking::Client;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use crate::actions::{ActionEvent, Actions};
+use crate::advisories;
+use crate::alerts::{Alert, AlertEngine, AlertEvent, Severity};
+#[cfg(unix)]
+use crate::attach::{Attached, Message, Share};
+use crate::battery;
+use crate::calibration;
+use crate::clock::{self, Clock};
+use crate::config::Config;
+use crate::degree_days;
+use crate::events::{EventLog, LogEntry};
+use crate::formatters::{self, Format, FormatError, Parsed};
+use crate::forward::Forwarder;
+use crate::history::History;
+use crate::hooks::Hooks;
+use crate::http;
+use crate::i18n::Text;
+use crate::listener::Listener;
+use crate::mqtt::Mqtt;
+use crate::output::Output;
+use crate::plugin::{Plugin, Sink};
+use crate::recorder::Recorder;
+use crate::reference;
+use crate::simulation::{self, Feed};
+use crate::staleness;
+use crate::model::{ApiResponse, Tag};
+use crate::scheduler::Scheduler;
+use crate::store::{AlertRecord, Store};
+use crate::tags;
+use crate::telemetry;
+use crate::trend;
+use crate::update::UpdateCheck;
+use crate::websocket::Stream;
+use crate::wind::Wind;
+
+// Days of the event log loaded from the store on startup.
+const EVENT_LOG_DAYS: i64 = 7;
+// Pages of a paginated API response followed at most, in case the links go around in a circle.
+const MAX_PAGES: usize = 100;
+// An incremental API is fetched in full this often, so that the tags removed from it go away.
+const FULL_FETCH_HOURS: i64 = 1;
+// The wall time running ahead of the awake time by this much between polls means the machine was suspended.
+const SUSPEND_SECONDS: i64 = 60;
+
+/**
+ * Owns the data sources and the latest data. Shared by the TUI and the headless mode.
+*/
+pub struct Poller {
+	pub config: Config,
+	// Time of the data: the staleness, the min/max days, the alerts, the event log and the daily digest. The replayed
+	// timeline in replays, so that alert tuning against a recorded incident behaves as it did.
+	pub clock: Arc<dyn Clock>,
+	// The real time, which the fetch schedule, the throttling and the logs of this run keep to even in replays.
+	pub wall: Arc<dyn Clock>,
+	client: Client,
+	pub data: ApiResponse,
+	pub history: History,
+	pub network_error: Option<String>,
+	// Data the sources sent but that couldn't be parsed, per source.
+	pub problems: Vec<(Source, FormatError)>,
+	// Result of the latest conditional API request. None if the API doesn't support them.
+	pub cache_status: Option<CacheStatus>,
+	// Format of the latest API response that could be parsed.
+	pub api_format: Option<Format>,
+	validators: Validators,
+	// The latest API data, which the changes from an incremental API are merged into.
+	api_data: ApiResponse,
+	full_fetched: Option<DateTime<Utc>>,
+	pub store_error: bool,
+	pub wind: Option<Wind>,
+	pub alerts: AlertEngine,
+	// Alerts started and cleared on the latest poll.
+	pub alert_events: Vec<AlertEvent>,
+	// Alerts and network errors with their times, for the event log screen.
+	pub events: EventLog,
+	pub hook_error: bool,
+	hooks: Hooks,
+	pub action_error: bool,
+	actions: Actions,
+	// Why the output sink last failed.
+	pub sink_error: Option<String>,
+	sink: Option<Sink>,
+	// Why an output last failed to send a refresh.
+	pub output_error: Option<String>,
+	outputs: Vec<Box<dyn Output>>,
+	// When each tag last came in from a source, by tag ID, on the data's clock. Not when it measured, see datetime.
+	pub fetched: HashMap<String, DateTime<Utc>>,
+	// The source each tag came from, by tag ID. None for the simulated ones.
+	pub sources: HashMap<String, Source>,
+	// Growing degree days of the season per tag ID.
+	pub degree_days: HashMap<String, f64>,
+	listener: Option<Listener>,
+	plugin: Option<Plugin>,
+	stream: Option<Stream>,
+	// Demo or replay data instead of the API and the listener.
+	feed: Option<Box<dyn Feed>>,
+	// The TUIs attached to the headless mode, or the headless instance this TUI is attached to.
+	#[cfg(unix)]
+	share: Option<Share>,
+	#[cfg(unix)]
+	attached: Option<Attached>,
+	recorder: Option<Recorder>,
+	store: Option<Store>,
+	// When to fetch from the API, paused and refreshed with the p and r keys.
+	pub scheduler: Scheduler,
+	pub update: Option<UpdateCheck>,
+	degree_days_refresh: Option<DateTime<Utc>>,
+	// Why the latest reload of the config failed, with the previous config still in use.
+	pub reload_error: Option<String>,
+	// Wall and awake time of the previous poll, to tell a suspend.
+	polled: Option<(DateTime<Utc>, DateTime<Utc>)>,
+	// When the machine last woke from a suspend, on the data's clock. The reports missed while it slept don't make
+	// the tags unreachable.
+	pub woke: Option<DateTime<Utc>>,
+}
+
+impl Poller {
+	/**
+	 * Open the store and start the listener as configured.
+	*/
+	pub fn new(config: Config, wall: Arc<dyn Clock>) -> Result<Poller, String> {
+		// Replays run on their own clock, from the start of the recording.
+		let feed = match &config.simulation {
+			Some(simulation) => Some(simulation::open(simulation, config.replay_speed)?),
+			None => None,
+		};
+		let clock = feed.as_ref().and_then(|feed| feed.clock()).unwrap_or_else(|| Arc::clone(&wall));
+		
+		// Long enough for the 7 day min/max window.
+		let mut history = History::new(chrono::Duration::days(7));
+		history.set_capacity(config.history_samples);
+		
+		let store = match &config.store {
+			Some(path) => Some(Store::open(path)
+				.map_err(|error| format!("Could not open the history store {}: {}", path.display(), error))?),
+			None => None,
+		};
+		
+		// Seed the history so that min/max are right from the start.
+		if let Some(store) = &store {
+			if let Ok(readings) = store.readings(None, Some(clock.now() - chrono::Duration::days(7))) {
+				history.seed(&readings);
+			}
+		}
+		
+		let recorder = match &config.record {
+			Some(dir) => Some(Recorder::open(dir)?),
+			None => None,
+		};
+		
+		let listener = match &config.listen {
+			Some(address) => Some(Listener::start(address, &config, recorder.clone())?),
+			None => None,
+		};
+		let plugin = match &config.command {
+			Some(command) => Some(Plugin::start(command, recorder.clone())?),
+			None => None,
+		};
+		let stream = match &config.websocket {
+			Some(url) => Some(Stream::start(url, recorder.clone())?),
+			None => None,
+		};
+		let sink = match &config.sink {
+			Some(command) => Some(Sink::start(command)?),
+			None => None,
+		};
+		#[cfg(unix)]
+		let share = match (&config.socket, config.headless) {
+			(Some(path), true) => Some(Share::start(path)?),
+			_ => None,
+		};
+		#[cfg(unix)]
+		let attached = match (&config.socket, config.attach) {
+			(Some(path), true) => Some(Attached::start(path)),
+			_ => None,
+		};
+		#[cfg(not(unix))]
+		if config.attach {
+			return Err("Attaching needs Unix sockets".into());
+		}
+		
+		// The event log of the previous runs.
+		let events = store.as_ref()
+			.and_then(|store| store.events(clock.now() - chrono::Duration::days(EVENT_LOG_DAYS)).ok())
+			.unwrap_or_default();
+		
+		// Alerts active before a restart continue with the same IDs.
+		let mut alerts = AlertEngine::new();
+		if let Some(store) = &store {
+			if let Ok(active) = store.active_alerts() {
+				alerts.restore(active);
+			}
+		}
+		
+		let client = http::client(&config.http)?;
+		let update = config.update_check.then(|| UpdateCheck::start(client.clone()));
+		// Sources with their own proxy get their own client.
+		let via = |proxy: Option<&str>| match proxy {
+			Some(proxy) => http::client_via(&config.http, Some(proxy)),
+			None => Ok(client.clone()),
+		};
+		let wind = match &config.wind {
+			Some(wind) => Some(Wind::new(wind.clone(), via(wind.proxy())?)),
+			None => None,
+		};
+		let api_client = via(config.api_proxy.as_deref())?;
+		let mut outputs: Vec<Box<dyn Output>> = Vec::new();
+		if let Some(url) = &config.forward.url {
+			outputs.push(Box::new(Forwarder::start(url, &config.forward, client.clone())?));
+		}
+		if let Some(url) = &config.mqtt.url {
+			outputs.push(Box::new(Mqtt::start(url, &config.mqtt, config.units.precision)?));
+		}
+		
+		let scheduler = Scheduler::new(chrono::Duration::seconds(60), config.align_refresh, wall.now());
+		return Ok(Poller {
+			actions: Actions::new(client),
+			config,
+			clock,
+			wall,
+			client: api_client,
+			wind,
+			alerts,
+			alert_events: Vec::new(),
+			events: EventLog::new(events),
+			hook_error: false,
+			hooks: Hooks::new(),
+			action_error: false,
+			sink_error: None,
+			sink,
+			output_error: None,
+			outputs,
+			fetched: HashMap::new(),
+			sources: HashMap::new(),
+			degree_days: HashMap::new(),
+			data: Vec::new(),
+			history,
+			network_error: None,
+			problems: Vec::new(),
+			cache_status: None,
+			api_format: None,
+			validators: Validators::default(),
+			api_data: Vec::new(),
+			full_fetched: None,
+			store_error: false,
+			listener,
+			plugin,
+			stream,
+			feed,
+			#[cfg(unix)]
+			share,
+			#[cfg(unix)]
+			attached,
+			recorder,
+			store,
+			scheduler,
+			update,
+			degree_days_refresh: None,
+			reload_error: None,
+			polled: None,
+			woke: None,
+		});
+	}
+	
+	/**
+	 * Switch to a reloaded config: the sources are started again, but the readings, the history, the alerts and the
+	 * event log are kept. On error the previous config stays in use.
+	*/
+	pub fn reload(&mut self, config: Config) -> Result<(), String> {
+		// The new listener may need the same port, and the new share the same socket. The attached TUIs connect again.
+		let listener = self.listener.take();
+		#[cfg(unix)]
+		drop(self.share.take());
+		let mut new = match Poller::new(config, Arc::clone(&self.wall)) {
+			Ok(new) => new,
+			Err(error) => {
+				drop(listener);
+				self.listener = match &self.config.listen {
+					Some(address) => Listener::start(address, &self.config, self.recorder.clone()).ok(),
+					None => None,
+				};
+				#[cfg(unix)]
+				{
+					self.share = self.config.socket.as_deref().filter(|_| self.config.headless).and_then(|path| Share::start(path).ok());
+				}
+				return Err(error);
+			},
+		};
+		new.data = std::mem::take(&mut self.data);
+		new.history = std::mem::replace(&mut self.history, History::new(chrono::Duration::zero()));
+		new.history.set_capacity(new.config.history_samples);
+		new.alerts = std::mem::replace(&mut self.alerts, AlertEngine::new());
+		new.events = std::mem::replace(&mut self.events, EventLog::new(Vec::new()));
+		new.fetched = std::mem::take(&mut self.fetched);
+		new.sources = std::mem::take(&mut self.sources);
+		new.degree_days = std::mem::take(&mut self.degree_days);
+		new.scheduler.paused = self.scheduler.paused;
+		new.woke = self.woke;
+		*self = new;
+		return Ok(());
+	}
+	
+	/**
+	 * Check the listener and fetch from the API when it's time. Returns true if new data arrived.
+	*/
+	pub fn poll(&mut self) -> bool {
+		let mut updated = false;
+		let now = self.clock.now();
+		let wall = self.wall.now();
+		let network_error = self.network_error.is_some();
+		
+		// Everything is out of date after a suspend, so all is fetched right away instead of on the schedule.
+		if self.resumed() {
+			self.woke = Some(now);
+			if !self.scheduler.paused {
+				self.scheduler.refresh();
+				if let Some(wind) = &mut self.wind {
+					wind.refresh();
+				}
+			}
+		}
+		
+		if let Some(mut new_data) = self.listener.as_ref().and_then(|listener| listener.take()) {
+			telemetry::span("refresh", || {
+				telemetry::span("normalize", || normalize(&mut new_data, &self.config));
+				self.receive(Some(Source::Listener), new_data);
+			});
+			updated = true;
+		}
+		if let Some(mut new_data) = self.feed.as_mut().and_then(|feed| feed.take(now)) {
+			normalize(&mut new_data, &self.config);
+			self.receive(None, new_data);
+			updated = true;
+		}
+		
+		if let Some(mut new_data) = self.plugin.as_ref().and_then(|plugin| plugin.take()) {
+			telemetry::span("refresh", || {
+				telemetry::span("normalize", || normalize(&mut new_data, &self.config));
+				self.receive(Some(Source::Command), new_data);
+			});
+			updated = true;
+		}
+		
+		if let Some(mut new_data) = self.stream.as_ref().and_then(|stream| stream.take()) {
+			telemetry::span("refresh", || {
+				telemetry::span("normalize", || normalize(&mut new_data, &self.config));
+				self.receive(Some(Source::WebSocket), new_data);
+			});
+			updated = true;
+		}
+		
+		// Attached to the headless instance: its tags as they are, with its history and alerts.
+		let mut shared_alerts: Option<Vec<Alert>> = None;
+		#[cfg(unix)]
+		if let Some(messages) = self.attached.as_ref().map(|attached| attached.take()) {
+			for message in messages {
+				match message {
+					Message::History { tag_id, samples } => self.history.extend(&tag_id, samples),
+					Message::Active { alerts } => {
+						self.alerts = AlertEngine::new();
+						self.alerts.restore(alerts);
+					},
+					Message::State { tags, sources, alerts, network_error } => {
+						self.receive(None, tags);
+						self.sources = sources;
+						self.network_error = network_error;
+						shared_alerts = Some(alerts);
+						updated = true;
+					},
+				}
+			}
+			if let Some(problem) = self.attached.as_ref().and_then(|attached| attached.problem()) {
+				self.network_error = Some(problem);
+			}
+		}
+		
+		let listener_problem = self.listener.as_ref().and_then(|listener| listener.problem());
+		self.set_problem(Source::Listener, listener_problem);
+		let plugin_problem = self.plugin.as_ref().and_then(|plugin| plugin.problem());
+		self.set_problem(Source::Command, plugin_problem);
+		let stream_problem = self.stream.as_ref().and_then(|stream| stream.problem());
+		self.set_problem(Source::WebSocket, stream_problem);
+		
+		if !self.config.api_url.is_empty() && self.scheduler.is_due(wall) {
+			let since = self.since(wall);
+			telemetry::span("refresh", || {
+				match fetch_data(&self.config, &self.client, &mut self.validators, since, self.recorder.as_ref(), wall) {
+					Ok(Some(parsed)) => {
+						self.api_format = Some(parsed.format);
+						self.api_data = match since {
+							Some(_) => tags::merge(&self.api_data, parsed.data),
+							None => {
+								self.full_fetched = Some(wall);
+								parsed.data
+							},
+						};
+						self.receive(Some(Source::Api), self.api_data.clone());
+						self.scheduler.succeeded(wall);
+						self.network_error = None;
+						// The tags that could be parsed are shown, and the skipped ones reported.
+						self.set_problem(Source::Api, parsed.skipped);
+						self.cache_status = self.validators.is_set().then_some(CacheStatus::Modified);
+						updated = true;
+					},
+					// Nothing changed since the previous response, no need to parse or render it again.
+					Ok(None) => {
+						self.scheduler.succeeded(wall);
+						self.network_error = None;
+						self.cache_status = Some(CacheStatus::NotModified);
+					},
+					// The source answered, but with data that can't be used.
+					Err(error) if error.is::<FormatError>() => {
+						self.scheduler.succeeded(wall);
+						self.network_error = None;
+						self.set_problem(Source::Api, error.downcast_ref::<FormatError>().cloned());
+					},
+					Err(error) => {
+						self.scheduler.failed(wall);
+						self.network_error = Some(error.to_string());
+					}
+				}
+			});
+		}
+		
+		// Pausing is for metered connections, so the wind isn't fetched either.
+		if let (Some(wind), false) = (&mut self.wind, self.scheduler.paused) {
+			wind.poll(wall);
+		}
+		
+		staleness::apply(&mut self.data, &self.config, &self.history, now, self.woke);
+		
+		self.alert_events = match shared_alerts {
+			Some(alerts) => self.alerts.sync(alerts),
+			None if self.config.attach => Vec::new(),
+			None => {
+				let advisories = if self.config.advisories { advisories::check(&self.data, &self.history, now) } else { Vec::new() };
+				self.alerts.evaluate(&self.data, &self.history, &self.config.alerts, &advisories, now)
+			},
+		};
+		if let (Some(store), false) = (&self.store, self.alert_events.is_empty()) {
+			self.store_error = store.record_alerts(&self.alert_events, now).is_err();
+		}
+		match self.hooks.update(self.alerts.severity(), &self.config.hooks) {
+			Ok(true) => self.hook_error = false,
+			Ok(false) => {},
+			Err(_) => self.hook_error = true,
+		}
+		
+		let network = match (&self.network_error, network_error) {
+			(Some(error), false) => Some((ActionEvent::NetworkError, Some(error.as_str()))),
+			(None, true) => Some((ActionEvent::NetworkRestored, None)),
+			_ => None,
+		};
+		let config = &self.config;
+		self.actions.run(&config.actions, &self.alert_events, network, &self.data, |severity| config.quiet(severity, now), now);
+		
+		let network = network.map(|(_, error)| error.map_or(Ok(()), Err));
+		let entries = LogEntry::of(&self.alert_events, network, now);
+		if let (Some(store), false) = (&self.store, entries.is_empty()) {
+			self.store_error = store.record_events(&entries).is_err();
+		}
+		self.events.add(entries);
+		if self.actions.take_failed() {
+			self.action_error = true;
+		}
+		if let Some(failure) = self.sink.as_ref().and_then(|sink| sink.take_failure()) {
+			self.sink_error = Some(failure);
+		}
+		// The tags are sent on as shown, with the staleness applied.
+		for output in &mut self.outputs {
+			if updated {
+				output.send(&self.data, now);
+			}
+			if let Some(failure) = output.take_failure() {
+				self.output_error = Some(failure);
+			}
+		}
+		#[cfg(unix)]
+		if let Some(share) = &mut self.share {
+			let active: Vec<Alert> = self.alerts.active().cloned().collect();
+			let state = Message::State {
+				tags: self.data.clone(),
+				sources: self.sources.clone(),
+				alerts: active.clone(),
+				network_error: self.network_error.clone(),
+			};
+			if updated || !self.alert_events.is_empty() || self.network_error.is_some() != network_error {
+				share.send(&state);
+			}
+			share.accept(&self.history, &state, active);
+		}
+		
+		return updated;
+	}
+	
+	/**
+	 * Has the machine been suspended since the previous poll: the wall time moved on further than the awake time.
+	*/
+	fn resumed(&mut self) -> bool {
+		let (wall, awake) = (self.wall.now(), self.wall.awake());
+		let resumed = self.polled.is_some_and(|(previous_wall, previous_awake)| {
+			(wall - previous_wall) - (awake - previous_awake) >= chrono::Duration::seconds(SUSPEND_SECONDS)
+		});
+		self.polled = Some((wall, awake));
+		return resumed;
+	}
+	
+	/**
+	 * Should the bell ring for the alerts started on the latest poll: the warnings and critical ones, if the bell is on
+	 * and it isn't the quiet hours.
+	*/
+	pub fn rings(&self) -> bool {
+		let now = self.clock.now();
+		return self.config.bell && self.alert_events.iter().any(|event| match event {
+			AlertEvent::Started(alert) => alert.severity > Severity::Info && !self.config.quiet(Some(alert.severity), now),
+			_ => false,
+		});
+	}
+	
+	/**
+	 * Open the link configured for the tag, if any.
+	*/
+	pub fn open_link(&self, tag: &Tag) {
+		if let Some(link) = self.config.tag(tag).and_then(|tag_config| tag_config.link.as_ref()) {
+			self.actions.open(link, tag, self.clock.now());
+		}
+	}
+	
+	/**
+	 * The time to ask the custom API for the changes since: the latest measurement it has sent. None for a full fetch,
+	 * the first one and then every FULL_FETCH_HOURS, and for the other formats.
+	*/
+	fn since(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+		if !self.config.api_incremental || self.api_format != Some(Format::Custom) {
+			return None;
+		}
+		if self.full_fetched.is_none_or(|fetched| now - fetched >= chrono::Duration::hours(FULL_FETCH_HOURS)) {
+			return None;
+		}
+		return self.api_data.iter().filter_map(Tag::measured_at).max();
+	}
+	
+	fn set_problem(&mut self, source: Source, problem: Option<FormatError>) {
+		self.problems.retain(|(problem_source, _)| *problem_source != source);
+		if let Some(problem) = problem {
+			self.problems.push((source, problem));
+		}
+	}
+	
+	/**
+	 * Record new data into the history and the store.
+	*/
+	fn receive(&mut self, source: Option<Source>, mut data: ApiResponse) {
+		self.history.add(&data);
+		self.history.fill_min_max(&mut data);
+		trend::fill(&mut data, &self.history, &self.config.trend);
+		
+		if let Some(store) = &self.store {
+			self.store_error = store.record(&data).is_err();
+		}
+		if let Some(sink) = &mut self.sink {
+			sink.send(&data);
+		}
+		let now = self.clock.now();
+		self.fetched.extend(data.iter().map(|tag| (tag.id.clone(), now)));
+		
+		// The tags of a source replace its previous ones, and the other sources' tags stay. Without a source, e.g.
+		// simulated, the data replaces all.
+		let mut merged = match source {
+			Some(source) => {
+				// In the previous order, without the tags gone from the source.
+				let sources = &self.sources;
+				let kept: ApiResponse = self.data.iter()
+					.filter(|tag| sources.get(&tag.id) != Some(&source) || data.iter().any(|new| new.id == tag.id))
+					.cloned()
+					.collect();
+				self.sources.extend(data.iter().map(|tag| (tag.id.clone(), source)));
+				tags::merge(&kept, data)
+			},
+			None => {
+				self.sources.clear();
+				data
+			},
+		};
+		// The reference tag may come from another source.
+		reference::apply(&mut merged, &self.config);
+		self.data = merged;
+		
+		if self.degree_days_refresh.is_none_or(|refresh| self.wall.now() - refresh >= chrono::Duration::minutes(15)) {
+			self.refresh_degree_days();
+		}
+	}
+	
+	/**
+	 * Sum the growing degree days of the tags that track them from the store.
+	*/
+	fn refresh_degree_days(&mut self) {
+		let Some(store) = &self.store else {
+			return;
+		};
+		self.degree_days_refresh = Some(self.wall.now());
+		let today = self.config.zone().date(self.clock.now());
+		
+		for tag in &self.data {
+			let Some(config) = self.config.tag(tag).and_then(|tag_config| tag_config.degree_days.as_ref()) else {
+				continue;
+			};
+			let Some(season_start) = config.season_start(today) else {
+				continue;
+			};
+			if let Ok(days) = store.daily_temperatures(&tag.id, season_start, self.config.zone()) {
+				let total = days.iter().map(|(_, min, max)| degree_days::daily(*min, *max, config.base)).sum();
+				self.degree_days.insert(tag.id.clone(), total);
+			}
+		}
+	}
+	
+	/**
+	 * The alert log of the history store since the time, the latest first. Empty without a store.
+	*/
+	pub fn alert_log(&self, since: DateTime<Utc>) -> Vec<AlertRecord> {
+		let mut records = self.store.as_ref()
+			.and_then(|store| store.alert_log(None, Some(since)).ok())
+			.unwrap_or_default();
+		records.reverse();
+		return records;
+	}
+	
+	pub fn wind_speed(&self) -> Option<f64> {
+		return self.wind.as_ref().and_then(|wind| wind.speed);
+	}
+	
+	/**
+	 * Errors to show to the user.
+	*/
+	pub fn errors(&self) -> Vec<Text> {
+		let mut errors = Vec::new();
+		if self.network_error.is_some() {
+			errors.push(Text::NetworkError);
+		}
+		// The network is fine, but a source sent data that couldn't be parsed, in full or in part.
+		if !self.problems.is_empty() {
+			errors.push(Text::DataError);
+		}
+		if self.store_error {
+			errors.push(Text::StoreError);
+		}
+		if self.recorder.as_ref().is_some_and(|recorder| recorder.failed()) {
+			errors.push(Text::RecordError);
+		}
+		if self.hook_error {
+			errors.push(Text::HookError);
+		}
+		if self.action_error {
+			errors.push(Text::ActionError);
+		}
+		if self.sink_error.is_some() {
+			errors.push(Text::SinkError);
+		}
+		if self.output_error.is_some() {
+			errors.push(Text::OutputError);
+		}
+		if self.reload_error.is_some() {
+			errors.push(Text::ReloadError);
+		}
+		return errors;
+	}
+}
+
+/**
+ * Where the data comes from: fetched from the API, pushed to the listener, written by the command or streamed over
+ * the WebSocket.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+	Api,
+	Listener,
+	Command,
+	WebSocket,
+}
+
+impl Source {
+	pub fn text(self) -> Text {
+		match self {
+			Source::Api => Text::SourceApi,
+			Source::Listener => Text::SourceListener,
+			Source::Command => Text::SourceCommand,
+			Source::WebSocket => Text::SourceWebSocket,
+		}
+	}
+}
+
+/**
+ * Whether the API data changed on the latest request.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+	Modified,
+	NotModified,
+}
+
+/**
+ * ETag and Last-Modified of the latest API response. Sent back with the next request so that the API can answer
+ * 304 Not Modified when nothing has changed.
+*/
+#[derive(Debug, Default)]
+struct Validators {
+	etag: Option<String>,
+	last_modified: Option<String>,
+}
+
+impl Validators {
+	fn is_set(&self) -> bool {
+		return self.etag.is_some() || self.last_modified.is_some();
+	}
+}
+
+/**
+ * Get data from the API, following the pages of a paginated response. None if it hasn't changed since the previous
+ * request.
+*/
+fn fetch_data(config: &Config, client: &Client, validators: &mut Validators, since: Option<DateTime<Utc>>, recorder: Option<&Recorder>, now: DateTime<Utc>) -> Result<Option<Parsed>, Box<dyn std::error::Error>> {
+	let mut url = Url::parse(&config.api_url)?;
+	if let Some(since) = since {
+		url.query_pairs_mut().append_pair("since", &since.to_rfc3339_opts(SecondsFormat::Secs, true));
+	}
+	
+	let mut result: Option<Parsed> = None;
+	for page in 0..MAX_PAGES {
+		// The validators are of the whole response from the configured URL, and the changes since a time are another.
+		let validators = (page == 0 && since.is_none()).then_some(&mut *validators);
+		let Some((body, next)) = telemetry::span("fetch", || fetch_page(config, client, url, validators))? else {
+			return Ok(None);
+		};
+		if let Some(recorder) = recorder {
+			recorder.record("api", now, &body);
+		}
+		
+		let mut parsed = telemetry::span("parse", || formatters::parse_text(&body))?;
+		match &mut result {
+			Some(pages) => {
+				pages.data.append(&mut parsed.data);
+				pages.skipped = pages.skipped.take().or(parsed.skipped);
+			},
+			None => result = Some(parsed),
+		}
+		match next {
+			Some(next) => url = next,
+			None => break,
+		}
+	}
+	
+	if let Some(parsed) = &mut result {
+		telemetry::span("normalize", || normalize(&mut parsed.data, config));
+	}
+	return Ok(result);
+}
+
+/**
+ * One page of the API response, with the URL of the next page if the API paginates with Link headers. None if it
+ * hasn't changed since the validators.
+*/
+fn fetch_page(config: &Config, client: &Client, url: Url, validators: Option<&mut Validators>) -> Result<Option<(String, Option<Url>)>, reqwest::Error> {
+	let mut request = client.get(url);
+	if let Some(token) = &config.cloud_token {
+		request = request.bearer_auth(token);
+	}
+	if let Some(validators) = &validators {
+		if let Some(etag) = &validators.etag {
+			request = request.header(IF_NONE_MATCH, etag);
+		}
+		if let Some(last_modified) = &validators.last_modified {
+			request = request.header(IF_MODIFIED_SINCE, last_modified);
+		}
+	}
+	
+	let response = request.send()?;
+	if response.status() == StatusCode::NOT_MODIFIED {
+		return Ok(None);
+	}
+	let response = response.error_for_status()?;
+	let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from);
+	if let Some(validators) = validators {
+		*validators = Validators { etag: header(ETAG), last_modified: header(LAST_MODIFIED) };
+	}
+	// Relative to the page, like links in HTML.
+	let next = header(LINK).as_deref().and_then(next_link).and_then(|next| response.url().join(&next).ok());
+	return response.text().map(|body| Some((body, next)));
+}
+
+/**
+ * The target of the rel="next" link of a Link header, e.g. <https://example.com/tags?page=2>; rel="next".
+*/
+fn next_link(header: &str) -> Option<String> {
+	return header.split(',').find_map(|link| {
+		let (target, params) = link.split_once(';')?;
+		let next = params.split(';')
+			.filter_map(|param| param.trim().strip_prefix("rel="))
+			.any(|rel| rel.trim_matches('"').split_whitespace().any(|rel| rel.eq_ignore_ascii_case("next")));
+		return next.then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string());
+	});
+}
+
+/**
+ * Apply the config to freshly received data.
+*/
+fn normalize(data: &mut ApiResponse, config: &Config) {
+	data.iter_mut().for_each(Tag::drop_invalid);
+	// RFC 3339 from here on, whatever the source sent.
+	for tag in data.iter_mut() {
+		if let Some(datetime) = clock::parse_datetime(&tag.datetime, config.source_timezone) {
+			tag.datetime = datetime.to_rfc3339();
+		}
+	}
+	tags::apply_aliases(data, config);
+	calibration::apply(data, config);
+	battery::apply(data, config);
+}
@@ -0,0 +1,69 @@
+use toml::{Table, Value};
+
+/**
+ * Names of the profiles in the config file, the [profile.NAME] tables, in alphabetical order.
+*/
+pub fn names(file: &Table) -> Vec<String> {
+	return match file.get("profile") {
+		Some(Value::Table(profiles)) => profiles.keys().cloned().collect(),
+		_ => Vec::new(),
+	};
+}
+
+/**
+ * The config file with the profile laid over it: the keys of the profile replace the ones at the top, and its tables
+ * are merged into the tables of the same name, e.g. [profile.cottage.tags.sauna] only changes the keys it has of
+ * [tags.sauna]. Arrays are replaced as a whole, e.g. the sources or the alerts.
+*/
+pub fn apply(mut file: Table, name: &str) -> Result<Table, String> {
+	let profile = match file.remove("profile") {
+		Some(Value::Table(mut profiles)) => profiles.remove(name),
+		_ => None,
+	};
+	let Some(Value::Table(profile)) = profile else {
+		return Err(format!("No profile \"{}\" in the config file", name));
+	};
+	merge(&mut file, profile);
+	return Ok(file);
+}
+
+fn merge(base: &mut Table, overlay: Table) {
+	for (key, value) in overlay {
+		match (base.get_mut(&key), value) {
+			(Some(Value::Table(base)), Value::Table(overlay)) => merge(base, overlay),
+			(_, value) => {
+				base.insert(key, value);
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn profiles() {
+		let file: Table = toml::from_str(r#"
+			theme = "default"
+			layout = "cards"
+			[tags.sauna]
+			name = "Sauna"
+			order = 1
+			[profile.cottage]
+			theme = "solarized"
+			[profile.cottage.tags.sauna]
+			name = "Cottage sauna"
+			[profile.home]
+		"#).unwrap();
+		assert_eq!(names(&file), ["cottage", "home"]);
+		
+		let cottage = apply(file.clone(), "cottage").unwrap();
+		assert_eq!(cottage["theme"].as_str(), Some("solarized"));
+		assert_eq!(cottage["layout"].as_str(), Some("cards"));
+		assert_eq!(cottage["tags"]["sauna"]["name"].as_str(), Some("Cottage sauna"));
+		assert_eq!(cottage["tags"]["sauna"]["order"].as_integer(), Some(1));
+		assert!(!cottage.contains_key("profile"));
+		assert!(apply(file, "office").is_err());
+	}
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use crate::config::Config;
+use crate::metrics::DerivedMetric;
+use crate::store::Reading;
+
+/**
+ * Metric of the stored readings to answer a query about.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum QueryMetric {
+	Temperature,
+	Humidity,
+	Pressure,
+	BatteryVoltage,
+	DewPoint,
+	AbsoluteHumidity,
+	HeatIndex,
+	VaporPressureDeficit,
+}
+
+impl QueryMetric {
+	fn derived(self) -> Option<DerivedMetric> {
+		return match self {
+			QueryMetric::DewPoint => Some(DerivedMetric::DewPoint),
+			QueryMetric::AbsoluteHumidity => Some(DerivedMetric::AbsoluteHumidity),
+			QueryMetric::HeatIndex => Some(DerivedMetric::HeatIndex),
+			QueryMetric::VaporPressureDeficit => Some(DerivedMetric::VaporPressureDeficit),
+			_ => None,
+		};
+	}
+	
+	pub fn value(self, reading: &Reading) -> Option<f64> {
+		return match self {
+			QueryMetric::Temperature => Some(reading.temperature),
+			QueryMetric::Humidity => Some(reading.humidity),
+			QueryMetric::Pressure => reading.pressure,
+			QueryMetric::BatteryVoltage => reading.battery_voltage,
+			derived => Some(derived.derived()?.value(reading.temperature, reading.humidity)),
+		};
+	}
+	
+	/**
+	 * The value with the unit, in the configured units and language.
+	*/
+	pub fn format(self, value: f64, config: &Config) -> String {
+		let (units, lang) = (&config.units, config.lang());
+		return match self {
+			QueryMetric::Temperature => units.temperature(value, units.precision.temperature, lang),
+			QueryMetric::Humidity => format!("{}%", units.humidity(value, units.precision.humidity, lang)),
+			QueryMetric::Pressure => format!("{} hPa", units.pressure(value, units.precision.pressure, lang)),
+			QueryMetric::BatteryVoltage => format!("{} V", lang.number(value, units.precision.battery_voltage)),
+			derived => derived.derived().map(|derived| derived.format_value(value, units, lang)).unwrap_or_default(),
+		};
+	}
+}
+
+/**
+ * How the values of the period are summed up.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Aggregate {
+	Min,
+	Max,
+	Avg,
+	// The latest value.
+	Last,
+	// The latest minus the first.
+	Change,
+	Count,
+}
+
+/**
+ * The aggregate of the values in chronological order, with the time of the value for the ones that pick a single
+ * reading. None without values.
+*/
+pub fn aggregate(values: &[(DateTime<Utc>, f64)], aggregate: Aggregate) -> Option<(f64, Option<DateTime<Utc>>)> {
+	let (first, last) = (values.first()?, values.last()?);
+	let pick = |better: fn(f64, f64) -> bool| values.iter()
+		.fold(first, |picked, value| if better(value.1, picked.1) { value } else { picked });
+	return Some(match aggregate {
+		Aggregate::Min => {
+			let (datetime, value) = pick(|value, picked| value < picked);
+			(*value, Some(*datetime))
+		},
+		Aggregate::Max => {
+			let (datetime, value) = pick(|value, picked| value > picked);
+			(*value, Some(*datetime))
+		},
+		Aggregate::Avg => (values.iter().map(|(_, value)| value).sum::<f64>() / values.len() as f64, None),
+		Aggregate::Last => (last.1, Some(last.0)),
+		Aggregate::Change => (last.1 - first.1, None),
+		Aggregate::Count => (values.len() as f64, None),
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn aggregates() {
+		let at = |time: &str| format!("2024-05-01T{}:00Z", time).parse::<DateTime<Utc>>().unwrap();
+		let values = [(at("12:00"), 60.0), (at("13:00"), 75.5), (at("14:00"), 58.0), (at("15:00"), 75.5), (at("16:00"), 62.0)];
+		assert_eq!(aggregate(&values, Aggregate::Max), Some((75.5, Some(at("13:00")))));
+		assert_eq!(aggregate(&values, Aggregate::Min), Some((58.0, Some(at("14:00")))));
+		assert_eq!(aggregate(&values, Aggregate::Avg), Some((66.2, None)));
+		assert_eq!(aggregate(&values, Aggregate::Last), Some((62.0, Some(at("16:00")))));
+		assert_eq!(aggregate(&values, Aggregate::Change), Some((2.0, None)));
+		assert_eq!(aggregate(&values, Aggregate::Count), Some((5.0, None)));
+		assert_eq!(aggregate(&[], Aggregate::Max), None);
+	}
+}
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+#[cfg(feature = "tui")]
+use crate::theme;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RangeColor {
+	Blue,
+	Cyan,
+	Green,
+	Yellow,
+	Red,
+	Magenta,
+	White,
+}
+
+impl RangeColor {
+	pub fn name(&self) -> &'static str {
+		match self {
+			RangeColor::Blue => "blue",
+			RangeColor::Cyan => "cyan",
+			RangeColor::Green => "green",
+			RangeColor::Yellow => "yellow",
+			RangeColor::Red => "red",
+			RangeColor::Magenta => "magenta",
+			RangeColor::White => "white",
+		}
+	}
+	
+	#[cfg(feature = "tui")]
+	pub fn index(&self) -> i16 {
+		match self {
+			RangeColor::Blue => theme::BLUE,
+			RangeColor::Cyan => theme::CYAN,
+			RangeColor::Green => theme::GREEN,
+			RangeColor::Yellow => theme::YELLOW,
+			RangeColor::Red => theme::RED,
+			RangeColor::Magenta => theme::MAGENTA,
+			RangeColor::White => theme::WHITE,
+		}
+	}
+}
+
+/**
+ * Values from min (inclusive) to max (exclusive) are shown in the color. Either end can be left open.
+*/
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ColorRange {
+	pub min: Option<f64>,
+	pub max: Option<f64>,
+	pub color: RangeColor,
+}
+
+impl ColorRange {
+	pub fn contains(&self, value: f64) -> bool {
+		return self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value < max);
+	}
+}
+
+/**
+ * Color ranges per metric. Metrics not set here fall back to the global ranges, then to the defaults.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Ranges {
+	pub temperature: Option<Vec<ColorRange>>,
+	pub humidity: Option<Vec<ColorRange>>,
+}
+
+pub const DEFAULT_TEMPERATURE: &[ColorRange] = &[
+	ColorRange { min: None, max: Some(0.0), color: RangeColor::Blue },
+	ColorRange { min: Some(18.0), max: Some(24.0), color: RangeColor::Green },
+	ColorRange { min: Some(28.0), max: None, color: RangeColor::Red },
+];
+
+pub const DEFAULT_HUMIDITY: &[ColorRange] = &[
+	ColorRange { min: Some(60.0), max: None, color: RangeColor::Yellow },
+];
+
+/**
+ * Color of the first range the value falls in.
+*/
+pub fn color(ranges: &[ColorRange], value: f64) -> Option<RangeColor> {
+	return ranges.iter().find(|range| range.contains(value)).map(|range| range.color);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn default_temperature_ranges() {
+		assert_eq!(color(DEFAULT_TEMPERATURE, -5.0), Some(RangeColor::Blue));
+		assert_eq!(color(DEFAULT_TEMPERATURE, 0.0), None);
+		assert_eq!(color(DEFAULT_TEMPERATURE, 18.0), Some(RangeColor::Green));
+		assert_eq!(color(DEFAULT_TEMPERATURE, 24.0), None);
+		assert_eq!(color(DEFAULT_TEMPERATURE, 30.0), Some(RangeColor::Red));
+		assert_eq!(color(DEFAULT_HUMIDITY, 65.0), Some(RangeColor::Yellow));
+		assert_eq!(color(DEFAULT_HUMIDITY, 45.0), None);
+	}
+	
+	#[test]
+	fn first_matching_range_wins() {
+		let ranges: Ranges = toml::from_str(r#"
+			temperature = [
+				{ max = 5.0, color = "cyan" },
+				{ max = 10.0, color = "magenta" },
+			]
+		"#).unwrap();
+		let temperature = ranges.temperature.unwrap();
+		
+		assert_eq!(color(&temperature, 2.0), Some(RangeColor::Cyan));
+		assert_eq!(color(&temperature, 7.0), Some(RangeColor::Magenta));
+		assert_eq!(color(&temperature, 12.0), None);
+		assert!(ranges.humidity.is_none());
+	}
+}
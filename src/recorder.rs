@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+
+/**
+ * Saves the raw payloads from the API and the listener as they arrived, a file per payload named by the time
+ * received and the source, e.g. "20240501T120000.123Z-api.json". For bug reports about payloads that don't parse,
+ * and for test fixtures from real gateways. Shared with the listener thread.
+*/
+#[derive(Clone)]
+pub struct Recorder {
+	dir: PathBuf,
+	// A payload couldn't be saved.
+	failed: Arc<AtomicBool>,
+}
+
+impl Recorder {
+	/**
+	 * Record into the directory, created if needed.
+	*/
+	pub fn open(dir: &Path) -> Result<Recorder, String> {
+		fs::create_dir_all(dir).map_err(|error| format!("Could not create the record directory {}: {}", dir.display(), error))?;
+		return Ok(Recorder { dir: dir.to_path_buf(), failed: Arc::new(AtomicBool::new(false)) });
+	}
+	
+	/**
+	 * Save the payload as it is, whether it parses or not.
+	*/
+	pub fn record(&self, source: &str, received: DateTime<Utc>, payload: &str) {
+		let result = fs::write(self.dir.join(file_name(source, received)), payload);
+		self.failed.store(result.is_err(), Ordering::Relaxed);
+	}
+	
+	pub fn failed(&self) -> bool {
+		return self.failed.load(Ordering::Relaxed);
+	}
+}
+
+/**
+ * Sortable by time, to the millisecond so that the listener's pushes don't overwrite each other.
+*/
+fn file_name(source: &str, received: DateTime<Utc>) -> String {
+	return format!("{}-{}.json", received.format("%Y%m%dT%H%M%S%.3fZ"), source);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn payloads_as_received() {
+		let dir = std::env::temp_dir().join(format!("ruuvi-tc-record-{}", std::process::id()));
+		let recorder = Recorder::open(&dir).unwrap();
+		let received = "2024-05-01T12:00:00.123456Z".parse::<DateTime<Utc>>().unwrap();
+		recorder.record("listener", received, "{ not json");
+		
+		let saved = fs::read_to_string(dir.join("20240501T120000.123Z-listener.json"));
+		fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(saved.unwrap(), "{ not json");
+		assert!(!recorder.failed());
+	}
+}
@@ -0,0 +1,52 @@
+use crate::config::Config;
+use crate::model::ApiResponse;
+
+/**
+ * Fill the temperature deltas of the tags against the reference tag, e.g. indoors against outdoors for the
+ * insulation or a sauna heating up. The reference itself and tags without a temperature get none.
+*/
+pub fn apply(data: &mut ApiResponse, config: &Config) {
+	let Some(key) = &config.reference else {
+		return;
+	};
+	let Some((id, reference)) = data.iter()
+		.find(|tag| tag.id == *key || tag.name == *key)
+		.and_then(|tag| Some((tag.id.clone(), tag.temperature()?))) else {
+		return;
+	};
+	
+	for tag in data.iter_mut().filter(|tag| tag.id != id) {
+		if let Some(temperature) = &mut tag.temperature {
+			temperature.delta = Some(temperature.current - reference);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::model::{Metric, Tag};
+	
+	fn tag(name: &str, temperature: Option<f64>) -> Tag {
+		return Tag {
+			temperature: temperature.map(Metric::from_current),
+			..Tag::test(&name.to_lowercase(), name)
+		};
+	}
+	
+	#[test]
+	fn deltas_against_the_reference() {
+		let mut data = vec![tag("Indoor", Some(21.25)), tag("Outdoor", Some(2.75)), tag("Cellar", None)];
+		let config = Config { reference: Some("Outdoor".into()), ..Config::default() };
+		apply(&mut data, &config);
+		
+		assert_eq!(data[0].temperature.as_ref().unwrap().delta, Some(18.5));
+		assert_eq!(data[1].temperature.as_ref().unwrap().delta, None);
+		assert!(data[2].temperature.is_none());
+		
+		// Nothing to compare to without the reference's temperature.
+		let mut data = vec![tag("Indoor", Some(21.25)), tag("Outdoor", None)];
+		apply(&mut data, &config);
+		assert_eq!(data[0].temperature.as_ref().unwrap().delta, None);
+	}
+}
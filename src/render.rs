@@ -0,0 +1,2195 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use crate::backend::{Pane, Screen};
+use crate::alerts::{Alert, AlertMetric, Severity};
+use crate::bigdigits;
+use crate::chart;
+use crate::events::EventKind;
+use crate::floorplan::{self, PlanMetric, Segment};
+use crate::floors;
+use crate::frost;
+use crate::clock::Zone;
+use crate::config::{self, Config};
+use crate::glyphs::Glyphs;
+use crate::gradient;
+use crate::groups::{self, Group};
+use crate::history::{Extreme, History, Sample};
+use crate::i18n::{Lang, Text};
+use crate::inspect;
+use crate::metrics::{self, DerivedMetric};
+use crate::model::{ApiResponse, Metric, Tag, TagMetadata};
+use crate::nudge::{self, Nudge};
+use crate::pages::PageConfig;
+use crate::palette::Palette;
+use crate::poller::{CacheStatus, Poller};
+use crate::ranges::{self, ColorRange, RangeColor, Ranges};
+use crate::responsive::{self, Quantity};
+use crate::table::{self, TableConfig};
+use crate::summary;
+use crate::targets::TargetRange;
+use crate::theme::{self, Role, Style, Theme};
+use crate::weather;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+	// A card per tag.
+	#[default]
+	Cards,
+	// A row per tag with fixed width columns.
+	Table,
+	// A short line per tag for small terminals.
+	Minimal,
+	// The tags stacked by floor like a building.
+	Floors,
+	// The floorplan drawn in the config, with the values in its placeholders.
+	Floorplan,
+	// The most detailed of the cards, table and minimal that the tags fit in.
+	Auto,
+}
+
+impl Layout {
+	/**
+	 * The next layout for the layout key. The floorplan only if there is one.
+	*/
+	pub fn next(self, floorplan: bool) -> Layout {
+		match self {
+			Layout::Auto => Layout::Cards,
+			Layout::Cards => Layout::Table,
+			Layout::Table => Layout::Minimal,
+			Layout::Minimal => Layout::Floors,
+			Layout::Floors if floorplan => Layout::Floorplan,
+			Layout::Floors | Layout::Floorplan => Layout::Auto,
+		}
+	}
+}
+
+/**
+ * Period of the min/max values shown on the cards.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum MinMaxWindow {
+	// As the source gives them, filled in from the history if it doesn't.
+	#[default]
+	#[serde(rename = "source")]
+	Source,
+	#[serde(rename = "today")]
+	Today,
+	#[serde(rename = "24h")]
+	Last24Hours,
+	#[serde(rename = "7d")]
+	Last7Days,
+}
+
+impl MinMaxWindow {
+	/**
+	 * The next window, for cycling through them with a key.
+	*/
+	pub fn next(self) -> MinMaxWindow {
+		match self {
+			MinMaxWindow::Source => MinMaxWindow::Today,
+			MinMaxWindow::Today => MinMaxWindow::Last24Hours,
+			MinMaxWindow::Last24Hours => MinMaxWindow::Last7Days,
+			MinMaxWindow::Last7Days => MinMaxWindow::Source,
+		}
+	}
+	
+	/**
+	 * Start of the window, None for the source's own min/max.
+	*/
+	pub fn start(self, now: DateTime<Utc>, zone: Zone) -> Option<DateTime<Utc>> {
+		match self {
+			MinMaxWindow::Source => None,
+			MinMaxWindow::Today => zone.midnight(zone.date(now)),
+			MinMaxWindow::Last24Hours => Some(now - chrono::Duration::hours(24)),
+			MinMaxWindow::Last7Days => Some(now - chrono::Duration::days(7)),
+		}
+	}
+}
+
+/**
+ * Min/max period of a metric instead of the min_max_window, e.g. the lowest temperature of the last 24 hours, which
+ * at 8 a.m. says more than the lowest since midnight.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MinMaxWindows {
+	pub temperature: Option<MinMaxWindow>,
+	pub humidity: Option<MinMaxWindow>,
+	pub pressure: Option<MinMaxWindow>,
+}
+
+impl MinMaxWindows {
+	pub fn of(&self, metric: AlertMetric) -> Option<MinMaxWindow> {
+		return match metric {
+			AlertMetric::Temperature => self.temperature,
+			AlertMetric::Humidity => self.humidity,
+			AlertMetric::Pressure => self.pressure,
+			_ => None,
+		};
+	}
+}
+
+/**
+ * UI state changed with keys while running.
+*/
+#[derive(Debug, Clone)]
+pub struct View {
+	// Layout chosen in the config or with the layout key.
+	pub layout: Layout,
+	// Absolute timestamps instead of the relative "Updated" times.
+	pub absolute_time: bool,
+	// Names of the collapsed groups. Toggled with the number keys.
+	pub collapsed: HashSet<String>,
+	// Min/max period, cycled with the m key. The M key resets the min/max to start from that moment.
+	pub min_max_window: MinMaxWindow,
+	// Windows of the metrics configured apart from the others, until the m key picks one for all.
+	pub min_max_windows: MinMaxWindows,
+	pub min_max_reset: Option<DateTime<Utc>>,
+	// Index of the page shown, switched with Tab and the number keys.
+	pub page: usize,
+	// The event log screen instead of the tags, toggled with the a key.
+	pub event_log: bool,
+	// What the colors and symbols mean, toggled with the ? key.
+	pub legend: bool,
+	// The config in use instead of the tags, toggled with the c key, and the lines scrolled past.
+	pub inspect: bool,
+	pub inspect_scroll: usize,
+	// Time zone of the clock times and of the day for the min/max.
+	pub zone: Zone,
+	// ID of the tag clicked with the mouse, and whether it's shown alone with big digits.
+	pub selected: Option<String>,
+	pub detail: bool,
+	// Tags scrolled past with the scroll wheel in the cards, table and minimal layouts.
+	pub scroll: usize,
+	// Where the tags were drawn in the last frame, for finding the clicked one. Filled in by the renderers.
+	pub regions: RefCell<Vec<Region>>,
+	// Warning threshold being nudged with + and -, waiting for y to save it.
+	pub nudge: Option<Nudge>,
+	// Where the S key saved the screen, or why it couldn't, shown until the next key.
+	pub snapshot: Option<Result<PathBuf, String>>,
+	// The command palette, open from the : key until a command is run or it's closed.
+	pub palette: Option<Palette>,
+}
+
+/**
+ * Part of the screen a tag was drawn on.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+	pub rows: Range<i32>,
+	pub columns: Range<i32>,
+	pub tag_id: String,
+}
+
+impl View {
+	pub fn new(config: &Config) -> View {
+		return View {
+			layout: config.layout,
+			// The ages would go stale between the redraws of an e-ink screen.
+			absolute_time: config.absolute_time || config.eink.is_some(),
+			collapsed: HashSet::new(),
+			min_max_window: config.min_max_window,
+			min_max_windows: config.min_max_windows,
+			min_max_reset: None,
+			page: 0,
+			event_log: false,
+			legend: false,
+			inspect: false,
+			inspect_scroll: 0,
+			zone: config.zone(),
+			selected: None,
+			detail: false,
+			scroll: 0,
+			regions: RefCell::new(Vec::new()),
+			nudge: None,
+			snapshot: None,
+			palette: None,
+		};
+	}
+	
+	/**
+	 * The page shown, None without pages.
+	*/
+	pub fn page<'a>(&self, config: &'a Config) -> Option<&'a PageConfig> {
+		return config.pages.get(self.page);
+	}
+	
+	/**
+	 * Switch to the page with the number (1-9, in the config order), in its layout.
+	*/
+	pub fn select_page(&mut self, number: usize, config: &Config) {
+		let Some(page) = number.checked_sub(1).and_then(|index| config.pages.get(index)) else {
+			return;
+		};
+		self.page = number - 1;
+		self.scroll = 0;
+		self.layout = page.layout.unwrap_or(config.layout);
+	}
+	
+	/**
+	 * Switch to the next page, from the last back to the first.
+	*/
+	pub fn next_page(&mut self, config: &Config) {
+		if !config.pages.is_empty() {
+			self.select_page((self.page + 1) % config.pages.len() + 1, config);
+		}
+	}
+	
+	/**
+	 * The tag drawn at the row and column in the last frame.
+	*/
+	pub fn tag_at(&self, y: i32, x: i32) -> Option<String> {
+		return self.regions.borrow()
+			.iter()
+			.find(|region| region.rows.contains(&y) && region.columns.contains(&x))
+			.map(|region| region.tag_id.clone());
+	}
+	
+	/**
+	 * Select the clicked tag, or nothing when the click missed the tags. A double-click shows the tag alone, and a
+	 * click there goes back to the tags.
+	*/
+	pub fn click(&mut self, y: i32, x: i32, double: bool) {
+		if self.detail {
+			self.detail = false;
+			return;
+		}
+		self.selected = self.tag_at(y, x);
+		self.detail = double && self.selected.is_some();
+	}
+	
+	/**
+	 * Scroll the tags by the number of tags, keeping at least one of the count tags on the screen.
+	*/
+	pub fn scroll_by(&mut self, tags: isize, count: usize) {
+		self.scroll = self.scroll.saturating_add_signed(tags).min(count.saturating_sub(1));
+	}
+	
+	/**
+	 * Scroll the config screen by the lines, not past its last line.
+	*/
+	pub fn scroll_inspect(&mut self, lines: isize, count: usize) {
+		self.inspect_scroll = self.inspect_scroll.saturating_add_signed(lines).min(count.saturating_sub(1));
+	}
+	
+	/**
+	 * Move to the next min/max window for all the metrics, forgetting a reset.
+	*/
+	pub fn cycle_min_max_window(&mut self) {
+		self.min_max_window = self.min_max_window.next();
+		self.min_max_windows = MinMaxWindows::default();
+		self.min_max_reset = None;
+	}
+	
+	pub fn min_max_window(&self, metric: AlertMetric) -> MinMaxWindow {
+		return self.min_max_windows.of(metric).unwrap_or(self.min_max_window);
+	}
+	
+	/**
+	 * Start of the min/max period of the metric: the window or the reset, whichever is later. None for the source's
+	 * own min/max.
+	*/
+	fn min_max_since(&self, metric: AlertMetric, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+		let start = self.min_max_window(metric).start(now, self.zone);
+		return match (start, self.min_max_reset) {
+			(Some(start), Some(reset)) => Some(start.max(reset)),
+			(start, reset) => start.or(reset),
+		};
+	}
+	
+	/**
+	 * Min and max of a metric of the tag in its period, including the current value. The value picks the metric
+	 * from the history samples.
+	*/
+	pub fn min_max(&self, tag: &Tag, kind: AlertMetric, metric: &Metric, history: &History, now: DateTime<Utc>, value: impl Fn(&Sample) -> Option<f64>) -> (f64, f64) {
+		let (min, max) = self.extremes(tag, kind, metric, history, now, value);
+		return (min.value, max.value);
+	}
+	
+	/**
+	 * The min/max with when they were measured. The times are known from the history, not of the source's own
+	 * min/max.
+	*/
+	pub fn extremes(&self, tag: &Tag, kind: AlertMetric, metric: &Metric, history: &History, now: DateTime<Utc>, value: impl Fn(&Sample) -> Option<f64>) -> (Extreme, Extreme) {
+		let current = Extreme { value: metric.current, datetime: tag.measured_at() };
+		let Some(since) = self.min_max_since(kind, now) else {
+			let source = |value: Option<f64>| value.map_or(current, |value| Extreme { value, datetime: None });
+			return (source(metric.min), source(metric.max));
+		};
+		
+		let (min, max) = history.extremes(&tag.id, since, value).unwrap_or((current, current));
+		return (
+			if current.value < min.value { current } else { min },
+			if current.value > max.value { current } else { max },
+		);
+	}
+	
+	/**
+	 * Label of the min/max period of the metric, None for the source's own min/max.
+	*/
+	pub fn min_max_label(&self, metric: AlertMetric, lang: Lang, now: DateTime<Utc>) -> Option<String> {
+		let since = self.min_max_since(metric, now)?;
+		if self.min_max_reset == Some(since) {
+			return Some(lang.since(&self.zone.local(since)));
+		}
+		
+		let text = match self.min_max_window(metric) {
+			MinMaxWindow::Today => Text::Today,
+			MinMaxWindow::Last24Hours => Text::Last24Hours,
+			MinMaxWindow::Last7Days => Text::Last7Days,
+			MinMaxWindow::Source => return None,
+		};
+		return Some(lang.text(text).to_string());
+	}
+	
+	/**
+	 * Collapse or expand the group with the number (1-9, in the config order).
+	*/
+	pub fn toggle_group(&mut self, number: usize, config: &Config) {
+		let Some(group) = number.checked_sub(1).and_then(|index| config.groups.get(index)) else {
+			return;
+		};
+		if !self.collapsed.remove(&group.name) {
+			self.collapsed.insert(group.name.clone());
+		}
+	}
+}
+
+// Smallest terminal (columns, rows) the card and kiosk layouts fit in.
+const MINIMUM_SIZE: (i32, i32) = (40, 12);
+
+/**
+ * The main render function.
+*/
+pub fn render(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	let history = &poller.history;
+	let lang = config.lang();
+	// Erase instead of clear: refresh() then only sends the changed cells instead of repainting the whole screen,
+	// which flickers on slow terminals and over SSH.
+	screen.erase();
+	if render_too_small(screen, theme, MINIMUM_SIZE, lang) {
+		return;
+	}
+	
+	render_paused(screen, theme, poller);
+	render_tabs(screen, theme, poller, view);
+	render_summary(screen, theme, data, poller, view);
+	
+	// The outdoor tag is pinned to the top as a big widget instead of a normal card.
+	if let Some(tag) = data.iter().find(|tag| config.is_outdoor_tag(tag)) {
+		render_outdoor_widget(screen, theme, tag, poller);
+	}
+	
+	// Weather tendency of the outdoor tags.
+	let mut weather = false;
+	for tag in data.iter().filter(|tag| config.tag(tag).is_some_and(|tag_config| tag_config.outdoor)) {
+		let (Some(pressure), Some(change)) = (&tag.pressure, weather::pressure_tendency(history, &tag.id)) else {
+			continue;
+		};
+		screen.addstr(&format!("{}: {} hPa {} {}\n", tag.label(), config.units.pressure(pressure.current, config.units.precision.pressure, lang), weather::indicator(change, config.glyphs()), lang.text(weather::describe(change))));
+		weather = true;
+	}
+	if weather {
+		screen.addstr("\n");
+	}
+	
+	for group in scrolled(groups::split(data.iter().filter(|tag| !config.is_outdoor_tag(tag)), &config.groups), view) {
+		if render_group_header(screen, theme, &group, poller, view) {
+			screen.addstr("\n");
+			continue;
+		}
+		for tag in &group.tags {
+			let start = screen.cursor();
+			render_card(screen, theme, tag, poller, view);
+			mark(screen, view, tag, start);
+		}
+	}
+	
+	render_errors(screen, theme, poller);
+	render_source_status(screen, theme, poller);
+	
+	screen.refresh();
+}
+
+/**
+ * A metric on the cards: the value on the values row, and the min/max on its own row unless they are inline. The
+ * metrics config picks the fields and their order.
+*/
+struct CardField {
+	value: Box<FieldValue>,
+	// Derived metrics have no history for the min/max.
+	min_max: Option<fn(&mut dyn Screen, &Card)>,
+}
+
+// Draws the value after the separator. False if there's nothing to draw, and nothing was drawn.
+type FieldValue = dyn Fn(&mut dyn Screen, &Card, &str) -> bool;
+
+/**
+ * The field of the metric, None for the battery voltage, which is on the updated row, and for the signal strength,
+ * which is on the detail screen.
+*/
+fn card_field(metric: AlertMetric) -> Option<CardField> {
+	return match metric {
+		AlertMetric::Temperature => Some(CardField { value: Box::new(temperature_field), min_max: Some(temperature_min_max) }),
+		AlertMetric::Humidity => Some(CardField { value: Box::new(humidity_field), min_max: Some(humidity_min_max) }),
+		AlertMetric::Pressure => Some(CardField { value: Box::new(pressure_field), min_max: Some(pressure_min_max) }),
+		AlertMetric::Derived(derived) => Some(CardField {
+			value: Box::new(move |screen: &mut dyn Screen, card: &Card, separator: &str| derived_field(screen, card, derived, separator)),
+			min_max: None,
+		}),
+		AlertMetric::BatteryVoltage | AlertMetric::Rssi => None,
+	};
+}
+
+/**
+ * The card the fields are drawn on.
+*/
+struct Card<'a> {
+	theme: &'a Theme,
+	tag: &'a Tag,
+	poller: &'a Poller,
+	view: &'a View,
+	now: DateTime<Utc>,
+	// The min/max period of the temperature if it's not the source's, e.g. " (24 h)".
+	label: String,
+}
+
+impl Card<'_> {
+	/**
+	 * Min/max of the metric in its period, with their times.
+	*/
+	fn min_max(&self, kind: AlertMetric, metric: &Option<Metric>, value: impl Fn(&Sample) -> Option<f64>) -> Option<(Extreme, Extreme)> {
+		return metric.as_ref().map(|metric| self.view.extremes(self.tag, kind, metric, &self.poller.history, self.now, value));
+	}
+	
+	/**
+	 * The period of a metric configured apart from the temperature, e.g. " (Today)", or nothing when it's the same.
+	*/
+	fn own_label(&self, metric: AlertMetric) -> String {
+		let label = self.view.min_max_label(metric, self.poller.config.lang(), self.now).map(|label| format!(" ({})", label)).unwrap_or_default();
+		return if label == self.label { String::new() } else { label };
+	}
+	
+	/**
+	 * The min/max as "min…max" and the unit, or the missing value. With min_max_times their times follow them, with
+	 * the unit on both: "38.50% at 4:12 AM…41.00% at 2:30 PM".
+	*/
+	fn range(&self, min_max: Option<(Extreme, Extreme)>, unit: &str, format: impl Fn(f64) -> String) -> String {
+		let config = &self.poller.config;
+		let Some((min, max)) = min_max else {
+			return config.missing_value.clone();
+		};
+		let times = min.datetime.zip(max.datetime).filter(|_| config.min_max_times);
+		let Some((min_at, max_at)) = times else {
+			return format!("{}…{}{}", format(min.value), format(max.value), unit);
+		};
+		let time = |datetime| extreme_time(datetime, config.lang(), self.view.zone, self.now);
+		return format!("{}{} {}…{}{} {}", format(min.value), unit, time(min_at), format(max.value), unit, time(max_at));
+	}
+}
+
+fn temperature_field(screen: &mut dyn Screen, card: &Card, separator: &str) -> bool {
+	let (tag, config) = (card.tag, &card.poller.config);
+	let (units, lang) = (&config.units, config.lang());
+	screen.addstr(separator);
+	let temperature = tag.temperature().map(|temperature| units.temperature(temperature, units.precision.temperature, lang));
+	attributed(screen, missing_style(card.theme, tag, config.temperature_color(tag), tag.temperature()), &temperature.unwrap_or(config.missing_value.clone()));
+	styled(screen, card.theme, Role::Trend, trend_symbol(&tag.temperature, config));
+	if let Some(delta) = tag.temperature.as_ref().and_then(|temperature| temperature.delta) {
+		// Kelvin and Celsius degrees are the same size.
+		styled(screen, card.theme, Role::Trend, &format!(" (Δ {}{})", lang.signed(delta, 1), units.temperature_symbol()));
+	}
+	if config.compact_min_max {
+		let min_max = card.min_max(AlertMetric::Temperature, &tag.temperature, |sample| sample.temperature);
+		screen.addstr(&format!(" ({})", card.range(min_max, "", |value| units.temperature(value, units.precision.temperature, lang))));
+	}
+	return true;
+}
+
+fn temperature_min_max(screen: &mut dyn Screen, card: &Card) {
+	let config = &card.poller.config;
+	let min_max = card.min_max(AlertMetric::Temperature, &card.tag.temperature, |sample| sample.temperature);
+	screen.addstr(&format!("{}{}\n", card.range(min_max, "", |value| config.units.temperature(value, 2, config.lang())), card.label));
+}
+
+fn humidity_field(screen: &mut dyn Screen, card: &Card, separator: &str) -> bool {
+	let (tag, config) = (card.tag, &card.poller.config);
+	let (units, lang) = (&config.units, config.lang());
+	screen.addstr(separator);
+	let humidity = tag.humidity().map(|humidity| format!("{}%", units.humidity(humidity, units.precision.humidity, lang)));
+	attributed(screen, missing_style(card.theme, tag, config.humidity_color(tag), tag.humidity()), &humidity.unwrap_or(config.missing_value.clone()));
+	styled(screen, card.theme, Role::Trend, trend_symbol(&tag.humidity, config));
+	if config.compact_min_max {
+		let min_max = card.min_max(AlertMetric::Humidity, &tag.humidity, |sample| sample.humidity);
+		screen.addstr(&format!(" ({}){}", card.range(min_max, "", |value| units.humidity(value, units.precision.humidity, lang)), card.own_label(AlertMetric::Humidity)));
+	}
+	return true;
+}
+
+fn humidity_min_max(screen: &mut dyn Screen, card: &Card) {
+	let config = &card.poller.config;
+	let min_max = card.min_max(AlertMetric::Humidity, &card.tag.humidity, |sample| sample.humidity);
+	screen.addstr(&format!("{}{}\n", card.range(min_max, "%", |value| config.units.humidity(value, 2, config.lang())), card.own_label(AlertMetric::Humidity)));
+}
+
+/**
+ * The pressure is on the values row only with the min/max inline, the min/max row has the tendency otherwise.
+*/
+fn pressure_field(screen: &mut dyn Screen, card: &Card, separator: &str) -> bool {
+	let (tag, config) = (card.tag, &card.poller.config);
+	let (units, lang) = (&config.units, config.lang());
+	let (true, Some(pressure), Some(min_max)) = (config.compact_min_max, &tag.pressure, card.min_max(AlertMetric::Pressure, &tag.pressure, |sample| sample.pressure)) else {
+		return false;
+	};
+	screen.addstr(&format!(
+		"{}{} hPa{} ({}){}",
+		separator,
+		units.pressure(pressure.current, units.precision.pressure, lang),
+		format_tendency(tag, card.poller, lang),
+		card.range(Some(min_max), "", |value| units.pressure(value, units.precision.pressure, lang)),
+		card.own_label(AlertMetric::Pressure)
+	));
+	return true;
+}
+
+fn pressure_min_max(screen: &mut dyn Screen, card: &Card) {
+	let (tag, config) = (card.tag, &card.poller.config);
+	let (units, lang) = (&config.units, config.lang());
+	if let Some(min_max) = card.min_max(AlertMetric::Pressure, &tag.pressure, |sample| sample.pressure) {
+		screen.addstr(&format!(
+			"{}{}{}\n",
+			card.range(Some(min_max), " hPa", |value| units.pressure(value, units.precision.pressure, lang)),
+			format_tendency(tag, card.poller, lang),
+			card.own_label(AlertMetric::Pressure)
+		));
+	}
+}
+
+fn derived_field(screen: &mut dyn Screen, card: &Card, derived: DerivedMetric, separator: &str) -> bool {
+	let config = &card.poller.config;
+	let value = AlertMetric::Derived(derived).value(card.tag);
+	screen.addstr(&format!("{}{} ", separator, derived.label(config.lang())));
+	attributed(
+		screen,
+		missing_style(card.theme, card.tag, None, value),
+		&value.map(|value| derived.format_value(value, &config.units, config.lang())).unwrap_or(config.missing_value.clone()),
+	);
+	return true;
+}
+
+/**
+ * Card of a tag: title with the status, values, derived metrics and the details.
+*/
+fn render_card(screen: &mut dyn Screen, theme: &Theme, tag: &Tag, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	let lang = config.lang();
+	
+	// Title row.
+	attributed(screen, selected_style(theme.style(Role::Title), tag, view), tag.label());
+	
+	// Battery low indicator. Critical battery is more severe than low.
+	if tag.battery_low {
+		let role = if tag.battery_critical { Role::Critical } else { Role::Warning };
+		let mut text = lang.text(if tag.battery_critical { Text::BatteryCritical } else { Text::BatteryLow }).to_string();
+		if let Some(voltage) = tag.battery_voltage {
+			text += &format!(" ({}%)", lang.number(config.battery_model_for(tag).percentage(voltage), 0));
+		}
+		screen.addstr(" ");
+		styled(screen, theme, role, &text);
+	}
+	else if tag.unreachable {
+		screen.addstr(" ");
+		styled(screen, theme, Role::Unknown, lang.text(Text::Unreachable));
+	}
+	if let Some(warning) = outdoor_warning(tag, poller) {
+		screen.addstr(" ");
+		styled(screen, theme, Role::Warning, lang.text(warning.text()));
+	}
+	
+	screen.addstr("\n");
+	
+	let tag_config = config.tag(tag);
+	if let Some(summary) = tag.metadata.summary(lang.text(Text::Floor)) {
+		styled(screen, theme, Role::Trend, &format!("{}\n", summary));
+	}
+	if let Some(note) = tag_config.and_then(|tag_config| tag_config.note.as_ref()) {
+		styled(screen, theme, Role::Trend, &format!("{}\n", note));
+	}
+	
+	// The metrics in the configured order, with the min/max inline when compact.
+	let now = poller.clock.now();
+	let card = Card {
+		theme,
+		tag,
+		poller,
+		view,
+		now,
+		label: view.min_max_label(AlertMetric::Temperature, lang, now).map(|label| format!(" ({})", label)).unwrap_or_default(),
+	};
+	let fields: Vec<CardField> = config.metrics.iter()
+		.filter(|metric| config.shows(tag, **metric))
+		.filter_map(|metric| card_field(*metric))
+		.collect();
+	let mut separator = "";
+	for field in &fields {
+		if (field.value)(screen, &card, separator) {
+			separator = " ";
+		}
+	}
+	if config.compact_min_max {
+		screen.addstr(&card.label);
+	}
+	
+	// Feels-like of outdoor tags.
+	let units = &config.units;
+	let outdoor = config.tag(tag).is_some_and(|tag_config| tag_config.outdoor);
+	if let (true, Some(wind_speed), Some(temperature)) = (outdoor, poller.wind_speed(), tag.temperature()) {
+		screen.addstr(&format!(" {} {}", lang.text(Text::FeelsLike), units.temperature(metrics::wind_chill(temperature, wind_speed), units.precision.temperature, lang)));
+	}
+	
+	screen.addstr("\n");
+	
+	// The values the tag sent, before the calibration.
+	if let Some(raw) = format_raw(tag, config) {
+		styled(screen, theme, Role::Trend, &format!("{}: {}\n", lang.text(Text::Raw), raw));
+	}
+	
+	// Target bands and how far outside them the values are.
+	let shows = |metric| config.shows(tag, metric);
+	if let Some(targets) = tag_config.and_then(|tag_config| tag_config.target.as_ref()) {
+		// Kelvin shifts the band and the value alike, so the deviation stays the same.
+		let temperature = targets.temperature.map(|target| TargetRange { min: target.min.map(|min| units.convert(min)), max: target.max.map(|max| units.convert(max)) });
+		let bands = [
+			(AlertMetric::Temperature, temperature, tag.temperature().map(|temperature| units.convert(temperature)), units.temperature_symbol()),
+			(AlertMetric::Humidity, targets.humidity, tag.humidity(), "%"),
+		];
+		screen.addstr(lang.text(Text::Target));
+		for (target, value, unit) in bands.iter().filter(|(metric, ..)| shows(*metric)).filter_map(|(_, target, value, unit)| Some((target.as_ref()?, value, unit))) {
+			screen.addstr(&format!(" {}{} ", format_target(target, lang), unit));
+			let Some(value) = value else {
+				styled(screen, theme, Role::Unknown, &config.missing_value);
+				screen.addstr(" ");
+				continue;
+			};
+			let deviation = target.deviation(*value);
+			if deviation == 0.0 {
+				styled(screen, theme, Role::Value, config.glyphs().arrow("✓", "ok"));
+			}
+			else {
+				let arrow = if deviation > 0.0 { config.glyphs().arrow("▲", "^") } else { config.glyphs().arrow("▼", "v") };
+				styled(screen, theme, Role::Warning, &format!("{} {}{}", arrow, lang.signed(deviation, 1), unit));
+			}
+			screen.addstr(" ");
+		}
+		screen.addstr("\n");
+	}
+	
+	// Derived metrics.
+	let derived = config.derived_for(tag);
+	if !derived.is_empty() {
+		let values: Vec<String> = derived.iter()
+			.map(|metric| match (tag.temperature(), tag.humidity()) {
+				(Some(temperature), Some(humidity)) => format!("{} {}", metric.label(lang), metric.format(temperature, humidity, units, lang)),
+				_ => format!("{} {}", metric.label(lang), config.missing_value),
+			})
+			.collect();
+		screen.addstr(&values.join("  "));
+		screen.addstr("\n");
+	}
+	
+	// Extra numbers from the source, e.g. CO₂ of a sensor next to the tag.
+	if let Some(extras) = metrics::format_extras(tag, &config.extras, lang) {
+		screen.addstr(&format!("{}\n", extras));
+	}
+	
+	// Growing degree days.
+	if let (Some(total), Some(degree_days)) = (poller.degree_days.get(&tag.id), config.tag(tag).and_then(|tag_config| tag_config.degree_days.as_ref())) {
+		screen.addstr(&format!(
+			"{} {} ({} {}°C)\n",
+			lang.text(Text::GrowingDegreeDays),
+			lang.number(*total, 1),
+			lang.text(Text::Base),
+			lang.number(degree_days.base, 0)
+		));
+	}
+	
+	// Min/max lines.
+	if !config.compact_min_max {
+		for min_max in fields.iter().filter_map(|field| field.min_max) {
+			min_max(screen, &card);
+		}
+	}
+	
+	// Updated string and the battery voltage, with the labels abbreviated if the line doesn't fit.
+	let updated = format_updated(&tag.datetime, view, lang, now);
+	let voltage = tag.battery_voltage.filter(|_| shows(AlertMetric::BatteryVoltage))
+		.map(|voltage| format!("{} V", lang.number(voltage, config.units.precision.battery_voltage)));
+	let line = |label: fn(Lang, Text) -> &'static str| {
+		let battery = voltage.as_ref().map(|voltage| format!("  {}: {}", label(lang, Text::Battery), voltage)).unwrap_or_default();
+		format!("{}: {}{}", label(lang, Text::Updated), updated, battery)
+	};
+	let full = line(Lang::text);
+	let width = screen.size().1.max(0) as usize;
+	screen.addstr(&if full.chars().count() < width { full } else { line(Lang::abbreviation) });
+	
+	screen.addstr("\n\n");
+}
+
+/**
+ * Rows render_card() takes for the tag, for choosing the layout.
+*/
+fn card_height(tag: &Tag, poller: &Poller) -> usize {
+	let config = &poller.config;
+	// Title, values, updated and the empty line after the card, and the temperature, humidity and pressure min/max
+	// unless they are inline.
+	let mut height = 4;
+	if !config.compact_min_max {
+		let shows = |metric| config.shows(tag, metric);
+		height += shows(AlertMetric::Temperature) as usize + shows(AlertMetric::Humidity) as usize;
+		height += (tag.pressure.is_some() && shows(AlertMetric::Pressure)) as usize;
+	}
+	if !config.derived_for(tag).is_empty() {
+		height += 1;
+	}
+	if !tag.extras.is_empty() {
+		height += 1;
+	}
+	if format_raw(tag, config).is_some() {
+		height += 1;
+	}
+	if tag.metadata != TagMetadata::default() {
+		height += 1;
+	}
+	if let Some(tag_config) = config.tag(tag) {
+		height += tag_config.note.is_some() as usize + tag_config.target.is_some() as usize;
+	}
+	if poller.degree_days.contains_key(&tag.id) && config.tag(tag).is_some_and(|tag_config| tag_config.degree_days.is_some()) {
+		height += 1;
+	}
+	return height;
+}
+
+/**
+ * The layout to draw. Auto picks cards if all the cards fit on the screen, then the table, and the minimal layout if
+ * not even the table fits.
+*/
+pub fn resolve_layout(layout: Layout, size: (i32, i32), data: &ApiResponse, poller: &Poller) -> Layout {
+	if layout != Layout::Auto {
+		return layout;
+	}
+	let config = &poller.config;
+	let (height, width) = (size.0.max(0) as usize, size.1.max(0) as usize);
+	let headers = groups::split(data.iter(), &config.groups).iter().filter(|group| group.name.is_some()).count();
+	// The errors and source status take a few rows at the bottom.
+	let lang = config.lang();
+	let errors = poller.errors().into_iter().map(|error| table::wrap(lang.text(error), width.saturating_sub(1)).len()).sum::<usize>() + 1;
+	// The summary line and the empty line after it, more if it wraps.
+	let errors = errors + if config.summary { 2 } else { 0 };
+	
+	let cards = data.iter()
+		.map(|tag| match config.is_outdoor_tag(tag) {
+			// Title, big digits and the details line.
+			true => bigdigits::HEIGHT + 3,
+			false => card_height(tag, poller),
+		})
+		.sum::<usize>() + headers + errors;
+	if cards <= height && width >= MINIMUM_SIZE.0 as usize {
+		return Layout::Cards;
+	}
+	
+	// Header, rows and the empty line after them.
+	let table = data.len() + headers + 2 + errors;
+	if table <= height && width >= table_width(&config.table) {
+		return Layout::Table;
+	}
+	return Layout::Minimal;
+}
+/**
+ * Header of a named group with its number (the key to collapse and expand it), average temperature and active
+ * alerts. Returns true if the group is collapsed. Tags in no group have no header.
+*/
+fn render_group_header(screen: &mut dyn Screen, theme: &Theme, group: &Group, poller: &Poller, view: &View) -> bool {
+	let Some(name) = group.name else {
+		return false;
+	};
+	let lang = poller.config.lang();
+	let number = poller.config.groups.iter().position(|group| group.name == name).unwrap_or(0) + 1;
+	let collapsed = view.collapsed.contains(name);
+	
+	let glyphs = poller.config.glyphs();
+	let triangle = if collapsed { glyphs.arrow("▸", "+") } else { glyphs.arrow("▾", "-") };
+	styled(screen, theme, Role::Title, &format!("{} {} {}", triangle, number, name));
+	if let Some(average) = group.average_temperature() {
+		screen.addstr(&format!("  {} {}", lang.text(Text::Average), poller.config.units.temperature(average, poller.config.units.precision.temperature, lang)));
+	}
+	
+	let alerts: Vec<&Alert> = poller.alerts.active()
+		.filter(|alert| group.tags.iter().any(|tag| tag.id == alert.tag_id))
+		.collect();
+	if let Some(severity) = alerts.iter().map(|alert| alert.severity).max() {
+		screen.addstr("  ");
+		styled(screen, theme, severity_role(severity), &lang.alerts(alerts.len()));
+	}
+	screen.addstr("\n");
+	
+	return collapsed;
+}
+
+/**
+ * The groups without the tags scrolled past. A collapsed group counts as a tag.
+*/
+fn scrolled<'a>(groups: Vec<Group<'a>>, view: &View) -> Vec<Group<'a>> {
+	let mut skip = view.scroll;
+	let mut shown = Vec::new();
+	for mut group in groups {
+		let collapsed = group.name.is_some_and(|name| view.collapsed.contains(name));
+		let count = if collapsed { 1 } else { group.tags.len() };
+		if skip >= count {
+			skip -= count;
+			continue;
+		}
+		if !collapsed {
+			group.tags.drain(..skip);
+		}
+		skip = 0;
+		shown.push(group);
+	}
+	return shown;
+}
+
+/**
+ * Remember where the tag was drawn, from the start to the cursor: the part of the row, or the whole rows when the
+ * tag took more than one.
+*/
+fn mark(screen: &dyn Screen, view: &View, tag: &Tag, start: (i32, i32)) {
+	let end = screen.cursor();
+	let (rows, columns) = match end.0 == start.0 {
+		true => (start.0..start.0 + 1, start.1..end.1),
+		false => (start.0..end.0 + (end.1 > 0) as i32, 0..i32::MAX),
+	};
+	view.regions.borrow_mut().push(Region { rows, columns, tag_id: tag.id.clone() });
+}
+
+/**
+ * The style reversed for the selected tag.
+*/
+fn selected_style(style: Style, tag: &Tag, view: &View) -> Style {
+	if view.selected.as_ref() != Some(&tag.id) {
+		return style;
+	}
+	return Style { attributes: style.attributes ^ theme::REVERSE, ..style };
+}
+
+/**
+ * Table layout: a row per tag with fixed width, decimal aligned columns.
+*/
+pub fn render_table(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	let table = &config.table;
+	let lang = config.lang();
+	screen.erase();
+	if render_too_small(screen, theme, (table_width(table) as i32, 5), lang) {
+		return;
+	}
+	
+	render_paused(screen, theme, poller);
+	render_tabs(screen, theme, poller, view);
+	render_summary(screen, theme, data, poller, view);
+	
+	let column = |title: &str| format!(" {:>width$}", table::fit(title, table.value_width), width = table.value_width);
+	let header = [table.name(""), column(config.units.temperature_symbol().trim()), column("%"), column("hPa"), column("V")].concat();
+	styled(screen, theme, Role::Title, &format!("{}  {}\n", header, lang.text(Text::Updated)));
+	
+	for group in scrolled(groups::split(data.iter(), &config.groups), view) {
+		if render_group_header(screen, theme, &group, poller, view) {
+			continue;
+		}
+		for tag in &group.tags {
+			let start = screen.cursor();
+			render_table_row(screen, theme, tag, poller, view);
+			mark(screen, view, tag, start);
+		}
+	}
+	
+	screen.addstr("\n");
+	render_errors(screen, theme, poller);
+	render_source_status(screen, theme, poller);
+	
+	screen.refresh();
+}
+
+/**
+ * Name, four value columns with the trend arrows or spaces between them and a short updated time.
+*/
+fn table_width(table: &TableConfig) -> usize {
+	return table.name_width + 4 * (table.value_width + 1) + 2 + 15;
+}
+
+/**
+ * Row of a tag in the table layout.
+*/
+fn render_table_row(screen: &mut dyn Screen, theme: &Theme, tag: &Tag, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	let table = &config.table;
+	let lang = config.lang();
+	
+	attributed(screen, selected_style(Style::new(-1, -1, theme::NORMAL), tag, view), &table.name(tag.label()));
+	screen.addstr(" ");
+	let missing = &config.missing_value;
+	let shows = |metric| config.shows(tag, metric);
+	// Hidden metrics leave their column and the trend after it empty.
+	let blank = " ".repeat(table.value_width + 1);
+	if shows(AlertMetric::Temperature) {
+		let temperature = tag.temperature().map(|temperature| config.units.convert(temperature));
+		attributed(screen, missing_style(theme, tag, config.temperature_color(tag), temperature), &table.number(temperature, config.units.precision.temperature, missing, lang));
+		styled(screen, theme, Role::Trend, trend_symbol(&tag.temperature, config));
+	}
+	else {
+		screen.addstr(&blank);
+	}
+	if shows(AlertMetric::Humidity) {
+		attributed(screen, missing_style(theme, tag, config.humidity_color(tag), tag.humidity()), &table.number(tag.humidity(), config.units.precision.humidity, missing, lang));
+		styled(screen, theme, Role::Trend, trend_symbol(&tag.humidity, config));
+	}
+	else {
+		screen.addstr(&blank);
+	}
+	if shows(AlertMetric::Pressure) {
+		screen.addstr(&table.number(tag.pressure(), config.units.precision.pressure, missing, lang));
+		let tendency = tag.pressure.as_ref().and_then(|_| weather::pressure_tendency(&poller.history, &tag.id));
+		styled(screen, theme, Role::Trend, tendency.map(|change| weather::indicator(change, config.glyphs())).unwrap_or(" "));
+	}
+	else {
+		screen.addstr(&blank);
+	}
+	
+	let voltage = if shows(AlertMetric::BatteryVoltage) { table.number(tag.battery_voltage, config.units.precision.battery_voltage, missing, lang) } else { blank[1..].to_string() };
+	if tag.battery_low {
+		styled(screen, theme, if tag.battery_critical { Role::Critical } else { Role::Warning }, &voltage);
+	}
+	else {
+		screen.addstr(&voltage);
+	}
+	screen.addstr("  ");
+	
+	if tag.unreachable {
+		styled(screen, theme, Role::Unknown, lang.text(Text::Unreachable));
+	}
+	else {
+		screen.addstr(&format_updated(&tag.datetime, view, lang, poller.clock.now()));
+	}
+	screen.addstr("\n");
+}
+
+/**
+ * Minimal layout for small terminals: a line per tag with the name, temperature, humidity and any problem.
+*/
+pub fn render_minimal(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	let lang = config.lang();
+	screen.erase();
+	render_paused(screen, theme, poller);
+	render_tabs(screen, theme, poller, view);
+	render_summary(screen, theme, data, poller, view);
+	
+	for group in scrolled(groups::split(data.iter(), &config.groups), view) {
+		if render_group_header(screen, theme, &group, poller, view) {
+			continue;
+		}
+		for tag in &group.tags {
+			let start = screen.cursor();
+			attributed(screen, selected_style(Style::new(-1, -1, theme::NORMAL), tag, view), &config.table.short_name(tag.label()));
+			screen.addstr(" ");
+			let (temperature, humidity) = short_values(tag, config, (8, 4));
+			attributed(screen, missing_style(theme, tag, config.temperature_color(tag), tag.temperature()), &format!("{:>8}", temperature));
+			let trend = if config.shows(tag, AlertMetric::Temperature) { trend_symbol(&tag.temperature, config) } else { " " };
+			styled(screen, theme, Role::Trend, trend);
+			attributed(screen, missing_style(theme, tag, config.humidity_color(tag), tag.humidity()), &format!("{:>4}", humidity));
+			
+			if tag.unreachable {
+				screen.addstr(" ");
+				styled(screen, theme, Role::Unknown, lang.text(Text::Unreachable));
+			}
+			else if tag.battery_low {
+				screen.addstr(" ");
+				styled(screen, theme, if tag.battery_critical { Role::Critical } else { Role::Warning }, lang.text(Text::BatteryLow));
+			}
+			else if let Some(warning) = outdoor_warning(tag, poller) {
+				screen.addstr(" ");
+				styled(screen, theme, Role::Warning, lang.text(warning.text()));
+			}
+			screen.addstr("\n");
+			mark(screen, view, tag, start);
+		}
+	}
+	
+	render_errors(screen, theme, poller);
+	
+	screen.refresh();
+}
+
+/**
+ * The temperature and the humidity of the minimal and floors layouts within the widths of their columns, the missing
+ * value for the missing ones and empty for the hidden ones.
+*/
+fn short_values(tag: &Tag, config: &Config, widths: (usize, usize)) -> (String, String) {
+	let value = |metric, value: Option<String>| match config.shows(tag, metric) {
+		true => value.unwrap_or(config.missing_value.clone()),
+		false => String::new(),
+	};
+	return (
+		value(AlertMetric::Temperature, tag.temperature().map(|temperature| responsive::value(Quantity::Temperature, temperature, config, widths.0))),
+		value(AlertMetric::Humidity, tag.humidity().map(|humidity| responsive::value(Quantity::Humidity, humidity, config, widths.1))),
+	);
+}
+
+/**
+ * Floors layout: the tags stacked by floor like a cross-section of the building, the top floor on top, for a quick
+ * read of the temperature from the attic to the basement.
+*/
+pub fn render_floors(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	let lang = config.lang();
+	screen.erase();
+	let tag_width = floor_tag_width(&config.table);
+	if render_too_small(screen, theme, (tag_width as i32 + 5, 5), lang) {
+		return;
+	}
+	render_paused(screen, theme, poller);
+	render_tabs(screen, theme, poller, view);
+	render_summary(screen, theme, data, poller, view);
+	
+	// The tags side by side in the rooms of the floor, wrapping to more rows when they don't fit. The last column is
+	// left empty, writing there would wrap the line.
+	let width = screen.size().1 as usize - 1;
+	let per_row = ((width - 4 + 1) / (tag_width + 1)).max(1);
+	let floors = floors::stack(data.iter(), config);
+	let glyphs = config.glyphs();
+	let (horizontal, vertical) = (glyphs.line("─", "-"), glyphs.line("│", "|"));
+	for (index, floor) in floors.iter().enumerate() {
+		let title = match floor.name {
+			Some(name) if name.parse::<f64>().is_ok() => format!("{} {}", lang.text(Text::FloorNumber), name),
+			Some(name) => name.to_string(),
+			None => lang.text(Text::NoFloor).to_string(),
+		};
+		let corners = if index == 0 { (glyphs.line("┌", "+"), glyphs.line("┐", "+")) } else { (glyphs.line("├", "+"), glyphs.line("┤", "+")) };
+		screen.addstr(&format!("{}{} ", corners.0, horizontal));
+		styled(screen, theme, Role::Title, &title);
+		screen.addstr(&format!(" {}{}\n", horizontal.repeat(width.saturating_sub(table::columns(&title) + 5)), corners.1));
+		
+		for row in floor.tags.chunks(per_row) {
+			screen.addstr(&format!("{} ", vertical));
+			for tag in row {
+				let start = screen.cursor();
+				let style = if tag.unreachable { theme.style(Role::Unknown) } else { theme.style(Role::Value) };
+				attributed(screen, selected_style(style, tag, view), &config.table.short_name(tag.label()));
+				let (temperature, humidity) = short_values(tag, config, (8, 5));
+				attributed(screen, missing_style(theme, tag, config.temperature_color(tag), tag.temperature()), &format!("{:>8}", temperature));
+				attributed(screen, missing_style(theme, tag, config.humidity_color(tag), tag.humidity()), &format!("{:>5}", humidity));
+				mark(screen, view, tag, start);
+				screen.addstr(" ");
+			}
+			screen.addstr(&format!("{}{}\n", " ".repeat(width - 3 - row.len() * (tag_width + 1)), vertical));
+		}
+	}
+	if !floors.is_empty() {
+		screen.addstr(&format!("{}{}{}\n", glyphs.line("└", "+"), horizontal.repeat(width - 2), glyphs.line("┘", "+")));
+	}
+	
+	render_errors(screen, theme, poller);
+	render_source_status(screen, theme, poller);
+	
+	screen.refresh();
+}
+
+/**
+ * A tag in the floors layout: the name, temperature and humidity, in characters.
+*/
+fn floor_tag_width(table: &TableConfig) -> usize {
+	return table.short_name_width + 8 + 5;
+}
+
+/**
+ * Floorplan layout: the floorplan drawn in the config with the values of the tags in its placeholders.
+*/
+pub fn render_floorplan(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	screen.erase();
+	render_paused(screen, theme, poller);
+	render_tabs(screen, theme, poller, view);
+	
+	for line in config.floorplan.as_deref().unwrap_or_default().lines() {
+		for segment in floorplan::parse(line) {
+			let (tag_key, metric, width) = match segment {
+				Segment::Text(text) => {
+					screen.addstr(text);
+					continue;
+				},
+				Segment::Value { tag, metric, width } => (tag, metric, width),
+			};
+			let Some(tag) = data.iter().find(|tag| tag.id == tag_key || tag.name == tag_key || tag.label() == tag_key) else {
+				styled(screen, theme, Role::Trend, &table::fit("–", width));
+				continue;
+			};
+			
+			// The values narrow down to the placeholder, the names are cut.
+			let (text, style) = match metric {
+				PlanMetric::Temperature => match tag.temperature() {
+					Some(temperature) => (responsive::value(Quantity::Temperature, temperature, config, width), value_style(theme, tag, config.temperature_color(tag))),
+					None => (config.missing_value.clone(), theme.style(Role::Unknown)),
+				},
+				PlanMetric::Humidity => match tag.humidity() {
+					Some(humidity) => (responsive::value(Quantity::Humidity, humidity, config, width), value_style(theme, tag, config.humidity_color(tag))),
+					None => (config.missing_value.clone(), theme.style(Role::Unknown)),
+				},
+				PlanMetric::Pressure => match &tag.pressure {
+					Some(pressure) => (responsive::value(Quantity::Pressure, pressure.current, config, width), theme.style(Role::Value)),
+					None => ("–".into(), theme.style(Role::Trend)),
+				},
+				PlanMetric::Name => (tag.label().to_string(), theme.style(Role::Title)),
+			};
+			let style = if tag.unreachable { theme.style(Role::Unknown) } else { style };
+			let start = screen.cursor();
+			attributed(screen, selected_style(style, tag, view), &table::ellipsize(&text, width));
+			mark(screen, view, tag, start);
+		}
+		screen.addstr("\n");
+	}
+	
+	screen.addstr("\n");
+	render_errors(screen, theme, poller);
+	render_source_status(screen, theme, poller);
+	
+	screen.refresh();
+}
+
+/**
+ * Alerts page: the active alerts, the most severe first, and the alert log of the last 24 hours if there's a history
+ * store.
+*/
+pub fn render_alerts(screen: &mut dyn Screen, theme: &Theme, poller: &Poller, view: &View) {
+	let lang = poller.config.lang();
+	let now = poller.clock.now();
+	screen.erase();
+	if render_too_small(screen, theme, MINIMUM_SIZE, lang) {
+		return;
+	}
+	render_paused(screen, theme, poller);
+	render_tabs(screen, theme, poller, view);
+	
+	let mut alerts: Vec<&Alert> = poller.alerts.active().collect();
+	alerts.sort_by_key(|alert| (std::cmp::Reverse(alert.severity), alert.started_at));
+	styled(screen, theme, Role::Title, &format!("{}\n", lang.text(Text::ActiveAlerts)));
+	if alerts.is_empty() {
+		styled(screen, theme, Role::Value, &format!("{}\n", lang.text(Text::NoActiveAlerts)));
+	}
+	for alert in alerts {
+		let since = format!("  {}\n", lang.since(&view.zone.local(alert.started_at)));
+		let text = format!("{}: {}", alert.tag_name, alert.message);
+		render_wrapped(screen, Some(theme.style(severity_role(alert.severity))), &text, 0, since.chars().count());
+		screen.addstr(&since);
+	}
+	
+	let log = poller.alert_log(now - chrono::Duration::hours(24));
+	if !log.is_empty() {
+		screen.addstr("\n");
+		styled(screen, theme, Role::Title, &format!("{}\n", lang.text(Text::AlertLog)));
+		// The rest of the screen, leaving room for the errors.
+		let mut rows = (screen.size().0 as usize).saturating_sub(log.len().min(3) + 8);
+		for record in &log {
+			let clock = format!("{} ", lang.clock(&view.zone.local(record.alert.started_at)));
+			let state = format!("  {}\n", record.state());
+			let text = format!("{}: {}", record.alert.tag_name, record.alert.message);
+			let (indent, suffix) = (clock.chars().count(), state.chars().count());
+			let lines = table::wrap(&text, wrap_width(screen, indent + suffix)).len();
+			if lines > rows {
+				break;
+			}
+			rows -= lines;
+			screen.addstr(&clock);
+			render_wrapped(screen, Some(theme.style(severity_role(record.alert.severity))), &text, indent, suffix);
+			styled(screen, theme, Role::Trend, &state);
+		}
+	}
+	
+	screen.addstr("\n");
+	render_errors(screen, theme, poller);
+	
+	screen.refresh();
+}
+
+/**
+ * Informational alerts are dimmed like the trends.
+*/
+fn severity_role(severity: Severity) -> Role {
+	return match severity {
+		Severity::Info => Role::Trend,
+		Severity::Warning => Role::Warning,
+		Severity::Critical => Role::Critical,
+	};
+}
+
+/**
+ * Event log screen: the alerts starting and ending and the network errors, the latest first, as many as fit.
+*/
+pub fn render_event_log(screen: &mut dyn Screen, theme: &Theme, poller: &Poller) {
+	let lang = poller.config.lang();
+	let zone = poller.config.zone();
+	let today = zone.date(poller.clock.now());
+	screen.erase();
+	if render_too_small(screen, theme, MINIMUM_SIZE, lang) {
+		return;
+	}
+	render_paused(screen, theme, poller);
+	styled(screen, theme, Role::Title, &format!("{}\n\n", lang.text(Text::EventLog)));
+	
+	let mut rows = (screen.size().0 as usize).saturating_sub(4);
+	let mut entries = poller.events.entries().peekable();
+	if entries.peek().is_none() {
+		styled(screen, theme, Role::Trend, &format!("{}\n", lang.text(Text::NoEvents)));
+	}
+	for entry in entries {
+		let time = format!("{:>14}  ", lang.timestamp(&zone.local(entry.time), today));
+		let severity_role = entry.severity.map_or(Role::Warning, severity_role);
+		let tag = entry.tag_name.as_deref().unwrap_or_default();
+		// The text, its style and the state after it.
+		let (text, style, state) = match entry.kind {
+			EventKind::AlertStarted => (format!("{}: {}", tag, entry.message), Some(theme.style(severity_role)), Some((Role::Trend, Text::AlertStarted))),
+			EventKind::AlertCleared => (format!("{}: {}", tag, entry.message), None, Some((Role::Value, Text::AlertCleared))),
+			EventKind::AlertUnknown => (format!("{}: {}", tag, entry.message), None, Some((Role::Unknown, Text::Unknown))),
+			EventKind::NetworkError => (format!("{}: {}", lang.text(Text::NetworkError), entry.message), Some(theme.style(Role::Critical)), None),
+			EventKind::NetworkRestored => (lang.text(Text::NetworkRestored).to_string(), Some(theme.style(Role::Value)), None),
+		};
+		let state = state.map(|(role, text)| (role, format!("  {}", lang.text(text))));
+		let (indent, suffix) = (time.chars().count(), state.as_ref().map_or(0, |(_, state)| state.chars().count()));
+		let lines = table::wrap(&text, wrap_width(screen, indent + suffix)).len();
+		if lines > rows {
+			break;
+		}
+		rows -= lines;
+		
+		screen.addstr(&time);
+		render_wrapped(screen, style, &text, indent, suffix);
+		if let Some((role, state)) = state {
+			styled(screen, theme, role, &state);
+		}
+		screen.addstr("\n");
+	}
+	
+	screen.refresh();
+}
+
+/**
+ * The config in use, with the secrets masked, to tell why the client does what it does. Lines longer than the screen
+ * are cut, and the scroll wheel and j and k scroll.
+*/
+pub fn render_inspect(screen: &mut dyn Screen, theme: &Theme, poller: &Poller, view: &View) {
+	let lang = poller.config.lang();
+	screen.erase();
+	if render_too_small(screen, theme, MINIMUM_SIZE, lang) {
+		return;
+	}
+	let (height, width) = screen.size();
+	styled(screen, theme, Role::Title, &format!("{}\n", lang.text(Text::ConfigInUse)));
+	let path = config::config_path().filter(|path| path.exists()).map_or_else(|| lang.text(Text::NoConfigFile).to_string(), |path| path.display().to_string());
+	styled(screen, theme, Role::Trend, &format!("{}\n\n", path));
+	
+	for line in inspect::lines(&poller.config).iter().skip(view.inspect_scroll).take((height - 3).max(0) as usize) {
+		screen.addstr(&format!("{}\n", line.chars().take(width as usize - 1).collect::<String>()));
+	}
+	screen.refresh();
+}
+
+/**
+ * Legend screen: what the colors and symbols mean with the configured ranges, trend symbols and alert rules, each in
+ * the style it has on the other screens.
+*/
+pub fn render_legend(screen: &mut dyn Screen, theme: &Theme, poller: &Poller) {
+	let config = &poller.config;
+	let lang = config.lang();
+	let units = &config.units;
+	screen.erase();
+	if render_too_small(screen, theme, MINIMUM_SIZE, lang) {
+		return;
+	}
+	styled(screen, theme, Role::Title, &format!("{}\n\n", lang.text(Text::Legend)));
+	
+	let temperature = |value: f64| units.temperature(value, 1, lang);
+	let humidity = |value: f64| format!("{}%", units.humidity(value, 1, lang));
+	let mut ranges = |title: &str, ranges: &[ColorRange], format: &dyn Fn(f64) -> String| {
+		screen.addstr(&format!("{}:", title));
+		for range in ranges {
+			screen.addstr("  ");
+			attributed(screen, theme.range_style(Some(range.color)), &format_range(range, format));
+		}
+		screen.addstr("\n");
+	};
+	ranges(lang.text(Text::Temperature), config.ranges.temperature.as_deref().unwrap_or(ranges::DEFAULT_TEMPERATURE), &temperature);
+	ranges(lang.text(Text::Humidity), config.ranges.humidity.as_deref().unwrap_or(ranges::DEFAULT_HUMIDITY), &humidity);
+	
+	// Tags with their own ranges, in a stable order.
+	let mut tags: Vec<(&String, &Ranges)> = config.tags.iter()
+		.filter_map(|(key, tag_config)| Some((tag_config.name.as_ref().unwrap_or(key), tag_config.ranges.as_ref()?)))
+		.collect();
+	tags.sort_by_key(|(name, _)| *name);
+	for (name, tag_ranges) in tags {
+		if let Some(tag_ranges) = &tag_ranges.temperature {
+			ranges(&format!("{}, {}", name, lang.text(Text::Temperature).to_lowercase()), tag_ranges, &temperature);
+		}
+		if let Some(tag_ranges) = &tag_ranges.humidity {
+			ranges(&format!("{}, {}", name, lang.text(Text::Humidity).to_lowercase()), tag_ranges, &humidity);
+		}
+	}
+	screen.addstr("\n");
+	
+	for trend in [1, 0, -1] {
+		styled(screen, theme, Role::Trend, config.vocabulary.symbol(trend, config.glyphs()));
+		screen.addstr(&format!(" {}  ", config.vocabulary.word(trend, lang)));
+	}
+	screen.addstr("\n");
+	styled(screen, theme, Role::Unknown, &config.missing_value);
+	screen.addstr(&format!(" {}  ", lang.text(Text::Missing)));
+	styled(screen, theme, Role::Unknown, lang.text(Text::Unreachable));
+	screen.addstr("  ");
+	styled(screen, theme, Role::Warning, lang.text(Text::BatteryLow));
+	screen.addstr("  ");
+	styled(screen, theme, Role::Critical, lang.text(Text::BatteryCritical));
+	screen.addstr("  ");
+	styled(screen, theme, Role::Warning, lang.text(Text::FrostRisk));
+	screen.addstr("\n");
+	
+	if !config.alerts.is_empty() {
+		styled(screen, theme, Role::Title, &format!("\n{}\n", lang.text(Text::AlertRules)));
+		for rule in &config.alerts {
+			let limit = |value: f64| match rule.metric {
+				AlertMetric::Temperature => temperature(value),
+				AlertMetric::Humidity => humidity(value),
+				AlertMetric::Pressure => format!("{} hPa", units.pressure(value, 1, lang)),
+				AlertMetric::BatteryVoltage => format!("{} V", lang.number(value, 2)),
+				AlertMetric::Rssi => format!("{} dBm", value),
+				AlertMetric::Derived(derived) => derived.format_value(value, units, lang),
+			};
+			// A change in degrees is the same in Celsius and Kelvin, without the offset and the sign.
+			let change = |value: f64| match rule.metric.is_temperature() {
+				true => format!("{}{}/{} min", lang.number(value, 1), units.temperature_symbol(), rule.within_minutes),
+				false => format!("{}/{} min", limit(value), rule.within_minutes),
+			};
+			let limits: Vec<String> = [
+				rule.above.map(|above| format!("> {}", limit(above))),
+				rule.below.map(|below| format!("< {}", limit(below))),
+				rule.rise.map(|rise| format!("+{}", change(rise))),
+				rule.fall.map(|fall| format!("-{}", change(fall))),
+			]
+				.into_iter()
+				.flatten()
+				.collect();
+			styled(screen, theme, severity_role(rule.severity), &format!("{} ({})", rule.name, rule.severity.name()));
+			screen.addstr(&format!(": {} {}\n", rule.metric.name(), limits.join(" ")));
+		}
+	}
+	
+	screen.refresh();
+}
+
+/**
+ * The values a range covers, e.g. "18.0 … 24.0", "< 0.0" or "≥ 28.0".
+*/
+fn format_range(range: &ColorRange, format: &dyn Fn(f64) -> String) -> String {
+	return match (range.min, range.max) {
+		(Some(min), Some(max)) => format!("{} … {}", format(min), format(max)),
+		(Some(min), None) => format!("≥ {}", format(min)),
+		(None, Some(max)) => format!("< {}", format(max)),
+		(None, None) => range.color.name().to_string(),
+	};
+}
+
+/**
+ * Kiosk layout: a single tag centered on the screen with big digits.
+*/
+pub fn render_kiosk(screen: &mut dyn Screen, theme: &Theme, tag: Option<&Tag>, poller: &Poller, view: &View) {
+	let lang = poller.config.lang();
+	screen.erase();
+	if render_too_small(screen, theme, MINIMUM_SIZE, lang) {
+		return;
+	}
+	let (height, width) = screen.size();
+	let center = |text: &str| ((width - table::columns(text) as i32) / 2).max(0);
+	
+	let Some(tag) = tag else {
+		let text = lang.text(Text::WaitingForData);
+		screen.mv(height / 2, center(text));
+		screen.addstr(text);
+		screen.refresh();
+		return;
+	};
+	
+	// Title, big temperature, humidity and status lines, and below them the chart of the pair and the latest alerts of
+	// the tag outside the kiosk mode, as far as there's room.
+	let config = &poller.config;
+	let now = poller.clock.now();
+	let extremes = if config.kiosk { None } else { extremes_line(tag, poller, view, now) };
+	let mut room = height - bigdigits::HEIGHT as i32 - 4 - extremes.is_some() as i32;
+	let gradient = render_gradient(tag, poller, view, width as usize).filter(|rows| (rows.len() + 1) as i32 <= room);
+	room -= gradient.as_ref().map_or(0, |rows| rows.len() + 1) as i32;
+	let alerts = if config.kiosk { Vec::new() } else { tag_alerts(tag, poller, (room - 2).clamp(0, MAX_TAG_ALERTS)) };
+	room -= if alerts.is_empty() { 0 } else { alerts.len() as i32 + 2 };
+	let mut y = (room / 2).max(0);
+	
+	screen.mv(y, center(tag.label()));
+	styled(screen, theme, Role::Title, tag.label());
+	y += 2;
+	
+	let style = missing_style(theme, tag, config.temperature_color(tag), tag.temperature());
+	let units = &config.units;
+	let temperature = tag.temperature().map(|temperature| units.temperature(temperature, units.precision.temperature, lang));
+	for row in bigdigits::render(&temperature.unwrap_or(config.missing_value.clone()), config.glyphs()) {
+		screen.mv(y, center(&row));
+		attributed(screen, style, &row);
+		y += 1;
+	}
+	y += 1;
+	
+	let mut details = match tag.humidity() {
+		_ if !config.shows(tag, AlertMetric::Humidity) => String::new(),
+		Some(humidity) => format!("{}% {}", units.humidity(humidity, units.precision.humidity, lang), trend_symbol(&tag.humidity, config)),
+		None => config.missing_value.clone(),
+	};
+	let outdoor = config.tag(tag).is_some_and(|tag_config| tag_config.outdoor) || config.is_outdoor_tag(tag);
+	if let (true, Some(wind_speed), Some(temperature)) = (outdoor, poller.wind_speed(), tag.temperature()) {
+		details += &format!("  {} {}", lang.text(Text::FeelsLike), units.temperature(metrics::wind_chill(temperature, wind_speed), units.precision.temperature, lang));
+	}
+	screen.mv(y, center(&details));
+	attributed(screen, missing_style(theme, tag, config.humidity_color(tag), tag.humidity()), &details);
+	y += 1;
+	
+	// The detail view tells the measurement from the fetch, e.g. of a tag the gateway still lists but no longer hears.
+	let updated = match poller.fetched.get(&tag.id).filter(|_| !config.kiosk) {
+		Some(fetched) => format!(
+			"{} {}  {} {}",
+			lang.text(Text::Measured),
+			format_updated(&tag.datetime, view, lang, now),
+			lang.text(Text::Fetched),
+			format_updated(&fetched.to_rfc3339(), view, lang, now)
+		),
+		None => format!("{} {}", lang.text(Text::Updated), format_updated(&tag.datetime, view, lang, now)),
+	};
+	let status = match (tag.unreachable, tag.rssi) {
+		(true, _) if config.kiosk => lang.text(Text::Unreachable).to_string(),
+		(true, _) => format!("{}  {}", lang.text(Text::Unreachable), updated),
+		(false, Some(rssi)) => format!("{}  {} {} dBm", updated, signal_bars(rssi, config.glyphs()), rssi),
+		(false, None) => updated,
+	};
+	screen.mv(y, center(&status));
+	styled(screen, theme, if tag.unreachable { Role::Unknown } else { Role::Trend }, &status);
+	y += 1;
+	if let Some(extremes) = extremes {
+		screen.mv(y, center(&extremes));
+		styled(screen, theme, Role::Trend, &extremes);
+		y += 1;
+	}
+	y += 1;
+	
+	if let Some(rows) = gradient {
+		screen.mv(y, center(&rows[0]));
+		styled(screen, theme, Role::Title, &rows[0]);
+		let x = center(&rows[1]);
+		for (index, row) in rows[1..].iter().enumerate() {
+			screen.mv(y + 1 + index as i32, x);
+			styled(screen, theme, Role::Trend, row);
+		}
+		y += rows.len() as i32 + 1;
+	}
+	
+	if !alerts.is_empty() {
+		let title = lang.text(Text::LatestAlerts);
+		screen.mv(y, center(title));
+		styled(screen, theme, Role::Title, title);
+		let x = alerts.iter().map(|(text, _)| center(text)).min().unwrap_or(0);
+		for (index, (text, role)) in alerts.iter().enumerate() {
+			screen.mv(y + 1 + index as i32, x);
+			styled(screen, theme, *role, text);
+		}
+	}
+	
+	if poller.scheduler.paused {
+		let text = lang.text(Text::Paused);
+		screen.mv(0, center(text));
+		styled(screen, theme, Role::Warning, text);
+	}
+	
+	// Errors on the bottom rows.
+	let errors: Vec<String> = poller.errors().into_iter().flat_map(|error| table::wrap(lang.text(error), wrap_width(screen, 0))).collect();
+	for (index, error) in errors.iter().enumerate() {
+		screen.mv(height - errors.len() as i32 + index as i32, center(error));
+		styled(screen, theme, Role::Critical, error);
+	}
+	
+	screen.refresh();
+}
+
+/**
+ * The temperature min and max of the detail view with when they were measured, e.g. "min -3.2°C at 4:12 AM  max
+ * 5.1°C at 2:30 PM (Today)". None for the source's own min/max, which come without the times.
+*/
+fn extremes_line(tag: &Tag, poller: &Poller, view: &View, now: DateTime<Utc>) -> Option<String> {
+	let (config, lang) = (&poller.config, poller.config.lang());
+	let label = view.min_max_label(AlertMetric::Temperature, lang, now)?;
+	let (min, max) = view.extremes(tag, AlertMetric::Temperature, tag.temperature.as_ref()?, &poller.history, now, |sample| sample.temperature);
+	let units = &config.units;
+	let extreme = |text, extreme: Extreme| Some(format!(
+		"{} {} {}",
+		lang.text(text),
+		units.temperature(extreme.value, units.precision.temperature, lang),
+		extreme_time(extreme.datetime?, lang, view.zone, now)
+	));
+	return Some(format!("{}  {} ({})", extreme(Text::Min, min)?, extreme(Text::Max, max)?, label));
+}
+
+// Alerts of the tag listed at most in the detail view.
+const MAX_TAG_ALERTS: i32 = 5;
+
+/**
+ * The latest alert events of the tag as lines with their roles, e.g. "12:05 PM  temperature above 6.00  started".
+*/
+fn tag_alerts(tag: &Tag, poller: &Poller, count: i32) -> Vec<(String, Role)> {
+	let lang = poller.config.lang();
+	let zone = poller.config.zone();
+	let today = zone.date(poller.clock.now());
+	return poller.events.entries()
+		.filter(|entry| entry.tag_name.as_deref() == Some(tag.label()))
+		.take(count.max(0) as usize)
+		.map(|entry| {
+			let (state, role) = match entry.kind {
+				EventKind::AlertStarted => (Text::AlertStarted, entry.severity.map_or(Role::Warning, severity_role)),
+				EventKind::AlertCleared => (Text::AlertCleared, Role::Value),
+				_ => (Text::Unknown, Role::Unknown),
+			};
+			(format!("{}  {}  {}", lang.timestamp(&zone.local(entry.time), today), entry.message, lang.text(state)), role)
+		})
+		.collect();
+}
+
+// Rows of the pair's chart, and the most columns, each half an hour of the day.
+const GRADIENT_HEIGHT: usize = 5;
+const GRADIENT_COLUMNS: usize = 48;
+
+/**
+ * Chart of the temperature difference between the tag and its pair over the last day, with a title above it. None
+ * without a pair or its history.
+*/
+fn render_gradient(tag: &Tag, poller: &Poller, view: &View, width: usize) -> Option<Vec<String>> {
+	let config = &poller.config;
+	let lang = config.lang();
+	let pair = gradient::pair(tag, &poller.data, config)?;
+	let now = poller.clock.now();
+	let window = chrono::Duration::hours(24);
+	// Room for e.g. "-12.3°C |" on the left.
+	let label_width = 7;
+	let columns = width.saturating_sub(label_width + 4).min(GRADIENT_COLUMNS);
+	let differences = gradient::differences(&poller.history, &tag.id, &pair.id, now, window, columns);
+	
+	// Kelvin and Celsius degrees are the same size.
+	let label = |difference: f64| format!("{}{}", lang.signed(difference, 1), config.units.temperature_symbol());
+	let mut rows = chart::bars(&differences, GRADIENT_HEIGHT, 1.0, config.glyphs(), label, label_width);
+	if rows.is_empty() {
+		return None;
+	}
+	let today = view.zone.date(now);
+	let (start, end) = (lang.timestamp(&view.zone.local(now - window), today), lang.clock(&view.zone.local(now)));
+	rows.push(chart::ends(&start, &end, columns, label_width));
+	rows.insert(0, format!("{} − {}, {}", tag.label(), pair.label(), lang.text(Text::Last24Hours)));
+	return Some(rows);
+}
+
+/**
+ * Frost or condensation warning of an outdoor tag with fresh data.
+*/
+fn outdoor_warning(tag: &Tag, poller: &Poller) -> Option<frost::Warning> {
+	let config = &poller.config;
+	let outdoor = config.tag(tag).is_some_and(|tag_config| tag_config.outdoor) || config.is_outdoor_tag(tag);
+	if !outdoor || tag.unreachable {
+		return None;
+	}
+	return frost::check(tag, &poller.history, &config.frost);
+}
+
+/**
+ * The tags drawn by the list on the left and the detail of the selected one on the right, for wide terminals.
+*/
+pub fn render_split(screen: &mut dyn Screen, theme: &Theme, list: impl FnOnce(&mut dyn Screen), tag: &Tag, poller: &Poller, view: &View) {
+	let (height, width) = screen.size();
+	let left = width / 2;
+	list(&mut Pane::new(screen, 0, 0, (height, left)));
+	render_kiosk(&mut Pane::new(screen, 0, left + 1, (height, width - left - 1)), theme, Some(tag), poller, view);
+	
+	let line = poller.config.glyphs().line("│", "|");
+	screen.set_style(Some(theme.style(Role::Trend)));
+	for y in 0..height {
+		screen.mv(y, left);
+		screen.addstr(line);
+	}
+	screen.set_style(None);
+	screen.refresh();
+}
+
+/**
+ * Indicator of the paused polling on the top line, so that old values aren't mistaken for current ones.
+*/
+fn render_paused(screen: &mut dyn Screen, theme: &Theme, poller: &Poller) {
+	if poller.scheduler.paused {
+		styled(screen, theme, Role::Warning, &format!("{}\n", poller.config.lang().text(Text::Paused)));
+	}
+}
+
+/**
+ * The pages as tabs on the top line with their numbers, the page shown highlighted. Nothing without pages.
+*/
+fn render_tabs(screen: &mut dyn Screen, theme: &Theme, poller: &Poller, view: &View) {
+	let pages = &poller.config.pages;
+	if pages.is_empty() {
+		return;
+	}
+	for (index, page) in pages.iter().enumerate() {
+		let tab = format!(" {} {} ", index + 1, page.name);
+		if index == view.page {
+			let style = theme.style(Role::Title);
+			attributed(screen, Style { attributes: style.attributes | theme::REVERSE, ..style }, &tab);
+		}
+		else {
+			styled(screen, theme, Role::Trend, &tab);
+		}
+		screen.addstr(" ");
+	}
+	screen.addstr("\n\n");
+}
+
+/**
+ * Summary line of all the tags, if enabled: the warmest and the coldest, the indoor averages, the tags in alert and
+ * the freshness of the data. Wrapped between the parts.
+*/
+fn render_summary(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, view: &View) {
+	let config = &poller.config;
+	if !config.summary {
+		return;
+	}
+	let (units, lang) = (&config.units, config.lang());
+	let summary = summary::summarize(data, config, &poller.alerts);
+	
+	let mut parts: Vec<(Option<Role>, String)> = Vec::new();
+	if let Some((label, temperature)) = &summary.warmest {
+		parts.push((None, format!("{} {} {}", lang.text(Text::Warmest), label, units.temperature(*temperature, units.precision.temperature, lang))));
+	}
+	if let Some((label, temperature)) = &summary.coldest {
+		parts.push((None, format!("{} {} {}", lang.text(Text::Coldest), label, units.temperature(*temperature, units.precision.temperature, lang))));
+	}
+	if let Some(indoor) = summary.indoor {
+		let groups: Vec<String> = summary.groups.iter().map(|(name, average)| format!("{} {}", name, units.temperature(*average, units.precision.temperature, lang))).collect();
+		let groups = if groups.is_empty() { String::new() } else { format!(" ({})", groups.join(", ")) };
+		parts.push((None, format!("{} {} {}{}", lang.text(Text::Indoors), lang.text(Text::Average), units.temperature(indoor, units.precision.temperature, lang), groups)));
+	}
+	if let Some(severity) = summary.severity.filter(|_| summary.alerting > 0) {
+		parts.push((Some(severity_role(severity)), format!("{} {}", summary.alerting, lang.text(Text::InAlert))));
+	}
+	if let Some(oldest) = summary.oldest {
+		parts.push((Some(Role::Trend), format!("{} {}", lang.text(Text::Oldest), format_updated(&oldest.to_rfc3339(), view, lang, poller.clock.now()))));
+	}
+	if summary.unreachable > 0 {
+		parts.push((Some(Role::Unknown), format!("{} {}", summary.unreachable, lang.text(Text::Unreachable).to_lowercase())));
+	}
+	
+	let width = wrap_width(screen, 0);
+	let mut column = 0;
+	for (role, text) in parts {
+		let length = table::columns(&text);
+		if column > 0 && column + 2 + length > width {
+			screen.addstr("\n");
+			column = 0;
+		}
+		else if column > 0 {
+			screen.addstr("  ");
+			column += 2;
+		}
+		screen.set_style(role.map(|role| theme.style(role)));
+		screen.addstr(&text);
+		screen.set_style(None);
+		column += length;
+	}
+	screen.addstr("\n\n");
+}
+
+/**
+ * Where the S key saved the screen on the bottom row, or why it couldn't.
+*/
+pub fn render_snapshot_saved(screen: &mut dyn Screen, theme: &Theme, lang: Lang, saved: &Result<PathBuf, String>) {
+	let (text, role) = match saved {
+		Ok(path) => (format!("{} {}", lang.text(Text::SnapshotSaved), path.display()), Role::Trend),
+		Err(error) => (error.clone(), Role::Critical),
+	};
+	let text = table::ellipsize(&text, wrap_width(screen, 0));
+	screen.mv(screen.size().0 - 1, 0);
+	styled(screen, theme, role, &text);
+	screen.refresh();
+}
+
+// Matches of the command palette listed at most.
+const PALETTE_MATCHES: usize = 6;
+
+/**
+ * The command palette on the bottom rows, over whatever the layout drew there: the input after a colon on the last
+ * row and the best matches above it, the selected one reversed.
+*/
+pub fn render_palette(screen: &mut dyn Screen, theme: &Theme, data: &ApiResponse, poller: &Poller, palette: &Palette) {
+	let (height, _) = screen.size();
+	let width = wrap_width(screen, 0);
+	let matches = palette.matches(&poller.config, data);
+	let shown = matches.len().min(PALETTE_MATCHES).min(height.saturating_sub(1).max(0) as usize);
+	// The selected match stays in view when Tab moves past the ones shown.
+	let first = palette.selected.saturating_sub(shown.saturating_sub(1));
+	for (row, (index, command)) in matches.iter().enumerate().skip(first).take(shown).enumerate() {
+		screen.mv(height - 1 - shown as i32 + row as i32, 0);
+		let style = theme.style(Role::Value);
+		let style = if index == palette.selected { Style { attributes: style.attributes ^ theme::REVERSE, ..style } } else { style };
+		attributed(screen, style, &format!("{:<width$}", table::ellipsize(&format!(" {}", command.label), width), width = width));
+	}
+	screen.mv(height - 1, 0);
+	styled(screen, theme, Role::Title, &format!("{:<width$}", table::ellipsize(&format!(":{}", palette.input), width), width = width));
+	screen.refresh();
+}
+
+/**
+ * The threshold being nudged on the bottom row, over whatever the layout drew there, e.g. "cellar_cold: temperature
+ * below 4.0 → 2.5  y saves into the config, n cancels".
+*/
+pub fn render_nudge(screen: &mut dyn Screen, theme: &Theme, poller: &Poller, nudge: &Nudge) {
+	let Some(rule) = poller.config.alerts.get(nudge.rule) else {
+		return;
+	};
+	let lang = poller.config.lang();
+	let decimals = nudge::decimals(rule.metric);
+	let (text, role) = match &nudge.error {
+		Some(error) => (format!("{}  {}", error, lang.text(Text::SaveThreshold)), Role::Critical),
+		None => (
+			format!(
+				"{}: {} {} {} → {}  {}",
+				rule.name,
+				rule.metric.name(),
+				lang.text(if nudge.above { Text::Above } else { Text::Below }),
+				lang.number(nudge.from, decimals),
+				lang.number(nudge.to, decimals),
+				lang.text(Text::SaveThreshold)
+			),
+			Role::Warning,
+		),
+	};
+	let text = table::ellipsize(&text, wrap_width(screen, 0));
+	screen.mv(screen.size().0 - 1, 0);
+	styled(screen, theme, role, &text);
+	screen.refresh();
+}
+
+/**
+ * The errors of the poller in red, each on its own lines. The data errors are left to the source status, which has
+ * the details.
+*/
+fn render_errors(screen: &mut dyn Screen, theme: &Theme, poller: &Poller) {
+	let lang = poller.config.lang();
+	for error in poller.errors().into_iter().filter(|error| *error != Text::DataError) {
+		render_wrapped(screen, Some(theme.style(Role::Critical)), lang.text(error), 0, 0);
+		screen.addstr("\n");
+	}
+}
+
+/**
+ * Columns for wrapped text on a line with the given columns taken. The last column is left empty, because writing
+ * to it already moves the cursor to the next line.
+*/
+fn wrap_width(screen: &dyn Screen, taken: usize) -> usize {
+	return (screen.size().1.max(0) as usize).saturating_sub(taken + 1);
+}
+
+/**
+ * The text word wrapped in the style, rather than broken mid-word by the terminal. The cursor is after an indent of
+ * the given width, and the last line leaves room for a suffix of the width. The continuation lines are indented under
+ * the first one, and the cursor is left at the end of the last line.
+*/
+fn render_wrapped(screen: &mut dyn Screen, style: Option<Style>, text: &str, indent: usize, suffix: usize) {
+	screen.set_style(style);
+	for (index, line) in table::wrap(text, wrap_width(screen, indent + suffix)).iter().enumerate() {
+		if index > 0 {
+			screen.addstr(&format!("\n{}", " ".repeat(indent)));
+		}
+		screen.addstr(line);
+	}
+	screen.set_style(None);
+}
+
+/**
+ * Source health: data from the sources that couldn't be parsed, with the start of the JSON, and whether the API
+ * data changed on the latest conditional request. And a newer release, if the update check found one.
+*/
+fn render_source_status(screen: &mut dyn Screen, theme: &Theme, poller: &Poller) {
+	let lang = poller.config.lang();
+	if !poller.problems.is_empty() {
+		styled(screen, theme, Role::Warning, &format!("{}\n", lang.text(Text::SourceProblems)));
+		for (source, problem) in &poller.problems {
+			screen.addstr("  ");
+			render_wrapped(screen, None, &format!("{}: {}", lang.text(source.text()), problem.message), 2, 0);
+			screen.addstr("\n");
+			styled(screen, theme, Role::Trend, &format!("    {}\n", problem.snippet));
+		}
+	}
+	
+	let text = match poller.cache_status {
+		Some(CacheStatus::Modified) => Some(Text::SourceModified),
+		Some(CacheStatus::NotModified) => Some(Text::SourceNotModified),
+		None => None,
+	};
+	if let Some(text) = text {
+		styled(screen, theme, Role::Trend, &format!("{}\n", lang.text(text)));
+	}
+	
+	if let Some(version) = poller.update.as_ref().and_then(|update| update.available()) {
+		styled(screen, theme, Role::Trend, &format!("{}: {}\n", lang.text(Text::UpdateAvailable), version));
+	}
+}
+
+/**
+ * If the terminal is smaller than the layout needs, show that instead of garbled overlapping text. The layout
+ * comes back by itself when the terminal is resized.
+*/
+fn render_too_small(screen: &mut dyn Screen, theme: &Theme, minimum: (i32, i32), lang: Lang) -> bool {
+	let (height, width) = screen.size();
+	if width >= minimum.0 && height >= minimum.1 {
+		return false;
+	}
+	
+	let lines = [lang.text(Text::TerminalTooSmall).to_string(), lang.terminal_size(minimum, (width, height))];
+	for (index, line) in lines.iter().enumerate() {
+		let x = ((width - line.chars().count() as i32) / 2).max(0);
+		screen.mv((height / 2 - 1 + index as i32).max(0), x);
+		styled(screen, theme, Role::Warning, line);
+	}
+	screen.refresh();
+	
+	return true;
+}
+
+/**
+ * Big temperature of the outdoor tag with the feels-like temperature and today's min/max.
+*/
+fn render_outdoor_widget(screen: &mut dyn Screen, theme: &Theme, tag: &Tag, poller: &Poller) {
+	let lang = poller.config.lang();
+	styled(screen, theme, Role::Title, tag.label());
+	if tag.unreachable {
+		screen.addstr(" ");
+		styled(screen, theme, Role::Unknown, lang.text(Text::Unreachable));
+	}
+	screen.addstr("\n");
+	
+	let config = &poller.config;
+	let style = missing_style(theme, tag, config.temperature_color(tag), tag.temperature());
+	let units = &config.units;
+	let temperature = tag.temperature().map(|temperature| units.temperature(temperature, units.precision.temperature, lang));
+	for row in bigdigits::render(&temperature.unwrap_or(config.missing_value.clone()), config.glyphs()) {
+		attributed(screen, style, &format!("{}\n", row));
+	}
+	
+	if let (Some(wind_speed), Some(temperature)) = (poller.wind_speed(), tag.temperature()) {
+		screen.addstr(&format!(
+			"{} {} ({} {} m/s)  ",
+			lang.text(Text::FeelsLike),
+			units.temperature(metrics::wind_chill(temperature, wind_speed), units.precision.temperature, lang),
+			lang.text(Text::Wind),
+			lang.number(wind_speed, 1)
+		));
+	}
+	
+	let zone = poller.config.zone();
+	let midnight = zone.midnight(zone.date(poller.clock.now()));
+	if let Some((min, max)) = midnight.and_then(|midnight| poller.history.temperature_range(&tag.id, midnight)) {
+		screen.addstr(&format!("{} {}…{}", lang.text(Text::Today), units.temperature(min, units.precision.temperature, lang), units.temperature(max, units.precision.temperature, lang)));
+	}
+	
+	screen.addstr("\n\n");
+}
+
+/**
+ * Target band like "2.0…6.0", "≥ 65.0" or "≤ 6.0".
+*/
+fn format_target(target: &TargetRange, lang: Lang) -> String {
+	match (target.min, target.max) {
+		(Some(min), Some(max)) => format!("{}…{}", lang.number(min, 1), lang.number(max, 1)),
+		(Some(min), None) => format!("≥ {}", lang.number(min, 1)),
+		(None, Some(max)) => format!("≤ {}", lang.number(max, 1)),
+		(None, None) => "–".into(),
+	}
+}
+
+/**
+ * When a min or max was measured, e.g. "at 4:12 AM", with the date if it wasn't today.
+*/
+fn extreme_time(datetime: DateTime<Utc>, lang: Lang, zone: Zone, now: DateTime<Utc>) -> String {
+	return format!("{} {}", lang.text(Text::At), lang.timestamp(&zone.local(datetime), zone.date(now)));
+}
+
+/**
+ * The "Updated" time, relative or absolute as chosen in the view.
+*/
+pub fn format_updated(datetime: &str, view: &View, lang: Lang, now: DateTime<Utc>) -> String {
+	if !view.absolute_time {
+		return format_time_ago(datetime, now, lang);
+	}
+	
+	match datetime.parse::<DateTime<Utc>>() {
+		Ok(parsed) => lang.timestamp(&view.zone.local(parsed), view.zone.date(now)),
+		Err(_) => lang.unknown().into(),
+	}
+}
+
+/**
+ * The calibrated values the tag sent, e.g. "21.85°C 61.20%", None if none are calibrated.
+*/
+fn format_raw(tag: &Tag, config: &Config) -> Option<String> {
+	let lang = config.lang();
+	let raw = |metric, value: &Option<Metric>| value.as_ref().and_then(|value| value.raw).filter(|_| config.shows(tag, metric));
+	let values: Vec<String> = [
+		raw(AlertMetric::Temperature, &tag.temperature).map(|temperature| config.units.temperature(temperature, 2, lang)),
+		raw(AlertMetric::Humidity, &tag.humidity).map(|humidity| format!("{}%", config.units.humidity(humidity, 2, lang))),
+	].into_iter().flatten().collect();
+	return (!values.is_empty()).then(|| values.join(" "));
+}
+
+/**
+ * Get human readable time ago.
+*/
+fn format_time_ago(datetime: &str, now: DateTime<Utc>, lang: Lang) -> String {
+	if let Ok(parsed) = datetime.parse::<DateTime<Utc>>() {
+		return lang.time_ago((now - parsed).num_seconds());
+	}
+	else {
+		return lang.unknown().into() // &str to String.
+	}
+}
+
+/**
+ * Signal strength as four bars, a bar for each 10 dB above -100 dBm, e.g. "▂▄▆·" for -65 dBm.
+*/
+fn signal_bars(rssi: i32, glyphs: Glyphs) -> String {
+	let bars = ((rssi + 100) / 10).clamp(0, 4) as usize;
+	let (full, empty) = if glyphs.blocks { (["▂", "▄", "▆", "█"], "·") } else { (["|"; 4], ".") };
+	return (0..4).map(|bar| if bar < bars { full[bar] } else { empty }).collect();
+}
+
+/**
+ * The barometer indicator and the three hour pressure change, e.g. " ↑ +2.1 hPa/3 h". Empty without the history for
+ * it.
+*/
+fn format_tendency(tag: &Tag, poller: &Poller, lang: Lang) -> String {
+	let Some(change) = tag.pressure.as_ref().and_then(|_| weather::pressure_tendency(&poller.history, &tag.id)) else {
+		return String::new();
+	};
+	return format!(" {} {} hPa/3 h", weather::indicator(change, poller.config.glyphs()), lang.signed(change, 1));
+}
+
+/**
+ * Add text with the theme style of the role.
+*/
+fn styled(screen: &mut dyn Screen, theme: &Theme, role: Role, text: &str) {
+	attributed(screen, theme.style(role), text);
+}
+
+/**
+ * Style of a value colored by its range. Values of tags without fresh data are dimmed instead, whatever the range.
+*/
+fn value_style(theme: &Theme, tag: &Tag, color: Option<RangeColor>) -> Style {
+	if tag.unreachable {
+		return theme.style(Role::Unknown);
+	}
+	return theme.range_style(color);
+}
+
+/**
+ * Style of a value that may be missing: the missing value is dimmed.
+*/
+fn missing_style(theme: &Theme, tag: &Tag, color: Option<RangeColor>, value: Option<f64>) -> Style {
+	return match value {
+		Some(_) => value_style(theme, tag, color),
+		None => theme.style(Role::Unknown),
+	};
+}
+
+/**
+ * Trend symbol of the metric, a space for a missing metric to keep the columns.
+*/
+fn trend_symbol<'a>(metric: &Option<Metric>, config: &'a Config) -> &'a str {
+	return match metric {
+		Some(metric) => config.vocabulary.symbol(metric.trend.unwrap_or(0), config.glyphs()),
+		None => " ",
+	};
+}
+
+/**
+ * Add text with the style.
+*/
+fn attributed(screen: &mut dyn Screen, style: Style, text: &str) {
+	screen.set_style(Some(style));
+	screen.addstr(text);
+	screen.set_style(None);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn range_legend() {
+		let format = |value: f64| format!("{:.1}", value);
+		let range = |min, max| ColorRange { min, max, color: RangeColor::Green };
+		assert_eq!(format_range(&range(Some(18.0), Some(24.0)), &format), "18.0 … 24.0");
+		assert_eq!(format_range(&range(None, Some(0.0)), &format), "< 0.0");
+		assert_eq!(format_range(&range(Some(28.0), None), &format), "≥ 28.0");
+		assert_eq!(format_range(&range(None, None), &format), "green");
+	}
+	
+	#[test]
+	fn signal_strength_bars() {
+		assert_eq!(signal_bars(-65, Glyphs::UNICODE), "▂▄▆·");
+		assert_eq!(signal_bars(-40, Glyphs::UNICODE), "▂▄▆█");
+		assert_eq!(signal_bars(-95, Glyphs::ASCII), "....");
+		assert_eq!(signal_bars(-85, Glyphs::ASCII), "|...");
+	}
+	
+	#[test]
+	fn time_ago() {
+		let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		assert_eq!(format_time_ago("2024-05-01T11:59:58Z", now, Lang::En), "just now");
+		assert_eq!(format_time_ago("2024-05-01T11:59:15Z", now, Lang::En), "45 seconds ago");
+		assert_eq!(format_time_ago("2024-05-01T11:58:59Z", now, Lang::En), "1 minute ago");
+		assert_eq!(format_time_ago("2024-04-29T11:00:00Z", now, Lang::En), "2 days ago");
+	}
+	
+	#[test]
+	fn time_ago_in_the_future() {
+		let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		assert_eq!(format_time_ago("2024-05-01T12:00:03Z", now, Lang::En), "just now");
+		assert_eq!(format_time_ago("2024-05-01T12:02:00Z", now, Lang::En), "in 2 minutes");
+		assert_eq!(format_time_ago("2024-05-01T12:02:00Z", now, Lang::Fi), "2 minuutin päästä");
+	}
+	
+	#[test]
+	fn min_max_label() {
+		let now = "2024-05-01T11:05:00Z".parse::<DateTime<Utc>>().unwrap();
+		let mut view = View::new(&Config { timezone: Some(chrono_tz::Europe::Helsinki), ..Config::default() });
+		assert_eq!(view.min_max_label(AlertMetric::Temperature, Lang::En, now), None);
+		
+		view.cycle_min_max_window();
+		assert_eq!(view.min_max_label(AlertMetric::Temperature, Lang::Fi, now).as_deref(), Some("Tänään"));
+		
+		// A reset later than the window start takes over.
+		view.min_max_reset = Some("2024-04-30T21:00:01Z".parse().unwrap());
+		assert_eq!(view.min_max_label(AlertMetric::Temperature, Lang::Fi, now).as_deref(), Some("klo 0.00 alkaen"));
+	}
+	
+	#[test]
+	fn min_max_window_per_metric() {
+		// At 8 a.m. the night's low is in the last 24 hours but not today.
+		let now = "2024-05-01T05:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let windows = MinMaxWindows { temperature: Some(MinMaxWindow::Last24Hours), ..MinMaxWindows::default() };
+		let config = Config { timezone: Some(chrono_tz::Europe::Helsinki), min_max_window: MinMaxWindow::Today, min_max_windows: windows, ..Config::default() };
+		let mut view = View::new(&config);
+		let mut history = History::new(chrono::Duration::days(7));
+		let reading = |datetime: &str, temperature: f64| vec![Tag {
+			datetime: datetime.into(),
+			temperature: Some(Metric::from_current(temperature)),
+			humidity: Some(Metric::from_current(temperature + 60.0)),
+			..Tag::test("cellar", "Cellar")
+		}];
+		history.add(&reading("2024-04-30T20:00:00Z", 4.0));
+		history.add(&reading("2024-04-30T22:00:00Z", 6.0));
+		let data = reading("2024-05-01T05:00:00Z", 8.0);
+		history.add(&data);
+		let tag = &data[0];
+		
+		let temperature = tag.temperature.as_ref().unwrap();
+		let humidity = tag.humidity.as_ref().unwrap();
+		assert_eq!(view.min_max(tag, AlertMetric::Temperature, temperature, &history, now, |sample| sample.temperature), (4.0, 8.0));
+		let (min, max) = view.extremes(tag, AlertMetric::Temperature, temperature, &history, now, |sample| sample.temperature);
+		assert_eq!((min.datetime, max.datetime), ("2024-04-30T20:00:00Z".parse().ok(), Some(now)));
+		assert_eq!(view.min_max(tag, AlertMetric::Humidity, humidity, &history, now, |sample| sample.humidity), (66.0, 68.0));
+		assert_eq!(view.min_max_label(AlertMetric::Humidity, Lang::En, now).as_deref(), Some("Today"));
+		
+		// The key picks one window for all.
+		view.cycle_min_max_window();
+		assert_eq!(view.min_max_window(AlertMetric::Temperature), MinMaxWindow::Last24Hours);
+		assert_eq!(view.min_max(tag, AlertMetric::Humidity, humidity, &history, now, |sample| sample.humidity), (64.0, 68.0));
+	}
+	
+	#[test]
+	fn clicks_select_the_tags() {
+		let mut view = View::new(&Config::default());
+		let region = |rows, columns, id: &str| Region { rows, columns, tag_id: id.into() };
+		view.regions.replace(vec![region(2..6, 0..i32::MAX, "sauna"), region(6..7, 4..17, "cellar")]);
+		
+		view.click(3, 40, false);
+		assert_eq!(view.selected.as_deref(), Some("sauna"));
+		view.click(6, 20, true);
+		assert_eq!((view.selected.as_deref(), view.detail), (None, false));
+		view.click(6, 10, true);
+		assert_eq!((view.selected.as_deref(), view.detail), (Some("cellar"), true));
+		// A click on the tag shown alone goes back to the tags.
+		view.click(0, 0, false);
+		assert_eq!((view.selected.as_deref(), view.detail), (Some("cellar"), false));
+	}
+	
+	#[test]
+	fn scrolling_skips_tags_in_the_groups() {
+		let tag = |id: &str| Tag::test(id, id);
+		let data = [tag("Sauna"), tag("Bedroom"), tag("Attic"), tag("Cellar")];
+		let groups = vec![
+			groups::GroupConfig { name: "Upstairs".into(), tags: vec!["Bedroom".into(), "Attic".into()], location: None },
+			groups::GroupConfig { name: "Downstairs".into(), tags: vec!["Cellar".into()], location: None },
+		];
+		let mut view = View::new(&Config::default());
+		let names = |view: &View| scrolled(groups::split(data.iter(), &groups), view)
+			.iter()
+			.map(|group| group.tags.iter().map(|tag| tag.id.as_str()).collect::<Vec<_>>().join(" "))
+			.collect::<Vec<_>>();
+		
+		view.scroll_by(1, data.len());
+		assert_eq!(names(&view), ["Attic", "Cellar", "Sauna"]);
+		view.collapsed.insert("Upstairs".into());
+		assert_eq!(names(&view), ["Cellar", "Sauna"]);
+		view.scroll_by(10, data.len());
+		assert_eq!(view.scroll, 3);
+		view.scroll_by(-5, data.len());
+		assert_eq!(view.scroll, 0);
+	}
+	
+	#[test]
+	fn auto_layout_fits_the_tags() {
+		let poller = Poller::new(Config::default(), std::sync::Arc::new(crate::clock::SystemClock)).unwrap();
+		let tag = |id: &str| Tag {
+			temperature: Some(crate::model::Metric::from_current(21.0)),
+			humidity: Some(crate::model::Metric::from_current(40.0)),
+			..Tag::test(id, id)
+		};
+		let data: ApiResponse = (0..6).map(|index| tag(&index.to_string())).collect();
+		
+		// Six cards take 36 rows, the table 9 rows and 73 columns.
+		assert_eq!(resolve_layout(Layout::Auto, (40, 80), &data, &poller), Layout::Cards);
+		assert_eq!(resolve_layout(Layout::Auto, (20, 80), &data, &poller), Layout::Table);
+		assert_eq!(resolve_layout(Layout::Auto, (20, 40), &data, &poller), Layout::Minimal);
+		assert_eq!(resolve_layout(Layout::Table, (40, 80), &data, &poller), Layout::Table);
+	}
+	
+	#[test]
+	fn invalid_time() {
+		assert_eq!(format_time_ago("", Utc::now(), Lang::En), "unknown");
+	}
+}
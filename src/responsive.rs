@@ -0,0 +1,76 @@
+use crate::config::Config;
+use crate::i18n::Lang;
+use crate::table;
+use crate::units::TemperatureUnit;
+
+/**
+ * A metric whose values are shown with a unit.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+	Temperature,
+	Humidity,
+	Pressure,
+	BatteryVoltage,
+}
+
+/**
+ * The value with its unit in at most the width, for the narrow columns of all the layouts. Rather than being cut at
+ * an arbitrary character, it gives up its detail a step at a time until it fits: the plus sign, the long unit, the
+ * decimals one by one and last the unit, e.g. "+21.43°C", "21.43°C", "21.43°", "21.4°", "21°", "21". Filled with #
+ * if not even the bare number fits, like a spreadsheet does, as a cut number would be a wrong one.
+*/
+pub fn value(quantity: Quantity, value: f64, config: &Config, width: usize) -> String {
+	let units = &config.units;
+	let precision = &units.precision;
+	let (value, decimals, signed, unit, short_unit) = match quantity {
+		Quantity::Temperature if units.temperature == TemperatureUnit::Kelvin => (units.convert(value), units.decimals(precision.temperature, 3), false, " K", "K"),
+		Quantity::Temperature => (value, units.decimals(precision.temperature, 3), true, "°C", "°"),
+		Quantity::Humidity => (value, units.decimals(precision.humidity, 4), false, "%", "%"),
+		Quantity::Pressure => (value, units.decimals(precision.pressure, 2), false, " hPa", "hPa"),
+		Quantity::BatteryVoltage => (value, precision.battery_voltage, false, " V", "V"),
+	};
+	let lang = config.lang();
+	let mut forms = Vec::new();
+	if signed {
+		forms.push(format!("{}{}", lang.signed(value, decimals), unit));
+	}
+	forms.push(format!("{}{}", lang.number(value, decimals), unit));
+	forms.extend((0..=decimals).rev().map(|decimals| format!("{}{}", lang.number(value, decimals), short_unit)));
+	forms.push(lang.number(value, 0));
+	return fitting(forms, width);
+}
+
+/**
+ * The bare number to the decimals or fewer, to fit the width, e.g. for the table columns. Filled with # if it doesn't
+ * fit without decimals.
+*/
+pub fn number(value: f64, decimals: usize, lang: Lang, width: usize) -> String {
+	return fitting((0..=decimals).rev().map(|decimals| lang.number(value, decimals)).collect(), width);
+}
+
+/**
+ * The first of the forms, from the most detailed to the shortest, that fits the width.
+*/
+fn fitting(forms: Vec<String>, width: usize) -> String {
+	return forms.into_iter().find(|form| table::columns(form) <= width).unwrap_or_else(|| "#".repeat(width));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::units::{Precision, Units};
+	
+	#[test]
+	fn narrowing() {
+		let config = Config { units: Units { precision: Precision { temperature: 2, ..Precision::default() }, ..Units::default() }, ..Config::default() };
+		let widths: Vec<String> = (2..=8).rev().map(|width| value(Quantity::Temperature, 21.43, &config, width)).collect();
+		assert_eq!(widths, ["+21.43°C", "21.43°C", "21.43°", "21.4°", "21°", "21°", "21"]);
+		assert_eq!(value(Quantity::Temperature, -5.26, &config, 5), "-5.3°");
+		assert_eq!(value(Quantity::Pressure, 1012.94, &config, 8), "1013hPa");
+		assert_eq!(value(Quantity::Pressure, 1012.94, &config, 10), "1012.9 hPa");
+		assert_eq!(value(Quantity::Humidity, 100.0, &config, 2), "##");
+		assert_eq!(number(1012.94, 1, Lang::Fi, 6), "1012,9");
+		assert_eq!(number(1012.94, 1, Lang::En, 5), "1013");
+	}
+}
@@ -0,0 +1,266 @@
+use base64::Engine;
+
+/**
+ * Decoder for RuuviTag broadcasts, Data Format 3 (RAWv1) and 5 (RAWv2):
+ * https://docs.ruuvi.com/communication/bluetooth-advertisements
+ * Values the tag couldn't measure (marked invalid in the broadcast) are None.
+*/
+#[derive(Debug, Clone, PartialEq)]
+#[allow(dead_code)] // Sources don't show the acceleration yet.
+pub struct Measurement {
+	pub data_format: u8,
+	// °C.
+	pub temperature: Option<f64>,
+	// Relative humidity, %.
+	pub humidity: Option<f64>,
+	// Pa.
+	pub pressure: Option<f64>,
+	// G, per axis.
+	pub acceleration: Option<(f64, f64, f64)>,
+	// V.
+	pub battery_voltage: Option<f64>,
+	// dBm. Data Format 5 only, like the rest below.
+	pub tx_power: Option<i8>,
+	pub movement_counter: Option<u8>,
+	pub measurement_sequence: Option<u16>,
+	pub mac: Option<[u8; 6]>,
+}
+
+// Ruuvi Innovations' Bluetooth company ID, little endian as in the advertisement.
+const MANUFACTURER_ID: [u8; 2] = [0x99, 0x04];
+
+/**
+ * Decode a broadcast. The data can be the whole advertisement, the manufacturer data (starting with the company
+ * ID 0x0499) or just the payload starting with the format byte.
+*/
+pub fn decode(data: &[u8]) -> Result<Measurement, String> {
+	let payload = match data.first() {
+		Some(3 | 5) => data,
+		_ => match data.windows(2).position(|pair| pair == MANUFACTURER_ID) {
+			Some(index) => &data[index + 2..],
+			None => data,
+		},
+	};
+	
+	match payload.first() {
+		Some(3) => decode_format_3(payload),
+		Some(5) => decode_format_5(payload),
+		Some(format) => Err(format!("Unsupported Ruuvi data format {}", format)),
+		None => Err("Empty Ruuvi broadcast".into()),
+	}
+}
+
+/**
+ * Decode a broadcast given as hex, like the gateways send it, or base64.
+*/
+pub fn decode_text(data: &str) -> Result<Measurement, String> {
+	return decode(&text_bytes(data).ok_or("Ruuvi broadcast is neither hex nor base64")?);
+}
+
+/**
+ * Whether a broadcast given as hex or base64 is from a Ruuvi device: it has the manufacturer data of Ruuvi
+ * Innovations, or it's just a payload of a Ruuvi format. Decoding it can still fail, e.g. for a new format.
+*/
+pub fn is_ruuvi_text(data: &str) -> bool {
+	return text_bytes(data).is_some_and(|bytes| matches!(bytes.first(), Some(3 | 5)) || bytes.windows(2).any(|pair| pair == MANUFACTURER_ID));
+}
+
+fn text_bytes(data: &str) -> Option<Vec<u8>> {
+	if data.len().is_multiple_of(2) && data.chars().all(|character| character.is_ascii_hexdigit()) {
+		return (0..data.len()).step_by(2).map(|index| u8::from_str_radix(&data[index..index + 2], 16).ok()).collect();
+	}
+	return base64::engine::general_purpose::STANDARD.decode(data).ok();
+}
+
+fn decode_format_3(payload: &[u8]) -> Result<Measurement, String> {
+	check_length(payload, 14)?;
+	
+	// Sign and magnitude: the top bit of the integer part is the sign, the fraction is in hundredths.
+	let temperature = (payload[2] & 0x7F) as f64 + payload[3] as f64 / 100.0;
+	
+	return Ok(Measurement {
+		data_format: 3,
+		temperature: Some(if payload[2] & 0x80 != 0 { -temperature } else { temperature }),
+		humidity: Some(payload[1] as f64 * 0.5),
+		pressure: Some(u16_at(payload, 4) as f64 + 50000.0),
+		acceleration: Some((i16_at(payload, 6) as f64 / 1000.0, i16_at(payload, 8) as f64 / 1000.0, i16_at(payload, 10) as f64 / 1000.0)),
+		battery_voltage: Some(u16_at(payload, 12) as f64 / 1000.0),
+		tx_power: None,
+		movement_counter: None,
+		measurement_sequence: None,
+		mac: None,
+	});
+}
+
+fn decode_format_5(payload: &[u8]) -> Result<Measurement, String> {
+	check_length(payload, 24)?;
+	
+	let temperature = i16_at(payload, 1);
+	let humidity = u16_at(payload, 3);
+	let pressure = u16_at(payload, 5);
+	let acceleration = (i16_at(payload, 7), i16_at(payload, 9), i16_at(payload, 11));
+	// 11 bits of battery voltage above 1.6 V in mV, 5 bits of TX power above -40 dBm in 2 dBm steps.
+	let power = u16_at(payload, 13);
+	let voltage = power >> 5;
+	let tx_power = (power & 0x1F) as i8;
+	let sequence = u16_at(payload, 16);
+	let mac: [u8; 6] = payload[18..24].try_into().map_err(|_| "Invalid MAC address")?;
+	
+	return Ok(Measurement {
+		data_format: 5,
+		temperature: (temperature != i16::MIN).then_some(temperature as f64 * 0.005),
+		humidity: (humidity != u16::MAX).then_some(humidity as f64 * 0.0025),
+		pressure: (pressure != u16::MAX).then_some(pressure as f64 + 50000.0),
+		acceleration: (acceleration != (i16::MIN, i16::MIN, i16::MIN))
+			.then_some((acceleration.0 as f64 / 1000.0, acceleration.1 as f64 / 1000.0, acceleration.2 as f64 / 1000.0)),
+		battery_voltage: (voltage != 0x7FF).then_some((voltage as f64 + 1600.0) / 1000.0),
+		tx_power: (tx_power != 0x1F).then_some(tx_power * 2 - 40),
+		movement_counter: (payload[15] != u8::MAX).then_some(payload[15]),
+		measurement_sequence: (sequence != u16::MAX).then_some(sequence),
+		mac: (mac != [0xFF; 6]).then_some(mac),
+	});
+}
+
+fn u16_at(payload: &[u8], index: usize) -> u16 {
+	return u16::from_be_bytes([payload[index], payload[index + 1]]);
+}
+
+fn i16_at(payload: &[u8], index: usize) -> i16 {
+	return u16_at(payload, index) as i16;
+}
+
+fn check_length(payload: &[u8], length: usize) -> Result<(), String> {
+	if payload.len() < length {
+		return Err(format!("Ruuvi data format {} needs {} bytes, got {}", payload[0], length, payload.len()));
+	}
+	return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	// Test vectors from the Ruuvi documentation.
+	
+	fn bytes(hex: &str) -> Vec<u8> {
+		return (0..hex.len()).step_by(2).map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap()).collect();
+	}
+	
+	fn assert_close(actual: Option<f64>, expected: f64) {
+		let actual = actual.unwrap();
+		assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+	}
+	
+	#[test]
+	fn format_3_valid() {
+		let measurement = decode(&bytes("03291A1ECE1EFC18F94202CA0B53")).unwrap();
+		assert_eq!(measurement.data_format, 3);
+		assert_close(measurement.temperature, 26.3);
+		assert_close(measurement.humidity, 20.5);
+		assert_close(measurement.pressure, 102766.0);
+		assert_eq!(measurement.acceleration, Some((-1.0, -1.726, 0.714)));
+		assert_close(measurement.battery_voltage, 2.899);
+	}
+	
+	#[test]
+	fn format_3_limits() {
+		let maximum = decode(&bytes("03FF7F63FFFF7FFF7FFF7FFFFFFF")).unwrap();
+		assert_close(maximum.temperature, 127.99);
+		assert_close(maximum.humidity, 127.5);
+		assert_close(maximum.pressure, 115535.0);
+		assert_eq!(maximum.acceleration, Some((32.767, 32.767, 32.767)));
+		assert_close(maximum.battery_voltage, 65.535);
+		
+		let minimum = decode(&bytes("0300FF6300008001800180010000")).unwrap();
+		assert_close(minimum.temperature, -127.99);
+		assert_close(minimum.humidity, 0.0);
+		assert_close(minimum.pressure, 50000.0);
+		assert_eq!(minimum.acceleration, Some((-32.767, -32.767, -32.767)));
+		assert_close(minimum.battery_voltage, 0.0);
+	}
+	
+	#[test]
+	fn format_5_valid() {
+		let measurement = decode(&bytes("0512FC5394C37C0004FFFC040CAC364200CDCBB8334C884F")).unwrap();
+		assert_eq!(measurement.data_format, 5);
+		assert_close(measurement.temperature, 24.3);
+		assert_close(measurement.humidity, 53.49);
+		assert_close(measurement.pressure, 100044.0);
+		assert_eq!(measurement.acceleration, Some((0.004, -0.004, 1.036)));
+		assert_close(measurement.battery_voltage, 2.977);
+		assert_eq!(measurement.tx_power, Some(4));
+		assert_eq!(measurement.movement_counter, Some(66));
+		assert_eq!(measurement.measurement_sequence, Some(205));
+		assert_eq!(measurement.mac, Some([0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F]));
+	}
+	
+	#[test]
+	fn format_5_limits() {
+		let maximum = decode(&bytes("057FFFFFFEFFFE7FFF7FFF7FFFFFDEFEFFFECBB8334C884F")).unwrap();
+		assert_close(maximum.temperature, 163.835);
+		assert_close(maximum.humidity, 163.835);
+		assert_close(maximum.pressure, 115534.0);
+		assert_eq!(maximum.acceleration, Some((32.767, 32.767, 32.767)));
+		assert_close(maximum.battery_voltage, 3.646);
+		assert_eq!(maximum.tx_power, Some(20));
+		assert_eq!(maximum.movement_counter, Some(254));
+		assert_eq!(maximum.measurement_sequence, Some(65534));
+		
+		let minimum = decode(&bytes("058001000000008001800180010000000000CBB8334C884F")).unwrap();
+		assert_close(minimum.temperature, -163.835);
+		assert_close(minimum.humidity, 0.0);
+		assert_close(minimum.pressure, 50000.0);
+		assert_eq!(minimum.acceleration, Some((-32.767, -32.767, -32.767)));
+		assert_close(minimum.battery_voltage, 1.6);
+		assert_eq!(minimum.tx_power, Some(-40));
+		assert_eq!(minimum.movement_counter, Some(0));
+		assert_eq!(minimum.measurement_sequence, Some(0));
+	}
+	
+	#[test]
+	fn format_5_invalid_values_are_none() {
+		let measurement = decode(&bytes("058000FFFFFFFF800080008000FFFFFFFFFFFFFFFFFFFFFF")).unwrap();
+		assert_eq!(measurement, Measurement {
+			data_format: 5,
+			temperature: None,
+			humidity: None,
+			pressure: None,
+			acceleration: None,
+			battery_voltage: None,
+			tx_power: None,
+			movement_counter: None,
+			measurement_sequence: None,
+			mac: None,
+		});
+	}
+	
+	#[test]
+	fn hex_and_base64() {
+		let measurement = decode_text("0201061BFF99040512FC5394C37C0004FFFC040CAC364200CDCBB8334C884F").unwrap();
+		assert_eq!(measurement.pressure, Some(100044.0));
+		assert_eq!(decode_text("BRL8U5TDfAAE//wEDKw2QgDNy7gzTIhP"), Ok(measurement));
+		assert!(decode_text("not a broadcast").is_err());
+	}
+	
+	#[test]
+	fn advertisement_and_manufacturer_data() {
+		let payload = decode(&bytes("0512FC5394C37C0004FFFC040CAC364200CDCBB8334C884F")).unwrap();
+		assert_eq!(decode(&bytes("99040512FC5394C37C0004FFFC040CAC364200CDCBB8334C884F")), Ok(payload.clone()));
+		assert_eq!(decode(&bytes("0201061BFF99040512FC5394C37C0004FFFC040CAC364200CDCBB8334C884F")), Ok(payload));
+	}
+	
+	#[test]
+	fn unsupported_and_short_data() {
+		assert_eq!(decode(&bytes("0401")), Err("Unsupported Ruuvi data format 4".into()));
+		assert_eq!(decode(&bytes("0512FC")), Err("Ruuvi data format 5 needs 24 bytes, got 3".into()));
+		assert!(decode(&[]).is_err());
+	}
+	
+	#[test]
+	fn ruuvi_broadcasts_are_told_apart() {
+		assert!(is_ruuvi_text("0201060DFF9904060A1B2C3D4E5F607182"));
+		assert!(is_ruuvi_text("0512FC"));
+		assert!(!is_ruuvi_text("0201061AFF4C000215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5"));
+		assert!(!is_ruuvi_text("not hex!"));
+	}
+}
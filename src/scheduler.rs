@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, Utc};
+
+// Wait before the first retry after a failed fetch. Doubled on every failure, up to the interval.
+const FIRST_RETRY_SECONDS: i64 = 5;
+
+/**
+ * When to fetch from the API next: on the interval after a success, backing off after failures. Can be paused,
+ * and a refresh can be requested whenever, also while paused.
+*/
+pub struct Scheduler {
+	interval: Duration,
+	// Fetch on the wall clock multiples of the interval, e.g. at :00 every minute, instead of an interval after the
+	// previous fetch.
+	aligned: bool,
+	next: DateTime<Utc>,
+	// Failed fetches in a row.
+	failures: u32,
+	// Refresh requested by the user, fetched on the next poll regardless of the schedule.
+	requested: bool,
+	pub paused: bool,
+}
+
+impl Scheduler {
+	/**
+	 * The first fetch is due right away.
+	*/
+	pub fn new(interval: Duration, aligned: bool, now: DateTime<Utc>) -> Scheduler {
+		return Scheduler {
+			interval,
+			aligned,
+			next: now,
+			failures: 0,
+			requested: false,
+			paused: false,
+		};
+	}
+	
+	pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+		return self.requested || (!self.paused && now >= self.next);
+	}
+	
+	pub fn succeeded(&mut self, now: DateTime<Utc>) {
+		self.failures = 0;
+		self.requested = false;
+		self.next = match self.aligned {
+			true => next_boundary(now, self.interval),
+			false => now + self.interval,
+		};
+	}
+	
+	pub fn failed(&mut self, now: DateTime<Utc>) {
+		self.failures += 1;
+		self.requested = false;
+		let backoff = Duration::seconds(FIRST_RETRY_SECONDS << (self.failures - 1).min(16));
+		self.next = now + backoff.min(self.interval);
+	}
+	
+	/**
+	 * Fetch on the next poll and forget the failures, so that the retries start over from the shortest wait.
+	*/
+	pub fn refresh(&mut self) {
+		self.failures = 0;
+		self.requested = true;
+	}
+	
+	/**
+	 * Failed fetches in a row, 0 after a success.
+	*/
+	pub fn failures(&self) -> u32 {
+		return self.failures;
+	}
+	
+	/**
+	 * Fetch every interval from now on, sooner than scheduled if the new interval is shorter.
+	*/
+	pub fn set_interval(&mut self, interval: Duration, now: DateTime<Utc>) {
+		self.interval = interval;
+		self.next = self.next.min(now + interval);
+	}
+	
+	pub fn toggle_pause(&mut self) {
+		self.paused = !self.paused;
+	}
+}
+
+/**
+ * The first multiple of the interval since the epoch after now.
+*/
+fn next_boundary(now: DateTime<Utc>, interval: Duration) -> DateTime<Utc> {
+	let interval = interval.num_milliseconds().max(1);
+	let since_epoch = now.timestamp_millis();
+	let next = (since_epoch.div_euclid(interval) + 1) * interval;
+	return DateTime::from_timestamp_millis(next).unwrap_or(now);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn start() -> DateTime<Utc> {
+		return "2024-05-01T12:00:00Z".parse().unwrap();
+	}
+	
+	#[test]
+	fn interval_and_backoff() {
+		let now = start();
+		let mut scheduler = Scheduler::new(Duration::seconds(60), false, now);
+		assert!(scheduler.is_due(now));
+		
+		scheduler.succeeded(now);
+		assert!(!scheduler.is_due(now + Duration::seconds(59)));
+		assert!(scheduler.is_due(now + Duration::seconds(60)));
+		
+		scheduler.failed(now);
+		assert!(scheduler.is_due(now + Duration::seconds(5)));
+		scheduler.failed(now);
+		assert!(!scheduler.is_due(now + Duration::seconds(9)));
+		assert!(scheduler.is_due(now + Duration::seconds(10)));
+		for _ in 0..10 {
+			scheduler.failed(now);
+		}
+		assert!(!scheduler.is_due(now + Duration::seconds(59)));
+		assert!(scheduler.is_due(now + Duration::seconds(60)));
+	}
+	
+	#[test]
+	fn aligned_to_the_clock() {
+		let now = start() + Duration::milliseconds(17_250);
+		let mut scheduler = Scheduler::new(Duration::seconds(60), true, now);
+		assert!(scheduler.is_due(now));
+		
+		scheduler.succeeded(now);
+		assert!(!scheduler.is_due(start() + Duration::milliseconds(59_999)));
+		assert!(scheduler.is_due(start() + Duration::seconds(60)));
+		
+		// A fetch right on the boundary waits for the next one.
+		scheduler.succeeded(start() + Duration::seconds(60));
+		assert!(!scheduler.is_due(start() + Duration::seconds(119)));
+		assert!(scheduler.is_due(start() + Duration::seconds(120)));
+	}
+	
+	#[test]
+	fn pause_and_refresh() {
+		let now = start();
+		let mut scheduler = Scheduler::new(Duration::seconds(60), false, now);
+		scheduler.toggle_pause();
+		assert!(!scheduler.is_due(now + Duration::hours(1)));
+		
+		// A refresh is fetched while paused, once.
+		scheduler.refresh();
+		assert!(scheduler.is_due(now));
+		scheduler.succeeded(now);
+		assert!(!scheduler.is_due(now + Duration::hours(1)));
+		
+		// And resets the backoff.
+		scheduler.toggle_pause();
+		scheduler.failed(now);
+		scheduler.failed(now);
+		scheduler.refresh();
+		scheduler.failed(now);
+		assert!(scheduler.is_due(now + Duration::seconds(5)));
+	}
+}
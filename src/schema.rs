@@ -0,0 +1,32 @@
+use crate::actions::WebhookBody;
+use crate::forward::ForwardLine;
+use crate::status::StatusJson;
+
+/**
+ * Version of the JSON outputs: the status command's JSON, the default webhook body and the forwarded lines. Within a version fields are
+ * only added, so integrations keep working with the fields they know. Removing a field or changing its type or
+ * meaning raises the version.
+*/
+pub const SCHEMA_VERSION: u32 = 1;
+
+/**
+ * Outputs with a JSON Schema, printed with --schema.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaName {
+	Status,
+	Webhook,
+	Forward,
+}
+
+/**
+ * The JSON Schema of the output, generated from the same structs the output is written from.
+*/
+pub fn json_schema(name: SchemaName) -> String {
+	let schema = match name {
+		SchemaName::Status => schemars::schema_for!(StatusJson),
+		SchemaName::Webhook => schemars::schema_for!(WebhookBody),
+		SchemaName::Forward => schemars::schema_for!(ForwardLine),
+	};
+	return serde_json::to_string_pretty(&schema).unwrap_or_default();
+}
@@ -0,0 +1,165 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use chrono::{DateTime, SecondsFormat, Utc};
+use crate::backend::Key;
+use crate::model::ApiResponse;
+use crate::render::View;
+
+/**
+ * Log of what happened in the TUI, for bug reports about glitches that are hard to describe: the keys pressed, the
+ * view after each change and a summary of each refresh. The readings are left out unless asked for, so that the log
+ * can be attached to a public issue.
+*/
+pub struct SessionLog {
+	file: File,
+	// Tag names and readings in the refresh lines too.
+	values: bool,
+	// The view as last written, so that only the changes are.
+	view: String,
+}
+
+impl SessionLog {
+	/**
+	 * Append to the file, created if needed.
+	*/
+	pub fn open(path: &Path, values: bool) -> Result<SessionLog, String> {
+		let file = OpenOptions::new().create(true).append(true).open(path)
+			.map_err(|error| format!("Could not open the session log {}: {}", path.display(), error))?;
+		let mut log = SessionLog { file, values, view: String::new() };
+		log.write(Utc::now(), &format!("start version {}", env!("CARGO_PKG_VERSION")));
+		return Ok(log);
+	}
+	
+	pub fn key(&mut self, key: Key, now: DateTime<Utc>) {
+		self.write(now, &format!("key {}", describe_key(key)));
+	}
+	
+	/**
+	 * Write the view and the terminal size if either changed since the last time.
+	*/
+	pub fn view(&mut self, view: &View, size: (i32, i32), now: DateTime<Utc>) {
+		let description = format!("{}x{} {}", size.1, size.0, describe_view(view));
+		if description != self.view {
+			self.write(now, &format!("view {}", description));
+			self.view = description;
+		}
+	}
+	
+	/**
+	 * The number of tags and the errors after new data, with the readings if enabled.
+	*/
+	pub fn refresh(&mut self, data: &ApiResponse, errors: &[&str], now: DateTime<Utc>) {
+		let unreachable = data.iter().filter(|tag| tag.unreachable).count();
+		let mut line = format!("refresh {} tags, {} unreachable", data.len(), unreachable);
+		if !errors.is_empty() {
+			line.push_str(&format!(", errors: {}", errors.join("; ")));
+		}
+		self.write(now, &line);
+		if !self.values {
+			return;
+		}
+		for tag in data {
+			let metric = |value: Option<f64>| value.map_or("-".to_string(), |value| value.to_string());
+			let values = format!(
+				"temperature {} humidity {} pressure {} battery {}",
+				metric(tag.temperature()), metric(tag.humidity.as_ref().map(|humidity| humidity.current)),
+				metric(tag.pressure.as_ref().map(|pressure| pressure.current)), metric(tag.battery_voltage),
+			);
+			self.write(now, &format!("  tag {} {:?} {} at {}", tag.id, tag.label(), values, tag.datetime));
+		}
+	}
+	
+	fn write(&mut self, now: DateTime<Utc>, line: &str) {
+		// Nowhere to report a failing log, the TUI goes on without it.
+		let _ = writeln!(self.file, "{} {}", now.to_rfc3339_opts(SecondsFormat::Millis, true), line).and_then(|_| self.file.flush());
+	}
+}
+
+fn describe_key(key: Key) -> String {
+	return match key {
+		Key::Char(key) if key.is_ascii_graphic() => key.to_string(),
+		Key::Char(key) => format!("{:?}", key),
+		Key::Resize => "resize".into(),
+		Key::Interrupt => "interrupt".into(),
+		Key::Click(y, x) => format!("click {},{}", y, x),
+		Key::DoubleClick(y, x) => format!("double-click {},{}", y, x),
+		Key::ScrollUp => "scroll up".into(),
+		Key::ScrollDown => "scroll down".into(),
+		Key::FocusGained => "focus gained".into(),
+		Key::FocusLost => "focus lost".into(),
+	};
+}
+
+/**
+ * What the view shows, e.g. "layout Table page 0 min/max Today selected 3 detail".
+*/
+fn describe_view(view: &View) -> String {
+	let mut parts = vec![format!("layout {:?} page {} min/max {:?}", view.layout, view.page, view.min_max_window)];
+	if let Some(selected) = &view.selected {
+		parts.push(format!("selected {}", selected));
+	}
+	let flags = [
+		(view.detail, "detail"),
+		(view.event_log, "event log"),
+		(view.legend, "legend"),
+		(view.absolute_time, "absolute time"),
+		(view.min_max_reset.is_some(), "min/max reset"),
+		(view.nudge.is_some(), "nudge"),
+	];
+	parts.extend(flags.iter().filter(|(on, _)| *on).map(|(_, name)| name.to_string()));
+	if view.scroll > 0 {
+		parts.push(format!("scrolled {}", view.scroll));
+	}
+	if !view.collapsed.is_empty() {
+		let mut collapsed: Vec<&str> = view.collapsed.iter().map(String::as_str).collect();
+		collapsed.sort();
+		parts.push(format!("collapsed {}", collapsed.join(", ")));
+	}
+	return parts.join(" ");
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::fs;
+	use crate::config::Config;
+	use crate::model::{Metric, Tag};
+	
+	#[test]
+	fn keys_views_and_refreshes() {
+		let path = std::env::temp_dir().join(format!("ruuvi-tc-session-{}.log", std::process::id()));
+		let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let data = vec![Tag {
+			datetime: "2024-05-01T11:59:00Z".into(),
+			temperature: Some(Metric::from_current(62.1)),
+			battery_voltage: Some(2.9),
+			..Tag::test("1", "Sauna")
+		}];
+		let mut view = View::new(&Config::default());
+		
+		let mut log = SessionLog::open(&path, false).unwrap();
+		log.view(&view, (24, 80), now);
+		log.view(&view, (24, 80), now);
+		log.key(Key::DoubleClick(3, 10), now);
+		view.selected = Some("1".into());
+		view.detail = true;
+		log.view(&view, (24, 80), now);
+		log.refresh(&data, &["Network error"], now);
+		
+		let mut log = SessionLog::open(&path, true).unwrap();
+		log.refresh(&data, &[], now);
+		
+		let written = fs::read_to_string(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+		let lines: Vec<&str> = written.lines().filter(|line| !line.contains(" start version ")).collect();
+		assert_eq!(lines, [
+			"2024-05-01T12:00:00.000Z view 80x24 layout Cards page 0 min/max Source",
+			"2024-05-01T12:00:00.000Z key double-click 3,10",
+			"2024-05-01T12:00:00.000Z view 80x24 layout Cards page 0 min/max Source selected 1 detail",
+			"2024-05-01T12:00:00.000Z refresh 1 tags, 0 unreachable, errors: Network error",
+			"2024-05-01T12:00:00.000Z refresh 1 tags, 0 unreachable",
+			"2024-05-01T12:00:00.000Z   tag 1 \"Sauna\" temperature 62.1 humidity - pressure - battery 2.9 at 2024-05-01T11:59:00Z",
+		]);
+	}
+}
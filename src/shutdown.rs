@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static RELOAD: AtomicBool = AtomicBool::new(false);
+
+/**
+ * Turn SIGINT (Ctrl-C) and SIGTERM into a shutdown request so that the main loops can exit cleanly. SIGHUP asks for
+ * a reload of the config in the modes that support it and shuts down in the others.
+*/
+pub fn install(reload: bool) -> Result<(), String> {
+	ctrlc::set_handler(|| REQUESTED.store(true, Ordering::SeqCst))
+		.map_err(|error| format!("Could not set the signal handler: {}", error))?;
+	#[cfg(unix)]
+	{
+		use signal_hook::consts::{SIGHUP, SIGTERM};
+		let hangup = if reload { &RELOAD } else { &REQUESTED };
+		// Only an atomic store, which is safe in a signal handler.
+		let result = unsafe {
+			signal_hook::low_level::register(SIGTERM, || REQUESTED.store(true, Ordering::SeqCst))
+				.and_then(|_| signal_hook::low_level::register(SIGHUP, move || hangup.store(true, Ordering::SeqCst)))
+		};
+		result.map_err(|error| format!("Could not set the signal handler: {}", error))?;
+	}
+	#[cfg(not(unix))]
+	let _ = reload;
+	return Ok(());
+}
+
+pub fn requested() -> bool {
+	return REQUESTED.load(Ordering::SeqCst);
+}
+
+/**
+ * Whether a reload was asked for with SIGHUP since the last call.
+*/
+pub fn reload_requested() -> bool {
+	return RELOAD.swap(false, Ordering::SeqCst);
+}
+
+/**
+ * Whether the terminal is gone, e.g. the SSH connection dropped, when the SIGHUP was a hangup and not a reload.
+*/
+pub fn hung_up() -> bool {
+	#[cfg(unix)]
+	return std::fs::File::open("/dev/tty").is_err();
+	#[cfg(not(unix))]
+	return false;
+}
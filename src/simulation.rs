@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use crate::clock::{Clock, ScaledClock};
+use crate::model::{ApiResponse, Metric, Tag, TagMetadata};
+use crate::store::{Reading, Store};
+
+/**
+ * Generated or recorded data instead of the API and the listener, for demos, screenshots and developing the UI
+ * without a Ruuvi setup.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Simulation {
+	// Random walk data of a few made-up tags.
+	Demo,
+	// A recorded history played back: a JSON array of readings or a history store.
+	Replay(PathBuf),
+}
+
+impl Simulation {
+	/**
+	 * From the --source values: demo, or replay and the file.
+	*/
+	pub fn parse(values: &[String]) -> Result<Simulation, String> {
+		match values {
+			[kind] if kind == "demo" => Ok(Simulation::Demo),
+			[kind, path] if kind == "replay" => Ok(Simulation::Replay(PathBuf::from(path))),
+			[kind] if kind == "replay" => Err("--source replay needs the file to play back".into()),
+			_ => Err(format!("Unknown source \"{}\", use demo or replay <file>", values.join(" "))),
+		}
+	}
+}
+
+/**
+ * Data from a simulation, taken on every poll.
+*/
+pub trait Feed {
+	/**
+	 * All the tags if there's something new by the time, None otherwise.
+	*/
+	fn take(&mut self, now: DateTime<Utc>) -> Option<ApiResponse>;
+	
+	/**
+	 * The clock to run on, if not the system clock.
+	*/
+	fn clock(&self) -> Option<Arc<dyn Clock>> {
+		return None;
+	}
+}
+
+/**
+ * Start the simulation. Replays play back at the speed, e.g. 60 for an hour a minute.
+*/
+pub fn open(simulation: &Simulation, speed: f64) -> Result<Box<dyn Feed>, String> {
+	return match simulation {
+		Simulation::Demo => Ok(Box::new(Demo::new(Utc::now().timestamp_subsec_nanos() as u64))),
+		Simulation::Replay(path) => Ok(Box::new(Replay::open(path, speed)?)),
+	};
+}
+
+// Seconds between the demo measurements, like a gateway.
+const DEMO_INTERVAL_SECONDS: i64 = 10;
+
+/**
+ * Made-up tags whose values wander around their usual levels.
+*/
+pub struct Demo {
+	tags: Vec<DemoTag>,
+	random: u64,
+	last: Option<DateTime<Utc>>,
+}
+
+struct DemoTag {
+	id: &'static str,
+	name: &'static str,
+	// Usual temperature and humidity the values are pulled back towards.
+	usual: (f64, f64),
+	temperature: f64,
+	humidity: f64,
+	pressure: f64,
+	battery_voltage: f64,
+}
+
+impl Demo {
+	pub fn new(seed: u64) -> Demo {
+		let tags = [
+			("DE:40:00:00:00:01", "Living room", 21.5, 40.0, 3.0),
+			("DE:40:00:00:00:02", "Bedroom", 19.5, 45.0, 2.95),
+			("DE:40:00:00:00:03", "Sauna", 65.0, 12.0, 2.8),
+			("DE:40:00:00:00:04", "Fridge", 4.0, 70.0, 2.6),
+			("DE:40:00:00:00:05", "Outdoor", -3.0, 85.0, 2.45),
+		];
+		return Demo {
+			tags: tags.iter().map(|&(id, name, temperature, humidity, battery_voltage)| DemoTag {
+				id,
+				name,
+				usual: (temperature, humidity),
+				temperature,
+				humidity,
+				pressure: 1013.0,
+				battery_voltage,
+			}).collect(),
+			// Xorshift can't start from zero.
+			random: seed | 1,
+			last: None,
+		};
+	}
+	
+	/**
+	 * Random number between -1 and 1 (xorshift64).
+	*/
+	fn random(&mut self) -> f64 {
+		self.random ^= self.random << 13;
+		self.random ^= self.random >> 7;
+		self.random ^= self.random << 17;
+		return (self.random >> 11) as f64 / (1u64 << 52) as f64 - 1.0;
+	}
+}
+
+impl Feed for Demo {
+	fn take(&mut self, now: DateTime<Utc>) -> Option<ApiResponse> {
+		if self.last.is_some_and(|last| now - last < Duration::seconds(DEMO_INTERVAL_SECONDS)) {
+			return None;
+		}
+		self.last = Some(now);
+		
+		let pressure_step = self.random() * 0.1;
+		for index in 0..self.tags.len() {
+			let steps = (self.random(), self.random());
+			let tag = &mut self.tags[index];
+			tag.temperature += steps.0 * 0.1 + (tag.usual.0 - tag.temperature) * 0.05;
+			tag.humidity = (tag.humidity + steps.1 * 0.5 + (tag.usual.1 - tag.humidity) * 0.05).clamp(0.0, 100.0);
+			tag.pressure += pressure_step;
+			tag.battery_voltage -= 0.00001;
+		}
+		
+		let datetime = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+		return Some(self.tags.iter().map(|tag| simulated_tag(tag.id, tag.name, &datetime, tag.temperature, tag.humidity, Some(tag.pressure), Some(tag.battery_voltage))).collect());
+	}
+}
+
+/**
+ * Recorded readings played back in their time order on a clock starting from the first reading.
+*/
+pub struct Replay {
+	readings: Vec<Reading>,
+	next: usize,
+	// Latest reading per tag, in the order the tags first appeared.
+	latest: Vec<Reading>,
+	clock: Arc<ScaledClock>,
+}
+
+impl Replay {
+	pub fn open(path: &PathBuf, speed: f64) -> Result<Replay, String> {
+		if !path.exists() {
+			return Err(format!("Replay file {} not found", path.display()));
+		}
+		let mut readings = if path.extension().is_some_and(|extension| extension == "json") {
+			let contents = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+			serde_json::from_str::<Vec<Reading>>(&contents).map_err(|error| format!("Invalid replay file {}: {}", path.display(), error))?
+		}
+		else {
+			Store::open(path)
+				.and_then(|store| Ok(store.readings(None, None)?))
+				.map_err(|error| format!("Could not read the history store {}: {}", path.display(), error))?
+		};
+		readings.sort_by_key(|reading| reading.datetime);
+		
+		let Some(start) = readings.first().map(|reading| reading.datetime) else {
+			return Err(format!("No readings to replay in {}", path.display()));
+		};
+		return Ok(Replay {
+			readings,
+			next: 0,
+			latest: Vec::new(),
+			clock: Arc::new(ScaledClock::new(start, speed)),
+		});
+	}
+}
+
+impl Feed for Replay {
+	fn take(&mut self, now: DateTime<Utc>) -> Option<ApiResponse> {
+		let start = self.next;
+		while let Some(reading) = self.readings.get(self.next).filter(|reading| reading.datetime <= now) {
+			match self.latest.iter_mut().find(|latest| latest.tag_id == reading.tag_id) {
+				Some(latest) => *latest = reading.clone(),
+				None => self.latest.push(reading.clone()),
+			}
+			self.next += 1;
+		}
+		if self.next == start {
+			return None;
+		}
+		
+		return Some(self.latest.iter().map(|reading| simulated_tag(
+			&reading.tag_id,
+			&reading.tag_name,
+			&reading.datetime.to_rfc3339_opts(SecondsFormat::Secs, true),
+			reading.temperature,
+			reading.humidity,
+			reading.pressure,
+			reading.battery_voltage,
+		)).collect());
+	}
+	
+	fn clock(&self) -> Option<Arc<dyn Clock>> {
+		return Some(self.clock.clone());
+	}
+}
+
+fn simulated_tag(id: &str, name: &str, datetime: &str, temperature: f64, humidity: f64, pressure: Option<f64>, battery_voltage: Option<f64>) -> Tag {
+	return Tag {
+		id: id.into(),
+		name: name.into(),
+		alias: None,
+		datetime: datetime.into(),
+		temperature: Some(Metric::from_current(temperature)),
+		humidity: Some(Metric::from_current(humidity)),
+		pressure: pressure.map(Metric::from_current),
+		battery_voltage,
+		battery_low: false,
+		battery_critical: false,
+		movement_counter: None,
+		measurement_sequence: None,
+		rssi: None,
+		unreachable: false,
+		metadata: TagMetadata::default(),
+		extras: BTreeMap::new(),
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use super::*;
+	
+	#[test]
+	fn demo_stays_plausible() {
+		let mut demo = Demo::new(42);
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let mut data = demo.take(start).unwrap();
+		assert_eq!(data.len(), 5);
+		assert!(demo.take(start + Duration::seconds(5)).is_none());
+		
+		for step in 1..=1000 {
+			data = demo.take(start + Duration::seconds(step * DEMO_INTERVAL_SECONDS)).unwrap();
+		}
+		let living_room = &data[0];
+		assert!((living_room.temperature().unwrap() - 21.5).abs() < 3.0);
+		assert!((0.0..=100.0).contains(&living_room.humidity().unwrap()));
+		assert_eq!(living_room.datetime, "2024-05-01T14:46:40Z");
+	}
+	
+	#[test]
+	fn replay_in_time_order() {
+		let path = std::env::temp_dir().join(format!("ruuvi-tc-replay-{}.json", std::process::id()));
+		fs::write(&path, r#"[
+			{ "tag_id": "b", "tag_name": "Bedroom", "datetime": "2024-05-01T12:00:10Z", "temperature": 19.0, "humidity": 45.0 },
+			{ "tag_id": "a", "tag_name": "Sauna", "datetime": "2024-05-01T12:00:00Z", "temperature": 60.0, "humidity": 10.0 },
+			{ "tag_id": "a", "tag_name": "Sauna", "datetime": "2024-05-01T12:01:00Z", "temperature": 62.0, "humidity": 9.0, "pressure": 1001.5 }
+		]"#).unwrap();
+		let mut replay = Replay::open(&path, 1.0).unwrap();
+		fs::remove_file(&path).unwrap();
+		
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		let temperatures = |data: ApiResponse| data.iter().map(|tag| (tag.id.clone(), tag.temperature().unwrap())).collect::<HashMap<_, _>>();
+		assert_eq!(temperatures(replay.take(start).unwrap()), HashMap::from([("a".into(), 60.0)]));
+		assert!(replay.take(start + Duration::seconds(5)).is_none());
+		assert_eq!(temperatures(replay.take(start + Duration::seconds(10)).unwrap()), HashMap::from([("a".into(), 60.0), ("b".into(), 19.0)]));
+		assert_eq!(temperatures(replay.take(start + Duration::hours(1)).unwrap()), HashMap::from([("a".into(), 62.0), ("b".into(), 19.0)]));
+		assert!(replay.take(start + Duration::hours(2)).is_none());
+	}
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::config::Config;
+use crate::history::History;
+use crate::model::ApiResponse;
+
+// Intervals learned from fewer measurements than this are too noisy to go by.
+const MINIMUM_SAMPLES: usize = 5;
+
+/**
+ * Flag tags that have missed too many of their reports as unreachable, whatever the source says. Run on every
+ * poll since the data gets older without new fetches. Measurements from before the wake from a suspend are aged from
+ * the wake, since the reports weren't missed by the tags but by the sleeping machine.
+*/
+pub fn apply(data: &mut ApiResponse, config: &Config, history: &History, now: DateTime<Utc>, woke: Option<DateTime<Utc>>) {
+	for tag in data.iter_mut() {
+		let interval = config.tag(tag)
+			.and_then(|tag_config| tag_config.report_interval_seconds)
+			.map(Duration::seconds)
+			.or_else(|| learned_interval(history.samples(&tag.id).map(|sample| sample.datetime)));
+		let Some(limit) = limit(interval, config) else {
+			continue;
+		};
+		
+		if tag.measured_at().is_some_and(|measured_at| now - measured_at.max(woke.unwrap_or(measured_at)) > limit) {
+			tag.unreachable = true;
+		}
+	}
+}
+
+/**
+ * How old the latest measurement can be: the missed reports worth of the tag's report interval, or the global
+ * limit for tags with an unknown interval. None if staleness isn't checked.
+*/
+pub fn limit(interval: Option<Duration>, config: &Config) -> Option<Duration> {
+	if config.unreachable_after_minutes <= 0 {
+		return None;
+	}
+	
+	match interval {
+		Some(interval) if config.unreachable_after_missed_reports > 0 => Some(interval * config.unreachable_after_missed_reports),
+		_ => Some(Duration::minutes(config.unreachable_after_minutes)),
+	}
+}
+
+/**
+ * Median time between the measurements. The median ignores single outages and bursts.
+*/
+pub fn learned_interval(datetimes: impl Iterator<Item = DateTime<Utc>>) -> Option<Duration> {
+	let datetimes: Vec<DateTime<Utc>> = datetimes.collect();
+	if datetimes.len() < MINIMUM_SAMPLES {
+		return None;
+	}
+	
+	let mut intervals: Vec<Duration> = datetimes.windows(2).map(|pair| pair[1] - pair[0]).collect();
+	intervals.sort();
+	return Some(intervals[intervals.len() / 2]);
+}
+
+/**
+ * Periods longer than the limit without measurements, as (last measurement before, first measurement after).
+ * The datetimes must be in order.
+*/
+pub fn gaps(datetimes: &[DateTime<Utc>], limit: Duration) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+	return datetimes.windows(2)
+		.filter(|pair| pair[1] - pair[0] > limit)
+		.map(|pair| (pair[0], pair[1]))
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn datetimes(seconds: &[i64]) -> impl Iterator<Item = DateTime<Utc>> {
+		let start = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+		return seconds.iter().map(move |offset| start + Duration::seconds(*offset)).collect::<Vec<_>>().into_iter();
+	}
+	
+	#[test]
+	fn interval_is_the_median() {
+		// A 10 minute outage in the middle doesn't change the interval.
+		assert_eq!(learned_interval(datetimes(&[0, 10, 20, 620, 630, 640])), Some(Duration::seconds(10)));
+		assert_eq!(learned_interval(datetimes(&[0, 10, 20])), None);
+	}
+	
+	#[test]
+	fn gaps_longer_than_the_limit() {
+		let datetimes: Vec<DateTime<Utc>> = datetimes(&[0, 10, 20, 620, 630, 660]).collect();
+		assert_eq!(gaps(&datetimes, Duration::seconds(30)), vec![(datetimes[2], datetimes[3])]);
+		assert_eq!(gaps(&datetimes, Duration::minutes(15)), vec![]);
+	}
+	
+	#[test]
+	fn limit_follows_the_interval() {
+		let config = Config::default();
+		assert_eq!(limit(Some(Duration::seconds(10)), &config), Some(Duration::seconds(30)));
+		assert_eq!(limit(Some(Duration::minutes(5)), &config), Some(Duration::minutes(15)));
+		assert_eq!(limit(None, &config), Some(Duration::minutes(15)));
+		
+		let disabled = Config { unreachable_after_minutes: 0, ..Config::default() };
+		assert_eq!(limit(Some(Duration::seconds(10)), &disabled), None);
+	}
+}
@@ -0,0 +1,318 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::json;
+use crate::alerts::{Alert, AlertMetric, Severity};
+use crate::config::Config;
+use crate::formatters::Format;
+use crate::i18n::Text;
+use crate::model::{Metric, Tag};
+use crate::schema::SCHEMA_VERSION;
+
+/**
+ * Output of the status command, for scripts and status bars.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatusFormat {
+	// A line per tag.
+	Text,
+	// One line with tmux color codes, for status-right.
+	Tmux,
+	// Waybar custom module JSON.
+	Waybar,
+	// The tags as JSON for scripts, in the same order on every run.
+	Json,
+}
+
+/**
+ * Overall state of the tags, shown as the waybar class and returned as the exit code of the status command.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+	Ok,
+	Warning,
+	Critical,
+	// Tags without fresh data, or no data at all.
+	Unknown,
+}
+
+impl State {
+	/**
+	 * The most severe of the alerts. Tags without fresh data make the state unknown instead of the warning of their
+	 * unreachable alerts, unless other alerts are worse.
+	*/
+	pub fn of<'a>(alerts: impl Iterator<Item = &'a Alert>, data: &[Tag]) -> State {
+		let severity = alerts.filter(|alert| alert.rule != "unreachable").map(|alert| alert.severity).max();
+		return match severity {
+			Some(Severity::Critical) => State::Critical,
+			Some(Severity::Warning) => State::Warning,
+			Some(Severity::Info) | None if data.is_empty() || data.iter().any(|tag| tag.unreachable) => State::Unknown,
+			Some(Severity::Info) | None => State::Ok,
+		};
+	}
+	
+	pub fn name(self) -> &'static str {
+		match self {
+			State::Ok => "ok",
+			State::Warning => "warning",
+			State::Critical => "critical",
+			State::Unknown => "unknown",
+		}
+	}
+	
+	/**
+	 * Exit code like the monitoring plugins: 0 ok, 1 warning, 2 critical and 3 unknown.
+	*/
+	pub fn exit_code(self) -> i32 {
+		match self {
+			State::Ok => 0,
+			State::Warning => 1,
+			State::Critical => 2,
+			State::Unknown => 3,
+		}
+	}
+}
+
+/**
+ * The tag's temperature and humidity with their trends in the configured vocabulary, e.g.
+ * "Sauna: +65.1°C ▴ rising, 12% ▸ steady".
+*/
+pub fn line(tag: &Tag, config: &Config) -> String {
+	let lang = config.lang();
+	let vocabulary = &config.vocabulary;
+	let temperature = tag.temperature().map(|temperature| config.units.temperature(temperature, config.units.precision.temperature, lang));
+	let humidity = tag.humidity().map(|humidity| format!("{}%", config.units.humidity(humidity, config.units.precision.humidity, lang)));
+	let metrics = [(AlertMetric::Temperature, temperature, &tag.temperature), (AlertMetric::Humidity, humidity, &tag.humidity)];
+	let shown = metrics.into_iter().filter(|(metric, ..)| config.shows(tag, *metric));
+	// Without fresh data the values are the last known ones, and the trends mean nothing.
+	if tag.unreachable {
+		let values: Vec<String> = shown.map(|(_, value, _)| value.unwrap_or(config.missing_value.clone())).collect();
+		return format!("{}: {} ({})", tag.label(), values.join(", "), lang.text(Text::Unknown));
+	}
+	let values: Vec<String> = shown
+		.map(|(_, value, metric)| match (value, metric) {
+			(Some(value), Some(metric)) => {
+				let trend = metric.trend.unwrap_or(0);
+				format!("{} {} {}", value, vocabulary.symbol(trend, config.glyphs()), vocabulary.word(trend, lang))
+			},
+			_ => config.missing_value.clone(),
+		})
+		.collect();
+	return format!("{}: {}", tag.label(), values.join(", "));
+}
+
+/**
+ * Short form for the one-line outputs: the temperature and its trend symbol, or a question mark without fresh data.
+*/
+fn short(tag: &Tag, config: &Config) -> String {
+	if !config.shows(tag, AlertMetric::Temperature) {
+		return tag.label().to_string();
+	}
+	let Some(temperature) = &tag.temperature else {
+		return format!("{} {}", tag.label(), config.missing_value);
+	};
+	let symbol = if tag.unreachable { "?" } else { config.vocabulary.symbol(temperature.trend.unwrap_or(0), config.glyphs()) };
+	return format!("{} {} {}", tag.label(), config.units.temperature(temperature.current, 1, config.lang()), symbol);
+}
+
+/**
+ * The tags in the format. The state is the waybar class. The source is the format the API answered in, for the JSON.
+*/
+pub fn format(format: StatusFormat, data: &[Tag], config: &Config, state: State, source: Option<Format>) -> String {
+	return match format {
+		StatusFormat::Text => data.iter().map(|tag| line(tag, config)).collect::<Vec<_>>().join("\n"),
+		StatusFormat::Tmux => data.iter()
+			.map(|tag| match config.temperature_color(tag) {
+				_ if tag.unreachable => format!("#[fg=colour244,dim]{}#[default]", short(tag, config)),
+				Some(color) => format!("#[fg={}]{}#[default]", color.name(), short(tag, config)),
+				None => short(tag, config),
+			})
+			.collect::<Vec<_>>()
+			.join(" | "),
+		StatusFormat::Waybar => json!({
+			"text": data.iter().map(|tag| short(tag, config)).collect::<Vec<_>>().join("  "),
+			"tooltip": data.iter().map(|tag| line(tag, config)).collect::<Vec<_>>().join("\n"),
+			"class": state.name(),
+		}).to_string(),
+		StatusFormat::Json => serde_json::to_string(&json(data, state, source)).unwrap_or_default(),
+	};
+}
+
+/**
+ * The JSON of the status command, see SCHEMA_VERSION for what may change. The fields are in alphabetical order so
+ * that consecutive outputs can be diffed.
+*/
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StatusJson {
+	/// Version of this schema.
+	pub schema_version: u32,
+	/// Format the API answered in, null if it didn't answer.
+	pub source: Option<Format>,
+	pub state: State,
+	/// The tags sorted by ID.
+	pub tags: Vec<TagJson>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TagJson {
+	pub battery_critical: bool,
+	pub battery_low: bool,
+	/// Volts.
+	pub battery_voltage: Option<f64>,
+	/// Relative humidity, %.
+	pub humidity: Option<MetricJson>,
+	/// MAC address or the ID the source gives, stable across runs.
+	pub id: String,
+	/// RFC 3339.
+	pub measured_at: Option<String>,
+	/// The alias or the name from the source.
+	pub name: String,
+	/// hPa.
+	pub pressure: Option<MetricJson>,
+	/// °C, whatever the configured unit.
+	pub temperature: Option<MetricJson>,
+	/// No fresh data: the values are the last known ones.
+	pub unreachable: bool,
+}
+
+/**
+ * A value after the calibration, with its trend.
+*/
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MetricJson {
+	pub trend: TrendJson,
+	pub value: f64,
+}
+
+/**
+ * Trend as a word that doesn't follow the language or the vocabulary.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendJson {
+	Rising,
+	Steady,
+	Falling,
+}
+
+fn json(data: &[Tag], state: State, source: Option<Format>) -> StatusJson {
+	return StatusJson { schema_version: SCHEMA_VERSION, source, state, tags: tags_json(data) };
+}
+
+/**
+ * The tags as in the status command's JSON, sorted by ID.
+*/
+pub fn tags_json(data: &[Tag]) -> Vec<TagJson> {
+	let metric = |metric: &Option<Metric>| metric.as_ref().map(|metric| MetricJson {
+		value: metric.current,
+		trend: match metric.trend.unwrap_or(0) {
+			1 => TrendJson::Rising,
+			-1 => TrendJson::Falling,
+			_ => TrendJson::Steady,
+		},
+	});
+	let mut tags: Vec<&Tag> = data.iter().collect();
+	tags.sort_by(|a, b| a.id.cmp(&b.id));
+	return tags.into_iter()
+		.map(|tag| TagJson {
+			id: tag.id.clone(),
+			name: tag.label().to_string(),
+			measured_at: tag.measured_at().map(|measured_at| measured_at.to_rfc3339()),
+			unreachable: tag.unreachable,
+			temperature: metric(&tag.temperature),
+			humidity: metric(&tag.humidity),
+			pressure: metric(&tag.pressure),
+			battery_voltage: tag.battery_voltage,
+			battery_low: tag.battery_low,
+			battery_critical: tag.battery_critical,
+		})
+		.collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::TagConfig;
+	use crate::i18n::Lang;
+	use crate::model::Metric;
+	
+	fn tag(name: &str, temperature: f64, trend: i8) -> Tag {
+		let mut temperature = Metric::from_current(temperature);
+		temperature.trend = Some(trend);
+		return Tag {
+			temperature: Some(temperature),
+			humidity: Some(Metric::from_current(40.0)),
+			..Tag::test(name, name)
+		};
+	}
+	
+	#[test]
+	fn localized_outputs() {
+		let data = [tag("Sauna", 65.04, 1), tag("Fridge", -1.0, -1)];
+		let mut config = Config::default();
+		assert_eq!(format(StatusFormat::Text, &data, &config, State::Ok, None), "Sauna: +65.0°C ▴ rising, 40% ▸ steady\nFridge: -1.0°C ▾ falling, 40% ▸ steady");
+		assert_eq!(format(StatusFormat::Tmux, &data, &config, State::Ok, None), "#[fg=red]Sauna +65.0°C ▴#[default] | #[fg=blue]Fridge -1.0°C ▾#[default]");
+		
+		config.lang = Some(Lang::Fi);
+		config.vocabulary.rising_symbol = Some("^".into());
+		let waybar: serde_json::Value = serde_json::from_str(&format(StatusFormat::Waybar, &data, &config, State::Warning, None)).unwrap();
+		assert_eq!(waybar["text"], "Sauna +65,0°C ^  Fridge -1,0°C ▾");
+		assert_eq!(waybar["tooltip"], "Sauna: +65,0°C ^ nousussa, 40% ▸ vakaa\nFridge: -1,0°C ▾ laskussa, 40% ▸ vakaa");
+		assert_eq!(waybar["class"], "warning");
+	}
+	
+	#[test]
+	fn json_is_sorted_by_id() {
+		let data = [tag("Sauna", 65.04, 1), Tag { unreachable: true, humidity: None, ..tag("Fridge", -1.0, -1) }];
+		let json: serde_json::Value = serde_json::from_str(&format(StatusFormat::Json, &data, &Config::default(), State::Unknown, Some(Format::Gateway))).unwrap();
+		assert_eq!(json["schema_version"], SCHEMA_VERSION);
+		assert_eq!(json["source"], "gateway");
+		assert_eq!(json["state"], "unknown");
+		assert_eq!(json["tags"][0]["id"], "Fridge");
+		assert_eq!(json["tags"][0]["unreachable"], true);
+		assert_eq!(json["tags"][0]["humidity"], serde_json::Value::Null);
+		assert_eq!(json["tags"][1]["temperature"], serde_json::json!({ "value": 65.04, "trend": "rising" }));
+	}
+	
+	#[test]
+	fn missing_values() {
+		let mut config = Config::default();
+		let data = [Tag { humidity: None, ..tag("Sauna", 65.0, 1) }, Tag { temperature: None, ..tag("Fridge", 4.0, 0) }];
+		assert_eq!(format(StatusFormat::Text, &data, &config, State::Ok, None), "Sauna: +65.0°C ▴ rising, —\nFridge: —, 40% ▸ steady");
+		config.missing_value = "n/a".into();
+		assert_eq!(format(StatusFormat::Tmux, &data[1..], &config, State::Ok, None), "Fridge n/a");
+	}
+	
+	#[test]
+	fn hidden_metrics() {
+		let mut config = Config::default();
+		let freezer = TagConfig { hidden: vec![AlertMetric::Humidity], ..TagConfig::default() };
+		config.tags.insert("Freezer".into(), freezer);
+		let data = [tag("Freezer", -18.0, 0), Tag { unreachable: true, ..tag("Freezer", -18.0, 0) }];
+		assert_eq!(format(StatusFormat::Text, &data, &config, State::Ok, None), "Freezer: -18.0°C ▸ steady\nFreezer: -18.0°C (unknown)");
+	}
+	
+	#[test]
+	fn unknown_tags() {
+		let stale = [Tag { unreachable: true, ..tag("Sauna", 65.0, 1) }];
+		let config = Config::default();
+		assert_eq!(line(&stale[0], &config), "Sauna: +65.0°C, 40% (unknown)");
+		assert_eq!(format(StatusFormat::Tmux, &stale, &config, State::Unknown, None), "#[fg=colour244,dim]Sauna +65.0°C ?#[default]");
+		
+		let alert = |rule: &str, severity| Alert {
+			tag_id: "Sauna".into(),
+			tag_name: "Sauna".into(),
+			rule: rule.into(),
+			severity,
+			message: String::new(),
+			value: None,
+			peak: None,
+			started_at: chrono::Utc::now(),
+		};
+		let unreachable = [alert("unreachable", Severity::Warning)];
+		assert_eq!(State::of(unreachable.iter(), &stale), State::Unknown);
+		assert_eq!(State::of([alert("too_hot", Severity::Critical)].iter(), &stale), State::Critical);
+		assert_eq!(State::of([].iter(), &[tag("Sauna", 65.0, 1)]).exit_code(), 0);
+		assert_eq!(State::of([].iter(), &[]).exit_code(), 3);
+	}
+}
@@ -0,0 +1,389 @@
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Deserialize;
+use crate::alerts::{Alert, AlertEvent, Severity};
+use crate::clock::Zone;
+use crate::events::{EventKind, LogEntry};
+use crate::model::ApiResponse;
+
+/**
+ * Persistent history of the readings in SQLite.
+*/
+pub struct Store {
+	connection: Connection,
+}
+
+/**
+ * One alert occurrence in the alert log.
+*/
+#[derive(Debug, Clone)]
+pub struct AlertRecord {
+	pub alert: Alert,
+	pub cleared_at: Option<DateTime<Utc>>,
+	// Ended without data instead of clearing.
+	pub unknown: bool,
+	pub acknowledged_by: Option<String>,
+	pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+impl AlertRecord {
+	/**
+	 * Active, cleared, or unknown if it ended without data.
+	*/
+	pub fn state(&self) -> &'static str {
+		return match (self.cleared_at, self.unknown) {
+			(None, _) => "active",
+			(Some(_), false) => "cleared",
+			(Some(_), true) => "unknown",
+		};
+	}
+}
+
+/**
+ * One stored reading. Also the format of the JSON replay files.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct Reading {
+	pub tag_id: String,
+	#[serde(default)]
+	pub tag_name: String,
+	pub datetime: DateTime<Utc>,
+	pub temperature: f64,
+	pub humidity: f64,
+	pub pressure: Option<f64>,
+	pub battery_voltage: Option<f64>,
+}
+
+impl Store {
+	/**
+	 * Open the database, creating it and the directory if needed.
+	*/
+	pub fn open(path: &Path) -> Result<Store, Box<dyn std::error::Error>> {
+		if let Some(directory) = path.parent() {
+			fs::create_dir_all(directory)?;
+		}
+		
+		let connection = Connection::open(path)?;
+		connection.execute_batch("
+			CREATE TABLE IF NOT EXISTS readings (
+				tag_id TEXT NOT NULL,
+				tag_name TEXT NOT NULL,
+				datetime TEXT NOT NULL,
+				temperature REAL NOT NULL,
+				humidity REAL NOT NULL,
+				battery_voltage REAL,
+				UNIQUE (tag_id, datetime)
+			);
+			CREATE INDEX IF NOT EXISTS readings_datetime ON readings (datetime);
+			CREATE TABLE IF NOT EXISTS alerts (
+				id TEXT PRIMARY KEY,
+				tag_id TEXT NOT NULL,
+				tag_name TEXT NOT NULL,
+				rule TEXT NOT NULL,
+				severity TEXT NOT NULL,
+				message TEXT NOT NULL,
+				value REAL,
+				started_at TEXT NOT NULL,
+				cleared_at TEXT
+			);
+			CREATE TABLE IF NOT EXISTS events (
+				datetime TEXT NOT NULL,
+				kind TEXT NOT NULL,
+				severity TEXT,
+				tag_name TEXT,
+				message TEXT NOT NULL
+			);
+			CREATE INDEX IF NOT EXISTS events_datetime ON events (datetime);
+		")?;
+		
+		// Databases created before pressure was stored.
+		let has_pressure = connection.prepare("SELECT pressure FROM readings LIMIT 0").is_ok();
+		if !has_pressure {
+			connection.execute_batch("ALTER TABLE readings ADD COLUMN pressure REAL")?;
+		}
+		
+		// Alert logs created before the peak values and acknowledgements.
+		let has_peak = connection.prepare("SELECT peak FROM alerts LIMIT 0").is_ok();
+		if !has_peak {
+			connection.execute_batch("
+				ALTER TABLE alerts ADD COLUMN peak REAL;
+				ALTER TABLE alerts ADD COLUMN acknowledged_by TEXT;
+				ALTER TABLE alerts ADD COLUMN acknowledged_at TEXT;
+			")?;
+		}
+		
+		// Alert logs created before the alerts could end as unknown.
+		let has_unknown = connection.prepare("SELECT unknown FROM alerts LIMIT 0").is_ok();
+		if !has_unknown {
+			connection.execute_batch("ALTER TABLE alerts ADD COLUMN unknown INTEGER NOT NULL DEFAULT 0")?;
+		}
+		
+		return Ok(Store { connection });
+	}
+	
+	/**
+	 * Record the readings. The same measurement fetched twice is stored only once.
+	*/
+	pub fn record(&self, data: &ApiResponse) -> Result<usize, rusqlite::Error> {
+		let mut statement = self.connection.prepare_cached(
+			"INSERT OR IGNORE INTO readings (tag_id, tag_name, datetime, temperature, humidity, battery_voltage, pressure)
+			VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+		)?;
+		let mut inserted = 0;
+		
+		for tag in data {
+			// Readings without the temperature or humidity are shown as missing, but not stored.
+			let (Some(datetime), Some(temperature), Some(humidity)) = (tag.measured_at(), tag.temperature(), tag.humidity()) else {
+				continue;
+			};
+			
+			inserted += statement.execute(params![
+				tag.id,
+				tag.label(),
+				format_datetime(&datetime),
+				temperature,
+				humidity,
+				tag.battery_voltage,
+				tag.pressure(),
+			])?;
+		}
+		
+		return Ok(inserted);
+	}
+	
+	/**
+	 * Readings in chronological order, optionally filtered by tag (ID or name) and start time.
+	*/
+	pub fn readings(&self, tag: Option<&str>, since: Option<DateTime<Utc>>) -> Result<Vec<Reading>, rusqlite::Error> {
+		let mut statement = self.connection.prepare(
+			"SELECT tag_id, tag_name, datetime, temperature, humidity, battery_voltage, pressure FROM readings
+			WHERE (?1 IS NULL OR tag_id = ?1 OR tag_name = ?1) AND (?2 IS NULL OR datetime >= ?2)
+			ORDER BY datetime, tag_id"
+		)?;
+		let rows = statement.query_map(params![tag, since.as_ref().map(format_datetime)], |row| {
+			let datetime: String = row.get(2)?;
+			Ok(Reading {
+				tag_id: row.get(0)?,
+				tag_name: row.get(1)?,
+				datetime: datetime.parse().unwrap_or_default(),
+				temperature: row.get(3)?,
+				humidity: row.get(4)?,
+				battery_voltage: row.get(5)?,
+				pressure: row.get(6)?,
+			})
+		})?;
+		
+		return rows.collect();
+	}
+	
+	/**
+	 * The ID and the latest name of the tag (ID or name), None without readings of it.
+	*/
+	pub fn tag(&self, tag: &str) -> Result<Option<(String, String)>, rusqlite::Error> {
+		return self.connection.query_row(
+			"SELECT tag_id, tag_name FROM readings WHERE tag_id = ?1 OR tag_name = ?1 ORDER BY datetime DESC LIMIT 1",
+			params![tag],
+			|row| Ok((row.get(0)?, row.get(1)?)),
+		).optional();
+	}
+	
+	/**
+	 * Lowest and highest temperature per local day of the tag (ID or name), starting from the given day.
+	*/
+	pub fn daily_temperatures(&self, tag: &str, since: NaiveDate, zone: Zone) -> Result<Vec<(NaiveDate, f64, f64)>, rusqlite::Error> {
+		let mut days: Vec<(NaiveDate, f64, f64)> = Vec::new();
+		for reading in self.readings(Some(tag), zone.midnight(since))? {
+			let day = zone.date(reading.datetime);
+			match days.last_mut() {
+				Some((last, min, max)) if *last == day => {
+					*min = min.min(reading.temperature);
+					*max = max.max(reading.temperature);
+				},
+				_ => days.push((day, reading.temperature, reading.temperature)),
+			}
+		}
+		
+		return Ok(days);
+	}
+	
+	/**
+	 * Average battery voltage per local day of the tag (ID or name), starting from the given day. Days without a
+	 * voltage are left out.
+	*/
+	pub fn daily_battery_voltages(&self, tag: &str, since: NaiveDate, zone: Zone) -> Result<Vec<(NaiveDate, f64)>, rusqlite::Error> {
+		// The sum and the count of the voltages of each day.
+		let mut days: Vec<(NaiveDate, f64, usize)> = Vec::new();
+		for reading in self.readings(Some(tag), zone.midnight(since))? {
+			let Some(voltage) = reading.battery_voltage else {
+				continue;
+			};
+			let day = zone.date(reading.datetime);
+			match days.last_mut() {
+				Some((last, sum, count)) if *last == day => {
+					*sum += voltage;
+					*count += 1;
+				},
+				_ => days.push((day, voltage, 1)),
+			}
+		}
+		
+		return Ok(days.into_iter().map(|(day, sum, count)| (day, sum / count as f64)).collect());
+	}
+	
+	/**
+	 * Record alerts starting and clearing. An alert already recorded, e.g. by a previous run, isn't duplicated.
+	*/
+	pub fn record_alerts(&self, events: &[AlertEvent], now: DateTime<Utc>) -> Result<(), rusqlite::Error> {
+		for event in events {
+			match event {
+				AlertEvent::Started(alert) => {
+					self.connection.execute(
+						"INSERT OR IGNORE INTO alerts (id, tag_id, tag_name, rule, severity, message, value, started_at, peak)
+						VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+						params![
+							alert.id(),
+							alert.tag_id,
+							alert.tag_name,
+							alert.rule,
+							alert.severity.name(),
+							alert.message,
+							alert.value,
+							format_datetime(&alert.started_at),
+							alert.peak,
+						],
+					)?;
+				},
+				AlertEvent::Cleared(alert) | AlertEvent::Unknown(alert) => {
+					self.connection.execute(
+						"UPDATE alerts SET cleared_at = ?2, peak = ?3, unknown = ?4 WHERE id = ?1 AND cleared_at IS NULL",
+						params![alert.id(), format_datetime(&now), alert.peak, matches!(event, AlertEvent::Unknown(_))],
+					)?;
+				},
+			}
+		}
+		
+		return Ok(());
+	}
+	
+	/**
+	 * Alerts that haven't cleared.
+	*/
+	pub fn active_alerts(&self) -> Result<Vec<Alert>, rusqlite::Error> {
+		let records = self.alert_log(None, None)?;
+		return Ok(records.into_iter().filter(|record| record.cleared_at.is_none()).map(|record| record.alert).collect());
+	}
+	
+	/**
+	 * The alert log in the order the alerts started, optionally filtered by tag (ID or name) and start time.
+	*/
+	pub fn alert_log(&self, tag: Option<&str>, since: Option<DateTime<Utc>>) -> Result<Vec<AlertRecord>, rusqlite::Error> {
+		let mut statement = self.connection.prepare(
+			"SELECT tag_id, tag_name, rule, severity, message, value, peak, started_at, cleared_at, acknowledged_by, acknowledged_at, unknown
+			FROM alerts WHERE (?1 IS NULL OR tag_id = ?1 OR tag_name = ?1) AND (?2 IS NULL OR started_at >= ?2)
+			ORDER BY started_at, tag_id, rule"
+		)?;
+		let rows = statement.query_map(params![tag, since.as_ref().map(format_datetime)], |row| {
+			let severity: String = row.get(3)?;
+			let started_at: String = row.get(7)?;
+			let cleared_at: Option<String> = row.get(8)?;
+			let acknowledged_at: Option<String> = row.get(10)?;
+			Ok(AlertRecord {
+				alert: Alert {
+					tag_id: row.get(0)?,
+					tag_name: row.get(1)?,
+					rule: row.get(2)?,
+					severity: Severity::from_name(&severity).unwrap_or(Severity::Warning),
+					message: row.get(4)?,
+					value: row.get(5)?,
+					peak: row.get(6)?,
+					started_at: started_at.parse().unwrap_or_default(),
+				},
+				cleared_at: cleared_at.and_then(|cleared_at| cleared_at.parse().ok()),
+				unknown: row.get(11)?,
+				acknowledged_by: row.get(9)?,
+				acknowledged_at: acknowledged_at.and_then(|acknowledged_at| acknowledged_at.parse().ok()),
+			})
+		})?;
+		
+		return rows.collect();
+	}
+	
+	/**
+	 * Record the entries of the event log.
+	*/
+	pub fn record_events(&self, entries: &[LogEntry]) -> Result<(), rusqlite::Error> {
+		for entry in entries {
+			self.connection.execute(
+				"INSERT INTO events (datetime, kind, severity, tag_name, message) VALUES (?1, ?2, ?3, ?4, ?5)",
+				params![
+					format_datetime(&entry.time),
+					entry.kind.name(),
+					entry.severity.map(|severity| severity.name()),
+					entry.tag_name,
+					entry.message,
+				],
+			)?;
+		}
+		return Ok(());
+	}
+	
+	/**
+	 * The event log since the time in chronological order.
+	*/
+	pub fn events(&self, since: DateTime<Utc>) -> Result<Vec<LogEntry>, rusqlite::Error> {
+		let mut statement = self.connection.prepare(
+			"SELECT datetime, kind, severity, tag_name, message FROM events WHERE datetime >= ?1 ORDER BY datetime, rowid"
+		)?;
+		let rows = statement.query_map(params![format_datetime(&since)], |row| {
+			let datetime: String = row.get(0)?;
+			let kind: String = row.get(1)?;
+			let severity: Option<String> = row.get(2)?;
+			Ok((datetime, kind, severity, row.get(3)?, row.get(4)?))
+		})?;
+		
+		let mut entries = Vec::new();
+		for row in rows {
+			let (datetime, kind, severity, tag_name, message) = row?;
+			// Kinds of a newer version are skipped.
+			let Some(kind) = EventKind::from_name(&kind) else {
+				continue;
+			};
+			entries.push(LogEntry {
+				time: datetime.parse().unwrap_or_default(),
+				kind,
+				severity: severity.and_then(|severity| Severity::from_name(&severity)),
+				tag_name,
+				message,
+			});
+		}
+		return Ok(entries);
+	}
+	
+	/**
+	 * Mark the alert acknowledged by the person. Returns false if there's no such alert.
+	*/
+	pub fn acknowledge(&self, id: &str, by: &str, now: DateTime<Utc>) -> Result<bool, rusqlite::Error> {
+		let updated = self.connection.execute(
+			"UPDATE alerts SET acknowledged_by = ?2, acknowledged_at = ?3 WHERE id = ?1",
+			params![id, by, format_datetime(&now)],
+		)?;
+		return Ok(updated > 0);
+	}
+	
+	/**
+	 * Delete readings and events older than the given time. Returns the number of deleted readings.
+	*/
+	pub fn prune(&self, before: DateTime<Utc>) -> Result<usize, rusqlite::Error> {
+		self.connection.execute("DELETE FROM events WHERE datetime < ?1", params![format_datetime(&before)])?;
+		return self.connection.execute("DELETE FROM readings WHERE datetime < ?1", params![format_datetime(&before)]);
+	}
+}
+
+/**
+ * Fixed width UTC format so that the datetimes sort correctly as text.
+*/
+fn format_datetime(datetime: &DateTime<Utc>) -> String {
+	return datetime.to_rfc3339_opts(SecondsFormat::Secs, true);
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use crate::alerts::{AlertEngine, Severity};
+use crate::config::Config;
+use crate::groups;
+use crate::model::{ApiResponse, Tag};
+
+/**
+ * Figures over all the tags for the summary line above them, to see at a glance how things are before reading the
+ * tags one by one.
+*/
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+	// Label and temperature of the warmest and the coldest tag.
+	pub warmest: Option<(String, f64)>,
+	pub coldest: Option<(String, f64)>,
+	// Average temperature of the indoor tags, and of the indoor tags of each named group.
+	pub indoor: Option<f64>,
+	pub groups: Vec<(String, f64)>,
+	// Tags with an active alert, and the most severe of the alerts. Informational alerts don't count.
+	pub alerting: usize,
+	pub severity: Option<Severity>,
+	// Measurement time of the least recently updated tag that isn't unreachable, and the unreachable tags.
+	pub oldest: Option<DateTime<Utc>>,
+	pub unreachable: usize,
+}
+
+/**
+ * Summarize the tags. Outdoor tags count for the warmest and the coldest, but not for the indoor averages.
+*/
+pub fn summarize(data: &ApiResponse, config: &Config, alerts: &AlertEngine) -> Summary {
+	let temperatures: Vec<(&Tag, f64)> = data.iter().filter_map(|tag| Some((tag, tag.temperature()?))).collect();
+	let label = |(tag, temperature): &(&Tag, f64)| (tag.label().to_string(), *temperature);
+	let warmest = temperatures.iter().max_by(|a, b| a.1.total_cmp(&b.1)).map(label);
+	let coldest = temperatures.iter().min_by(|a, b| a.1.total_cmp(&b.1)).map(label);
+	
+	let outdoor = |tag: &Tag| config.is_outdoor_tag(tag) || config.tag(tag).is_some_and(|tag_config| tag_config.outdoor);
+	let indoor: Vec<f64> = temperatures.iter().filter(|(tag, _)| !outdoor(tag)).map(|(_, temperature)| *temperature).collect();
+	let groups = groups::split(data.iter().filter(|tag| !outdoor(tag)), &config.groups)
+		.into_iter()
+		.filter_map(|group| Some((group.name?.to_string(), group.average_temperature()?)))
+		.collect();
+	
+	let mut alerting: Vec<&str> = alerts.active()
+		.filter(|alert| alert.severity > Severity::Info)
+		.map(|alert| alert.tag_id.as_str())
+		.collect();
+	alerting.sort();
+	alerting.dedup();
+	
+	return Summary {
+		warmest,
+		coldest,
+		indoor: (!indoor.is_empty()).then(|| indoor.iter().sum::<f64>() / indoor.len() as f64),
+		groups,
+		alerting: alerting.len(),
+		severity: alerts.severity(),
+		oldest: data.iter().filter(|tag| !tag.unreachable).filter_map(|tag| tag.measured_at()).min(),
+		unreachable: data.iter().filter(|tag| tag.unreachable).count(),
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::TagConfig;
+	use crate::groups::GroupConfig;
+	use crate::model::Metric;
+	
+	fn tag(name: &str, temperature: f64, datetime: &str) -> Tag {
+		return Tag {
+			datetime: datetime.into(),
+			temperature: Some(Metric::from_current(temperature)),
+			..Tag::test(name, name)
+		};
+	}
+	
+	#[test]
+	fn summary_of_the_tags() {
+		let mut config = Config::default();
+		config.tags.insert("Balcony".into(), TagConfig { outdoor: true, ..TagConfig::default() });
+		config.groups = vec![GroupConfig { name: "Upstairs".into(), tags: vec!["Bedroom".into(), "Balcony".into()], location: None }];
+		let data = vec![
+			tag("Sauna", 62.0, "2024-05-01T11:58:00Z"),
+			tag("Bedroom", 20.0, "2024-05-01T11:55:00Z"),
+			tag("Balcony", -4.0, "2024-05-01T11:59:00Z"),
+			Tag { unreachable: true, ..tag("Cellar", 8.0, "2024-05-01T09:00:00Z") },
+		];
+		let summary = summarize(&data, &config, &AlertEngine::new());
+		
+		assert_eq!(summary.warmest, Some(("Sauna".into(), 62.0)));
+		assert_eq!(summary.coldest, Some(("Balcony".into(), -4.0)));
+		assert_eq!(summary.indoor, Some(30.0));
+		assert_eq!(summary.groups, [("Upstairs".to_string(), 20.0)]);
+		assert_eq!(summary.alerting, 0);
+		assert_eq!(summary.oldest, Some("2024-05-01T11:55:00Z".parse().unwrap()));
+		assert_eq!(summary.unreachable, 1);
+	}
+}
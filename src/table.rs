@@ -0,0 +1,200 @@
+use serde::Deserialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use crate::i18n::Lang;
+use crate::responsive;
+
+// Invisible break point in a long word, shown as a hyphen when the word is broken there.
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/**
+ * Column widths and decimals of the table layout. Numbers are right aligned to a fixed number of
+ * decimals so that the decimal points line up and values don't shift as they change. Longer names are cut with an
+ * ellipsis so that they don't push the values. The minimal and floors layouts have their own, shorter name column.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TableConfig {
+	pub name_width: usize,
+	pub value_width: usize,
+	// All the columns to these decimals, the precision of each metric if not set.
+	pub decimals: Option<usize>,
+	pub short_name_width: usize,
+}
+
+impl Default for TableConfig {
+	fn default() -> TableConfig {
+		return TableConfig {
+			name_width: 20,
+			value_width: 8,
+			decimals: None,
+			short_name_width: 12,
+		};
+	}
+}
+
+impl TableConfig {
+	/**
+	 * The number right aligned in the value column to the table's decimals or the metric's, fewer if the column is too
+	 * narrow for them, or the missing value.
+	*/
+	pub fn number(&self, value: Option<f64>, decimals: usize, missing: &str, lang: Lang) -> String {
+		return match value {
+			Some(value) => format!("{:>width$}", responsive::number(value, self.decimals.unwrap_or(decimals), lang, self.value_width), width = self.value_width),
+			None => format!("{:>width$}", missing, width = self.value_width),
+		};
+	}
+	
+	/**
+	 * The name cut or padded to the name column.
+	*/
+	pub fn name(&self, text: &str) -> String {
+		return ellipsize(text, self.name_width);
+	}
+	
+	/**
+	 * The name cut or padded to the name column of the minimal and floors layouts.
+	*/
+	pub fn short_name(&self, text: &str) -> String {
+		return ellipsize(text, self.short_name_width);
+	}
+}
+
+/**
+ * Columns the text takes on the terminal: two for the wide characters like 🌱, none for the combining ones.
+*/
+pub fn columns(text: &str) -> usize {
+	return text.width();
+}
+
+/**
+ * Cut or pad the text to exactly the width in columns. A wide character that would only half fit is cut too.
+*/
+pub fn fit(text: &str, width: usize) -> String {
+	let mut fitted = String::new();
+	let mut used = 0;
+	for character in text.chars() {
+		let columns = character.width().unwrap_or(0);
+		if used + columns > width {
+			break;
+		}
+		fitted.push(character);
+		used += columns;
+	}
+	fitted.extend(std::iter::repeat_n(' ', width - used));
+	return fitted;
+}
+
+/**
+ * Like fit, but a cut text ends with an ellipsis to show that there's more.
+*/
+pub fn ellipsize(text: &str, width: usize) -> String {
+	if columns(text) <= width {
+		return fit(text, width);
+	}
+	return fit(text, width.saturating_sub(1)) + if width > 0 { "…" } else { "" };
+}
+
+/**
+ * Word wrap the text to lines of at most the width in columns. A word that doesn't fit on the line is
+ * broken at its last soft hyphen that does, or if there's none and the word is longer than a line, at the width.
+ * Broken words end with a hyphen, and the unused soft hyphens are left out.
+*/
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+	// Room for a character and the hyphen.
+	let width = width.max(2);
+	let visible = |text: &str| columns(&text.replace(SOFT_HYPHEN, ""));
+	let mut lines = Vec::new();
+	let mut line = String::new();
+	
+	for word in text.split_whitespace() {
+		let mut word = word;
+		loop {
+			let separator = if line.is_empty() { "" } else { " " };
+			let room = width.saturating_sub(visible(&line) + separator.len());
+			if visible(word) <= room {
+				line.push_str(separator);
+				line.push_str(word);
+				break;
+			}
+			let soft_break = word.match_indices(SOFT_HYPHEN)
+				.map(|(index, _)| index)
+				.rev()
+				.find(|index| visible(&word[..*index]) < room);
+			if let Some(index) = soft_break {
+				line.push_str(separator);
+				line.push_str(&word[..index]);
+				word = &word[index + SOFT_HYPHEN.len_utf8()..];
+			}
+			else if !line.is_empty() {
+				lines.push(std::mem::take(&mut line));
+				continue;
+			}
+			else {
+				// At least a character on the line, also a wide one on a narrow line.
+				let mut used = 0;
+				let index = word.char_indices()
+					.filter(|(_, c)| *c != SOFT_HYPHEN)
+					.find(|(index, c)| {
+						used += c.width().unwrap_or(0);
+						used > width - 1 && *index > 0
+					})
+					.map_or(word.len(), |(index, _)| index);
+				line.push_str(&word[..index]);
+				word = &word[index..];
+			}
+			line.push('-');
+			lines.push(std::mem::take(&mut line));
+		}
+	}
+	if !line.is_empty() || lines.is_empty() {
+		lines.push(line);
+	}
+	return lines.into_iter().map(|line| line.replace(SOFT_HYPHEN, "")).collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn decimal_points_line_up() {
+		let table = TableConfig::default();
+		let numbers = [
+			table.number(Some(-3.25), 1, "—", Lang::En),
+			table.number(Some(21.5), 1, "—", Lang::En),
+			table.number(Some(100.0), 1, "—", Lang::En),
+			table.number(None, 1, "—", Lang::En),
+		];
+		
+		assert!(numbers.iter().all(|number| number.chars().count() == 8));
+		assert_eq!(numbers[0].find('.'), numbers[1].find('.'));
+		assert_eq!(numbers[1].find('.'), numbers[2].find('.'));
+		assert_eq!(numbers[3], "       —");
+		assert_eq!(table.number(Some(21.5), 1, "—", Lang::Fi), "    21,5");
+		assert_eq!(TableConfig { decimals: Some(2), ..table }.number(Some(40.0), 0, "—", Lang::En), "   40.00");
+	}
+	
+	#[test]
+	fn names_are_cut_or_padded() {
+		assert_eq!(fit("Living room", 6), "Living");
+		assert_eq!(fit("Sauna", 8), "Sauna   ");
+		// The icon takes two columns.
+		assert_eq!(fit("🌱 Kasvari", 4), "🌱 K");
+		assert_eq!(fit("🌱 Kasvari", 1), " ");
+		assert_eq!(ellipsize("🌱 Kasvari", 6), "🌱 Ka…");
+		assert_eq!(columns(&ellipsize("🌱 Kasvari", 6)), 6);
+		assert_eq!(ellipsize("Living room", 6), "Livin…");
+		assert_eq!(ellipsize("Living room", 11), "Living room");
+		assert_eq!(ellipsize("Sauna", 8), "Sauna   ");
+		assert_eq!(ellipsize("Sauna", 0), "");
+	}
+	
+	#[test]
+	fn words_wrap_at_spaces_and_soft_hyphens() {
+		assert_eq!(wrap("Could not reach the API: connection refused", 20), ["Could not reach the", "API: connection", "refused"]);
+		assert_eq!(wrap("Sauna: humidity above 90 %", 40), ["Sauna: humidity above 90 %"]);
+		assert_eq!(wrap("Tuloste\u{AD}komento epäonnistui", 10), ["Tuloste-", "komento", "epäonnist-", "ui"]);
+		assert_eq!(wrap("Lämpö\u{AD}tila", 20), ["Lämpötila"]);
+		assert_eq!(wrap("", 20), [""]);
+	}
+}
@@ -0,0 +1,45 @@
+use crate::config::Config;
+use crate::model::ApiResponse;
+
+/**
+ * Apply the [tags] config section: friendly names, icons and display order.
+ * A tag is matched by its ID (MAC address or API tag ID) or by the name coming from the source.
+*/
+pub fn apply_aliases(data: &mut ApiResponse, config: &Config) {
+	if config.tags.is_empty() {
+		return;
+	}
+	
+	let mut ordered: Vec<_> = data.drain(..).map(|mut tag| {
+		let Some(tag_config) = config.tag(&tag) else {
+			return (i32::MAX, tag);
+		};
+		
+		if let Some(name) = &tag_config.name {
+			tag.alias = Some(name.clone());
+		}
+		if let Some(icon) = &tag_config.icon {
+			tag.alias = Some(format!("{} {}", icon, tag.label()));
+		}
+		
+		return (tag_config.order.unwrap_or(i32::MAX), tag);
+	}).collect();
+	
+	// Tags with an order come first, the rest keep the source order.
+	ordered.sort_by_key(|(order, _)| *order);
+	data.extend(ordered.into_iter().map(|(_, tag)| tag));
+}
+
+/**
+ * The changed tags of an incremental update in place of the previous ones, and the new tags after them.
+*/
+pub fn merge(previous: &ApiResponse, changed: ApiResponse) -> ApiResponse {
+	let mut merged = previous.clone();
+	for tag in changed {
+		match merged.iter_mut().find(|previous| previous.id == tag.id) {
+			Some(previous) => *previous = tag,
+			None => merged.push(tag),
+		}
+	}
+	return merged;
+}
@@ -0,0 +1,53 @@
+use serde::Deserialize;
+
+/**
+ * Band a value should stay in, e.g. 2…6 °C for a fridge or 65…72 % for a humidor. Either end can be left out.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct TargetRange {
+	pub min: Option<f64>,
+	pub max: Option<f64>,
+}
+
+impl TargetRange {
+	/**
+	 * How far outside the band the value is: negative below it, positive above it and 0 within it.
+	*/
+	pub fn deviation(&self, value: f64) -> f64 {
+		if let Some(min) = self.min.filter(|min| value < *min) {
+			return value - min;
+		}
+		if let Some(max) = self.max.filter(|max| value > *max) {
+			return value - max;
+		}
+		return 0.0;
+	}
+}
+
+/**
+ * Target bands of a tag per metric.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Targets {
+	pub temperature: Option<TargetRange>,
+	pub humidity: Option<TargetRange>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn deviation_outside_the_band() {
+		let fridge = TargetRange { min: Some(2.0), max: Some(6.0) };
+		assert_eq!(fridge.deviation(4.0), 0.0);
+		assert_eq!(fridge.deviation(6.0), 0.0);
+		assert_eq!(fridge.deviation(7.5), 1.5);
+		assert_eq!(fridge.deviation(1.0), -1.0);
+		
+		let at_least = TargetRange { min: Some(65.0), max: None };
+		assert_eq!(at_least.deviation(99.0), 0.0);
+		assert_eq!(at_least.deviation(60.0), -5.0);
+	}
+}
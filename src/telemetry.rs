@@ -0,0 +1,80 @@
+use crate::config::Config;
+
+/**
+ * OTLP tracing of the refresh cycle (fetch, parse, normalize and render spans), built with the otel feature.
+*/
+#[cfg(feature = "otel")]
+mod otel {
+	use std::sync::OnceLock;
+	use opentelemetry::trace::{Tracer, TracerProvider as _};
+	use opentelemetry::KeyValue;
+	use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+	use opentelemetry_sdk::trace::{self, TracerProvider};
+	use opentelemetry_sdk::Resource;
+	
+	static PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+	static TRACER: OnceLock<trace::Tracer> = OnceLock::new();
+	
+	pub fn init(endpoint: &str) -> Result<(), String> {
+		let exporter = SpanExporter::builder()
+			.with_http()
+			.with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+			.build()
+			.map_err(|error| format!("Could not set up OTLP tracing: {}", error))?;
+		let provider = TracerProvider::builder()
+			.with_simple_exporter(exporter)
+			.with_resource(Resource::new(vec![KeyValue::new("service.name", env!("CARGO_PKG_NAME"))]))
+			.build();
+		
+		let _ = TRACER.set(provider.tracer(env!("CARGO_PKG_NAME")));
+		let _ = PROVIDER.set(provider);
+		return Ok(());
+	}
+	
+	pub fn span<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+		match TRACER.get() {
+			Some(tracer) => tracer.in_span(name, |_| f()),
+			None => f(),
+		}
+	}
+	
+	pub fn shutdown() {
+		if let Some(provider) = PROVIDER.get() {
+			let _ = provider.shutdown();
+		}
+	}
+}
+
+/**
+ * Start exporting the spans if an OTLP endpoint is configured.
+*/
+pub fn init(config: &Config) -> Result<(), String> {
+	let Some(endpoint) = &config.otlp_endpoint else {
+		return Ok(());
+	};
+	
+	#[cfg(feature = "otel")]
+	return otel::init(endpoint);
+	
+	#[cfg(not(feature = "otel"))]
+	return Err(format!("Tracing to {} needs a build with the otel feature", endpoint));
+}
+
+/**
+ * Run the function in a span, nested in the span it's called from.
+*/
+pub fn span<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+	#[cfg(feature = "otel")]
+	return otel::span(name, f);
+	
+	#[cfg(not(feature = "otel"))]
+	return { let _ = name; f() };
+}
+
+/**
+ * Flush the remaining spans.
+*/
+pub fn shutdown() {
+	#[cfg(feature = "otel")]
+	otel::shutdown();
+}
@@ -0,0 +1,211 @@
+use serde::Deserialize;
+use crate::background::Background;
+use crate::ranges::RangeColor;
+
+// The eight standard terminal colors. Larger indexes are from the 256 color palette.
+pub const BLACK: i16 = 0;
+pub const RED: i16 = 1;
+pub const GREEN: i16 = 2;
+pub const YELLOW: i16 = 3;
+pub const BLUE: i16 = 4;
+pub const MAGENTA: i16 = 5;
+pub const CYAN: i16 = 6;
+pub const WHITE: i16 = 7;
+
+// Text attributes, combined with |.
+pub const NORMAL: u8 = 0;
+pub const BOLD: u8 = 1;
+pub const UNDERLINE: u8 = 2;
+pub const REVERSE: u8 = 4;
+pub const DIM: u8 = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+	#[default]
+	Default,
+	HighContrast,
+	Monochrome,
+	Solarized,
+}
+
+/**
+ * What a piece of text is, the theme decides how it looks.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+	Title,
+	Value,
+	Trend,
+	Warning,
+	Critical,
+	// Tags without fresh data: their values may not be true anymore.
+	Unknown,
+}
+
+/**
+ * Colors (-1 is the terminal default) and attributes of a role. The backend maps them to the terminal.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Style {
+	pub foreground: i16,
+	pub background: i16,
+	pub attributes: u8,
+}
+
+impl Style {
+	pub const fn new(foreground: i16, background: i16, attributes: u8) -> Style {
+		return Style { foreground, background, attributes };
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+	pub colors: bool,
+	// Adapted for a light background.
+	pub light: bool,
+	pub title: Style,
+	pub value: Style,
+	pub trend: Style,
+	pub warning: Style,
+	pub critical: Style,
+	pub unknown: Style,
+}
+
+impl Theme {
+	/**
+	 * The named theme. The number of colors the terminal supports decides if Solarized can use its real palette.
+	*/
+	pub fn named(name: ThemeName, terminal_colors: i32) -> Theme {
+		match name {
+			ThemeName::Default => Theme {
+				colors: true,
+				light: false,
+				title: Style::new(GREEN, -1, BOLD),
+				value: Style::new(WHITE, -1, BOLD),
+				trend: Style::new(GREEN, -1, BOLD),
+				warning: Style::new(YELLOW, -1, BOLD),
+				critical: Style::new(RED, -1, BOLD),
+				unknown: Style::new(WHITE, -1, DIM),
+			},
+			ThemeName::HighContrast => Theme {
+				colors: true,
+				light: false,
+				title: Style::new(YELLOW, -1, BOLD | UNDERLINE),
+				value: Style::new(WHITE, -1, BOLD),
+				trend: Style::new(CYAN, -1, BOLD),
+				warning: Style::new(BLACK, YELLOW, BOLD),
+				critical: Style::new(WHITE, RED, BOLD),
+				unknown: Style::new(WHITE, -1, DIM),
+			},
+			ThemeName::Solarized if terminal_colors >= 256 => Theme {
+				colors: true,
+				light: false,
+				title: Style::new(33, -1, BOLD), // blue
+				value: Style::new(245, -1, BOLD), // base1
+				trend: Style::new(37, -1, BOLD), // cyan
+				warning: Style::new(136, -1, BOLD), // yellow
+				critical: Style::new(160, -1, BOLD), // red
+				unknown: Style::new(240, -1, NORMAL), // base01
+			},
+			ThemeName::Solarized => Theme {
+				colors: true,
+				light: false,
+				title: Style::new(BLUE, -1, BOLD),
+				value: Style::new(WHITE, -1, NORMAL),
+				trend: Style::new(CYAN, -1, BOLD),
+				warning: Style::new(YELLOW, -1, NORMAL),
+				critical: Style::new(RED, -1, BOLD),
+				unknown: Style::new(WHITE, -1, DIM),
+			},
+			ThemeName::Monochrome => Theme {
+				colors: false,
+				light: false,
+				title: Style::new(-1, -1, BOLD | UNDERLINE),
+				value: Style::new(-1, -1, BOLD),
+				trend: Style::new(-1, -1, NORMAL),
+				warning: Style::new(-1, -1, BOLD),
+				critical: Style::new(-1, -1, REVERSE),
+				unknown: Style::new(-1, -1, DIM),
+			},
+		}
+	}
+	
+	/**
+	 * The theme for the terminal's background. On a light one the white and yellow text that would be hard to read is
+	 * darker, and Solarized's content tones are swapped like in Solarized Light.
+	*/
+	pub fn on(self, background: Background, terminal_colors: i32) -> Theme {
+		if background != Background::Light || !self.colors {
+			return self;
+		}
+		let light = |style: Style| Style { foreground: light_foreground(style.foreground, style.background, terminal_colors >= 256), ..style };
+		return Theme {
+			colors: self.colors,
+			light: true,
+			title: light(self.title),
+			value: light(self.value),
+			trend: light(self.trend),
+			warning: light(self.warning),
+			critical: light(self.critical),
+			unknown: light(self.unknown),
+		};
+	}
+	
+	/**
+	 * The theme with every style dimmed, e.g. for the night on a display that burns in.
+	*/
+	pub fn dimmed(self) -> Theme {
+		let dim = |style: Style| Style { attributes: style.attributes | DIM, ..style };
+		return Theme {
+			title: dim(self.title),
+			value: dim(self.value),
+			trend: dim(self.trend),
+			warning: dim(self.warning),
+			critical: dim(self.critical),
+			unknown: dim(self.unknown),
+			..self
+		};
+	}
+	
+	pub fn style(&self, role: Role) -> Style {
+		match role {
+			Role::Title => self.title,
+			Role::Value => self.value,
+			Role::Trend => self.trend,
+			Role::Warning => self.warning,
+			Role::Critical => self.critical,
+			Role::Unknown => self.unknown,
+		}
+	}
+	
+	/**
+	 * Style for a value colored by its range. Without colors the value looks like any other value.
+	*/
+	pub fn range_style(&self, color: Option<RangeColor>) -> Style {
+		match color {
+			// The range colors are the basic ones, which don't need the palette.
+			Some(color) if self.colors && self.light => Style::new(light_foreground(color.index(), -1, false), self.value.background, self.value.attributes),
+			Some(color) if self.colors => Style::new(color.index(), self.value.background, self.value.attributes),
+			_ => self.style(Role::Value),
+		}
+	}
+}
+
+/**
+ * The color of text on a light background for the color on a dark one. Text on a color of its own stays as it is.
+*/
+fn light_foreground(foreground: i16, background: i16, palette: bool) -> i16 {
+	if background != -1 {
+		return foreground;
+	}
+	return match foreground {
+		WHITE => BLACK,
+		// Dark yellow from the 256 color palette, the basic yellow is too light on white.
+		YELLOW if palette => 136,
+		// Solarized base1 and base01.
+		245 => 240,
+		240 => 245,
+		foreground => foreground,
+	};
+}
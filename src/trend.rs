@@ -0,0 +1,132 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use crate::history::History;
+use crate::model::ApiResponse;
+
+/**
+ * Settings for the trends calculated from the history.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrendConfig {
+	// How far back the slope is calculated from.
+	pub window_minutes: i64,
+	// Change per hour needed for rising/falling, in °C and %.
+	pub temperature: f64,
+	pub humidity: f64,
+}
+
+impl Default for TrendConfig {
+	fn default() -> TrendConfig {
+		return TrendConfig {
+			window_minutes: 30,
+			temperature: 0.5,
+			humidity: 2.0,
+		};
+	}
+}
+
+/**
+ * Calculate the trends the source didn't provide from the history.
+*/
+pub fn fill(data: &mut ApiResponse, history: &History, config: &TrendConfig) {
+	let window = Duration::minutes(config.window_minutes);
+	
+	for tag in data.iter_mut() {
+		if let Some(temperature) = tag.temperature.as_mut().filter(|temperature| temperature.trend.is_none()) {
+			let points: Vec<_> = history.samples(&tag.id).filter_map(|sample| Some((sample.datetime, sample.temperature?))).collect();
+			temperature.trend = trend(&points, window, config.temperature);
+		}
+		if let Some(humidity) = tag.humidity.as_mut().filter(|humidity| humidity.trend.is_none()) {
+			let points: Vec<_> = history.samples(&tag.id).filter_map(|sample| Some((sample.datetime, sample.humidity?))).collect();
+			humidity.trend = trend(&points, window, config.humidity);
+		}
+	}
+}
+
+/**
+ * Trend (1 rising, 0 steady, -1 falling) of the points within the window before the latest point.
+ * None if there isn't enough history yet.
+*/
+pub fn trend(points: &[(DateTime<Utc>, f64)], window: Duration, threshold: f64) -> Option<i8> {
+	let slope = slope_per_hour(points, window)?;
+	
+	if slope >= threshold {
+		return Some(1);
+	}
+	else if slope <= -threshold {
+		return Some(-1);
+	}
+	else {
+		return Some(0);
+	}
+}
+
+/**
+ * Least squares slope per hour of the points within the window. The points must cover at least
+ * half of the window so that a couple of noisy samples don't make a trend.
+*/
+pub fn slope_per_hour(points: &[(DateTime<Utc>, f64)], window: Duration) -> Option<f64> {
+	let latest = points.iter().map(|(datetime, _)| *datetime).max()?;
+	let start = latest - window;
+	let points: Vec<(f64, f64)> = points.iter()
+		.filter(|(datetime, _)| *datetime >= start)
+		.map(|(datetime, value)| ((*datetime - start).num_seconds() as f64 / 3600.0, *value))
+		.collect();
+	
+	let first = points.iter().map(|(hours, _)| *hours).reduce(f64::min)?;
+	let span = window.num_seconds() as f64 / 3600.0 - first;
+	if points.len() < 2 || span < window.num_seconds() as f64 / 3600.0 / 2.0 {
+		return None;
+	}
+	
+	let count = points.len() as f64;
+	let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+	let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+	let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+	let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+	
+	if variance == 0.0 {
+		return None;
+	}
+	return Some(covariance / variance);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn points(values: &[(i64, f64)]) -> Vec<(DateTime<Utc>, f64)> {
+		let start = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+		return values.iter().map(|(minutes, value)| (start + Duration::minutes(*minutes), *value)).collect();
+	}
+	
+	#[test]
+	fn slope_is_per_hour() {
+		let slope = slope_per_hour(&points(&[(0, 20.0), (15, 20.5), (30, 21.0)]), Duration::minutes(30)).unwrap();
+		assert!((slope - 2.0).abs() < 1e-9);
+	}
+	
+	#[test]
+	fn points_outside_window_are_ignored() {
+		let slope = slope_per_hour(&points(&[(0, 0.0), (60, 20.0), (90, 20.0)]), Duration::minutes(30)).unwrap();
+		assert_eq!(slope, 0.0);
+	}
+	
+	#[test]
+	fn short_history_has_no_trend() {
+		assert_eq!(trend(&points(&[(0, 20.0), (5, 25.0)]), Duration::minutes(30), 0.5), None);
+		assert_eq!(trend(&points(&[(0, 20.0)]), Duration::minutes(30), 0.5), None);
+		assert_eq!(trend(&[], Duration::minutes(30), 0.5), None);
+	}
+	
+	#[test]
+	fn threshold_decides_the_trend() {
+		let rising = points(&[(0, 20.0), (30, 20.3)]); // 0.6 °C/h
+		assert_eq!(trend(&rising, Duration::minutes(30), 0.5), Some(1));
+		assert_eq!(trend(&rising, Duration::minutes(30), 1.0), Some(0));
+		
+		let falling = points(&[(0, 20.0), (30, 19.7)]);
+		assert_eq!(trend(&falling, Duration::minutes(30), 0.5), Some(-1));
+	}
+}
@@ -0,0 +1,133 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use crate::i18n::Lang;
+
+// Offset of the Kelvin scale from Celsius.
+const ZERO_CELSIUS: f64 = 273.15;
+
+/**
+ * Unit the temperatures are shown in. Ranges, alerts, targets and the stored history stay in °C.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TemperatureUnit {
+	#[default]
+	Celsius,
+	Kelvin,
+}
+
+/**
+ * How the measurements are shown.
+*/
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Units {
+	pub temperature: TemperatureUnit,
+	// Show the values to the resolution of the tags (0.005 °C, 0.0025 % and 1 Pa) instead of rounding them for reading
+	// at a glance, for experiments.
+	pub precise: bool,
+	// Decimals of each metric wherever it's shown or exported.
+	pub precision: Precision,
+}
+
+/**
+ * Decimals of the metrics. The tags measure finer than this, but the last digits only flicker with the noise.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Precision {
+	pub temperature: usize,
+	pub humidity: usize,
+	pub pressure: usize,
+	pub battery_voltage: usize,
+}
+
+impl Default for Precision {
+	fn default() -> Precision {
+		return Precision {
+			temperature: 1,
+			humidity: 0,
+			pressure: 1,
+			battery_voltage: 2,
+		};
+	}
+}
+
+/**
+ * The value rounded to the decimals, for the exports.
+*/
+pub fn round(value: f64, decimals: usize) -> f64 {
+	let scale = 10f64.powi(decimals as i32);
+	return (value * scale).round() / scale;
+}
+
+impl Units {
+	/**
+	 * Temperature with the unit. Celsius is signed, Kelvin can't go below zero.
+	*/
+	pub fn temperature(&self, celsius: f64, decimals: usize, lang: Lang) -> String {
+		let decimals = self.decimals(decimals, 3);
+		return match self.temperature {
+			TemperatureUnit::Celsius => format!("{}°C", lang.signed(celsius, decimals)),
+			TemperatureUnit::Kelvin => format!("{} K", lang.number(self.convert(celsius), decimals)),
+		};
+	}
+	
+	/**
+	 * Temperature in the unit, without formatting, e.g. for the table columns.
+	*/
+	pub fn convert(&self, celsius: f64) -> f64 {
+		return match self.temperature {
+			TemperatureUnit::Celsius => celsius,
+			TemperatureUnit::Kelvin => celsius + ZERO_CELSIUS,
+		};
+	}
+	
+	pub fn temperature_symbol(&self) -> &'static str {
+		return match self.temperature {
+			TemperatureUnit::Celsius => "°C",
+			TemperatureUnit::Kelvin => " K",
+		};
+	}
+	
+	pub fn humidity(&self, value: f64, decimals: usize, lang: Lang) -> String {
+		return lang.number(value, self.decimals(decimals, 4));
+	}
+	
+	/**
+	 * Pressure in hPa, where the tags' 1 Pa resolution is two decimals.
+	*/
+	pub fn pressure(&self, value: f64, decimals: usize, lang: Lang) -> String {
+		return lang.number(value, self.decimals(decimals, 2));
+	}
+	
+	/**
+	 * The decimals to show of a metric measured to the resolution.
+	*/
+	pub fn decimals(&self, decimals: usize, resolution: usize) -> usize {
+		return if self.precise { decimals.max(resolution) } else { decimals };
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn kelvin_and_precise() {
+		let celsius = Units::default();
+		assert_eq!(celsius.temperature(21.456, 2, Lang::En), "+21.46°C");
+		assert_eq!(celsius.humidity(45.0, 0, Lang::En), "45");
+		
+		let kelvin = Units { temperature: TemperatureUnit::Kelvin, ..Units::default() };
+		assert_eq!(kelvin.temperature(-3.15, 1, Lang::Fi), "270,0 K");
+		
+		let precise = Units { temperature: TemperatureUnit::Kelvin, precise: true, ..Units::default() };
+		assert_eq!(precise.temperature(24.305, 1, Lang::En), "297.455 K");
+		assert_eq!(precise.humidity(53.4925, 2, Lang::En), "53.4925");
+		assert_eq!(precise.pressure(1000.44, 1, Lang::En), "1000.44");
+		
+		assert_eq!(round(21.4567, 1), 21.5);
+		assert_eq!(round(1002.449, 0), 1002.0);
+	}
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use reqwest::blocking::Client;
+use reqwest::header::USER_AGENT;
+use serde::Deserialize;
+
+/**
+ * Version with the git commit and build date, e.g. "0.1.3 (1a2b3c4 2024-05-01)".
+*/
+pub const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("GIT_HASH"), " ", env!("BUILD_DATE"), ")");
+
+const RELEASES_URL: &str = "https://api.github.com/repos/joonaskokko/ruuvi-terminal-client/releases/latest";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+	tag_name: String,
+}
+
+/**
+ * Checks GitHub for a newer release in the background, so that a slow or missing network never holds up the UI.
+*/
+pub struct UpdateCheck {
+	// Newer release, once known.
+	available: Arc<Mutex<Option<String>>>,
+}
+
+impl UpdateCheck {
+	pub fn start(client: Client) -> UpdateCheck {
+		let available = Arc::new(Mutex::new(None));
+		let result = available.clone();
+		thread::spawn(move || {
+			if let Ok(Some(version)) = latest(&client) {
+				*result.lock().unwrap() = Some(version);
+			}
+		});
+		return UpdateCheck { available };
+	}
+	
+	pub fn available(&self) -> Option<String> {
+		return self.available.lock().unwrap().clone();
+	}
+}
+
+/**
+ * The latest release if it's newer than this build.
+*/
+pub fn latest(client: &Client) -> Result<Option<String>, Box<dyn std::error::Error>> {
+	let release = client.get(RELEASES_URL)
+		// GitHub rejects API requests without a user agent.
+		.header(USER_AGENT, concat!("ruuvi-terminal-client/", env!("CARGO_PKG_VERSION")))
+		.send()?
+		.error_for_status()?
+		.json::<Release>()?;
+	
+	let version = release.tag_name.trim_start_matches('v');
+	return Ok(is_newer(version, env!("CARGO_PKG_VERSION")).then(|| version.to_string()));
+}
+
+/**
+ * Compare dotted version numbers part by part. Pre-release suffixes like "-beta.1" are ignored.
+*/
+fn is_newer(version: &str, current: &str) -> bool {
+	let parts = |version: &str| -> Vec<u64> {
+		return version.split(['-', '+']).next().unwrap_or_default()
+			.split('.')
+			.map(|part| part.parse().unwrap_or(0))
+			.collect();
+	};
+	return parts(version) > parts(current);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn newer_versions() {
+		assert!(is_newer("0.2.0", "0.1.3"));
+		assert!(is_newer("0.1.10", "0.1.9"));
+		assert!(is_newer("1.0", "0.9.9"));
+		assert!(!is_newer("0.1.3", "0.1.3"));
+		assert!(!is_newer("0.1.2", "0.1.3"));
+		assert!(!is_newer("0.1.3-beta.1", "0.1.3"));
+	}
+}
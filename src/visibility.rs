@@ -0,0 +1,85 @@
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+// How often tmux is asked whether the pane is seen, running it every second would cost more than it saves.
+const TMUX_CHECK: Duration = Duration::from_secs(5);
+
+/**
+ * Whether anyone can see the TUI, so that the screen is redrawn less often when not: the terminal reported losing the
+ * focus (with focus_events), or the pane is in a detached tmux session or in a window that isn't shown.
+*/
+pub struct Visibility {
+	focused: bool,
+	// The pane, when running in tmux, and what tmux said about it last.
+	tmux_pane: Option<String>,
+	tmux_shown: bool,
+	checked_at: Option<Instant>,
+}
+
+impl Visibility {
+	pub fn new() -> Visibility {
+		return Visibility {
+			focused: true,
+			tmux_pane: std::env::var("TMUX").ok().and(std::env::var("TMUX_PANE").ok()),
+			tmux_shown: true,
+			checked_at: None,
+		};
+	}
+	
+	/**
+	 * The terminal gained or lost the focus.
+	*/
+	pub fn focus(&mut self, focused: bool) {
+		self.focused = focused;
+	}
+	
+	pub fn visible(&mut self) -> bool {
+		if let Some(pane) = &self.tmux_pane {
+			if self.checked_at.is_none_or(|checked_at| checked_at.elapsed() >= TMUX_CHECK) {
+				self.checked_at = Some(Instant::now());
+				// Without an answer the pane is taken as seen.
+				self.tmux_shown = tmux_shown(pane).unwrap_or(true);
+			}
+		}
+		return self.focused && self.tmux_shown;
+	}
+}
+
+impl Default for Visibility {
+	fn default() -> Visibility {
+		return Visibility::new();
+	}
+}
+
+/**
+ * Whether the session of the pane is attached and the pane's window is the one shown. None if tmux didn't say.
+*/
+fn tmux_shown(pane: &str) -> Option<bool> {
+	let output = Command::new("tmux").args(["display-message", "-p", "-t", pane, "#{session_attached} #{window_active}"]).output().ok()?;
+	if !output.status.success() {
+		return None;
+	}
+	return parse_tmux(&String::from_utf8_lossy(&output.stdout));
+}
+
+/**
+ * The answer of tmux, e.g. "1 1" for an attached session and the active window.
+*/
+fn parse_tmux(answer: &str) -> Option<bool> {
+	let mut values = answer.split_whitespace().map(|value| value.parse::<u32>().ok());
+	let (attached, active) = (values.next()??, values.next()??);
+	return Some(attached > 0 && active == 1);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn tmux_answers() {
+		assert_eq!(parse_tmux("1 1\n"), Some(true));
+		assert_eq!(parse_tmux("0 1\n"), Some(false));
+		assert_eq!(parse_tmux("2 0\n"), Some(false));
+		assert_eq!(parse_tmux(""), None);
+	}
+}
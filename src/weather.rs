@@ -0,0 +1,127 @@
+use chrono::Duration;
+use crate::glyphs::Glyphs;
+use crate::history::History;
+use crate::i18n::Text;
+
+/**
+ * Pressure change (hPa) over the last three hours, from the history. None if there isn't
+ * at least 2.5 hours of pressure history.
+*/
+pub fn pressure_tendency(history: &History, tag_id: &str) -> Option<f64> {
+	let samples: Vec<_> = history.samples(tag_id)
+		.filter_map(|sample| Some((sample.datetime, sample.pressure?)))
+		.collect();
+	let (latest_datetime, latest) = *samples.last()?;
+	let target = latest_datetime - Duration::hours(3);
+	
+	// The sample closest to three hours ago.
+	let (datetime, earlier) = samples.iter()
+		.min_by_key(|(datetime, _)| (*datetime - target).num_seconds().abs())?;
+	
+	if latest_datetime - *datetime < Duration::minutes(150) {
+		return None;
+	}
+	return Some(latest - earlier);
+}
+
+/**
+ * Barometer style class of the three hour pressure tendency, from 2 for rising fast to -2 for falling fast.
+*/
+fn level(change: f64) -> i8 {
+	if change >= 3.5 {
+		return 2;
+	}
+	else if change >= 1.5 {
+		return 1;
+	}
+	else if change > -1.5 {
+		return 0;
+	}
+	else if change > -3.5 {
+		return -1;
+	}
+	else {
+		return -2;
+	}
+}
+
+/**
+ * Barometer style description of the three hour pressure tendency.
+*/
+pub fn describe(change: f64) -> Text {
+	return match level(change) {
+		2 => Text::RisingFast,
+		1 => Text::Rising,
+		0 => Text::Steady,
+		-1 => Text::Falling,
+		_ => Text::FallingFast,
+	};
+}
+
+/**
+ * Barometer needle of the three hour pressure tendency, for the pressure values.
+*/
+pub fn indicator(change: f64, glyphs: Glyphs) -> &'static str {
+	return match level(change) {
+		2 => glyphs.arrow("⇈", "^"),
+		1 => glyphs.arrow("↑", "/"),
+		0 => glyphs.arrow("→", "-"),
+		-1 => glyphs.arrow("↓", "\\"),
+		_ => glyphs.arrow("⇊", "v"),
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::Utc;
+	use crate::store::Reading;
+	
+	fn history(pressures: &[(i64, f64)]) -> History {
+		let start = Utc::now() - Duration::hours(4);
+		let readings: Vec<Reading> = pressures.iter().map(|(minutes, pressure)| Reading {
+			tag_id: "outdoor".into(),
+			tag_name: "Outdoor".into(),
+			datetime: start + Duration::minutes(*minutes),
+			temperature: 0.0,
+			humidity: 0.0,
+			pressure: Some(*pressure),
+			battery_voltage: None,
+		}).collect();
+		
+		let mut history = History::new(Duration::hours(24));
+		history.seed(&readings);
+		return history;
+	}
+	
+	#[test]
+	fn tendency_over_three_hours() {
+		let history = history(&[(0, 1000.0), (60, 1001.0), (120, 1003.0), (180, 1004.0), (240, 1005.0)]);
+		assert_eq!(pressure_tendency(&history, "outdoor"), Some(4.0));
+	}
+	
+	#[test]
+	fn barometer_levels() {
+		assert_eq!((describe(4.0), indicator(4.0, Glyphs::UNICODE)), (Text::RisingFast, "⇈"));
+		assert_eq!((describe(1.5), indicator(1.5, Glyphs::UNICODE)), (Text::Rising, "↑"));
+		assert_eq!((describe(-1.0), indicator(-1.0, Glyphs::UNICODE)), (Text::Steady, "→"));
+		assert_eq!((describe(-1.5), indicator(-1.5, Glyphs::UNICODE)), (Text::Falling, "↓"));
+		assert_eq!((describe(-5.0), indicator(-5.0, Glyphs::UNICODE)), (Text::FallingFast, "⇊"));
+		assert_eq!(indicator(-1.5, Glyphs::ASCII), "\\");
+	}
+	
+	#[test]
+	fn short_history_has_no_tendency() {
+		let history = history(&[(0, 1000.0), (60, 1001.0)]);
+		assert_eq!(pressure_tendency(&history, "outdoor"), None);
+		assert_eq!(pressure_tendency(&history, "unknown"), None);
+	}
+	
+	#[test]
+	fn descriptions() {
+		assert_eq!(describe(4.0), Text::RisingFast);
+		assert_eq!(describe(0.5), Text::Steady);
+		assert_eq!(describe(-2.0), Text::Falling);
+		assert_eq!(describe(-6.0), Text::FallingFast);
+	}
+}
@@ -0,0 +1,173 @@
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde_json::Value;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+use crate::formatters::{self, FormatError, Parsed};
+use crate::model::ApiResponse;
+use crate::recorder::Recorder;
+use crate::tags;
+
+// The server is pinged this often, so that a connection that died silently is noticed.
+const PING: Duration = Duration::from_secs(30);
+// A connection that hasn't sent anything, not even a pong, in this long is dead.
+const SILENCE: Duration = Duration::from_secs(90);
+// How often the reading stops to ping and to check whether the client is quitting.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+// Wait before reconnecting, doubled after every failed connection up to the longest.
+const BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/**
+ * WebSocket endpoint as a streaming source, for APIs that push the tags as they change. Each message is JSON in any
+ * supported format, e.g. the custom API's array, or a single tag of the custom API. The tags in a message replace
+ * their earlier data and the others are kept, so the server can send only the changed tags.
+ *
+ * The connection is kept alive with pings and opened again when it drops, waiting longer after each failure.
+*/
+pub struct Stream {
+	latest: Arc<Mutex<Option<ApiResponse>>>,
+	// Why the latest message was rejected or the connection dropped, cleared by the next accepted message.
+	problem: Arc<Mutex<Option<FormatError>>>,
+	stop: Arc<AtomicBool>,
+}
+
+impl Stream {
+	/**
+	 * Connect to the URL and keep receiving in a background thread.
+	*/
+	pub fn start(url: &str, recorder: Option<Recorder>) -> Result<Stream, String> {
+		if !url.starts_with("ws://") && !url.starts_with("wss://") {
+			return Err(format!("The WebSocket URL {} must start with ws:// or wss://", url));
+		}
+		let latest = Arc::new(Mutex::new(None));
+		let problem = Arc::new(Mutex::new(None));
+		let stop = Arc::new(AtomicBool::new(false));
+		
+		let url = url.to_string();
+		let (thread_latest, thread_problem, thread_stop) = (Arc::clone(&latest), Arc::clone(&problem), Arc::clone(&stop));
+		thread::spawn(move || {
+			let mut backoff = BACKOFF;
+			// The tags received so far, which the messages are merged into.
+			let mut data = ApiResponse::new();
+			while !thread_stop.load(Ordering::Relaxed) {
+				let connected = Instant::now();
+				let dropped = match tungstenite::connect(&url) {
+					Ok((socket, _)) => receive(socket, &mut data, recorder.as_ref(), &thread_latest, &thread_problem, &thread_stop),
+					Err(error) => Some(FormatError::new(format!("Could not connect: {}", error), &url)),
+				};
+				let Some(dropped) = dropped else {
+					return;
+				};
+				*thread_problem.lock().unwrap() = Some(dropped);
+				
+				// A connection that lasted a while starts the backoff over.
+				if connected.elapsed() >= PING {
+					backoff = BACKOFF;
+				}
+				let reconnect = Instant::now() + backoff;
+				while Instant::now() < reconnect && !thread_stop.load(Ordering::Relaxed) {
+					thread::sleep(Duration::from_millis(100));
+				}
+				backoff = (backoff * 2).min(MAX_BACKOFF);
+			}
+		});
+		
+		return Ok(Stream { latest, problem, stop });
+	}
+	
+	/**
+	 * Take the data received since the last call, if any.
+	*/
+	pub fn take(&self) -> Option<ApiResponse> {
+		return self.latest.lock().ok()?.take();
+	}
+	
+	pub fn problem(&self) -> Option<FormatError> {
+		return self.problem.lock().ok()?.clone();
+	}
+}
+
+impl Drop for Stream {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+/**
+ * Read the messages until the connection drops or the client stops. Returns why the connection dropped.
+*/
+fn receive(mut socket: Socket, data: &mut ApiResponse, recorder: Option<&Recorder>, latest: &Mutex<Option<ApiResponse>>, problem: &Mutex<Option<FormatError>>, stop: &AtomicBool) -> Option<FormatError> {
+	let timeout = match socket.get_ref() {
+		MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(READ_TIMEOUT)),
+		MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_read_timeout(Some(READ_TIMEOUT)),
+		_ => Ok(()),
+	};
+	if let Err(error) = timeout {
+		return Some(FormatError::new(error, ""));
+	}
+	*problem.lock().unwrap() = None;
+	
+	let (mut heard, mut pinged) = (Instant::now(), Instant::now());
+	loop {
+		if stop.load(Ordering::Relaxed) {
+			let _ = socket.close(None);
+			return None;
+		}
+		if heard.elapsed() >= SILENCE {
+			return Some(FormatError::new(format!("No messages in {} s", SILENCE.as_secs()), ""));
+		}
+		if pinged.elapsed() >= PING {
+			pinged = Instant::now();
+			if let Err(error) = socket.send(Message::Ping(Vec::new())) {
+				return Some(FormatError::new(error, ""));
+			}
+		}
+		
+		let text = match socket.read() {
+			Ok(Message::Text(text)) => text,
+			Ok(Message::Binary(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+			Ok(Message::Close(frame)) => {
+				let reason = frame.map(|frame| frame.reason.into_owned()).unwrap_or_default();
+				return Some(FormatError::new("The server closed the connection", &reason));
+			},
+			// Pings are answered by tungstenite. They and the pongs show that the connection is alive.
+			Ok(_) => {
+				heard = Instant::now();
+				continue;
+			},
+			Err(tungstenite::Error::Io(error)) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+			Err(error) => return Some(FormatError::new(error, "")),
+		};
+		heard = Instant::now();
+		if let Some(recorder) = recorder {
+			recorder.record("websocket", Utc::now(), &text);
+		}
+		match parse(&text) {
+			Ok(changed) => {
+				*data = tags::merge(data, changed.data);
+				*latest.lock().unwrap() = Some(data.clone());
+				*problem.lock().unwrap() = changed.skipped;
+			},
+			Err(error) => *problem.lock().unwrap() = Some(error),
+		}
+	}
+}
+
+/**
+ * The tags of a message. A lone tag of the custom API is taken as an array of one.
+*/
+fn parse(text: &str) -> Result<Parsed, FormatError> {
+	let json: Value = serde_json::from_str(text).map_err(|error| FormatError::new(error, text))?;
+	if json.get("tag_id").is_some() {
+		return formatters::parse_detected(&Value::Array(vec![json]));
+	}
+	return formatters::parse_detected(&json);
+}
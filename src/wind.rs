@@ -0,0 +1,103 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+/**
+ * Where the wind speed for the feels-like temperature comes from.
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "source", rename_all = "kebab-case")]
+pub enum WindConfig {
+	// Fixed wind speed in m/s.
+	Fixed {
+		speed: f64,
+	},
+	// Current wind speed at the location from the Open-Meteo forecast API.
+	OpenMeteo {
+		latitude: f64,
+		longitude: f64,
+		#[serde(default = "default_refresh_minutes")]
+		refresh_minutes: i64,
+		// Proxy of the forecast requests instead of the [http] one, or "direct".
+		#[serde(default)]
+		proxy: Option<String>,
+	},
+}
+
+impl WindConfig {
+	pub fn proxy(&self) -> Option<&str> {
+		match self {
+			WindConfig::OpenMeteo { proxy, .. } => proxy.as_deref(),
+			WindConfig::Fixed { .. } => None,
+		}
+	}
+}
+
+fn default_refresh_minutes() -> i64 {
+	return 15;
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenMeteoResponse {
+	current: OpenMeteoCurrent,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenMeteoCurrent {
+	wind_speed_10m: f64,
+}
+
+/**
+ * Keeps the wind speed up to date.
+*/
+pub struct Wind {
+	config: WindConfig,
+	client: Client,
+	pub speed: Option<f64>,
+	last_fetch: Option<DateTime<Utc>>,
+}
+
+impl Wind {
+	pub fn new(config: WindConfig, client: Client) -> Wind {
+		let speed = match &config {
+			WindConfig::Fixed { speed } => Some(*speed),
+			WindConfig::OpenMeteo { .. } => None,
+		};
+		
+		return Wind {
+			config,
+			client,
+			speed,
+			last_fetch: None,
+		};
+	}
+	
+	/**
+	 * Fetch the wind speed if it's time. A failed fetch keeps the previous speed and is retried on the next interval.
+	*/
+	pub fn poll(&mut self, now: DateTime<Utc>) {
+		let WindConfig::OpenMeteo { latitude, longitude, refresh_minutes, .. } = &self.config else {
+			return;
+		};
+		if self.last_fetch.is_some_and(|last_fetch| now - last_fetch < Duration::minutes(*refresh_minutes)) {
+			return;
+		}
+		self.last_fetch = Some(now);
+		
+		let url = format!(
+			"https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=wind_speed_10m&wind_speed_unit=ms",
+			latitude,
+			longitude
+		);
+		if let Ok(response) = self.client.get(url).send().and_then(|response| response.json::<OpenMeteoResponse>()) {
+			self.speed = Some(response.current.wind_speed_10m);
+		}
+	}
+	
+	/**
+	 * Fetch on the next poll, e.g. after a suspend.
+	*/
+	pub fn refresh(&mut self) {
+		self.last_fetch = None;
+	}
+}
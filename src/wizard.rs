@@ -0,0 +1,158 @@
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use crate::cli::Args;
+use crate::config::Config;
+use crate::doctor::{self, Outcome};
+use crate::formatters;
+
+/**
+ * The source chosen in the first start setup.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Setup {
+	// URL of a Ruuvi Gateway, Ruuvi Station or a custom API.
+	Api(String),
+	// Ruuvi Cloud API token.
+	Cloud(String),
+	// Address to listen for Ruuvi Gateway pushes on.
+	Listen(String),
+	Demo,
+}
+
+impl Setup {
+	/**
+	 * The config file with the source, the rest left to the defaults.
+	*/
+	pub fn config_file(&self) -> String {
+		let quoted = |value: &str| toml::Value::String(value.into()).to_string();
+		return match self {
+			Setup::Api(url) => format!("api_url = {}\n", quoted(url)),
+			Setup::Cloud(token) => format!("cloud_token = {}\n", quoted(token)),
+			Setup::Listen(address) => format!("listen = {}\n", quoted(address)),
+			// The demo is only asked for from the command line.
+			Setup::Demo => String::new(),
+		};
+	}
+	
+	/**
+	 * Use the source for this run, also when it wasn't saved.
+	*/
+	pub fn apply(&self, args: &mut Args) {
+		match self {
+			Setup::Api(url) => std::env::set_var("API_URL", url),
+			Setup::Cloud(token) => std::env::set_var("RUUVI_CLOUD_TOKEN", token),
+			Setup::Listen(address) => args.listen = Some(address.clone()),
+			Setup::Demo => args.source = Some(vec!["demo".into()]),
+		}
+	}
+	
+	/**
+	 * Fetch from the source once like the doctor does, or see that the address is free to listen on. The outcome and
+	 * what was seen.
+	*/
+	fn test(&self) -> (Outcome, String) {
+		let config = match self {
+			Setup::Api(url) => Config { api_url: url.clone(), ..Config::default() },
+			Setup::Cloud(token) => Config { api_url: formatters::cloud::URL.into(), cloud_token: Some(token.clone()), ..Config::default() },
+			Setup::Listen(address) => return match TcpListener::bind(address) {
+				Ok(_) => (Outcome::Ok, format!("{} is free to listen on", address)),
+				Err(error) => (Outcome::Failed, format!("Can't listen on {}: {}", address, error)),
+			},
+			Setup::Demo => return (Outcome::Ok, "Demo data".into()),
+		};
+		let check = doctor::api(&config);
+		return (check.outcome, check.detail);
+	}
+}
+
+/**
+ * Whether to ask for a source on the first start: the TUI is started on a terminal without a config file.
+*/
+pub fn available(args: &Args, path: Option<&Path>) -> bool {
+	return args.command.is_none() && !args.headless && path.is_some_and(|path| !path.exists())
+		&& io::stdin().is_terminal() && io::stdout().is_terminal();
+}
+
+/**
+ * The first start setup on the terminal: ask for the source, try it and offer to save it into the config file at the
+ * path. None if given up with Ctrl-D.
+*/
+pub fn run(path: &Path) -> Option<Setup> {
+	let mut input = io::stdin().lock();
+	println!("No config file at {} and no API_URL, let's set up a source.\n", path.display());
+	loop {
+		println!("Where do the readings come from?");
+		println!("  1) A Ruuvi Gateway, Ruuvi Station or another HTTP API");
+		println!("  2) Ruuvi Cloud");
+		println!("  3) Ruuvi Gateway pushes to this computer");
+		println!("  4) Just show demo data");
+		let setup = match ask(&mut input, "Choose 1-4", "1")?.as_str() {
+			"1" => Setup::Api(ask(&mut input, "URL", "http://ruuvigateway.local/history")?),
+			"2" => Setup::Cloud(ask(&mut input, "API token", "")?),
+			"3" => Setup::Listen(ask(&mut input, "Address to listen on", "0.0.0.0:8080")?),
+			"4" => return Some(Setup::Demo),
+			_ => continue,
+		};
+		
+		println!("Trying it…");
+		let (outcome, detail) = setup.test();
+		println!("{}\n", detail);
+		if outcome == Outcome::Failed && !yes(&mut input, "Use it anyway?", false)? {
+			continue;
+		}
+		if yes(&mut input, &format!("Save it into {}?", path.display()), true)? {
+			match save(path, &setup.config_file()) {
+				Ok(()) => println!("Saved, see the README for the rest of the settings.\n"),
+				Err(error) => println!("Could not save it: {}\n", error),
+			}
+		}
+		return Some(setup);
+	}
+}
+
+fn save(path: &Path, contents: &str) -> Result<(), io::Error> {
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+	return fs::write(path, contents);
+}
+
+/**
+ * The answer to the question, the default for an empty one. None at the end of the input.
+*/
+fn ask(input: &mut impl BufRead, question: &str, default: &str) -> Option<String> {
+	match default.is_empty() {
+		true => print!("{}: ", question),
+		false => print!("{} [{}]: ", question, default),
+	}
+	io::stdout().flush().ok()?;
+	let mut line = String::new();
+	if input.read_line(&mut line).ok()? == 0 {
+		return None;
+	}
+	let answer = line.trim();
+	return Some(if answer.is_empty() { default.to_string() } else { answer.to_string() });
+}
+
+fn yes(input: &mut impl BufRead, question: &str, default: bool) -> Option<bool> {
+	let answer = ask(input, question, if default { "Y/n" } else { "y/N" })?;
+	return Some(match answer.to_lowercase().as_str() {
+		"y/n" => default,
+		answer => answer.starts_with('y'),
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn config_files() {
+		let config: Config = toml::from_str(&Setup::Api("http://gateway.local/history?a=\"1\"".into()).config_file()).unwrap();
+		assert_eq!(config.api_url, "http://gateway.local/history?a=\"1\"");
+		let config: Config = toml::from_str(&Setup::Listen("0.0.0.0:8080".into()).config_file()).unwrap();
+		assert_eq!(config.listen.as_deref(), Some("0.0.0.0:8080"));
+	}
+}
@@ -0,0 +1,42 @@
+#![allow(clippy::needless_return)]
+
+use std::fs;
+use ruuvi_terminal_client::formatters;
+
+/**
+ * The fixtures: responses of the custom API and of the Ruuvi Gateway firmware versions whose payload changed, plus the
+ * Ruuvi Cloud. Each has its snapshot in tests/snapshots with the tags it's parsed into.
+*/
+const FIXTURES: [&str; 7] = [
+	"custom",
+	"custom-full",
+	"cloud",
+	"gateway",
+	// Without decoding in the gateway, only the raw broadcasts.
+	"gateway-v1.9",
+	// Decoded fields next to the broadcasts.
+	"gateway-v1.13",
+	// The BLE channel fields, and extended advertisements with only the decoded fields.
+	"gateway-v1.15",
+];
+
+/**
+ * Each fixture is parsed into its snapshot: the format, the tags and what was skipped. A changed parse is a failure, as
+ * a regression would otherwise only show up on the wall. Review an intended change with `cargo insta review`.
+*/
+#[test]
+fn fixtures_match_the_snapshots() {
+	for name in FIXTURES {
+		let text = fs::read_to_string(format!("tests/fixtures/{}.json", name)).unwrap();
+		insta::assert_debug_snapshot!(name, formatters::parse_text(&text));
+	}
+}
+
+#[test]
+fn every_fixture_is_snapshotted() {
+	for entry in fs::read_dir("tests/fixtures").unwrap() {
+		let path = entry.unwrap().path();
+		let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+		assert!(FIXTURES.contains(&name.as_str()), "{} is not in FIXTURES", path.display());
+	}
+}